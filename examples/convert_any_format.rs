@@ -0,0 +1,52 @@
+//! An example of converting between any subtitle formats through the common cue model.
+//!
+//! ```shell
+//! $ cargo run --example convert_any_format
+//! ```
+
+use subtp::ass::SubStationAlpha;
+use subtp::srt::SubRip;
+use subtp::ttml::Ttml;
+use subtp::vtt::WebVtt;
+use subtp::SubtitleFormat;
+
+fn main() -> anyhow::Result<()> {
+    // Prepare the Advanced SubStation Alpha (.ass) format text.
+    let text = r#"[Script Info]
+ScriptType: v4.00+
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,{\i1}Never drink liquid nitrogen.{\i0}
+Dialogue: 0,0:00:05.00,0:00:09.00,Default,,0,0,0,,{\an8}It will perforate your stomach.\N{\c&H0080FF&}You could die.
+"#;
+
+    // Parse the Advanced SubStation Alpha (.ass) format text.
+    let ass = SubStationAlpha::parse(text)?;
+
+    // Show the cues in the common cue model.
+    for cue in ass.iter_cues() {
+        println!(
+            "{:?} --> {:?}: {:?}",
+            cue.start,
+            cue.end,
+            cue.plain_text()
+        );
+    }
+
+    // Convert to the other formats through the common cue model.
+    let srt: SubRip = ass.convert();
+    println!("Converted srt:\n{}", srt.render());
+
+    let vtt: WebVtt = ass.convert();
+    println!("Converted vtt:\n{}", vtt.render());
+
+    let ttml: Ttml = ass.convert();
+    println!("Converted ttml:\n{}", ttml.render());
+
+    Ok(())
+}
@@ -0,0 +1,43 @@
+//! An example of converting a SubRip Subtitle (.srt) format text to a WebVTT (.vtt) format text.
+//!
+//! ```shell
+//! $ cargo run --example convert_srt_to_vtt
+//! ```
+
+use subtp::convert::subrip_to_webvtt;
+use subtp::convert::ConversionOptions;
+use subtp::convert::FrameSize;
+use subtp::srt::SubRip;
+
+fn main() -> anyhow::Result<()> {
+    // Prepare the SubRip Subtitle (.srt) format text.
+    let text = r##"1
+00:00:01,000 --> 00:00:04,000
+<i>Never drink liquid nitrogen.</i>
+
+2
+00:00:05,000 --> 00:00:09,000 X1:40 X2:600 Y1:400 Y2:460
+It will perforate your stomach.
+<font color="#FF8000" face="Arial">You could die.</font>
+"##;
+
+    // Parse the SubRip Subtitle (.srt) format text to the `SubRip` struct.
+    let subrip = SubRip::parse(text)?;
+
+    // Convert the `SubRip` struct to the `WebVtt` struct with the frame size of the video.
+    let conversion = subrip_to_webvtt(
+        &subrip,
+        &ConversionOptions {
+            frame_size: Some(FrameSize {
+                width: 640,
+                height: 480,
+            }),
+        },
+    );
+    println!("Converted vtt:\n{}", conversion.output);
+
+    // Show the data that cannot be represented in the WebVTT.
+    println!("Conversion report:\n{}", conversion.report);
+
+    Ok(())
+}
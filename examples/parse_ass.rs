@@ -0,0 +1,42 @@
+//! An example of parsing an Advanced SubStation Alpha (.ass) format text.
+//!
+//! ```shell
+//! $ cargo run --example parse_ass
+//! ```
+
+use subtp::ass::SubStationAlpha;
+
+fn main() -> anyhow::Result<()> {
+    // Prepare the Advanced SubStation Alpha (.ass) format text.
+    let text = r#"[Script Info]
+Title: Sample
+ScriptType: v4.00+
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,Never drink liquid nitrogen.
+Dialogue: 0,0:00:05.00,0:00:09.00,Default,,0,0,0,,It will perforate your stomach.\NYou could die.
+"#;
+
+    // Parse the Advanced SubStation Alpha (.ass) format text to the `SubStationAlpha` struct.
+    let ass = SubStationAlpha::parse(text)?;
+    println!("Parsed ass:\n{:?}", ass);
+
+    // Render the `SubStationAlpha` struct to the Advanced SubStation Alpha (.ass) format text.
+    let rendered = ass.render();
+    println!("Rendered ass:\n{}", rendered);
+
+    // Get each event by iterator.
+    println!("Iterate events:");
+    if let Some(events) = ass.events() {
+        for event in &events.events {
+            println!("{} --> {}: {}", event.start, event.end, event.text);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! An example of parsing a Timed Text Markup Language (.ttml) format text.
+//!
+//! ```shell
+//! $ cargo run --example parse_ttml
+//! ```
+
+use subtp::ttml::Ttml;
+
+fn main() -> anyhow::Result<()> {
+    // Prepare the Timed Text Markup Language (.ttml) format text.
+    let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" ttp:frameRate="25">
+  <head>
+    <styling>
+      <style xml:id="italic" tts:fontStyle="italic"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 20%"/>
+    </layout>
+  </head>
+  <body region="bottom">
+    <div>
+      <p begin="00:00:01.000" end="00:00:04.000">Never drink liquid nitrogen.</p>
+      <p begin="00:00:05:00" end="225f">It will perforate your stomach.<br/><span style="italic">You could die.</span></p>
+    </div>
+  </body>
+</tt>
+"#;
+
+    // Parse the Timed Text Markup Language (.ttml) format text to the `Ttml` struct.
+    let ttml = Ttml::parse(text)?;
+    println!("Parsed ttml:\n{:?}", ttml);
+
+    // Render the `Ttml` struct to the Timed Text Markup Language (.ttml) format text.
+    let rendered = ttml.render();
+    println!("Rendered ttml:\n{}", rendered);
+
+    // Get each paragraph with the resolved timings.
+    println!("Iterate paragraphs:");
+    for timed in ttml.timed_paragraphs() {
+        println!(
+            "{:?} --> {:?}: {}",
+            timed.start,
+            timed.end,
+            timed.paragraph.plain_text()
+        );
+    }
+
+    Ok(())
+}
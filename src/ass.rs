@@ -0,0 +1,1774 @@
+//! A parser for the Advanced SubStation Alpha (`.ass`) and the SubStation Alpha (`.ssa`) formats provided by [`subtp::ass::SubStationAlpha`](SubStationAlpha).
+//!
+//! ## Example
+//! ```
+//! use subtp::ass::SubStationAlpha;
+//! use subtp::ass::AssEventKind;
+//! use subtp::ass::AssTimestamp;
+//!
+//! let text = r#"[Script Info]
+//! ScriptType: v4.00+
+//!
+//! [V4+ Styles]
+//! Format: Name, Fontname, Fontsize, PrimaryColour, Bold, Alignment
+//! Style: Default,Arial,48,&H00FFFFFF,0,2
+//!
+//! [Events]
+//! Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+//! Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,Hello, {\i1}world{\i0}!
+//! "#;
+//!
+//! let ass = SubStationAlpha::parse(text).unwrap();
+//!
+//! let events = ass.events().unwrap();
+//! assert_eq!(events.events[0].kind, AssEventKind::Dialogue);
+//! assert_eq!(
+//!     events.events[0].start,
+//!     AssTimestamp {
+//!         seconds: 1,
+//!         ..Default::default()
+//!     }
+//! );
+//! assert_eq!(events.events[0].text, "Hello, {\\i1}world{\\i0}!");
+//!
+//! let rendered = ass.render();
+//! assert_eq!(rendered, text);
+//! ```
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::ass_parser;
+use crate::convert::alignment_from_settings;
+use crate::convert::settings_from_alignment;
+use crate::format::Cue;
+use crate::format::CuePosition;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::format::color_rgb;
+use crate::format::position_from_settings;
+use crate::format::push_span;
+use crate::format::settings_from_position;
+
+/// The Advanced SubStation Alpha (`.ass`) or the SubStation Alpha (`.ssa`) format.
+///
+/// Parses from text by [`SubStationAlpha::parse`](SubStationAlpha::parse)
+/// and renders to text by [`SubStationAlpha::render`](SubStationAlpha::render).
+///
+/// The sections are kept in the order of the original text,
+/// and sections other than the script info, the styles and the events are passed through.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubStationAlpha {
+    /// The sections.
+    pub sections: Vec<AssSection>,
+}
+
+impl SubStationAlpha {
+    /// Parses the Advanced SubStation Alpha format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::ass::SubStationAlpha;
+    ///
+    /// let text = r#"[Script Info]
+    /// ScriptType: v4.00+
+    ///
+    /// [Events]
+    /// Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+    /// Dialogue: 0,0:00:01.00,0:00:04.00,Default,,0,0,0,,Hello, world!
+    /// "#;
+    ///
+    /// let ass = SubStationAlpha::parse(text).unwrap();
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        ass_parser::ass(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the Advanced SubStation Alpha format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Gets the script info section.
+    pub fn script_info(&self) -> Option<&AssScriptInfo> {
+        self.sections
+            .iter()
+            .find_map(|section| {
+                match section {
+                    | AssSection::ScriptInfo(script_info) => Some(script_info),
+                    | _ => None,
+                }
+            })
+    }
+
+    /// Gets the styles section.
+    pub fn styles(&self) -> Option<&AssStyles> {
+        self.sections
+            .iter()
+            .find_map(|section| {
+                match section {
+                    | AssSection::Styles(styles) => Some(styles),
+                    | _ => None,
+                }
+            })
+    }
+
+    /// Gets the events section.
+    pub fn events(&self) -> Option<&AssEvents> {
+        self.sections
+            .iter()
+            .find_map(|section| {
+                match section {
+                    | AssSection::Events(events) => Some(events),
+                    | _ => None,
+                }
+            })
+    }
+
+    /// Gets the mutable events section.
+    pub fn events_mut(&mut self) -> Option<&mut AssEvents> {
+        self.sections
+            .iter_mut()
+            .find_map(|section| {
+                match section {
+                    | AssSection::Events(events) => Some(events),
+                    | _ => None,
+                }
+            })
+    }
+}
+
+impl Display for SubStationAlpha {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for (i, section) in self
+            .sections
+            .iter()
+            .enumerate()
+        {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", section)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SubtitleFormat for SubStationAlpha {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the `Dialogue` events.
+    ///
+    /// The italic, the bold, the underline and the alignment of the style are applied to the cue,
+    /// and the override tags of them and the primary colour are mapped to the spans.
+    fn cues(&self) -> Vec<Cue> {
+        let Some(events) = self.events() else {
+            return vec![];
+        };
+
+        events
+            .events
+            .iter()
+            .filter(|event| event.kind == AssEventKind::Dialogue)
+            .map(|event| {
+                let style = self
+                    .styles()
+                    .and_then(|styles| {
+                        styles
+                            .styles
+                            .iter()
+                            .find(|style| style.name == event.style)
+                            .map(|style| (style, styles.version))
+                    });
+                let base = CueSpan {
+                    italic: style.is_some_and(|(style, _)| style.italic),
+                    bold: style.is_some_and(|(style, _)| style.bold),
+                    underline: style.is_some_and(|(style, _)| style.underline),
+                    voice: Some(event.name.clone())
+                        .filter(|name| !name.is_empty()),
+                    ..Default::default()
+                };
+                let alignment = style.map(|(style, version)| {
+                    match version {
+                        | AssStylesVersion::V4 => {
+                            numpad_alignment(style.alignment)
+                        },
+                        | AssStylesVersion::V4Plus => style.alignment,
+                    }
+                });
+
+                let (spans, alignment) =
+                    spans_from_ass_text(&event.text, &base, alignment);
+
+                Cue {
+                    identifier: None,
+                    start: event.start.into(),
+                    end: event.end.into(),
+                    spans,
+                    position: alignment.and_then(|alignment| {
+                        position_from_settings(&settings_from_alignment(
+                            alignment,
+                        ))
+                    }),
+                    style: Some(event.style.clone()),
+                }
+            })
+            .collect()
+    }
+
+    /// Creates from the cues with the `Default` style and the styles referenced by the cues.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        let mut script_info = AssScriptInfo::default();
+        script_info.set("ScriptType", "v4.00+");
+
+        let mut styles = AssStyles::default();
+        let mut events = AssEvents::default();
+        styles
+            .styles
+            .push(AssStyle::default());
+
+        for cue in cues {
+            let style = cue
+                .style
+                .unwrap_or_else(|| "Default".to_string());
+            if !styles
+                .styles
+                .iter()
+                .any(|s| s.name == style)
+            {
+                styles.styles.push(AssStyle {
+                    name: style.clone(),
+                    ..Default::default()
+                });
+            }
+
+            events.events.push(AssEvent {
+                start: cue.start.into(),
+                end: cue.end.into(),
+                style,
+                name: cue
+                    .spans
+                    .iter()
+                    .find_map(|span| span.voice.clone())
+                    .unwrap_or_default(),
+                text: ass_text_from_spans(&cue.spans, cue.position.as_ref()),
+                ..Default::default()
+            });
+        }
+
+        Self {
+            sections: vec![
+                AssSection::ScriptInfo(script_info),
+                AssSection::Styles(styles),
+                AssSection::Events(events),
+            ],
+        }
+    }
+}
+
+/// Converts the legacy alignment of the SubStation Alpha to the numpad layout.
+fn numpad_alignment(alignment: u8) -> u8 {
+    if (5..=7).contains(&alignment) {
+        alignment + 2
+    } else if (9..=11).contains(&alignment) {
+        alignment - 5
+    } else {
+        alignment
+    }
+}
+
+/// Converts the text with override tags to the spans
+/// and the alignment overridden by `{\anN}` or `{\aN}`.
+fn spans_from_ass_text(
+    text: &str,
+    base: &CueSpan,
+    mut alignment: Option<u8>,
+) -> (Vec<CueSpan>, Option<u8>) {
+    let mut spans = Vec::new();
+    let mut format = base.clone();
+    let mut rest = text;
+
+    while !rest.is_empty() {
+        if let Some(block) = rest.strip_prefix('{') {
+            if let Some(end) = block.find('}') {
+                for tag in block[..end]
+                    .split('\\')
+                    .map(str::trim)
+                {
+                    apply_override_tag(tag, base, &mut format, &mut alignment);
+                }
+                rest = &block[end + 1..];
+                continue;
+            }
+        }
+
+        let end = rest
+            .char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == '{')
+            .map_or(rest.len(), |(end, _)| end);
+        let text = rest[..end]
+            .replace("\\N", "\n")
+            .replace("\\n", "\n")
+            .replace("\\h", "\u{a0}");
+        push_span(
+            &mut spans,
+            CueSpan {
+                text,
+                ..format.clone()
+            },
+        );
+        rest = &rest[end..];
+    }
+
+    (spans, alignment)
+}
+
+/// Applies the override tag to the formatting of the span or the alignment.
+fn apply_override_tag(
+    tag: &str,
+    base: &CueSpan,
+    format: &mut CueSpan,
+    alignment: &mut Option<u8>,
+) {
+    let flag = |value: &str| value.parse::<u32>().ok();
+
+    if let Some(value) = tag.strip_prefix('i') {
+        if let Some(value) = flag(value) {
+            format.italic = value != 0;
+        }
+    } else if let Some(value) = tag.strip_prefix('b') {
+        if let Some(value) = flag(value) {
+            format.bold = value == 1 || value >= 700;
+        }
+    } else if let Some(value) = tag.strip_prefix('u') {
+        if let Some(value) = flag(value) {
+            format.underline = value != 0;
+        }
+    } else if let Some(value) = tag
+        .strip_prefix("1c")
+        .or_else(|| tag.strip_prefix('c'))
+    {
+        if value.is_empty() {
+            format.color = base.color.clone();
+        } else if let Some(colour) = AssColor::parse(value) {
+            format.color = Some(format!(
+                "#{:02x}{:02x}{:02x}",
+                colour.red, colour.green, colour.blue
+            ));
+        }
+    } else if let Some(value) = tag.strip_prefix("an") {
+        if let Ok(value) = value.parse::<u8>() {
+            *alignment = Some(value);
+        }
+    } else if let Some(value) = tag.strip_prefix('a') {
+        if let Ok(value) = value.parse::<u8>() {
+            *alignment = Some(numpad_alignment(value));
+        }
+    } else if tag.starts_with('r') {
+        *format = base.clone();
+    }
+}
+
+/// Converts the spans to the text with override tags.
+fn ass_text_from_spans(
+    spans: &[CueSpan],
+    position: Option<&CuePosition>,
+) -> String {
+    let mut text = String::new();
+    if let Some(position) = position {
+        let alignment =
+            alignment_from_settings(&settings_from_position(position));
+        if alignment != 2 {
+            text.push_str(&format!("{{\\an{}}}", alignment));
+        }
+    }
+
+    let mut format = CueSpan::default();
+    for span in spans {
+        let mut tags = String::new();
+        for (enabled, current, tag) in [
+            (span.italic, &mut format.italic, 'i'),
+            (span.bold, &mut format.bold, 'b'),
+            (
+                span.underline,
+                &mut format.underline,
+                'u',
+            ),
+        ] {
+            if enabled != *current {
+                *current = enabled;
+                tags.push_str(&format!("\\{}{}", tag, enabled as u8));
+            }
+        }
+
+        let color = span
+            .color
+            .as_deref()
+            .and_then(color_rgb);
+        let current = format
+            .color
+            .as_deref()
+            .and_then(color_rgb);
+        if color != current {
+            match color {
+                | Some((red, green, blue)) => {
+                    tags.push_str(&format!(
+                        "\\c&H{:02X}{:02X}{:02X}&",
+                        blue, green, red
+                    ))
+                },
+                | None => tags.push_str("\\c"),
+            }
+            format.color = span.color.clone();
+        }
+
+        if !tags.is_empty() {
+            text.push_str(&format!("{{{}}}", tags));
+        }
+        text.push_str(&span.text.replace('\n', "\\N"));
+    }
+
+    text
+}
+
+/// The section.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssSection {
+    /// The `[Script Info]` section.
+    ScriptInfo(AssScriptInfo),
+    /// The `[V4+ Styles]` or `[V4 Styles]` section.
+    Styles(AssStyles),
+    /// The `[Events]` section.
+    Events(AssEvents),
+    /// The `[Fonts]` section with raw lines of embedded fonts.
+    Fonts(Vec<String>),
+    /// The `[Graphics]` section with raw lines of embedded graphics.
+    Graphics(Vec<String>),
+    /// Any other section passed through.
+    Other(AssRawSection),
+}
+
+impl Display for AssSection {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::ScriptInfo(script_info) => {
+                write!(f, "{}", script_info)
+            },
+            | Self::Styles(styles) => {
+                write!(f, "{}", styles)
+            },
+            | Self::Events(events) => {
+                write!(f, "{}", events)
+            },
+            | Self::Fonts(lines) => {
+                write!(
+                    f,
+                    "{}",
+                    AssRawSection {
+                        name: "Fonts".to_string(),
+                        lines: lines.clone(),
+                    }
+                )
+            },
+            | Self::Graphics(lines) => {
+                write!(
+                    f,
+                    "{}",
+                    AssRawSection {
+                        name: "Graphics".to_string(),
+                        lines: lines.clone(),
+                    }
+                )
+            },
+            | Self::Other(section) => {
+                write!(f, "{}", section)
+            },
+        }
+    }
+}
+
+/// The section passed through with raw lines.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AssRawSection {
+    /// The section name without brackets.
+    pub name: String,
+    /// The raw lines.
+    pub lines: Vec<String>,
+}
+
+impl Display for AssRawSection {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "[{}]", self.name)?;
+
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The `[Script Info]` section.
+///
+/// ## Example
+/// ```
+/// use subtp::ass::AssScriptInfo;
+/// use subtp::ass::AssScriptInfoEntry;
+///
+/// let mut script_info = AssScriptInfo {
+///     entries: vec![AssScriptInfoEntry::Comment(
+///         "Generated by hand".to_string(),
+///     )],
+/// };
+/// script_info.set("ScriptType", "v4.00+");
+///
+/// assert_eq!(script_info.get("ScriptType"), Some("v4.00+"));
+/// assert_eq!(
+///     script_info.to_string(),
+///     "[Script Info]\n; Generated by hand\nScriptType: v4.00+\n".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AssScriptInfo {
+    /// The entries in order.
+    pub entries: Vec<AssScriptInfoEntry>,
+}
+
+impl AssScriptInfo {
+    /// Gets the value of the property by the key.
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<&str> {
+        self.entries
+            .iter()
+            .find_map(|entry| {
+                match entry {
+                    | AssScriptInfoEntry::Property {
+                        key: k,
+                        value,
+                    } if k.eq_ignore_ascii_case(key) => Some(value.as_str()),
+                    | _ => None,
+                }
+            })
+    }
+
+    /// Sets the value of the property by the key,
+    /// or appends a new property if not found.
+    pub fn set(
+        &mut self,
+        key: &str,
+        value: &str,
+    ) {
+        for entry in self.entries.iter_mut() {
+            if let AssScriptInfoEntry::Property {
+                key: k,
+                value: v,
+            } = entry
+            {
+                if k.eq_ignore_ascii_case(key) {
+                    *v = value.to_string();
+                    return;
+                }
+            }
+        }
+
+        self.entries
+            .push(AssScriptInfoEntry::Property {
+                key: key.to_string(),
+                value: value.to_string(),
+            });
+    }
+}
+
+impl Display for AssScriptInfo {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "[Script Info]")?;
+
+        for entry in &self.entries {
+            writeln!(f, "{}", entry)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The entry of the `[Script Info]` section.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AssScriptInfoEntry {
+    /// The comment line starting with `;`.
+    Comment(String),
+    /// The property line of `key: value`.
+    Property {
+        /// The key.
+        key: String,
+        /// The value.
+        value: String,
+    },
+}
+
+impl Display for AssScriptInfoEntry {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Comment(comment) => {
+                write!(f, "; {}", comment)
+            },
+            | Self::Property {
+                key,
+                value,
+            } => {
+                write!(f, "{}: {}", key, value)
+            },
+        }
+    }
+}
+
+/// The version of the styles section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AssStylesVersion {
+    /// The `[V4 Styles]` of the SubStation Alpha.
+    V4,
+    /// The `[V4+ Styles]` of the Advanced SubStation Alpha.
+    #[default]
+    V4Plus,
+}
+
+impl Display for AssStylesVersion {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::V4 => {
+                write!(f, "V4 Styles")
+            },
+            | Self::V4Plus => {
+                write!(f, "V4+ Styles")
+            },
+        }
+    }
+}
+
+/// The `[V4+ Styles]` or `[V4 Styles]` section.
+///
+/// Each style is rendered by the field order of the format line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssStyles {
+    /// The version of the section.
+    pub version: AssStylesVersion,
+    /// The field names of the format line.
+    pub format: Vec<String>,
+    /// The styles.
+    pub styles: Vec<AssStyle>,
+}
+
+impl Default for AssStyles {
+    fn default() -> Self {
+        Self {
+            version: AssStylesVersion::default(),
+            format: [
+                "Name",
+                "Fontname",
+                "Fontsize",
+                "PrimaryColour",
+                "SecondaryColour",
+                "OutlineColour",
+                "BackColour",
+                "Bold",
+                "Italic",
+                "Underline",
+                "StrikeOut",
+                "ScaleX",
+                "ScaleY",
+                "Spacing",
+                "Angle",
+                "BorderStyle",
+                "Outline",
+                "Shadow",
+                "Alignment",
+                "MarginL",
+                "MarginR",
+                "MarginV",
+                "Encoding",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            styles: vec![],
+        }
+    }
+}
+
+impl Display for AssStyles {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "[{}]", self.version)?;
+        writeln!(f, "Format: {}", self.format.join(", "))?;
+
+        for style in &self.styles {
+            let fields = self
+                .format
+                .iter()
+                .enumerate()
+                .map(|(index, name)| {
+                    style.render_field(index, name, self.version)
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "Style: {}", fields.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The style.
+///
+/// Field names follow the `[V4+ Styles]`,
+/// and the `TertiaryColour` of the `[V4 Styles]` is mapped to the `outline_colour`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssStyle {
+    /// The `Name`.
+    pub name: String,
+    /// The `Fontname`.
+    pub font_name: String,
+    /// The `Fontsize`.
+    pub font_size: f32,
+    /// The `PrimaryColour`.
+    pub primary_colour: AssColor,
+    /// The `SecondaryColour`.
+    pub secondary_colour: AssColor,
+    /// The `OutlineColour`, or the `TertiaryColour` in the SubStation Alpha.
+    pub outline_colour: AssColor,
+    /// The `BackColour`.
+    pub back_colour: AssColor,
+    /// The `Bold`.
+    pub bold: bool,
+    /// The `Italic`.
+    pub italic: bool,
+    /// The `Underline`.
+    pub underline: bool,
+    /// The `StrikeOut`.
+    pub strike_out: bool,
+    /// The `ScaleX` in percentage.
+    pub scale_x: f32,
+    /// The `ScaleY` in percentage.
+    pub scale_y: f32,
+    /// The `Spacing` in pixels.
+    pub spacing: f32,
+    /// The `Angle` in degrees.
+    pub angle: f32,
+    /// The `BorderStyle`.
+    pub border_style: u8,
+    /// The `Outline` width in pixels.
+    pub outline: f32,
+    /// The `Shadow` depth in pixels.
+    pub shadow: f32,
+    /// The `Alignment`, which is the numpad layout in the `[V4+ Styles]`.
+    pub alignment: u8,
+    /// The `MarginL` in pixels.
+    pub margin_l: i32,
+    /// The `MarginR` in pixels.
+    pub margin_r: i32,
+    /// The `MarginV` in pixels.
+    pub margin_v: i32,
+    /// The `AlphaLevel` of the SubStation Alpha.
+    pub alpha_level: i32,
+    /// The `Encoding`.
+    pub encoding: i32,
+    /// The raw text of the fields in the order of the format line,
+    /// which is rendered for the fields not edited since parsed and the unknown fields.
+    pub raw_fields: Vec<String>,
+}
+
+impl AssStyle {
+    /// Renders the field value by the field name of the format line.
+    fn field(
+        &self,
+        name: &str,
+        version: AssStylesVersion,
+    ) -> String {
+        let colour = |colour: &AssColor| {
+            match version {
+                | AssStylesVersion::V4 => (colour.value() as i32).to_string(),
+                | AssStylesVersion::V4Plus => colour.to_string(),
+            }
+        };
+        let boolean = |value: bool| {
+            if value {
+                "-1"
+            } else {
+                "0"
+            }
+            .to_string()
+        };
+
+        match normalize_field_name(name).as_str() {
+            | "name" => self.name.clone(),
+            | "fontname" => self.font_name.clone(),
+            | "fontsize" => self.font_size.to_string(),
+            | "primarycolour" => colour(&self.primary_colour),
+            | "secondarycolour" => colour(&self.secondary_colour),
+            | "outlinecolour" | "tertiarycolour" => {
+                colour(&self.outline_colour)
+            },
+            | "backcolour" => colour(&self.back_colour),
+            | "bold" => boolean(self.bold),
+            | "italic" => boolean(self.italic),
+            | "underline" => boolean(self.underline),
+            | "strikeout" => boolean(self.strike_out),
+            | "scalex" => self.scale_x.to_string(),
+            | "scaley" => self.scale_y.to_string(),
+            | "spacing" => self.spacing.to_string(),
+            | "angle" => self.angle.to_string(),
+            | "borderstyle" => self.border_style.to_string(),
+            | "outline" => self.outline.to_string(),
+            | "shadow" => self.shadow.to_string(),
+            | "alignment" => self.alignment.to_string(),
+            | "marginl" => self.margin_l.to_string(),
+            | "marginr" => self.margin_r.to_string(),
+            | "marginv" => self.margin_v.to_string(),
+            | "alphalevel" => self.alpha_level.to_string(),
+            | "encoding" => self.encoding.to_string(),
+            | _ => String::new(),
+        }
+    }
+
+    /// Renders the field by the raw text at the index if the value is not edited.
+    fn render_field(
+        &self,
+        index: usize,
+        name: &str,
+        version: AssStylesVersion,
+    ) -> String {
+        let value = self.field(name, version);
+        let Some(raw) = self.raw_fields.get(index) else {
+            return value;
+        };
+
+        let mut parsed = Self::default();
+        match parsed.set_field(name, raw) {
+            | Some(()) if parsed.field(name, version) == value => raw.clone(),
+            | _ => value,
+        }
+    }
+
+    /// Sets the field value from the text by the field name of the format line,
+    /// or `None` if the text is invalid for the field.
+    pub(crate) fn set_field(
+        &mut self,
+        name: &str,
+        text: &str,
+    ) -> Option<()> {
+        let text = text.trim();
+        match normalize_field_name(name).as_str() {
+            | "name" => self.name = text.to_string(),
+            | "fontname" => self.font_name = text.to_string(),
+            | "fontsize" => self.font_size = text.parse().ok()?,
+            | "primarycolour" => self.primary_colour = AssColor::parse(text)?,
+            | "secondarycolour" => {
+                self.secondary_colour = AssColor::parse(text)?
+            },
+            | "outlinecolour" | "tertiarycolour" => {
+                self.outline_colour = AssColor::parse(text)?
+            },
+            | "backcolour" => self.back_colour = AssColor::parse(text)?,
+            | "bold" => self.bold = parse_bool(text)?,
+            | "italic" => self.italic = parse_bool(text)?,
+            | "underline" => self.underline = parse_bool(text)?,
+            | "strikeout" => self.strike_out = parse_bool(text)?,
+            | "scalex" => self.scale_x = text.parse().ok()?,
+            | "scaley" => self.scale_y = text.parse().ok()?,
+            | "spacing" => self.spacing = text.parse().ok()?,
+            | "angle" => self.angle = text.parse().ok()?,
+            | "borderstyle" => self.border_style = text.parse().ok()?,
+            | "outline" => self.outline = text.parse().ok()?,
+            | "shadow" => self.shadow = text.parse().ok()?,
+            | "alignment" => self.alignment = text.parse().ok()?,
+            | "marginl" => self.margin_l = text.parse().ok()?,
+            | "marginr" => self.margin_r = text.parse().ok()?,
+            | "marginv" => self.margin_v = text.parse().ok()?,
+            | "alphalevel" => self.alpha_level = text.parse().ok()?,
+            | "encoding" => self.encoding = text.parse().ok()?,
+            | _ => {},
+        }
+        Some(())
+    }
+}
+
+/// Normalizes a field name of the format line for matching.
+fn normalize_field_name(name: &str) -> String {
+    name.trim()
+        .to_ascii_lowercase()
+        .replace("color", "colour")
+}
+
+/// Parses a boolean field written as `-1` or `0`.
+fn parse_bool(field: &str) -> Option<bool> {
+    field
+        .parse::<i32>()
+        .ok()
+        .map(|value| value != 0)
+}
+
+impl Default for AssStyle {
+    fn default() -> Self {
+        Self {
+            name: "Default".to_string(),
+            font_name: "Arial".to_string(),
+            font_size: 20.0,
+            primary_colour: AssColor {
+                red: 0xFF,
+                green: 0xFF,
+                blue: 0xFF,
+                alpha: 0,
+            },
+            secondary_colour: AssColor {
+                red: 0xFF,
+                green: 0,
+                blue: 0,
+                alpha: 0,
+            },
+            outline_colour: AssColor::default(),
+            back_colour: AssColor::default(),
+            bold: false,
+            italic: false,
+            underline: false,
+            strike_out: false,
+            scale_x: 100.0,
+            scale_y: 100.0,
+            spacing: 0.0,
+            angle: 0.0,
+            border_style: 1,
+            outline: 2.0,
+            shadow: 2.0,
+            alignment: 2,
+            margin_l: 10,
+            margin_r: 10,
+            margin_v: 10,
+            alpha_level: 0,
+            encoding: 1,
+            raw_fields: vec![],
+        }
+    }
+}
+
+/// The colour written as `&HAABBGGRR`.
+///
+/// The alpha is `0` for opaque and `0xFF` for transparent.
+///
+/// ## Example
+/// ```
+/// use subtp::ass::AssColor;
+///
+/// let colour = AssColor::parse("&H8000FFFF").unwrap();
+///
+/// assert_eq!(
+///     colour,
+///     AssColor {
+///         red: 0xFF,
+///         green: 0xFF,
+///         blue: 0,
+///         alpha: 0x80,
+///     }
+/// );
+/// assert_eq!(colour.to_string(), "&H8000FFFF".to_string());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AssColor {
+    /// The red.
+    pub red: u8,
+    /// The green.
+    pub green: u8,
+    /// The blue.
+    pub blue: u8,
+    /// The alpha.
+    pub alpha: u8,
+}
+
+impl AssColor {
+    /// Parses the colour from the hexadecimal `&HAABBGGRR` or the decimal form,
+    /// where a negative decimal is the signed 32-bit value of the SubStation Alpha.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let value = if let Some(hex) = text
+            .strip_prefix("&H")
+            .or_else(|| text.strip_prefix("&h"))
+        {
+            u32::from_str_radix(hex.trim_end_matches('&'), 16).ok()?
+        } else {
+            let value = text.parse::<i64>().ok()?;
+            u32::try_from(value)
+                .ok()
+                .or_else(|| {
+                    i32::try_from(value)
+                        .ok()
+                        .map(|value| value as u32)
+                })?
+        };
+
+        Some(Self::from_value(value))
+    }
+
+    /// Creates the colour from the value of `0xAABBGGRR`.
+    pub fn from_value(value: u32) -> Self {
+        let [
+            red,
+            green,
+            blue,
+            alpha,
+        ] = value.to_le_bytes();
+        Self {
+            red,
+            green,
+            blue,
+            alpha,
+        }
+    }
+
+    /// Gets the value of `0xAABBGGRR`.
+    pub fn value(&self) -> u32 {
+        u32::from_le_bytes([
+            self.red, self.green, self.blue, self.alpha,
+        ])
+    }
+}
+
+impl Display for AssColor {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "&H{:08X}", self.value())
+    }
+}
+
+/// The `[Events]` section.
+///
+/// Each event is rendered by the field order of the format line.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssEvents {
+    /// The field names of the format line.
+    pub format: Vec<String>,
+    /// The events.
+    pub events: Vec<AssEvent>,
+}
+
+impl Default for AssEvents {
+    fn default() -> Self {
+        Self {
+            format: [
+                "Layer", "Start", "End", "Style", "Name", "MarginL", "MarginR",
+                "MarginV", "Effect", "Text",
+            ]
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+            events: vec![],
+        }
+    }
+}
+
+impl Display for AssEvents {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "[Events]")?;
+        writeln!(f, "Format: {}", self.format.join(", "))?;
+
+        for event in &self.events {
+            let fields = self
+                .format
+                .iter()
+                .enumerate()
+                .map(|(index, name)| event.render_field(index, name))
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "{}: {}",
+                event.kind,
+                fields.join(",")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The kind of event line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AssEventKind {
+    /// The `Dialogue` line.
+    #[default]
+    Dialogue,
+    /// The `Comment` line, which is not displayed.
+    Comment,
+    /// The `Picture` line.
+    Picture,
+    /// The `Sound` line.
+    Sound,
+    /// The `Movie` line.
+    Movie,
+    /// The `Command` line.
+    Command,
+}
+
+impl Display for AssEventKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Dialogue => {
+                write!(f, "Dialogue")
+            },
+            | Self::Comment => {
+                write!(f, "Comment")
+            },
+            | Self::Picture => {
+                write!(f, "Picture")
+            },
+            | Self::Sound => {
+                write!(f, "Sound")
+            },
+            | Self::Movie => {
+                write!(f, "Movie")
+            },
+            | Self::Command => {
+                write!(f, "Command")
+            },
+        }
+    }
+}
+
+/// The event.
+///
+/// ## Example
+/// ```
+/// use subtp::ass::AssEvent;
+/// use subtp::ass::AssTimestamp;
+///
+/// let event = AssEvent {
+///     start: AssTimestamp {
+///         seconds: 1,
+///         ..Default::default()
+///     },
+///     end: AssTimestamp {
+///         seconds: 2,
+///         centiseconds: 50,
+///         ..Default::default()
+///     },
+///     style: "Default".to_string(),
+///     text: "Hello, world!".to_string(),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     event.to_string(),
+///     "Dialogue: 0,0:00:01.00,0:00:02.50,Default,,0,0,0,,Hello, world!".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct AssEvent {
+    /// The kind of event line.
+    pub kind: AssEventKind,
+    /// The `Layer`, or the `Marked` in the SubStation Alpha.
+    pub layer: i32,
+    /// The `Start` timestamp.
+    pub start: AssTimestamp,
+    /// The `End` timestamp.
+    pub end: AssTimestamp,
+    /// The `Style` name.
+    pub style: String,
+    /// The `Name` of the actor.
+    pub name: String,
+    /// The `MarginL` override in pixels.
+    pub margin_l: i32,
+    /// The `MarginR` override in pixels.
+    pub margin_r: i32,
+    /// The `MarginV` override in pixels.
+    pub margin_v: i32,
+    /// The `Effect`.
+    pub effect: String,
+    /// The `Text` with override tags.
+    pub text: String,
+    /// The raw text of the fields in the order of the format line,
+    /// which is rendered for the fields not edited since parsed and the unknown fields.
+    pub raw_fields: Vec<String>,
+}
+
+impl AssEvent {
+    /// Renders the field value by the field name of the format line.
+    fn field(
+        &self,
+        name: &str,
+    ) -> String {
+        match normalize_field_name(name).as_str() {
+            | "layer" => self.layer.to_string(),
+            | "marked" => format!("Marked={}", self.layer),
+            | "start" => self.start.to_string(),
+            | "end" => self.end.to_string(),
+            | "style" => self.style.clone(),
+            | "name" | "actor" => self.name.clone(),
+            | "marginl" => self.margin_l.to_string(),
+            | "marginr" => self.margin_r.to_string(),
+            | "marginv" => self.margin_v.to_string(),
+            | "effect" => self.effect.clone(),
+            | "text" => self.text.clone(),
+            | _ => String::new(),
+        }
+    }
+
+    /// Renders the field by the raw text at the index if the value is not edited.
+    fn render_field(
+        &self,
+        index: usize,
+        name: &str,
+    ) -> String {
+        let value = self.field(name);
+        let Some(raw) = self.raw_fields.get(index) else {
+            return value;
+        };
+
+        let mut parsed = Self::default();
+        match parsed.set_field(name, raw) {
+            | Some(()) if parsed.field(name) == value => raw.clone(),
+            | _ => value,
+        }
+    }
+
+    /// Sets the field value from the text by the field name of the format line,
+    /// or `None` if the text is invalid for the field.
+    pub(crate) fn set_field(
+        &mut self,
+        name: &str,
+        text: &str,
+    ) -> Option<()> {
+        match normalize_field_name(name).as_str() {
+            | "layer" => self.layer = text.trim().parse().ok()?,
+            | "marked" => {
+                self.layer = text
+                    .trim()
+                    .trim_start_matches("Marked=")
+                    .parse()
+                    .ok()?
+            },
+            | "start" => {
+                self.start = ass_parser::timestamp(text.trim()).ok()?
+            },
+            | "end" => self.end = ass_parser::timestamp(text.trim()).ok()?,
+            | "style" => self.style = text.trim().to_string(),
+            | "name" | "actor" => self.name = text.trim().to_string(),
+            | "marginl" => self.margin_l = text.trim().parse().ok()?,
+            | "marginr" => self.margin_r = text.trim().parse().ok()?,
+            | "marginv" => self.margin_v = text.trim().parse().ok()?,
+            | "effect" => self.effect = text.trim().to_string(),
+            | "text" => self.text = text.to_string(),
+            | _ => {},
+        }
+        Some(())
+    }
+}
+
+impl Display for AssEvent {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let fields = AssEvents::default()
+            .format
+            .iter()
+            .enumerate()
+            .map(|(index, name)| self.render_field(index, name))
+            .collect::<Vec<_>>();
+        write!(f, "{}: {}", self.kind, fields.join(","))
+    }
+}
+
+/// The timestamp written as `h:mm:ss.cc`.
+///
+/// ## Example
+/// ```
+/// use subtp::ass::AssTimestamp;
+///
+/// let timestamp = AssTimestamp {
+///     hours: 1,
+///     minutes: 2,
+///     seconds: 3,
+///     centiseconds: 4,
+/// };
+///
+/// assert_eq!(
+///     timestamp.to_string(),
+///     "1:02:03.04".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd, Default)]
+pub struct AssTimestamp {
+    /// The hours.
+    pub hours: u32,
+    /// The minutes.
+    pub minutes: u8,
+    /// The seconds.
+    pub seconds: u8,
+    /// The centiseconds.
+    pub centiseconds: u8,
+}
+
+impl AssTimestamp {
+    /// The maximum timestamp.
+    pub const MAX: Self = Self {
+        hours: u32::MAX,
+        minutes: 59,
+        seconds: 59,
+        centiseconds: 99,
+    };
+
+    /// Converts from the duration truncated to centiseconds, or `None` if the hours overflow.
+    ///
+    /// ## Example
+    /// ```
+    /// use std::time::Duration;
+    /// use subtp::ass::AssTimestamp;
+    ///
+    /// let timestamp = AssTimestamp::checked_from_duration(Duration::from_secs(300 * 3600)).unwrap();
+    /// assert_eq!(timestamp.to_string(), "300:00:00.00");
+    ///
+    /// assert_eq!(AssTimestamp::checked_from_duration(Duration::MAX), None);
+    /// ```
+    pub fn checked_from_duration(duration: Duration) -> Option<Self> {
+        let seconds = duration.as_secs();
+
+        Some(Self {
+            hours: u32::try_from(seconds / 3600).ok()?,
+            minutes: ((seconds % 3600) / 60) as u8,
+            seconds: (seconds % 60) as u8,
+            centiseconds: (duration.subsec_millis() / 10) as u8,
+        })
+    }
+}
+
+impl Display for AssTimestamp {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{:02}:{:02}.{:02}",
+            self.hours, self.minutes, self.seconds, self.centiseconds
+        )
+    }
+}
+
+/// Converts from the duration truncated to centiseconds, saturating at [`AssTimestamp::MAX`].
+impl From<Duration> for AssTimestamp {
+    fn from(duration: Duration) -> Self {
+        Self::checked_from_duration(duration).unwrap_or(Self::MAX)
+    }
+}
+
+impl From<AssTimestamp> for Duration {
+    fn from(timestamp: AssTimestamp) -> Self {
+        Duration::from_secs(
+            timestamp.hours as u64 * 3600
+                + timestamp.minutes as u64 * 60
+                + timestamp.seconds as u64,
+        ) + Duration::from_millis(timestamp.centiseconds as u64 * 10)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = r#"[Script Info]
+; Script generated by Aegisub
+Title: Sample
+ScriptType: v4.00+
+PlayResX: 1920
+PlayResY: 1080
+
+[Aegisub Project Garbage]
+Last Style Storage: Default
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,2,2,10,10,10,1
+Style: Sign,Times New Roman,36.5,&H0000FFFF,&H000000FF,&H00000000,&H80000000,-1,-1,0,0,100,100,0,0,1,2,0,8,10,10,10,1
+
+[Fonts]
+fontname: custom_0.ttf
+M'R8>-4"/<!O?``!L!N``&S$M`(P!*`"4`2``X`$J````
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:01.00,0:00:04.00,Default,Alice,0,0,0,,Hello, {\i1}world{\i0}!\Nsecond line
+Comment: 0,0:00:05.00,0:00:06.00,Default,,0,0,0,,Translator note
+Dialogue: 1,0:00:05.00,0:00:07.25,Sign,,0,0,0,,{\an8}Sign text
+"#;
+
+    #[test]
+    fn parse() {
+        let ass = SubStationAlpha::parse(TEXT).unwrap();
+
+        assert_eq!(ass.sections.len(), 5);
+        assert_eq!(
+            ass.script_info()
+                .unwrap()
+                .get("PlayResX"),
+            Some("1920")
+        );
+
+        let styles = ass.styles().unwrap();
+        assert_eq!(styles.version, AssStylesVersion::V4Plus);
+        assert_eq!(styles.styles.len(), 2);
+        assert_eq!(
+            styles.styles[1],
+            AssStyle {
+                name: "Sign".to_string(),
+                font_name: "Times New Roman".to_string(),
+                font_size: 36.5,
+                primary_colour: AssColor {
+                    red: 0xFF,
+                    green: 0xFF,
+                    blue: 0,
+                    alpha: 0,
+                },
+                secondary_colour: AssColor {
+                    red: 0xFF,
+                    green: 0,
+                    blue: 0,
+                    alpha: 0,
+                },
+                outline_colour: AssColor::default(),
+                back_colour: AssColor {
+                    alpha: 0x80,
+                    ..Default::default()
+                },
+                bold: true,
+                italic: true,
+                shadow: 0.0,
+                alignment: 8,
+                raw_fields: styles.styles[1]
+                    .raw_fields
+                    .clone(),
+                ..Default::default()
+            }
+        );
+
+        let events = ass.events().unwrap();
+        assert_eq!(events.events.len(), 3);
+        assert_eq!(
+            events.events[0],
+            AssEvent {
+                kind: AssEventKind::Dialogue,
+                start: AssTimestamp {
+                    seconds: 1,
+                    ..Default::default()
+                },
+                end: AssTimestamp {
+                    seconds: 4,
+                    ..Default::default()
+                },
+                style: "Default".to_string(),
+                name: "Alice".to_string(),
+                text: "Hello, {\\i1}world{\\i0}!\\Nsecond line".to_string(),
+                raw_fields: events.events[0]
+                    .raw_fields
+                    .clone(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            events.events[1].kind,
+            AssEventKind::Comment
+        );
+    }
+
+    #[test]
+    fn render() {
+        let ass = SubStationAlpha::parse(TEXT).unwrap();
+        assert_eq!(ass.render(), TEXT);
+
+        let ssa = r#"[Script Info]
+ScriptType: v4.00
+
+[V4 Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, TertiaryColour, BackColour, Bold, Italic, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, AlphaLevel, Encoding
+Style: Default,Arial,20,16777215,65535,65535,0,-1,0,1,2,2,2,10,10,10,0,0
+
+[Events]
+Format: Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: Marked=0,0:00:01.00,0:00:02.00,Default,,0,0,0,,Hello
+"#;
+        let ssa_parsed = SubStationAlpha::parse(ssa).unwrap();
+        assert_eq!(ssa_parsed.render(), ssa);
+    }
+
+    #[test]
+    fn render_raw_fields() {
+        let aegisub = r#"[Script Info]
+; Script generated by Aegisub 3.2.2
+; http://www.aegisub.org/
+Title: Default Aegisub file
+ScriptType: v4.00+
+WrapStyle: 0
+ScaledBorderAndShadow: yes
+YCbCr Matrix: None
+
+[V4+ Styles]
+Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding
+Style: Default,Arial,20.00,&H00FFFFFF,&H000000FF,&H00000000,&H00000000,0,0,0,0,100.00,100.00,0.00,0.00,1,2.00,2.00,2,10,10,10,1
+
+[Events]
+Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text
+Dialogue: 0,0:00:00.00,0:00:05.00,Default,,0000,0000,0000,,Hello
+"#;
+        let mut ass = SubStationAlpha::parse(aegisub).unwrap();
+        assert_eq!(ass.render(), aegisub);
+
+        // Edited fields are rendered from the values.
+        if let AssSection::Styles(styles) = &mut ass.sections[1] {
+            styles.styles[0].font_size = 24.5;
+        }
+        ass.events_mut()
+            .unwrap()
+            .events[0]
+            .margin_l = 5;
+        let rendered = ass.render();
+        assert!(rendered.contains("Style: Default,Arial,24.5,&H00FFFFFF,"));
+        assert!(rendered.contains(
+            "Dialogue: 0,0:00:00.00,0:00:05.00,Default,,5,0000,0000,,Hello"
+        ));
+
+        // Unknown fields and negative decimal colours.
+        let ssa = r#"[V4 Styles]
+Format: Name, Fontsize, Unknown, PrimaryColour
+Style: X,20,bar,-2147483640
+"#;
+        let mut ssa_parsed = SubStationAlpha::parse(ssa).unwrap();
+        assert_eq!(ssa_parsed.render(), ssa);
+        if let AssSection::Styles(styles) = &mut ssa_parsed.sections[0] {
+            assert_eq!(
+                styles.styles[0]
+                    .primary_colour
+                    .value(),
+                0x8000_0008
+            );
+            styles.styles[0].font_size = 21.0;
+        }
+        assert_eq!(
+            ssa_parsed.render(),
+            "[V4 Styles]\nFormat: Name, Fontsize, Unknown, PrimaryColour\nStyle: X,21,bar,-2147483640\n"
+        );
+    }
+
+    #[test]
+    fn edit_events() {
+        let mut ass = SubStationAlpha::parse(TEXT).unwrap();
+        ass.events_mut()
+            .unwrap()
+            .events
+            .retain(|event| event.kind == AssEventKind::Dialogue);
+
+        let reparsed = SubStationAlpha::parse(&ass.render()).unwrap();
+        assert_eq!(reparsed, ass);
+        assert_eq!(
+            reparsed
+                .events()
+                .unwrap()
+                .events
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn colour() {
+        assert_eq!(
+            AssColor::parse("&H00FF8040").unwrap(),
+            AssColor {
+                red: 0x40,
+                green: 0x80,
+                blue: 0xFF,
+                alpha: 0,
+            }
+        );
+        assert_eq!(
+            AssColor::parse("&HFF8040&").unwrap(),
+            AssColor {
+                red: 0x40,
+                green: 0x80,
+                blue: 0xFF,
+                alpha: 0,
+            }
+        );
+        assert_eq!(
+            AssColor::parse("255").unwrap(),
+            AssColor {
+                red: 0xFF,
+                ..Default::default()
+            }
+        );
+        assert!(AssColor::parse("red").is_none());
+        assert_eq!(
+            AssColor {
+                red: 0x40,
+                green: 0x80,
+                blue: 0xFF,
+                alpha: 0x10,
+            }
+            .to_string(),
+            "&H10FF8040"
+        );
+    }
+
+    #[test]
+    fn from_duration_to_timestamp() {
+        let timestamp: AssTimestamp = Duration::from_millis(3_661_505).into();
+        assert_eq!(
+            timestamp,
+            AssTimestamp {
+                hours: 1,
+                minutes: 1,
+                seconds: 1,
+                centiseconds: 50,
+            }
+        );
+
+        let timestamp: AssTimestamp = Duration::from_secs(300 * 3600).into();
+        assert_eq!(timestamp.hours, 300);
+        assert_eq!(
+            AssTimestamp::from(Duration::MAX),
+            AssTimestamp::MAX
+        );
+    }
+
+    #[test]
+    fn from_timestamp_to_duration() {
+        let duration: Duration = AssTimestamp {
+            hours: 1,
+            minutes: 1,
+            seconds: 1,
+            centiseconds: 50,
+        }
+        .into();
+        assert_eq!(
+            duration,
+            Duration::from_millis(3_661_500)
+        );
+    }
+
+    #[test]
+    fn subtitle_format() {
+        let ass = SubStationAlpha::parse(TEXT).unwrap();
+        let cues = ass.cues();
+
+        assert_eq!(cues.len(), 2);
+        assert_eq!(
+            cues[0].style,
+            Some("Default".to_string())
+        );
+        assert_eq!(cues[0].start, Duration::from_secs(1));
+        assert_eq!(cues[1].style, Some("Sign".to_string()));
+        assert!(
+            cues[1]
+                .spans
+                .iter()
+                .all(|span| span.italic && span.bold)
+        );
+        assert_eq!(
+            cues[1]
+                .position
+                .and_then(|position| position.line),
+            Some(crate::format::CueLine::Number(0))
+        );
+
+        let (spans, alignment) = spans_from_ass_text(
+            r"{\i1}Hello{\i0}, {\c&H0080FF&}world{\c}!\N{\b1\an7}Second\hline",
+            &CueSpan::default(),
+            None,
+        );
+        assert_eq!(alignment, Some(7));
+        assert_eq!(
+            spans,
+            vec![
+                CueSpan {
+                    text: "Hello".to_string(),
+                    italic: true,
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: ", ".to_string(),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "world".to_string(),
+                    color: Some("#ff8000".to_string()),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "!\n".to_string(),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "Second\u{a0}line".to_string(),
+                    bold: true,
+                    ..Default::default()
+                },
+            ]
+        );
+
+        let cue = Cue {
+            start: Duration::from_secs(1),
+            end: Duration::from_millis(2500),
+            spans,
+            position: position_from_settings(&settings_from_alignment(7)),
+            ..Default::default()
+        };
+        let ass = SubStationAlpha::from_cues(vec![cue.clone()]);
+        assert_eq!(
+            ass.events().unwrap().events[0].to_string(),
+            r"Dialogue: 0,0:00:01.00,0:00:02.50,Default,,0,0,0,,{\an7}{\i1}Hello{\i0}, {\c&H0080FF&}world{\c}!\N{\b1}Second"
+                .to_string()
+                + "\u{a0}line"
+        );
+        assert_eq!(
+            SubStationAlpha::parse(&ass.render())
+                .unwrap()
+                .cues(),
+            vec![Cue {
+                style: Some("Default".to_string()),
+                ..cue
+            }]
+        );
+    }
+}
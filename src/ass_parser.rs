@@ -0,0 +1,553 @@
+//! A parser implementation for the Advanced SubStation Alpha format.
+
+pub(crate) use ass_parser::ass;
+pub(crate) use ass_parser::timestamp;
+
+use crate::ass::AssEvent;
+use crate::ass::AssEventKind;
+use crate::ass::AssStyle;
+use crate::ass::AssStylesVersion;
+use crate::ass::AssTimestamp;
+
+/// Builds a style from the fields in the order of the format line.
+fn style_from_fields(
+    format: &[String],
+    fields: &str,
+) -> Option<AssStyle> {
+    let mut style = AssStyle::default();
+    let values = fields.splitn(format.len(), ',');
+    for (name, value) in format.iter().zip(values) {
+        style.set_field(name, value)?;
+        style
+            .raw_fields
+            .push(value.to_string());
+    }
+    Some(style)
+}
+
+/// Builds an event from the fields in the order of the format line.
+fn event_from_fields(
+    kind: AssEventKind,
+    format: &[String],
+    fields: &str,
+) -> Option<AssEvent> {
+    let mut event = AssEvent {
+        kind,
+        ..Default::default()
+    };
+    let values = fields.splitn(format.len(), ',');
+    for (name, value) in format.iter().zip(values) {
+        event.set_field(name, value)?;
+        event
+            .raw_fields
+            .push(value.to_string());
+    }
+    Some(event)
+}
+
+peg::parser! {
+    /// The parser for the Advanced SubStation Alpha format.
+    grammar ass_parser() for str {
+        use crate::ass::SubStationAlpha;
+        use crate::ass::AssSection;
+        use crate::ass::AssScriptInfo;
+        use crate::ass::AssScriptInfoEntry;
+        use crate::ass::AssStyles;
+        use crate::ass::AssEvents;
+        use crate::ass::AssRawSection;
+        use super::AssEvent;
+        use super::AssEventKind;
+        use super::AssStyle;
+        use super::AssStylesVersion;
+        use super::AssTimestamp;
+        use super::event_from_fields;
+        use super::style_from_fields;
+
+        /// Whitespace.
+        rule whitespace() = [' ' | '\t']
+
+        /// Newline.
+        rule newline() = "\r\n" / "\n" / "\r"
+
+        /// End of a line.
+        rule eol() = whitespace()* (newline() / ![_])
+
+        /// Any-digit number.
+        rule number() -> u32
+            = n:$(['0'..='9']+) {?
+                n.parse().or(Err("number in u32"))
+            }
+
+        /// Two-digit number.
+        rule two_number() -> u8
+            = n:$(['0'..='9']['0'..='9']) {?
+                n.parse().or(Err("two-digit number"))
+            }
+
+        /// Text until the end of the line.
+        rule rest_of_line() -> &'input str
+            = $((!newline() [_])*)
+
+        /// Blank lines or comment lines that are skipped.
+        rule skipped()
+            = (whitespace()* newline() / ";" rest_of_line() newline())*
+
+        /// Timestamp.
+        pub(crate) rule timestamp() -> AssTimestamp
+            = hours:number() ":" minutes:two_number() ":" seconds:two_number() "." centiseconds:two_number()
+            {
+                AssTimestamp {
+                    hours,
+                    minutes,
+                    seconds,
+                    centiseconds,
+                }
+            }
+
+        /// Header of a section.
+        rule section_header(name: &'static str)
+            = "[" n:$((!("]" / newline()) [_])+) "]" eol()
+            {?
+                if n.eq_ignore_ascii_case(name) {
+                    Ok(())
+                } else {
+                    Err(name)
+                }
+            }
+
+        /// Entry of the script info section.
+        rule script_info_entry() -> AssScriptInfoEntry
+            = ";" comment:rest_of_line() newline()?
+            {
+                AssScriptInfoEntry::Comment(comment.trim_start().to_string())
+            }
+            / !"[" key:$((!(":" / newline()) [_])+) ":" whitespace()* value:rest_of_line() newline()?
+            {
+                AssScriptInfoEntry::Property {
+                    key: key.trim().to_string(),
+                    value: value.trim_end().to_string(),
+                }
+            }
+
+        /// Script info section.
+        pub(crate) rule script_info() -> AssScriptInfo
+            = section_header("Script Info")
+                entries:(whitespace()* newline() { None } / e:script_info_entry() { Some(e) })*
+            {
+                AssScriptInfo {
+                    entries: entries.into_iter().flatten().collect(),
+                }
+            }
+
+        /// Format line.
+        rule format() -> Vec<String>
+            = "Format:" whitespace()* fields:$((!("," / newline()) [_])*) ++ "," eol()
+            {
+                fields
+                    .iter()
+                    .map(|field| field.trim().to_string())
+                    .collect()
+            }
+
+        /// Header of the styles section.
+        rule styles_version() -> AssStylesVersion
+            = section_header("V4+ Styles") { AssStylesVersion::V4Plus }
+                / section_header("V4 Styles") { AssStylesVersion::V4 }
+
+        /// Style line.
+        rule style(format: &[String]) -> AssStyle
+            = "Style:" whitespace()* fields:rest_of_line() (newline() / ![_])
+            {?
+                style_from_fields(format, fields).ok_or("valid style fields")
+            }
+
+        /// Styles section.
+        pub(crate) rule styles() -> AssStyles
+            = version:styles_version() skipped()
+                format:format() skipped()
+                styles:(s:style(&format) skipped() { s })*
+            {
+                AssStyles {
+                    version,
+                    format,
+                    styles,
+                }
+            }
+
+        /// Kind of event.
+        rule event_kind() -> AssEventKind
+            = "Dialogue" { AssEventKind::Dialogue }
+                / "Comment" { AssEventKind::Comment }
+                / "Picture" { AssEventKind::Picture }
+                / "Sound" { AssEventKind::Sound }
+                / "Movie" { AssEventKind::Movie }
+                / "Command" { AssEventKind::Command }
+
+        /// Event line.
+        rule event(format: &[String]) -> AssEvent
+            = kind:event_kind() ":" " "? fields:rest_of_line() (newline() / ![_])
+            {?
+                event_from_fields(kind, format, fields).ok_or("valid event fields")
+            }
+
+        /// Events section.
+        pub(crate) rule events() -> AssEvents
+            = section_header("Events") skipped()
+                format:format() skipped()
+                events:(e:event(&format) skipped() { e })*
+            {
+                AssEvents {
+                    format,
+                    events,
+                }
+            }
+
+        /// Line of a section passed through.
+        rule raw_line() -> String
+            = !"[" line:$((!newline() [_])+) (newline() / ![_])
+            {
+                line.trim_end().to_string()
+            }
+
+        /// Name of a section passed through.
+        rule raw_section_name() -> String
+            = "[" name:$((!("]" / newline()) [_])+) "]" eol()
+            {
+                name.to_string()
+            }
+
+        /// Section passed through.
+        rule raw_section() -> AssRawSection
+            = name:raw_section_name()
+                lines:(whitespace()* newline() { None } / l:raw_line() { Some(l) })*
+            {
+                AssRawSection {
+                    name,
+                    lines: lines.into_iter().flatten().collect(),
+                }
+            }
+
+        /// Any section.
+        pub(crate) rule section() -> AssSection
+            = s:script_info() { AssSection::ScriptInfo(s) }
+                / s:styles() { AssSection::Styles(s) }
+                / s:events() { AssSection::Events(s) }
+                / s:raw_section() {?
+                    if s.name.eq_ignore_ascii_case("Fonts") {
+                        Ok(AssSection::Fonts(s.lines))
+                    } else if s.name.eq_ignore_ascii_case("Graphics") {
+                        Ok(AssSection::Graphics(s.lines))
+                    } else {
+                        Ok(AssSection::Other(s))
+                    }
+                }
+
+        /// The entire Advanced SubStation Alpha file.
+        pub(crate) rule ass() -> SubStationAlpha
+            = "\u{feff}"? (whitespace() / newline())*
+                sections:section()*
+                (whitespace() / newline())*
+            {
+                SubStationAlpha {
+                    sections,
+                }
+            }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ass_parser;
+    use crate::ass::*;
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(
+            ass_parser::timestamp("0:00:01.50").unwrap(),
+            AssTimestamp {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                centiseconds: 50,
+            }
+        );
+        assert_eq!(
+            ass_parser::timestamp("12:34:56.78").unwrap(),
+            AssTimestamp {
+                hours: 12,
+                minutes: 34,
+                seconds: 56,
+                centiseconds: 78,
+            }
+        );
+        assert_eq!(
+            ass_parser::timestamp("300:00:00.00")
+                .unwrap()
+                .hours,
+            300
+        );
+
+        // Invalid digits.
+        assert!(ass_parser::timestamp("0:0:01.50").is_err());
+        assert!(ass_parser::timestamp("0:00:1.50").is_err());
+        assert!(ass_parser::timestamp("0:00:01.500").is_err());
+        // Invalid separators.
+        assert!(ass_parser::timestamp("0:00:01,50").is_err());
+    }
+
+    #[test]
+    fn script_info() {
+        let text = "[Script Info]\n; Comment line\nTitle: Sample\nScriptType: v4.00+\n\nPlayResX: 1920\n";
+        assert_eq!(
+            ass_parser::script_info(text).unwrap(),
+            AssScriptInfo {
+                entries: vec![
+                    AssScriptInfoEntry::Comment("Comment line".to_string()),
+                    AssScriptInfoEntry::Property {
+                        key: "Title".to_string(),
+                        value: "Sample".to_string(),
+                    },
+                    AssScriptInfoEntry::Property {
+                        key: "ScriptType".to_string(),
+                        value: "v4.00+".to_string(),
+                    },
+                    AssScriptInfoEntry::Property {
+                        key: "PlayResX".to_string(),
+                        value: "1920".to_string(),
+                    },
+                ],
+            }
+        );
+
+        assert!(ass_parser::script_info("[Events]\n").is_err());
+    }
+
+    #[test]
+    fn styles() {
+        let text = "[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, Bold, Alignment\nStyle: Default,Arial,48,&H00FFFFFF,-1,2\nStyle: Top, Arial ,36.5,&H80FF0000,0,8\n";
+        assert_eq!(
+            ass_parser::styles(text).unwrap(),
+            AssStyles {
+                version: AssStylesVersion::V4Plus,
+                format: vec![
+                    "Name".to_string(),
+                    "Fontname".to_string(),
+                    "Fontsize".to_string(),
+                    "PrimaryColour".to_string(),
+                    "Bold".to_string(),
+                    "Alignment".to_string(),
+                ],
+                styles: vec![
+                    AssStyle {
+                        name: "Default".to_string(),
+                        font_name: "Arial".to_string(),
+                        font_size: 48.0,
+                        primary_colour: AssColor {
+                            red: 0xFF,
+                            green: 0xFF,
+                            blue: 0xFF,
+                            alpha: 0,
+                        },
+                        bold: true,
+                        alignment: 2,
+                        raw_fields: [
+                            "Default",
+                            "Arial",
+                            "48",
+                            "&H00FFFFFF",
+                            "-1",
+                            "2"
+                        ]
+                        .map(String::from)
+                        .to_vec(),
+                        ..Default::default()
+                    },
+                    AssStyle {
+                        name: "Top".to_string(),
+                        font_name: "Arial".to_string(),
+                        font_size: 36.5,
+                        primary_colour: AssColor {
+                            red: 0,
+                            green: 0,
+                            blue: 0xFF,
+                            alpha: 0x80,
+                        },
+                        bold: false,
+                        alignment: 8,
+                        raw_fields: [
+                            "Top",
+                            " Arial ",
+                            "36.5",
+                            "&H80FF0000",
+                            "0",
+                            "8"
+                        ]
+                        .map(String::from)
+                        .to_vec(),
+                        ..Default::default()
+                    },
+                ],
+            }
+        );
+
+        // SubStation Alpha styles with decimal colours.
+        let text = "[V4 Styles]\nFormat: Name, PrimaryColour, TertiaryColour\nStyle: Default,16777215,255\n";
+        let styles = ass_parser::styles(text).unwrap();
+        assert_eq!(styles.version, AssStylesVersion::V4);
+        assert_eq!(
+            styles.styles[0].primary_colour,
+            AssColor {
+                red: 0xFF,
+                green: 0xFF,
+                blue: 0xFF,
+                alpha: 0,
+            }
+        );
+        assert_eq!(
+            styles.styles[0].outline_colour,
+            AssColor {
+                red: 0xFF,
+                green: 0,
+                blue: 0,
+                alpha: 0,
+            }
+        );
+
+        // Invalid field value.
+        assert!(
+            ass_parser::styles(
+                "[V4+ Styles]\nFormat: Name, Fontsize\nStyle: Default,large\n"
+            )
+            .is_err()
+        );
+        // Missing format line.
+        assert!(ass_parser::styles("[V4+ Styles]\nStyle: Default\n").is_err());
+    }
+
+    #[test]
+    fn events() {
+        let text = "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: 0,0:00:01.00,0:00:04.00,Default,Alice,0,0,0,,Hello, {\\i1}world{\\i0}!\\Nsecond line\nComment: 1,0:00:05.00,0:00:06.50,Default,,0,0,0,,note\n";
+        assert_eq!(
+            ass_parser::events(text).unwrap(),
+            AssEvents {
+                format: vec![
+                    "Layer".to_string(),
+                    "Start".to_string(),
+                    "End".to_string(),
+                    "Style".to_string(),
+                    "Name".to_string(),
+                    "MarginL".to_string(),
+                    "MarginR".to_string(),
+                    "MarginV".to_string(),
+                    "Effect".to_string(),
+                    "Text".to_string(),
+                ],
+                events: vec![
+                    AssEvent {
+                        kind: AssEventKind::Dialogue,
+                        layer: 0,
+                        start: AssTimestamp {
+                            seconds: 1,
+                            ..Default::default()
+                        },
+                        end: AssTimestamp {
+                            seconds: 4,
+                            ..Default::default()
+                        },
+                        style: "Default".to_string(),
+                        name: "Alice".to_string(),
+                        text: "Hello, {\\i1}world{\\i0}!\\Nsecond line"
+                            .to_string(),
+                        raw_fields: [
+                            "0",
+                            "0:00:01.00",
+                            "0:00:04.00",
+                            "Default",
+                            "Alice",
+                            "0",
+                            "0",
+                            "0",
+                            "",
+                            "Hello, {\\i1}world{\\i0}!\\Nsecond line",
+                        ]
+                        .map(String::from)
+                        .to_vec(),
+                        ..Default::default()
+                    },
+                    AssEvent {
+                        kind: AssEventKind::Comment,
+                        layer: 1,
+                        start: AssTimestamp {
+                            seconds: 5,
+                            ..Default::default()
+                        },
+                        end: AssTimestamp {
+                            seconds: 6,
+                            centiseconds: 50,
+                            ..Default::default()
+                        },
+                        style: "Default".to_string(),
+                        text: "note".to_string(),
+                        raw_fields: [
+                            "1",
+                            "0:00:05.00",
+                            "0:00:06.50",
+                            "Default",
+                            "",
+                            "0",
+                            "0",
+                            "0",
+                            "",
+                            "note",
+                        ]
+                        .map(String::from)
+                        .to_vec(),
+                        ..Default::default()
+                    },
+                ],
+            }
+        );
+
+        // SubStation Alpha events with marked field.
+        let text = "[Events]\nFormat: Marked, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\nDialogue: Marked=0,0:00:01.00,0:00:02.00,Default,,0000,0000,0000,,Text\n";
+        let events = ass_parser::events(text).unwrap();
+        assert_eq!(events.events[0].layer, 0);
+        assert_eq!(events.events[0].text, "Text");
+        assert_eq!(events.events[0].raw_fields[5], "0000");
+
+        // Invalid timestamp.
+        assert!(ass_parser::events(
+            "[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01,0:00:02.00,Text\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ass() {
+        let text = "\u{feff}[Script Info]\nTitle: Sample\n\n[Aegisub Project Garbage]\nLast Style Storage: Default\n\n[V4+ Styles]\nFormat: Name, Fontsize\nStyle: Default,20\n\n[Fonts]\nfontname: font.ttf\n!!!!\n\n[Events]\nFormat: Start, End, Text\nDialogue: 0:00:01.00,0:00:02.00,Hello\n";
+        let ass = ass_parser::ass(text).unwrap();
+        assert_eq!(ass.sections.len(), 5);
+        assert_eq!(
+            ass.sections[1],
+            AssSection::Other(AssRawSection {
+                name: "Aegisub Project Garbage".to_string(),
+                lines: vec!["Last Style Storage: Default".to_string()],
+            })
+        );
+        assert_eq!(
+            ass.sections[3],
+            AssSection::Fonts(vec![
+                "fontname: font.ttf".to_string(),
+                "!!!!".to_string(),
+            ])
+        );
+
+        // Empty file.
+        assert_eq!(
+            ass_parser::ass("").unwrap(),
+            SubStationAlpha {
+                sections: vec![],
+            }
+        );
+    }
+}
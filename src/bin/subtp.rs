@@ -0,0 +1,611 @@
+//! The command-line tool of subtp to convert, shift, validate and lint subtitles,
+//! enabled by the `cli` feature.
+//!
+//! ```shell
+//! $ cargo install subtp --features cli
+//! $ subtp convert input.srt -o output.vtt
+//! $ subtp shift --by -1500 input.vtt > output.vtt
+//! $ cat input.srt | subtp validate
+//! ```
+//!
+//! The input is read from the file or the standard input if omitted or `-`,
+//! decoded by detecting the character encoding,
+//! and the output is written to the file of `--output` or the standard output.
+//!
+//! Exit codes:
+//! - `0`: Succeeded.
+//! - `1`: `validate` or `lint` found problems.
+//! - `2`: Invalid arguments, or reading, parsing or writing failed.
+
+use std::fmt::Display;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::Parser;
+use clap::Subcommand;
+use clap::ValueEnum;
+use subtp::ParseError;
+use subtp::SubtitleFormat;
+use subtp::lint::Lint;
+use subtp::lint::LintRules;
+use subtp::srt::SubRip;
+use subtp::timeline::OutOfRange;
+use subtp::timeline::Timeline;
+use subtp::timeline::TimelineError;
+use subtp::validate::Validate;
+use subtp::validate::ValidateOptions;
+use subtp::vtt::WebVtt;
+
+/// The command-line arguments.
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Converts, shifts, validates and lints subtitles."
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+/// The subcommands.
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Converts between the SubRip Subtitle and the WebVTT.
+    Convert {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// The output format, or the format of the output file extension.
+        #[arg(long, value_enum)]
+        to: Option<Format>,
+    },
+    /// Shifts all timestamps by the milliseconds.
+    Shift {
+        /// The milliseconds to shift by, backward if negative.
+        #[arg(long, allow_hyphen_values = true)]
+        by: i64,
+        /// Clamps the timestamps out of range instead of failing.
+        #[arg(long)]
+        clamp: bool,
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Checks overlaps, gaps, ordering and sequence numbers of the cues.
+    Validate {
+        /// The minimum gap in milliseconds between the cues.
+        #[arg(long, default_value_t = 0)]
+        min_gap: u64,
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Checks the quality of the cues by the guidelines.
+    Lint {
+        /// The preset of the rules.
+        #[arg(long, value_enum, default_value_t = Preset::Netflix)]
+        preset: Preset,
+        #[command(flatten)]
+        input: InputArgs,
+    },
+    /// Sorts the cues by time and renumbers the sequence numbers from 1.
+    Renumber {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Merges the cues of the inputs sorted by time.
+    Merge {
+        /// The input files.
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// The format of the inputs, or detected from the extensions or the contents.
+        #[arg(long, value_enum)]
+        from: Option<Format>,
+        #[command(flatten)]
+        output: OutputArgs,
+        /// The output format, or the format of the output file extension or the first input.
+        #[arg(long, value_enum)]
+        to: Option<Format>,
+    },
+    /// Extracts the plain text of the cues without markup.
+    ExtractText {
+        #[command(flatten)]
+        input: InputArgs,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+/// The arguments of the input.
+#[derive(Debug, clap::Args)]
+struct InputArgs {
+    /// The input file, or the standard input if omitted or `-`.
+    input: Option<PathBuf>,
+    /// The input format, or detected from the extension or the content.
+    #[arg(long, value_enum)]
+    from: Option<Format>,
+}
+
+/// The arguments of the output.
+#[derive(Debug, clap::Args)]
+struct OutputArgs {
+    /// The output file, or the standard output if omitted or `-`.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// The subtitle format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// The SubRip Subtitle (.srt).
+    Srt,
+    /// The WebVTT (.vtt).
+    Vtt,
+}
+
+impl Format {
+    /// Detects the format from the extension of the path.
+    fn from_path(path: &Path) -> Option<Self> {
+        let extension = path
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            | "srt" => Some(Self::Srt),
+            | "vtt" => Some(Self::Vtt),
+            | _ => None,
+        }
+    }
+
+    /// Detects the format from the content.
+    fn from_content(text: &str) -> Self {
+        if text
+            .trim_start_matches('\u{feff}')
+            .starts_with("WEBVTT")
+        {
+            Self::Vtt
+        } else {
+            Self::Srt
+        }
+    }
+}
+
+/// The preset of the lint rules.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Preset {
+    /// The BBC subtitle guidelines.
+    Bbc,
+    /// The Netflix timed text style guide for English.
+    Netflix,
+    /// The Netflix timed text style guide for Japanese.
+    NetflixJapanese,
+}
+
+impl From<Preset> for LintRules {
+    fn from(preset: Preset) -> Self {
+        match preset {
+            | Preset::Bbc => LintRules::BBC,
+            | Preset::Netflix => LintRules::NETFLIX,
+            | Preset::NetflixJapanese => LintRules::NETFLIX_JAPANESE,
+        }
+    }
+}
+
+/// The error of the command.
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    /// Reading the input failed.
+    #[error("failed to read {name}: {source}")]
+    Read {
+        name: String,
+        source: std::io::Error,
+    },
+    /// Writing the output failed.
+    #[error("failed to write {name}: {source}")]
+    Write {
+        name: String,
+        source: std::io::Error,
+    },
+    /// Parsing the input failed.
+    #[error("failed to parse {name}: {source}\n{excerpt}")]
+    Parse {
+        name: String,
+        source: Box<ParseError>,
+        excerpt: String,
+    },
+    /// Editing the timeline failed.
+    #[error(transparent)]
+    Timeline(#[from] TimelineError),
+}
+
+/// The parsed subtitle document.
+#[derive(Debug, Clone, PartialEq)]
+enum Document {
+    /// The SubRip Subtitle.
+    SubRip(SubRip),
+    /// The WebVTT.
+    WebVtt(WebVtt),
+}
+
+impl Document {
+    /// Reads and parses the input from the file or the standard input.
+    fn read(
+        path: Option<&Path>,
+        format: Option<Format>,
+    ) -> Result<Self, CliError> {
+        let path = path.filter(|path| *path != Path::new("-"));
+        let name = display_name(path);
+        let mut bytes = Vec::new();
+        match path {
+            | Some(path) => {
+                std::fs::File::open(path)
+                    .and_then(|mut file| file.read_to_end(&mut bytes))
+            },
+            | None => std::io::stdin().read_to_end(&mut bytes),
+        }
+        .map_err(|source| {
+            CliError::Read {
+                name: name.clone(),
+                source,
+            }
+        })?;
+        let text = subtp::encoding::decode(&bytes).output;
+
+        let format = format
+            .or_else(|| path.and_then(Format::from_path))
+            .unwrap_or_else(|| Format::from_content(&text));
+        let document = match format {
+            | Format::Srt => SubRip::parse(&text).map(Self::SubRip),
+            | Format::Vtt => WebVtt::parse(&text).map(Self::WebVtt),
+        };
+
+        document.map_err(|source| {
+            CliError::Parse {
+                name,
+                excerpt: source
+                    .render_excerpt()
+                    .trim_end()
+                    .to_string(),
+                source: Box::new(source),
+            }
+        })
+    }
+
+    /// The format of the document.
+    fn format(&self) -> Format {
+        match self {
+            | Self::SubRip(_) => Format::Srt,
+            | Self::WebVtt(_) => Format::Vtt,
+        }
+    }
+
+    /// Converts the document to the format.
+    fn into_format(
+        self,
+        format: Format,
+    ) -> Self {
+        match (self, format) {
+            | (Self::SubRip(srt), Format::Vtt) => Self::WebVtt(srt.into()),
+            | (Self::WebVtt(vtt), Format::Srt) => Self::SubRip(vtt.into()),
+            | (document, _) => document,
+        }
+    }
+
+    /// Merges the cues and the other blocks of the document of the same format into this document.
+    fn append(
+        &mut self,
+        other: Self,
+    ) {
+        let other = other.into_format(self.format());
+        match (self, other) {
+            | (Self::SubRip(srt), Self::SubRip(other)) => {
+                srt.subtitles
+                    .extend(other.subtitles)
+            },
+            | (Self::WebVtt(vtt), Self::WebVtt(other)) => {
+                vtt.blocks
+                    .extend(other.blocks)
+            },
+            | _ => unreachable!("converted to the same format"),
+        }
+    }
+
+    /// Renders the document.
+    fn render(&self) -> String {
+        match self {
+            | Self::SubRip(srt) => srt.render(),
+            | Self::WebVtt(vtt) => vtt.render(),
+        }
+    }
+
+    /// The document as the operations of the commands.
+    fn subtitles(&self) -> &dyn Subtitles {
+        match self {
+            | Self::SubRip(srt) => srt,
+            | Self::WebVtt(vtt) => vtt,
+        }
+    }
+
+    /// The document as the mutable operations of the commands.
+    fn subtitles_mut(&mut self) -> &mut dyn Subtitles {
+        match self {
+            | Self::SubRip(srt) => srt,
+            | Self::WebVtt(vtt) => vtt,
+        }
+    }
+}
+
+/// The operations of the commands on any format.
+trait Subtitles {
+    /// Shifts all timestamps by the milliseconds.
+    fn shift(
+        &mut self,
+        milliseconds: i64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>;
+
+    /// Validates the cues and formats the findings.
+    fn validate(
+        &self,
+        options: &ValidateOptions,
+    ) -> Vec<String>;
+
+    /// Lints the cues and formats the violations.
+    fn lint(
+        &self,
+        rules: &LintRules,
+    ) -> Vec<String>;
+
+    /// Sorts the cues by time and renumbers the sequence numbers.
+    fn sort_and_renumber(&mut self);
+
+    /// The plain text of each cue.
+    fn plain_texts(&self) -> Vec<String>;
+}
+
+impl<T> Subtitles for T
+where T: SubtitleFormat + Timeline + Validate + Lint
+{
+    fn shift(
+        &mut self,
+        milliseconds: i64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError> {
+        Timeline::shift(self, milliseconds, out_of_range)
+    }
+
+    fn validate(
+        &self,
+        options: &ValidateOptions,
+    ) -> Vec<String> {
+        to_strings(Validate::validate(self, options))
+    }
+
+    fn lint(
+        &self,
+        rules: &LintRules,
+    ) -> Vec<String> {
+        to_strings(Lint::lint(self, rules))
+    }
+
+    fn sort_and_renumber(&mut self) {
+        self.sort_cues();
+        self.renumber();
+    }
+
+    fn plain_texts(&self) -> Vec<String> {
+        self.iter_cues()
+            .map(|cue| cue.plain_text())
+            .collect()
+    }
+}
+
+/// Formats the items for messages.
+fn to_strings<T: Display>(items: Vec<T>) -> Vec<String> {
+    items
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// The name of the input or the output for messages.
+fn display_name(path: Option<&Path>) -> String {
+    path.map(|path| path.display().to_string())
+        .unwrap_or_else(|| "<stdin>".to_string())
+}
+
+/// Writes the text to the file or the standard output.
+fn write_output(
+    output: &OutputArgs,
+    text: &str,
+) -> Result<(), CliError> {
+    let path = output
+        .output
+        .as_deref()
+        .filter(|path| *path != Path::new("-"));
+    match path {
+        | Some(path) => std::fs::write(path, text),
+        | None => {
+            let mut stdout = std::io::stdout().lock();
+            stdout
+                .write_all(text.as_bytes())
+                .and_then(|_| stdout.flush())
+        },
+    }
+    .map_err(|source| {
+        CliError::Write {
+            name: path
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "<stdout>".to_string()),
+            source,
+        }
+    })
+}
+
+/// Reports the problems of the input and returns whether no problems were found.
+fn report(
+    input: &InputArgs,
+    problems: Vec<String>,
+) -> bool {
+    let name = display_name(
+        input
+            .input
+            .as_deref()
+            .filter(|path| *path != Path::new("-")),
+    );
+    for problem in &problems {
+        println!("{}: {}", name, problem);
+    }
+    problems.is_empty()
+}
+
+/// Runs the command and returns whether no problems were found.
+fn run(command: Command) -> Result<bool, CliError> {
+    match command {
+        | Command::Convert {
+            input,
+            output,
+            to,
+        } => {
+            let document = Document::read(input.input.as_deref(), input.from)?;
+            let format = to
+                .or_else(|| {
+                    output
+                        .output
+                        .as_deref()
+                        .and_then(Format::from_path)
+                })
+                .unwrap_or(match document.format() {
+                    | Format::Srt => Format::Vtt,
+                    | Format::Vtt => Format::Srt,
+                });
+            write_output(
+                &output,
+                &document
+                    .into_format(format)
+                    .render(),
+            )?;
+            Ok(true)
+        },
+        | Command::Shift {
+            by,
+            clamp,
+            input,
+            output,
+        } => {
+            let mut document =
+                Document::read(input.input.as_deref(), input.from)?;
+            let out_of_range = if clamp {
+                OutOfRange::Clamp
+            } else {
+                OutOfRange::Error
+            };
+            document
+                .subtitles_mut()
+                .shift(by, out_of_range)?;
+            write_output(&output, &document.render())?;
+            Ok(true)
+        },
+        | Command::Validate {
+            min_gap,
+            input,
+        } => {
+            let document = Document::read(input.input.as_deref(), input.from)?;
+            let options = ValidateOptions {
+                min_gap: std::time::Duration::from_millis(min_gap),
+            };
+            let findings = document
+                .subtitles()
+                .validate(&options);
+            Ok(report(&input, findings))
+        },
+        | Command::Lint {
+            preset,
+            input,
+        } => {
+            let document = Document::read(input.input.as_deref(), input.from)?;
+            let violations = document
+                .subtitles()
+                .lint(&preset.into());
+            Ok(report(&input, violations))
+        },
+        | Command::Renumber {
+            input,
+            output,
+        } => {
+            let mut document =
+                Document::read(input.input.as_deref(), input.from)?;
+            document
+                .subtitles_mut()
+                .sort_and_renumber();
+            write_output(&output, &document.render())?;
+            Ok(true)
+        },
+        | Command::Merge {
+            inputs,
+            from,
+            output,
+            to,
+        } => {
+            let mut documents = inputs
+                .iter()
+                .map(|input| Document::read(Some(input), from))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter();
+            let first = documents
+                .next()
+                .expect("at least one input is required");
+            let format = to
+                .or_else(|| {
+                    output
+                        .output
+                        .as_deref()
+                        .and_then(Format::from_path)
+                })
+                .unwrap_or(first.format());
+
+            let mut merged = first.into_format(format);
+            for document in documents {
+                merged.append(document);
+            }
+            merged
+                .subtitles_mut()
+                .sort_and_renumber();
+            write_output(&output, &merged.render())?;
+            Ok(true)
+        },
+        | Command::ExtractText {
+            input,
+            output,
+        } => {
+            let document = Document::read(input.input.as_deref(), input.from)?;
+            let mut text = document
+                .subtitles()
+                .plain_texts()
+                .join("\n\n");
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            write_output(&output, &text)?;
+            Ok(true)
+        },
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        | Ok(true) => ExitCode::SUCCESS,
+        | Ok(false) => ExitCode::from(1),
+        | Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        },
+    }
+}
@@ -0,0 +1,1262 @@
+//! Conversions between the SubRip Subtitle (`.srt`) and the WebVTT (`.vtt`) formats.
+//!
+//! The conversions by [`From`] are provided for convenience,
+//! and [`subrip_to_webvtt`] and [`webvtt_to_subrip`] report the data that cannot be represented in the target format.
+//!
+//! ## Example
+//! ```
+//! use subtp::convert::subrip_to_webvtt;
+//! use subtp::convert::ConversionOptions;
+//! use subtp::srt::SubRip;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r##"1
+//! 00:00:01,000 --> 00:00:02,000
+//! <i>Hello</i>, <font color="#FF8000">world</font>!
+//! "##;
+//!
+//! let srt = SubRip::parse(text).unwrap();
+//!
+//! let conversion = subrip_to_webvtt(&srt, &ConversionOptions::default());
+//! assert!(conversion.report.is_lossless());
+//! assert_eq!(
+//!     conversion.output.render(),
+//!     r##"WEBVTT
+//!
+//! STYLE
+//! ::cue(.color_ff8000) { color: #ff8000; }
+//!
+//! 1
+//! 00:00:01.000 --> 00:00:02.000
+//! <i>Hello</i>, <c.color_ff8000>world</c>!
+//! "##
+//! );
+//!
+//! // Or simply convert without the report.
+//! let vtt: WebVtt = srt.into();
+//! ```
+
+use std::fmt::Display;
+
+use crate::css::CueSelector;
+use crate::css::Property;
+use crate::css::Selector;
+use crate::css::Stylesheet;
+use crate::srt::LinePosition;
+use crate::srt::SrtNode;
+use crate::srt::SrtSpanKind;
+use crate::srt::SrtSubtitle;
+use crate::srt::SrtText;
+use crate::srt::SrtTimestamp;
+use crate::srt::SubRip;
+use crate::vtt::Alignment;
+use crate::vtt::CueSettings;
+use crate::vtt::Line;
+use crate::vtt::LineAlignment;
+use crate::vtt::Percentage;
+use crate::vtt::Position;
+use crate::vtt::PositionAlignment;
+use crate::vtt::VttBlock;
+use crate::vtt::VttCue;
+use crate::vtt::VttCueNode;
+use crate::vtt::VttCueSpan;
+use crate::vtt::VttCueSpanKind;
+use crate::vtt::VttDescription;
+use crate::vtt::VttStyle;
+use crate::vtt::VttTimestamp;
+use crate::vtt::VttTimings;
+use crate::vtt::WebVtt;
+
+/// The default color classes of WebVTT with the equivalent colors.
+pub(crate) const DEFAULT_COLOR_CLASSES: [(&str, &str); 8] = [
+    ("white", "ffffff"),
+    ("lime", "00ff00"),
+    ("cyan", "00ffff"),
+    ("red", "ff0000"),
+    ("yellow", "ffff00"),
+    ("magenta", "ff00ff"),
+    ("blue", "0000ff"),
+    ("black", "000000"),
+];
+
+/// The options of conversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ConversionOptions {
+    /// The frame size of the video in pixels,
+    /// which is required to convert the pixel coordinates of [`LinePosition`].
+    pub frame_size: Option<FrameSize>,
+}
+
+/// The frame size of the video in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FrameSize {
+    /// The width.
+    pub width: u32,
+    /// The height.
+    pub height: u32,
+}
+
+/// The converted output with the report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conversion<T> {
+    /// The converted output.
+    pub output: T,
+    /// The report of the data that cannot be represented in the output.
+    pub report: ConversionReport,
+}
+
+/// The report of a conversion.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConversionReport {
+    /// The issues in order of the source.
+    pub issues: Vec<ConversionIssue>,
+}
+
+impl ConversionReport {
+    /// Checks whether the conversion has no issues.
+    pub fn is_lossless(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn push(
+        &mut self,
+        index: Option<usize>,
+        kind: ConversionIssueKind,
+    ) {
+        self.issues
+            .push(ConversionIssue {
+                index,
+                kind,
+            });
+    }
+}
+
+impl Display for ConversionReport {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The issue of a conversion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionIssue {
+    /// The index of the subtitle or the block in the source,
+    /// or `None` for the header.
+    pub index: Option<usize>,
+    /// The kind of issue.
+    pub kind: ConversionIssueKind,
+}
+
+impl Display for ConversionIssue {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self.index {
+            | Some(index) => write!(f, "block {}: {}", index, self.kind),
+            | None => write!(f, "header: {}", self.kind),
+        }
+    }
+}
+
+/// The kind of conversion issue.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionIssueKind {
+    /// The line position was dropped because the frame size is unknown.
+    LinePositionDropped(LinePosition),
+    /// The line position was converted to the cue settings without its bottom edge.
+    LinePositionApproximated(LinePosition),
+    /// The markup was dropped and only its text was kept, if any.
+    MarkupDropped(String),
+    /// The text read as markup in the target format was escaped.
+    MarkupEscaped(String),
+    /// The cue settings were approximated by an alignment tag such as `{\an8}`.
+    CueSettingsApproximated(CueSettings),
+    /// The cue identifier was dropped in favor of the sequence number.
+    IdentifierDropped(String),
+    /// The block was dropped.
+    BlockDropped(VttBlock),
+    /// The description of the header was dropped.
+    DescriptionDropped(VttDescription),
+}
+
+impl Display for ConversionIssueKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::LinePositionDropped(position) => {
+                write!(
+                    f,
+                    "line position `{}` was dropped without the frame size",
+                    position
+                )
+            },
+            | Self::LinePositionApproximated(position) => {
+                write!(
+                    f,
+                    "line position `{}` was approximated without the bottom edge",
+                    position
+                )
+            },
+            | Self::MarkupDropped(markup) => {
+                write!(f, "markup `{}` was dropped", markup)
+            },
+            | Self::MarkupEscaped(text) => {
+                write!(
+                    f,
+                    "text `{}` was escaped not to be markup",
+                    text
+                )
+            },
+            | Self::CueSettingsApproximated(settings) => {
+                write!(
+                    f,
+                    "cue settings `{}` were approximated by an alignment tag",
+                    settings
+                )
+            },
+            | Self::IdentifierDropped(identifier) => {
+                write!(
+                    f,
+                    "cue identifier `{}` was dropped",
+                    identifier
+                )
+            },
+            | Self::BlockDropped(block) => {
+                let kind = match block {
+                    | VttBlock::Que(_) => "cue",
+                    | VttBlock::Comment(_) => "comment",
+                    | VttBlock::Style(_) => "style",
+                    | VttBlock::Region(_) => "region",
+                };
+                write!(f, "{} block was dropped", kind)
+            },
+            | Self::DescriptionDropped(description) => {
+                let description = match description {
+                    | VttDescription::Side(text) => text,
+                    | VttDescription::Below(text) => text,
+                };
+                write!(
+                    f,
+                    "description `{}` was dropped",
+                    description.trim()
+                )
+            },
+        }
+    }
+}
+
+impl From<SrtTimestamp> for VttTimestamp {
+    fn from(timestamp: SrtTimestamp) -> Self {
+        Self {
+            hours: timestamp.hours,
+            minutes: timestamp.minutes,
+            seconds: timestamp.seconds,
+            milliseconds: timestamp.milliseconds,
+        }
+    }
+}
+
+impl From<VttTimestamp> for SrtTimestamp {
+    fn from(timestamp: VttTimestamp) -> Self {
+        Self {
+            hours: timestamp.hours,
+            minutes: timestamp.minutes,
+            seconds: timestamp.seconds,
+            milliseconds: timestamp.milliseconds,
+        }
+    }
+}
+
+impl From<SubRip> for WebVtt {
+    /// Converts with the default options and discards the report.
+    fn from(subrip: SubRip) -> Self {
+        subrip_to_webvtt(&subrip, &ConversionOptions::default()).output
+    }
+}
+
+impl From<WebVtt> for SubRip {
+    /// Converts with the default options and discards the report.
+    fn from(webvtt: WebVtt) -> Self {
+        webvtt_to_subrip(&webvtt).output
+    }
+}
+
+/// Converts the SubRip Subtitle to the WebVTT with the report.
+///
+/// - The sequence number is converted to the cue identifier.
+/// - `<i>`, `<b>` and `<u>` tags are kept as they are.
+/// - `<font color>` tags are converted to the class spans with the default color classes or the `::cue` rules of a STYLE block.
+/// - `{\anN}` tags are converted to the `line` and the `align` cue settings.
+/// - The line position is converted to the `line`, the `position` and the `size` cue settings with the frame size.
+pub fn subrip_to_webvtt(
+    subrip: &SubRip,
+    options: &ConversionOptions,
+) -> Conversion<WebVtt> {
+    let mut report = ConversionReport::default();
+    let mut color_rules: Vec<(String, String)> = Vec::new();
+    let mut cues = Vec::new();
+
+    for (index, subtitle) in subrip
+        .subtitles
+        .iter()
+        .enumerate()
+    {
+        let converted = convert_srt_text(&subtitle.text.join("\n"));
+        for markup in converted.dropped {
+            report.push(
+                Some(index),
+                ConversionIssueKind::MarkupDropped(markup),
+            );
+        }
+        for rule in converted.color_rules {
+            if !color_rules.contains(&rule) {
+                color_rules.push(rule);
+            }
+        }
+
+        let mut settings = converted
+            .alignment
+            .map(settings_from_alignment)
+            .unwrap_or_default();
+        if let Some(line_position) = subtitle.line_position {
+            match options.frame_size {
+                | Some(frame_size) => {
+                    apply_line_position(
+                        &mut settings,
+                        &line_position,
+                        &frame_size,
+                    );
+                    report.push(
+                        Some(index),
+                        ConversionIssueKind::LinePositionApproximated(
+                            line_position,
+                        ),
+                    );
+                },
+                | None => {
+                    report.push(
+                        Some(index),
+                        ConversionIssueKind::LinePositionDropped(line_position),
+                    );
+                },
+            }
+        }
+
+        cues.push(VttCue {
+            identifier: Some(subtitle.sequence.to_string()),
+            timings: VttTimings {
+                start: subtitle.start.into(),
+                end: subtitle.end.into(),
+            },
+            settings: if settings == CueSettings::default() {
+                None
+            } else {
+                Some(settings)
+            },
+            payload: converted
+                .text
+                .split('\n')
+                .map(|line| line.to_string())
+                .collect(),
+        });
+    }
+
+    let mut blocks: Vec<VttBlock> = Vec::new();
+    if !color_rules.is_empty() {
+        blocks.push(color_style(&color_rules).into());
+    }
+    blocks.extend(
+        cues.into_iter()
+            .map(VttBlock::from),
+    );
+
+    Conversion {
+        output: WebVtt {
+            blocks,
+            ..Default::default()
+        },
+        report,
+    }
+}
+
+/// Converts the WebVTT to the SubRip Subtitle with the report.
+///
+/// - The cues are numbered from 1 and the other identifiers are reported.
+/// - Italic, bold and underline spans are converted to `<i>`, `<b>` and `<u>` tags.
+/// - Class spans with colors of the default color classes or the `::cue` rules of STYLE blocks are converted to `<font color>` tags.
+/// - The cue settings are converted to `{\anN}` tags.
+/// - The text read as markup in the SubRip Subtitle, e.g. `&lt;b&gt;`, is escaped and reported.
+/// - The comments, the regions, the other styles and the other markup are reported.
+pub fn webvtt_to_subrip(webvtt: &WebVtt) -> Conversion<SubRip> {
+    let mut report = ConversionReport::default();
+    if let Some(description) = &webvtt.header.description {
+        report.push(
+            None,
+            ConversionIssueKind::DescriptionDropped(description.clone()),
+        );
+    }
+
+    let mut colors = DEFAULT_COLOR_CLASSES
+        .iter()
+        .map(|(class, _)| (class.to_string(), class.to_string()))
+        .collect::<Vec<_>>();
+    let mut subtitles = Vec::new();
+
+    for (index, block) in webvtt
+        .blocks
+        .iter()
+        .enumerate()
+    {
+        match block {
+            | VttBlock::Que(cue) => {
+                let sequence = subtitles.len() as u32 + 1;
+                if let Some(identifier) = &cue.identifier {
+                    if identifier != &sequence.to_string() {
+                        report.push(
+                            Some(index),
+                            ConversionIssueKind::IdentifierDropped(
+                                identifier.clone(),
+                            ),
+                        );
+                    }
+                }
+
+                let mut text = String::new();
+                if let Some(settings) = &cue.settings {
+                    let alignment = alignment_from_settings(settings);
+                    if alignment != 2 {
+                        text.push_str(&format!("{{\\an{}}}", alignment));
+                    }
+                    if normalize_settings(settings)
+                        != settings_from_alignment(alignment)
+                    {
+                        report.push(
+                            Some(index),
+                            ConversionIssueKind::CueSettingsApproximated(
+                                settings.clone(),
+                            ),
+                        );
+                    }
+                }
+
+                let mut issues = Vec::new();
+                push_srt_text(
+                    &cue.parse_payload().nodes,
+                    &colors,
+                    &mut text,
+                    &mut issues,
+                );
+                for kind in issues {
+                    report.push(Some(index), kind);
+                }
+
+                subtitles.push(SrtSubtitle {
+                    sequence,
+                    start: cue.timings.start.into(),
+                    end: cue.timings.end.into(),
+                    text: text
+                        .split('\n')
+                        .map(|line| line.to_string())
+                        .collect(),
+                    line_position: None,
+                });
+            },
+            | VttBlock::Style(style) => {
+                match class_colors(&style.parse_stylesheet()) {
+                    | Some(rules) => colors.extend(rules),
+                    | None => {
+                        report.push(
+                            Some(index),
+                            ConversionIssueKind::BlockDropped(block.clone()),
+                        );
+                    },
+                }
+            },
+            | VttBlock::Comment(_) | VttBlock::Region(_) => {
+                report.push(
+                    Some(index),
+                    ConversionIssueKind::BlockDropped(block.clone()),
+                );
+            },
+        }
+    }
+
+    Conversion {
+        output: SubRip {
+            subtitles,
+        },
+        report,
+    }
+}
+
+/// The text of a SubRip Subtitle converted to a WebVTT cue payload.
+struct ConvertedSrtText {
+    /// The cue text.
+    text: String,
+    /// The alignment of `{\anN}`.
+    alignment: Option<u8>,
+    /// The color classes and the colors used.
+    color_rules: Vec<(String, String)>,
+    /// The markup dropped.
+    dropped: Vec<String>,
+}
+
+/// Converts the text of a SubRip Subtitle to a WebVTT cue payload.
+fn convert_srt_text(text: &str) -> ConvertedSrtText {
+    let mut converted = ConvertedSrtText {
+        text: String::new(),
+        alignment: None,
+        color_rules: Vec::new(),
+        dropped: Vec::new(),
+    };
+    push_vtt_text(
+        &SrtText::parse(text).nodes,
+        &mut converted,
+    );
+
+    converted
+}
+
+/// Pushes the nodes of the text of a SubRip Subtitle as WebVTT cue text.
+fn push_vtt_text(
+    nodes: &[SrtNode],
+    converted: &mut ConvertedSrtText,
+) {
+    for node in nodes {
+        match node {
+            | SrtNode::Text(text) => {
+                let mut rest = text.as_str();
+                while let Some(c) = rest.chars().next() {
+                    match c {
+                        | '<' => {
+                            converted
+                                .text
+                                .push_str("&lt;")
+                        },
+                        | '>' => {
+                            converted
+                                .text
+                                .push_str("&gt;")
+                        },
+                        | '&' if !starts_with_reference(rest) => {
+                            converted
+                                .text
+                                .push_str("&amp;")
+                        },
+                        | _ => converted.text.push(c),
+                    }
+                    rest = &rest[c.len_utf8()..];
+                }
+            },
+            | SrtNode::Alignment(alignment) => {
+                converted.alignment = Some(alignment.number())
+            },
+            | SrtNode::Markup(markup) => {
+                converted
+                    .dropped
+                    .push(markup.clone())
+            },
+            | SrtNode::Span(span) if span.kind == SrtSpanKind::Font => {
+                for (key, value) in [
+                    ("face", &span.face),
+                    ("size", &span.size),
+                ] {
+                    if let Some(value) = value {
+                        converted
+                            .dropped
+                            .push(format!("{}=\"{}\"", key, value));
+                    }
+                }
+
+                let Some(color) = &span.color else {
+                    push_vtt_text(&span.children, converted);
+                    continue;
+                };
+                let (class, color) = color_class(color);
+                if let Some(color) = color {
+                    let rule = (class.clone(), color);
+                    if !converted
+                        .color_rules
+                        .contains(&rule)
+                    {
+                        converted
+                            .color_rules
+                            .push(rule);
+                    }
+                }
+                converted
+                    .text
+                    .push_str(&format!("<c.{}>", class));
+                push_vtt_text(&span.children, converted);
+                converted
+                    .text
+                    .push_str("</c>");
+            },
+            | SrtNode::Span(span) => {
+                converted
+                    .text
+                    .push_str(&format!("<{}>", span.kind));
+                push_vtt_text(&span.children, converted);
+                converted
+                    .text
+                    .push_str(&format!("</{}>", span.kind));
+            },
+        }
+    }
+}
+
+/// Gets the class name for the color,
+/// and the CSS color if the class is not a default color class.
+pub(crate) fn color_class(color: &str) -> (String, Option<String>) {
+    let color = color
+        .trim()
+        .to_ascii_lowercase();
+    let hex = color.strip_prefix('#');
+
+    if let Some((class, _)) = DEFAULT_COLOR_CLASSES
+        .iter()
+        .find(|(class, value)| *class == color || hex == Some(*value))
+    {
+        return (class.to_string(), None);
+    }
+
+    let name = color
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+    (format!("color_{}", name), Some(color))
+}
+
+/// Creates the STYLE block of the `::cue` rules for the color classes.
+pub(crate) fn color_style(color_rules: &[(String, String)]) -> VttStyle {
+    VttStyle {
+        style: color_rules
+            .iter()
+            .map(|(class, color)| {
+                format!(
+                    "::cue(.{}) {{ color: {}; }}",
+                    class, color
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Checks whether the text starts with a character reference such as `&amp;`.
+fn starts_with_reference(text: &str) -> bool {
+    let Some(end) = text.find(';') else {
+        return false;
+    };
+    let name = &text[1..end];
+    !name.is_empty()
+        && (name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric())
+            || name
+                .strip_prefix('#')
+                .map_or(false, |code| {
+                    !code.is_empty()
+                        && code
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric())
+                }))
+}
+
+/// Converts the alignment of `{\anN}` to the cue settings.
+pub(crate) fn settings_from_alignment(alignment: u8) -> CueSettings {
+    let line = if alignment >= 7 {
+        Some(Line::LineNumber(0, None))
+    } else if alignment >= 4 {
+        Some(Line::Percentage(
+            Percentage {
+                value: 50.0,
+            },
+            Some(LineAlignment::Center),
+        ))
+    } else {
+        None
+    };
+    let align = match alignment % 3 {
+        | 1 => Some(Alignment::Left),
+        | 0 => Some(Alignment::Right),
+        | _ => None,
+    };
+
+    CueSettings {
+        line,
+        align,
+        ..Default::default()
+    }
+}
+
+/// Converts the cue settings to the nearest alignment of `{\anN}`.
+pub(crate) fn alignment_from_settings(settings: &CueSettings) -> u8 {
+    let row = match settings.line {
+        | None => 0,
+        | Some(Line::LineNumber(n, _)) => {
+            if n < 0 {
+                0
+            } else {
+                2
+            }
+        },
+        | Some(Line::Percentage(percentage, _)) => {
+            if percentage.value < 100.0 / 3.0 {
+                2
+            } else if percentage.value < 200.0 / 3.0 {
+                1
+            } else {
+                0
+            }
+        },
+    };
+    let column = match settings.align {
+        | Some(Alignment::Start | Alignment::Left) => 1,
+        | Some(Alignment::End | Alignment::Right) => 3,
+        | Some(Alignment::Center) | None => 2,
+    };
+
+    row * 3 + column
+}
+
+/// Normalizes the alignment of the cue settings to compare with the ones of `{\anN}`.
+fn normalize_settings(settings: &CueSettings) -> CueSettings {
+    let align = match settings.align {
+        | Some(Alignment::Start | Alignment::Left) => Some(Alignment::Left),
+        | Some(Alignment::End | Alignment::Right) => Some(Alignment::Right),
+        | Some(Alignment::Center) | None => None,
+    };
+
+    CueSettings {
+        align,
+        ..settings.clone()
+    }
+}
+
+/// Applies the line position in pixels to the cue settings in percentages.
+fn apply_line_position(
+    settings: &mut CueSettings,
+    line_position: &LinePosition,
+    frame_size: &FrameSize,
+) {
+    let percentage = |value: f32, total: u32| {
+        Percentage {
+            value: if total == 0 {
+                0.0
+            } else {
+                ((value / total as f32 * 100.0) * 100.0).round() / 100.0
+            }
+            .clamp(0.0, 100.0),
+        }
+    };
+    let x1 = line_position
+        .x1
+        .min(line_position.x2) as f32;
+    let x2 = line_position
+        .x1
+        .max(line_position.x2) as f32;
+    let y1 = line_position
+        .y1
+        .min(line_position.y2) as f32;
+
+    settings.line = Some(Line::Percentage(
+        percentage(y1, frame_size.height),
+        Some(LineAlignment::Start),
+    ));
+    settings.position = Some(Position {
+        value: percentage((x1 + x2) / 2.0, frame_size.width),
+        alignment: Some(PositionAlignment::Center),
+    });
+    settings.size = Some(percentage(x2 - x1, frame_size.width));
+}
+
+/// Pushes the text of the WebVTT cue nodes as the SubRip Subtitle text.
+fn push_srt_text(
+    nodes: &[VttCueNode],
+    colors: &[(String, String)],
+    text: &mut String,
+    issues: &mut Vec<ConversionIssueKind>,
+) {
+    for node in nodes {
+        match node {
+            | VttCueNode::Text(t) => push_srt_plain_text(t, text, issues),
+            | VttCueNode::Raw {
+                text: t,
+                source,
+            } => {
+                if t.is_empty() {
+                    issues.push(ConversionIssueKind::MarkupDropped(
+                        source.clone(),
+                    ));
+                } else {
+                    push_srt_plain_text(t, text, issues);
+                }
+            },
+            | VttCueNode::Timestamp(timestamp) => {
+                issues.push(ConversionIssueKind::MarkupDropped(
+                    format!("<{}>", timestamp),
+                ))
+            },
+            | VttCueNode::Span(span) => {
+                let tag = match span.kind {
+                    | VttCueSpanKind::Italic => Some("i".to_string()),
+                    | VttCueSpanKind::Bold => Some("b".to_string()),
+                    | VttCueSpanKind::Underline => Some("u".to_string()),
+                    | VttCueSpanKind::Class => None,
+                    | VttCueSpanKind::Ruby => None,
+                    | VttCueSpanKind::RubyText => {
+                        issues.push(ConversionIssueKind::MarkupDropped(
+                            node.to_string(),
+                        ));
+                        continue;
+                    },
+                    | VttCueSpanKind::Voice | VttCueSpanKind::Language => {
+                        issues.push(ConversionIssueKind::MarkupDropped(
+                            format!(
+                                "<{} {}>",
+                                span.kind,
+                                span.annotation
+                                    .clone()
+                                    .unwrap_or_default()
+                            ),
+                        ));
+                        None
+                    },
+                };
+
+                let mut color = None;
+                for class in &span.classes {
+                    match colors
+                        .iter()
+                        .rev()
+                        .find(|(name, _)| name == class)
+                    {
+                        | Some((_, value)) if color.is_none() => {
+                            color = Some(value.clone())
+                        },
+                        | _ => {
+                            issues.push(ConversionIssueKind::MarkupDropped(
+                                format!(".{}", class),
+                            ))
+                        },
+                    }
+                }
+
+                if let Some(tag) = &tag {
+                    text.push_str(&format!("<{}>", tag));
+                }
+                if let Some(color) = &color {
+                    text.push_str(&format!("<font color=\"{}\">", color));
+                }
+                push_srt_text(&span.children, colors, text, issues);
+                if color.is_some() {
+                    text.push_str("</font>");
+                }
+                if let Some(tag) = &tag {
+                    text.push_str(&format!("</{}>", tag));
+                }
+            },
+        }
+    }
+}
+
+/// Pushes the decoded text of WebVTT cue text as the SubRip Subtitle text,
+/// escaping the markup read as tags or overrides in the SubRip Subtitle such as `<b>` and `{\an8}`.
+fn push_srt_plain_text(
+    t: &str,
+    text: &mut String,
+    issues: &mut Vec<ConversionIssueKind>,
+) {
+    let mut rest = t;
+    while let Some(c) = rest.chars().next() {
+        let line = rest
+            .find('\n')
+            .map_or(rest, |end| &rest[..end]);
+        let end = match c {
+            | '<' => line.find('>'),
+            | '{' if line.starts_with("{\\") => line.find('}'),
+            | _ => None,
+        };
+        let Some(end) = end else {
+            text.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        };
+
+        let markup = &rest[..=end];
+        text.push_str(
+            &markup
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('{', "&#123;"),
+        );
+        issues.push(ConversionIssueKind::MarkupEscaped(
+            markup.to_string(),
+        ));
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Gets the colors of the classes by the `::cue(.class)` rules of the stylesheet,
+/// or returns `None` if the stylesheet contains any other rules or declarations.
+pub(crate) fn class_colors(
+    stylesheet: &Stylesheet
+) -> Option<Vec<(String, String)>> {
+    let mut classes: Vec<String> = Vec::new();
+    for rule in &stylesheet.rules {
+        if rule
+            .declarations
+            .iter()
+            .any(|declaration| declaration.property != Property::Color)
+        {
+            return None;
+        }
+
+        for selector in &rule.selectors {
+            match selector {
+                | Selector::Cue(Some(CueSelector {
+                    kind: None,
+                    id: None,
+                    classes: selected,
+                    voice: None,
+                    lang: None,
+                })) if selected.len() == 1 => {
+                    if !classes.contains(&selected[0]) {
+                        classes.push(selected[0].clone());
+                    }
+                },
+                | _ => return None,
+            }
+        }
+    }
+
+    classes
+        .into_iter()
+        .map(|class| {
+            let span = VttCueSpan {
+                kind: VttCueSpanKind::Class,
+                classes: vec![class.clone()],
+                ..Default::default()
+            };
+            stylesheet
+                .compute(&VttCue::default(), &[&span])
+                .get(Property::Color)
+                .map(|color| (class, color.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timestamp() {
+        let srt = SrtTimestamp {
+            hours: 1,
+            minutes: 2,
+            seconds: 3,
+            milliseconds: 4,
+        };
+        let vtt: VttTimestamp = srt.into();
+        assert_eq!(
+            vtt,
+            VttTimestamp {
+                hours: 1,
+                minutes: 2,
+                seconds: 3,
+                milliseconds: 4,
+            }
+        );
+        assert_eq!(SrtTimestamp::from(vtt), srt);
+    }
+
+    #[test]
+    fn srt_text() {
+        let converted = convert_srt_text(
+            "{\\an8}<I>Hello</I> & <font color=\"red\" face=\"Arial\">you</font> <b>&amp;</b> <x>1 < 2",
+        );
+        assert_eq!(
+            converted.text,
+            "<i>Hello</i> &amp; <c.red>you</c> <b>&amp;</b> 1 &lt; 2"
+        );
+        assert_eq!(converted.alignment, Some(8));
+        assert!(
+            converted
+                .color_rules
+                .is_empty()
+        );
+        assert_eq!(
+            converted.dropped,
+            vec![
+                "face=\"Arial\"".to_string(),
+                "<x>".to_string()
+            ]
+        );
+
+        let converted = convert_srt_text(
+            "<font color=#00ff00>a</font><font face=x>b</font>{\\pos(1,2)}",
+        );
+        assert_eq!(converted.text, "<c.lime>a</c>b");
+        assert_eq!(converted.alignment, None);
+        assert_eq!(
+            converted.dropped,
+            vec![
+                "face=\"x\"".to_string(),
+                "{\\pos(1,2)}".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn alignment() {
+        for alignment in 1..=9 {
+            assert_eq!(
+                alignment_from_settings(&settings_from_alignment(alignment)),
+                alignment
+            );
+        }
+        assert_eq!(
+            alignment_from_settings(&CueSettings {
+                line: Some(Line::Percentage(
+                    Percentage {
+                        value: 10.0,
+                    },
+                    None
+                )),
+                align: Some(Alignment::Start),
+                ..Default::default()
+            }),
+            7
+        );
+    }
+
+    #[test]
+    fn color_rules() {
+        assert_eq!(
+            class_colors(&Stylesheet::parse(
+                "::cue(.a) { color: #fff; }\n::cue(.b-2) {\ncolor: red;\n}\n::cue(.a) { color: blue !important; }"
+            )),
+            Some(vec![
+                ("a".to_string(), "blue".to_string()),
+                ("b-2".to_string(), "red".to_string()),
+            ])
+        );
+        assert_eq!(
+            class_colors(&Stylesheet::parse(
+                "::cue { color: red; }"
+            )),
+            None
+        );
+        assert_eq!(
+            class_colors(&Stylesheet::parse(
+                "::cue(.a) { color: red; font-size: 1em; }"
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn srt_to_vtt() {
+        let srt = SubRip::parse(
+            r##"1
+00:00:01,000 --> 00:00:02,000 X1:100 X2:540 Y1:400 Y2:450
+<font color="#FF8000">Hello</font>
+
+2
+00:00:03,000 --> 00:00:04,000
+{\an7}<u>world</u>
+"##,
+        )
+        .unwrap();
+
+        let conversion = subrip_to_webvtt(&srt, &ConversionOptions::default());
+        assert_eq!(
+            conversion.report.issues,
+            vec![ConversionIssue {
+                index: Some(0),
+                kind: ConversionIssueKind::LinePositionDropped(LinePosition {
+                    x1: 100,
+                    x2: 540,
+                    y1: 400,
+                    y2: 450,
+                }),
+            }]
+        );
+        assert_eq!(
+            conversion.output.render(),
+            r##"WEBVTT
+
+STYLE
+::cue(.color_ff8000) { color: #ff8000; }
+
+1
+00:00:01.000 --> 00:00:02.000
+<c.color_ff8000>Hello</c>
+
+2
+00:00:03.000 --> 00:00:04.000 line:0 align:left
+<u>world</u>
+"##
+        );
+
+        let conversion = subrip_to_webvtt(
+            &srt,
+            &ConversionOptions {
+                frame_size: Some(FrameSize {
+                    width: 640,
+                    height: 480,
+                }),
+            },
+        );
+        assert_eq!(
+            conversion.report.issues[0].kind,
+            ConversionIssueKind::LinePositionApproximated(LinePosition {
+                x1: 100,
+                x2: 540,
+                y1: 400,
+                y2: 450,
+            })
+        );
+        match &conversion.output.blocks[1] {
+            | VttBlock::Que(cue) => {
+                assert_eq!(
+                    cue.settings
+                        .as_ref()
+                        .unwrap()
+                        .to_string(),
+                    "line:83.33%,start position:50%,center size:68.75%"
+                )
+            },
+            | _ => panic!("Expected a cue"),
+        }
+    }
+
+    #[test]
+    fn vtt_to_srt() {
+        let vtt = WebVtt::parse(
+            r##"WEBVTT Sample
+
+STYLE
+::cue(.orange) { color: #ff8000; }
+
+NOTE a comment
+
+intro
+00:00:01.000 --> 00:00:02.000 line:0 align:start
+<v Alice><i>Hello</i>, <c.orange.loud>world</c> &amp; <c.red>you</c>
+
+00:00:03.000 --> 00:00:04.000 line:0 position:10%
+<ruby>漢<rt>かん</rt></ruby> <00:00:03.500>done
+"##,
+        )
+        .unwrap();
+
+        let conversion = webvtt_to_subrip(&vtt);
+        assert_eq!(
+            conversion.output.render(),
+            r##"1
+00:00:01,000 --> 00:00:02,000
+{\an7}<i>Hello</i>, <font color="#ff8000">world</font> & <font color="red">you</font>
+
+2
+00:00:03,000 --> 00:00:04,000
+{\an8}漢 done
+"##
+        );
+
+        let messages = conversion
+            .report
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "header: description `Sample` was dropped",
+                "block 1: comment block was dropped",
+                "block 2: cue identifier `intro` was dropped",
+                "block 2: markup `<v Alice>` was dropped",
+                "block 2: markup `.loud` was dropped",
+                "block 3: cue settings `line:0 position:10%` were approximated by an alignment tag",
+                "block 3: markup `<rt>かん</rt>` was dropped",
+                "block 3: markup `<00:00:03.500>` was dropped",
+            ]
+        );
+    }
+
+    #[test]
+    fn vtt_to_srt_escaped() {
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\n&lt;b&gt;not bold&lt;/b&gt; {\\an8}\n1 &lt; 2 &amp; 3\n",
+        )
+        .unwrap();
+
+        let conversion = webvtt_to_subrip(&vtt);
+        assert_eq!(
+            conversion.output.subtitles[0].text,
+            vec![
+                "&lt;b&gt;not bold&lt;/b&gt; &#123;\\an8}",
+                "1 < 2 & 3"
+            ]
+        );
+        assert_eq!(
+            conversion.report.issues,
+            [
+                "<b>", "</b>", "{\\an8}"
+            ]
+            .into_iter()
+            .map(|markup| {
+                ConversionIssue {
+                    index: Some(0),
+                    kind: ConversionIssueKind::MarkupEscaped(
+                        markup.to_string(),
+                    ),
+                }
+            })
+            .collect::<Vec<_>>()
+        );
+
+        let round_trip = subrip_to_webvtt(
+            &conversion.output,
+            &ConversionOptions::default(),
+        );
+        assert_eq!(
+            round_trip.output.render(),
+            "WEBVTT\n\n1\n00:00:01.000 --> 00:00:02.000\n&lt;b&gt;not bold&lt;/b&gt; &#123;\\an8}\n1 &lt; 2 &amp; 3\n"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let text = r##"1
+00:00:01,000 --> 00:00:02,000
+<i>Hello</i>, <font color="#ff8000">world</font>!
+
+2
+00:00:03,000 --> 00:00:04,000
+{\an8}<b>Top</b> & <font color="red">red</font>
+"##;
+        let srt = SubRip::parse(text).unwrap();
+
+        let vtt = subrip_to_webvtt(&srt, &ConversionOptions::default());
+        assert!(vtt.report.is_lossless());
+
+        let converted = webvtt_to_subrip(&vtt.output);
+        assert!(converted.report.is_lossless());
+        assert_eq!(converted.output, srt);
+        assert_eq!(converted.output.render(), text);
+    }
+}
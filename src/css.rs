@@ -0,0 +1,1273 @@
+//! The `::cue` stylesheet parsed from the CSS of the WebVTT STYLE blocks.
+//!
+//! The rules with the selectors of `::cue`, `::cue(selector)`, `::cue-region` and `::cue-region(#id)` are supported,
+//! where the argument of `::cue()` is a compound selector of a tag name or `*`, classes, an identifier of the cue
+//! and the attributes of `[voice="..."]` and `[lang="..."]`.
+//! The rules with the other selectors, e.g. combinators and pseudo-classes, and at-rules are skipped,
+//! and the declarations of properties not allowed in WebVTT are dropped.
+//!
+//! The computed style of a cue or a span is resolved by the cascade of the matched rules
+//! by `!important`, the specificity and the order, and the inheritance of the inherited properties
+//! from the cue through the ancestor spans.
+//!
+//! ## Example
+//! ```
+//! use subtp::css::Property;
+//! use subtp::vtt::VttBlock;
+//! use subtp::vtt::VttCueNode;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r#"WEBVTT
+//!
+//! STYLE
+//! ::cue { color: white; background-color: black; }
+//! ::cue(v[voice="Roger"]) { color: yellow; }
+//! ::cue(.loud) { font-weight: bold; position: absolute; }
+//!
+//! 00:01.000 --> 00:04.000
+//! <v Roger>Hello <c.loud>world</c>!</v>
+//! "#;
+//!
+//! let vtt = WebVtt::parse(text).unwrap();
+//! let stylesheet = vtt.stylesheet();
+//! assert_eq!(stylesheet.rules.len(), 3);
+//!
+//! let VttBlock::Que(cue) = &vtt.blocks[1] else { unreachable!() };
+//! let payload = cue.parse_payload();
+//! let VttCueNode::Span(voice) = &payload.nodes[0] else { unreachable!() };
+//! let VttCueNode::Span(loud) = &voice.children[1] else { unreachable!() };
+//!
+//! let style = stylesheet.compute(cue, &[]);
+//! assert_eq!(style.get(Property::BackgroundColor), Some("black"));
+//!
+//! let style = stylesheet.compute(cue, &[voice, loud]);
+//! assert_eq!(style.get(Property::Color), Some("yellow"));
+//! assert_eq!(style.get(Property::BackgroundColor), None);
+//! assert_eq!(style.get(Property::FontWeight), Some("bold"));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use crate::vtt::RegionId;
+use crate::vtt::VttCue;
+use crate::vtt::VttCueSpan;
+use crate::vtt::VttCueSpanKind;
+use crate::vtt::VttRegion;
+
+/// The stylesheet of `::cue` rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Stylesheet {
+    /// The rules in order of the source.
+    pub rules: Vec<StyleRule>,
+}
+
+impl Stylesheet {
+    /// Parses the stylesheet from the CSS, skipping the unsupported rules and declarations.
+    pub fn parse(css: &str) -> Self {
+        let css = strip_comments(css);
+        let mut rules = Vec::new();
+        let mut rest = css.as_str();
+
+        while let Some(open) = find_outside(rest, '{') {
+            let prelude = rest[..open].trim();
+            let close = find_block_end(rest, open);
+            let body = &rest[open + 1..close];
+            rest = rest
+                .get(close + 1..)
+                .unwrap_or_default();
+
+            if prelude.starts_with('@') {
+                continue;
+            }
+
+            let selectors = split_outside(prelude, ',')
+                .into_iter()
+                .map(Selector::parse)
+                .collect::<Option<Vec<_>>>();
+            if let Some(selectors) = selectors {
+                rules.push(StyleRule {
+                    selectors,
+                    declarations: split_outside(body, ';')
+                        .into_iter()
+                        .filter_map(Declaration::parse)
+                        .collect(),
+                });
+            }
+        }
+
+        Self {
+            rules,
+        }
+    }
+
+    /// Computes the style of the cue, or the span at the end of the ancestor spans from the outermost one.
+    ///
+    /// A span inherits only the [inherited](Property::is_inherited) properties of its parent.
+    pub fn compute(
+        &self,
+        cue: &VttCue,
+        spans: &[&VttCueSpan],
+    ) -> ComputedStyle {
+        let mut style = self.cascade(|selector| {
+            match selector {
+                | Selector::Cue(None) => Some((0, 0, 0)),
+                | Selector::Cue(Some(selector)) => selector.matches_cue(cue),
+                | Selector::CueRegion(_) => None,
+            }
+        });
+
+        for span in spans {
+            let inherited = style;
+            style = self.cascade(|selector| {
+                match selector {
+                    | Selector::Cue(Some(selector)) => {
+                        selector.matches_span(span)
+                    },
+                    | _ => None,
+                }
+            });
+            for (property, value) in inherited
+                .properties
+                .into_iter()
+                .filter(|(property, _)| property.is_inherited())
+            {
+                style
+                    .properties
+                    .entry(property)
+                    .or_insert(value);
+            }
+        }
+
+        style
+    }
+
+    /// Computes the style of the region.
+    pub fn compute_region(
+        &self,
+        region: &VttRegion,
+    ) -> ComputedStyle {
+        self.cascade(|selector| {
+            match selector {
+                | Selector::CueRegion(None) => Some((0, 0, 0)),
+                | Selector::CueRegion(Some(id)) => {
+                    (region.id.as_ref() == Some(id)).then_some((1, 0, 0))
+                },
+                | Selector::Cue(_) => None,
+            }
+        })
+    }
+
+    /// Applies the declarations of the rules matched by the specificity in order of the cascade.
+    fn cascade(
+        &self,
+        specificity: impl Fn(&Selector) -> Option<(usize, usize, usize)>,
+    ) -> ComputedStyle {
+        let mut matched = Vec::new();
+        for rule in &self.rules {
+            let Some(specificity) = rule
+                .selectors
+                .iter()
+                .filter_map(&specificity)
+                .max()
+            else {
+                continue;
+            };
+
+            for declaration in &rule.declarations {
+                matched.push((
+                    declaration.important,
+                    specificity,
+                    declaration,
+                ));
+            }
+        }
+        matched.sort_by_key(|(important, specificity, _)| {
+            (*important, *specificity)
+        });
+
+        let mut style = ComputedStyle::default();
+        for (_, _, declaration) in matched {
+            style.properties.insert(
+                declaration.property,
+                declaration.value.clone(),
+            );
+        }
+
+        style
+    }
+}
+
+impl Display for Stylesheet {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for rule in &self.rules {
+            writeln!(f, "{}", rule)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The rule of the selectors and the declarations.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct StyleRule {
+    /// The selectors.
+    pub selectors: Vec<Selector>,
+    /// The declarations of the allowed properties.
+    pub declarations: Vec<Declaration>,
+}
+
+impl Display for StyleRule {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let selectors = self
+            .selectors
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        write!(f, "{} {{", selectors.join(", "))?;
+
+        for declaration in &self.declarations {
+            write!(f, " {};", declaration)?;
+        }
+
+        write!(f, " }}")
+    }
+}
+
+/// The selector of a rule.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Selector {
+    /// `::cue` of all cues, or `::cue(selector)` of the cues or the spans matched by the selector.
+    Cue(Option<CueSelector>),
+    /// `::cue-region` of all regions, or `::cue-region(#id)` of the region with the identifier.
+    CueRegion(Option<RegionId>),
+}
+
+impl Selector {
+    /// Parses the selector, or returns `None` if not supported.
+    ///
+    /// The pseudo-element may follow `video` or `*` of the originating element, as in `video::cue`.
+    /// A combinator before it, as in `video ::cue`, originates from a descendant of the video and matches nothing.
+    fn parse(selector: &str) -> Option<Self> {
+        let selector = selector.trim();
+        let selector = match selector.find("::") {
+            | Some(index) => {
+                let prefix = &selector[..index];
+                if !(prefix.is_empty()
+                    || prefix == "*"
+                    || prefix.eq_ignore_ascii_case("video"))
+                {
+                    return None;
+                }
+                &selector[index..]
+            },
+            | None => return None,
+        };
+
+        if let Some(argument) = selector.strip_prefix("::cue-region") {
+            match parse_argument(argument)? {
+                | None => Some(Self::CueRegion(None)),
+                | Some(argument) => {
+                    let (id, rest) =
+                        parse_identifier(argument.strip_prefix('#')?)?;
+                    rest.trim()
+                        .is_empty()
+                        .then_some(Self::CueRegion(Some(id)))
+                },
+            }
+        } else if let Some(argument) = selector.strip_prefix("::cue") {
+            match parse_argument(argument)? {
+                | None => Some(Self::Cue(None)),
+                | Some(argument) => {
+                    CueSelector::parse(argument)
+                        .map(|selector| Self::Cue(Some(selector)))
+                },
+            }
+        } else {
+            None
+        }
+    }
+}
+
+impl Display for Selector {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Cue(None) => write!(f, "::cue"),
+            | Self::Cue(Some(selector)) => write!(f, "::cue({})", selector),
+            | Self::CueRegion(None) => write!(f, "::cue-region"),
+            | Self::CueRegion(Some(id)) => {
+                write!(
+                    f,
+                    "::cue-region(#{})",
+                    escape_identifier(id)
+                )
+            },
+        }
+    }
+}
+
+/// The compound selector in `::cue()`.
+///
+/// ## Example
+/// ```
+/// use subtp::css::CueSelector;
+/// use subtp::css::Selector;
+/// use subtp::css::Stylesheet;
+/// use subtp::vtt::VttCueSpanKind;
+///
+/// let stylesheet = Stylesheet::parse(r#"::cue(v.loud[voice="Roger"]) { color: red; }"#);
+/// assert_eq!(
+///     stylesheet.rules[0].selectors,
+///     vec![Selector::Cue(Some(CueSelector {
+///         kind: Some(VttCueSpanKind::Voice),
+///         classes: vec!["loud".to_string()],
+///         voice: Some("Roger".to_string()),
+///         ..Default::default()
+///     }))]
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct CueSelector {
+    /// The kind of span by the tag name, or `None` of `*` or any span.
+    pub kind: Option<VttCueSpanKind>,
+    /// The identifier of the cue by `#id`.
+    pub id: Option<String>,
+    /// The classes of the span by `.class`.
+    pub classes: Vec<String>,
+    /// The voice of the voice span by `[voice="..."]`.
+    pub voice: Option<String>,
+    /// The language of the language span by `[lang="..."]`.
+    pub lang: Option<String>,
+}
+
+impl CueSelector {
+    /// Parses the compound selector, or returns `None` if not supported.
+    fn parse(selector: &str) -> Option<Self> {
+        let mut parsed = Self::default();
+        let mut rest = selector.trim();
+
+        if let Some(stripped) = rest.strip_prefix('*') {
+            rest = stripped;
+        } else if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (tag, stripped) = parse_identifier(rest)?;
+            parsed.kind = Some(VttCueSpanKind::from_tag(&tag)?);
+            rest = stripped;
+        }
+
+        while let Some(c) = rest.chars().next() {
+            match c {
+                | '.' => {
+                    let (class, stripped) = parse_identifier(&rest[1..])?;
+                    parsed.classes.push(class);
+                    rest = stripped;
+                },
+                | '#' => {
+                    let (id, stripped) = parse_identifier(&rest[1..])?;
+                    parsed.id = Some(id);
+                    rest = stripped;
+                },
+                | '[' => {
+                    let close = rest.find(']')?;
+                    let (name, value) = rest[1..close].split_once('=')?;
+                    let value = unquote(value.trim())?;
+                    match name.trim() {
+                        | "voice" => parsed.voice = Some(value),
+                        | "lang" => parsed.lang = Some(value),
+                        | _ => return None,
+                    }
+                    rest = &rest[close + 1..];
+                },
+                | _ => return None,
+            }
+        }
+
+        Some(parsed)
+    }
+
+    /// The specificity of the identifier, the classes and attributes, and the tag name.
+    fn specificity(&self) -> (usize, usize, usize) {
+        (
+            self.id.is_some() as usize,
+            self.classes.len()
+                + self.voice.is_some() as usize
+                + self.lang.is_some() as usize,
+            self.kind.is_some() as usize,
+        )
+    }
+
+    /// Matches the root of the cue by the identifier only.
+    fn matches_cue(
+        &self,
+        cue: &VttCue,
+    ) -> Option<(usize, usize, usize)> {
+        let matched = self.kind.is_none()
+            && self.classes.is_empty()
+            && self.voice.is_none()
+            && self.lang.is_none()
+            && self.id.is_some()
+            && self.id == cue.identifier;
+
+        matched.then(|| self.specificity())
+    }
+
+    /// Matches the span by the tag name, the classes and the attributes.
+    fn matches_span(
+        &self,
+        span: &VttCueSpan,
+    ) -> Option<(usize, usize, usize)> {
+        let annotation = |kind: VttCueSpanKind, value: &Option<String>| {
+            value.is_none() || (span.kind == kind && span.annotation == *value)
+        };
+        let matched = self.id.is_none()
+            && self
+                .kind
+                .map_or(true, |kind| kind == span.kind)
+            && self
+                .classes
+                .iter()
+                .all(|class| span.classes.contains(class))
+            && annotation(VttCueSpanKind::Voice, &self.voice)
+            && annotation(VttCueSpanKind::Language, &self.lang);
+
+        matched.then(|| self.specificity())
+    }
+}
+
+impl Display for CueSelector {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match &self.kind {
+            | Some(kind) => write!(f, "{}", kind)?,
+            | None if self.id.is_none()
+                && self.classes.is_empty()
+                && self.voice.is_none()
+                && self.lang.is_none() =>
+            {
+                write!(f, "*")?
+            },
+            | None => {},
+        }
+
+        if let Some(id) = &self.id {
+            write!(f, "#{}", escape_identifier(id))?;
+        }
+        for class in &self.classes {
+            write!(f, ".{}", escape_identifier(class))?;
+        }
+        if let Some(voice) = &self.voice {
+            write!(f, "[voice={:?}]", voice)?;
+        }
+        if let Some(lang) = &self.lang {
+            write!(f, "[lang={:?}]", lang)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The declaration of a property.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Declaration {
+    /// The property.
+    pub property: Property,
+    /// The value as written without `!important`.
+    pub value: String,
+    /// Whether the declaration is `!important`.
+    pub important: bool,
+}
+
+impl Declaration {
+    /// Parses the declaration, or returns `None` if the property is not allowed.
+    fn parse(declaration: &str) -> Option<Self> {
+        let (name, value) = declaration.split_once(':')?;
+        let property = Property::from_name(
+            &name
+                .trim()
+                .to_ascii_lowercase(),
+        )?;
+
+        let mut value = value.trim();
+        let mut important = false;
+        if let Some(index) = value.rfind('!') {
+            if value[index + 1..]
+                .trim()
+                .eq_ignore_ascii_case("important")
+            {
+                value = value[..index].trim_end();
+                important = true;
+            }
+        }
+        if value.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            property,
+            value: value.to_string(),
+            important,
+        })
+    }
+}
+
+impl Display for Declaration {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}: {}", self.property, self.value)?;
+
+        if self.important {
+            write!(f, " !important")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The properties allowed in `::cue` and `::cue-region` of WebVTT.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Property {
+    /// `color`.
+    Color,
+    /// `opacity`.
+    Opacity,
+    /// `visibility`.
+    Visibility,
+    /// `text-decoration`.
+    TextDecoration,
+    /// `text-decoration-line`.
+    TextDecorationLine,
+    /// `text-decoration-color`.
+    TextDecorationColor,
+    /// `text-decoration-style`.
+    TextDecorationStyle,
+    /// `text-decoration-thickness`.
+    TextDecorationThickness,
+    /// `text-shadow`.
+    TextShadow,
+    /// `background`.
+    Background,
+    /// `background-color`.
+    BackgroundColor,
+    /// `background-image`.
+    BackgroundImage,
+    /// `background-repeat`.
+    BackgroundRepeat,
+    /// `background-attachment`.
+    BackgroundAttachment,
+    /// `background-position`.
+    BackgroundPosition,
+    /// `background-size`.
+    BackgroundSize,
+    /// `background-origin`.
+    BackgroundOrigin,
+    /// `background-clip`.
+    BackgroundClip,
+    /// `outline`.
+    Outline,
+    /// `outline-color`.
+    OutlineColor,
+    /// `outline-style`.
+    OutlineStyle,
+    /// `outline-width`.
+    OutlineWidth,
+    /// `font`.
+    Font,
+    /// `font-style`.
+    FontStyle,
+    /// `font-variant`.
+    FontVariant,
+    /// `font-weight`.
+    FontWeight,
+    /// `font-stretch`.
+    FontStretch,
+    /// `font-size`.
+    FontSize,
+    /// `font-family`.
+    FontFamily,
+    /// `line-height`.
+    LineHeight,
+    /// `white-space`.
+    WhiteSpace,
+    /// `text-combine-upright`.
+    TextCombineUpright,
+    /// `ruby-position`.
+    RubyPosition,
+}
+
+impl Property {
+    /// All properties.
+    pub const ALL: [Self; 33] = [
+        Self::Color,
+        Self::Opacity,
+        Self::Visibility,
+        Self::TextDecoration,
+        Self::TextDecorationLine,
+        Self::TextDecorationColor,
+        Self::TextDecorationStyle,
+        Self::TextDecorationThickness,
+        Self::TextShadow,
+        Self::Background,
+        Self::BackgroundColor,
+        Self::BackgroundImage,
+        Self::BackgroundRepeat,
+        Self::BackgroundAttachment,
+        Self::BackgroundPosition,
+        Self::BackgroundSize,
+        Self::BackgroundOrigin,
+        Self::BackgroundClip,
+        Self::Outline,
+        Self::OutlineColor,
+        Self::OutlineStyle,
+        Self::OutlineWidth,
+        Self::Font,
+        Self::FontStyle,
+        Self::FontVariant,
+        Self::FontWeight,
+        Self::FontStretch,
+        Self::FontSize,
+        Self::FontFamily,
+        Self::LineHeight,
+        Self::WhiteSpace,
+        Self::TextCombineUpright,
+        Self::RubyPosition,
+    ];
+
+    /// Gets the property from the name in lowercase, or `None` if not allowed.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|property| property.name() == name)
+    }
+
+    /// Gets the name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            | Self::Color => "color",
+            | Self::Opacity => "opacity",
+            | Self::Visibility => "visibility",
+            | Self::TextDecoration => "text-decoration",
+            | Self::TextDecorationLine => "text-decoration-line",
+            | Self::TextDecorationColor => "text-decoration-color",
+            | Self::TextDecorationStyle => "text-decoration-style",
+            | Self::TextDecorationThickness => "text-decoration-thickness",
+            | Self::TextShadow => "text-shadow",
+            | Self::Background => "background",
+            | Self::BackgroundColor => "background-color",
+            | Self::BackgroundImage => "background-image",
+            | Self::BackgroundRepeat => "background-repeat",
+            | Self::BackgroundAttachment => "background-attachment",
+            | Self::BackgroundPosition => "background-position",
+            | Self::BackgroundSize => "background-size",
+            | Self::BackgroundOrigin => "background-origin",
+            | Self::BackgroundClip => "background-clip",
+            | Self::Outline => "outline",
+            | Self::OutlineColor => "outline-color",
+            | Self::OutlineStyle => "outline-style",
+            | Self::OutlineWidth => "outline-width",
+            | Self::Font => "font",
+            | Self::FontStyle => "font-style",
+            | Self::FontVariant => "font-variant",
+            | Self::FontWeight => "font-weight",
+            | Self::FontStretch => "font-stretch",
+            | Self::FontSize => "font-size",
+            | Self::FontFamily => "font-family",
+            | Self::LineHeight => "line-height",
+            | Self::WhiteSpace => "white-space",
+            | Self::TextCombineUpright => "text-combine-upright",
+            | Self::RubyPosition => "ruby-position",
+        }
+    }
+
+    /// Whether the property is inherited from the parent cue or span by default.
+    pub fn is_inherited(&self) -> bool {
+        matches!(
+            self,
+            Self::Color
+                | Self::Visibility
+                | Self::TextShadow
+                | Self::Font
+                | Self::FontStyle
+                | Self::FontVariant
+                | Self::FontWeight
+                | Self::FontStretch
+                | Self::FontSize
+                | Self::FontFamily
+                | Self::LineHeight
+                | Self::WhiteSpace
+                | Self::TextCombineUpright
+                | Self::RubyPosition
+        )
+    }
+}
+
+impl Display for Property {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The computed style of a cue, a span or a region.
+///
+/// The values are as written, where the shorthands such as `font` and `background` are not expanded.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ComputedStyle {
+    /// The values of the properties.
+    pub properties: BTreeMap<Property, String>,
+}
+
+impl ComputedStyle {
+    /// Gets the value of the property.
+    pub fn get(
+        &self,
+        property: Property,
+    ) -> Option<&str> {
+        self.properties
+            .get(&property)
+            .map(String::as_str)
+    }
+}
+
+/// Removes the comments of `/* */`.
+fn strip_comments(css: &str) -> String {
+    let mut stripped = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        stripped.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            | Some(end) => &rest[start + 2 + end + 2..],
+            | None => "",
+        };
+    }
+    stripped.push_str(rest);
+
+    stripped
+}
+
+/// Finds the character outside of quotes, parentheses and brackets.
+fn find_outside(
+    text: &str,
+    target: char,
+) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (index, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            | (_, '\\') => escaped = true,
+            | (Some(q), c) if c == q => quote = None,
+            | (Some(_), _) => {},
+            | (None, '"' | '\'') => quote = Some(c),
+            | (None, c) if c == target && depth == 0 => return Some(index),
+            | (None, '(' | '[') => depth += 1,
+            | (None, ')' | ']') => depth = depth.saturating_sub(1),
+            | _ => {},
+        }
+    }
+
+    None
+}
+
+/// Splits the text by the separator outside of quotes, parentheses and brackets.
+fn split_outside(
+    text: &str,
+    separator: char,
+) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(index) = find_outside(rest, separator) {
+        parts.push(&rest[..index]);
+        rest = &rest[index + separator.len_utf8()..];
+    }
+    parts.push(rest);
+
+    parts
+        .into_iter()
+        .filter(|part| !part.trim().is_empty())
+        .collect()
+}
+
+/// Finds the end of the block starting at the open brace with the nested blocks, or the end of the text.
+fn find_block_end(
+    text: &str,
+    open: usize,
+) -> usize {
+    let mut depth = 0usize;
+    let mut offset = open;
+    while let Some(index) = find_outside(&text[offset..], '}') {
+        depth += text[offset..offset + index]
+            .matches('{')
+            .count();
+        offset += index;
+        if depth <= 1 {
+            return offset;
+        }
+        depth -= 1;
+        offset += 1;
+    }
+
+    text.len()
+}
+
+/// Parses the argument in parentheses, or `Some(None)` if no argument.
+fn parse_argument(text: &str) -> Option<Option<&str>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(None);
+    }
+
+    text.strip_prefix('(')?
+        .strip_suffix(')')
+        .map(Some)
+}
+
+/// Parses the identifier with escapes at the start of the text and returns it with the rest.
+fn parse_identifier(text: &str) -> Option<(String, &str)> {
+    let mut identifier = String::new();
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(&(index, c)) = chars.peek() {
+        if c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            identifier.push(c);
+            chars.next();
+        } else if c == '\\' {
+            chars.next();
+            let mut hex = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if hex.len() < 6 && c.is_ascii_hexdigit() {
+                    hex.push(c);
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            if hex.is_empty() {
+                let (_, escaped) = chars.next()?;
+                identifier.push(escaped);
+            } else {
+                identifier.push(
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .unwrap_or(char::REPLACEMENT_CHARACTER),
+                );
+                if let Some(&(_, ' ')) = chars.peek() {
+                    chars.next();
+                }
+            }
+        } else {
+            return (!identifier.is_empty())
+                .then_some((identifier, &text[index..]));
+        }
+    }
+
+    (!identifier.is_empty()).then_some((identifier, ""))
+}
+
+/// Escapes the identifier for CSS.
+fn escape_identifier(identifier: &str) -> String {
+    let mut escaped = String::new();
+    for (index, c) in identifier.char_indices() {
+        if index == 0 && c.is_ascii_digit() {
+            escaped.push_str(&format!("\\{:x} ", c as u32));
+        } else if c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push('\\');
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+/// Removes the quotes of the attribute value.
+fn unquote(value: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+        {
+            return Some(inner.replace(
+                &format!("\\{}", quote),
+                &quote.to_string(),
+            ));
+        }
+    }
+
+    parse_identifier(value)
+        .filter(|(_, rest)| rest.is_empty())
+        .map(|(value, _)| value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::VttCueNode;
+    use crate::vtt::VttTimings;
+
+    #[test]
+    fn parse() {
+        let stylesheet = Stylesheet::parse(
+            r#"
+/* A comment { color: red; } */
+@media (min-width: 100px) { ::cue { color: blue; } }
+video::cue { color: white; position: absolute; font-size: 120% !important; }
+::cue(#\31 23), ::cue(#intro) { color: lime }
+::cue(b) { color: red; }
+::cue(c.a.b) { color: green; }
+::cue(v[voice="A, B"]) { color: yellow; }
+::cue(lang[lang=en]) { font-style: italic; }
+::cue(c b) { color: purple; }
+::cue(:past) { color: gray; }
+::cue(span) { color: gray; }
+::cue-region { background: black; }
+::cue-region(#top) { opacity: 0.5; }
+"#,
+        );
+
+        assert_eq!(
+            stylesheet.to_string(),
+            r#"::cue { color: white; font-size: 120% !important; }
+::cue(#\31 23), ::cue(#intro) { color: lime; }
+::cue(b) { color: red; }
+::cue(c.a.b) { color: green; }
+::cue(v[voice="A, B"]) { color: yellow; }
+::cue(lang[lang="en"]) { font-style: italic; }
+::cue-region { background: black; }
+::cue-region(#top) { opacity: 0.5; }
+"#
+        );
+        assert_eq!(
+            stylesheet.rules[1].selectors[0],
+            Selector::Cue(Some(CueSelector {
+                id: Some("123".to_string()),
+                ..Default::default()
+            }))
+        );
+        assert_eq!(
+            Stylesheet::parse(&stylesheet.to_string()),
+            stylesheet
+        );
+    }
+
+    #[test]
+    fn compute() {
+        let stylesheet = Stylesheet::parse(
+            r#"
+::cue { color: white; background-color: black; font-size: 100% !important; }
+::cue(#intro) { color: lime; }
+::cue(.loud) { color: red; font-size: 200%; }
+::cue(c.loud) { color: orange; }
+::cue(.loud) { font-weight: bold; }
+::cue(v[voice="Roger"]) { color: yellow; }
+::cue(*) { opacity: 0.9; }
+"#,
+        );
+        let cue = VttCue {
+            identifier: Some("intro".to_string()),
+            timings: VttTimings::default(),
+            settings: None,
+            payload: vec![
+                "<v Roger>Hello <c.loud>world</c><b.loud>!</b></v>".to_string(),
+            ],
+        };
+        let payload = cue.parse_payload();
+        let VttCueNode::Span(voice) = &payload.nodes[0] else {
+            panic!("not a span");
+        };
+        let VttCueNode::Span(class) = &voice.children[1] else {
+            panic!("not a span");
+        };
+        let VttCueNode::Span(bold) = &voice.children[2] else {
+            panic!("not a span");
+        };
+
+        let style = stylesheet.compute(&cue, &[]);
+        assert_eq!(style.get(Property::Color), Some("lime"));
+        assert_eq!(style.get(Property::Opacity), None);
+
+        let style = stylesheet.compute(&cue, &[voice]);
+        assert_eq!(
+            style.get(Property::Color),
+            Some("yellow")
+        );
+        assert_eq!(
+            style.get(Property::Opacity),
+            Some("0.9")
+        );
+        assert_eq!(
+            style.get(Property::BackgroundColor),
+            None
+        );
+
+        let style = stylesheet.compute(&cue, &[voice, class]);
+        assert_eq!(
+            style.get(Property::Color),
+            Some("orange")
+        );
+        assert_eq!(
+            style.get(Property::FontSize),
+            Some("200%")
+        );
+        assert_eq!(
+            style.get(Property::FontWeight),
+            Some("bold")
+        );
+
+        let style = stylesheet.compute(&cue, &[voice, bold]);
+        assert_eq!(style.get(Property::Color), Some("red"));
+
+        let region = VttRegion {
+            id: Some("top".to_string()),
+            ..Default::default()
+        };
+        let stylesheet = Stylesheet::parse(
+            "::cue-region(#top) { color: red; } ::cue-region { color: blue; opacity: 1; }",
+        );
+        let style = stylesheet.compute_region(&region);
+        assert_eq!(style.get(Property::Color), Some("red"));
+        assert_eq!(style.get(Property::Opacity), Some("1"));
+    }
+
+    #[test]
+    fn parse_selectors() {
+        assert_eq!(
+            Selector::parse("::cue(.c)"),
+            Some(Selector::Cue(Some(CueSelector {
+                classes: vec!["c".to_string()],
+                ..Default::default()
+            })))
+        );
+        assert_eq!(
+            Selector::parse(r#"::cue(v[voice="X"])"#),
+            Some(Selector::Cue(Some(CueSelector {
+                kind: Some(VttCueSpanKind::Voice),
+                voice: Some("X".to_string()),
+                ..Default::default()
+            })))
+        );
+        assert_eq!(
+            Selector::parse("::cue(#id)"),
+            Some(Selector::Cue(Some(CueSelector {
+                id: Some("id".to_string()),
+                ..Default::default()
+            })))
+        );
+        assert_eq!(
+            Selector::parse("::cue-region"),
+            Some(Selector::CueRegion(None))
+        );
+        assert_eq!(
+            Selector::parse("::cue-region(#top)"),
+            Some(Selector::CueRegion(Some(
+                "top".to_string()
+            )))
+        );
+
+        assert_eq!(
+            Selector::parse("video::cue"),
+            Some(Selector::Cue(None))
+        );
+        assert_eq!(
+            Selector::parse("VIDEO::cue-region"),
+            Some(Selector::CueRegion(None))
+        );
+        assert_eq!(
+            Selector::parse("*::cue(b)"),
+            Some(Selector::Cue(Some(CueSelector {
+                kind: Some(VttCueSpanKind::Bold),
+                ..Default::default()
+            })))
+        );
+
+        assert_eq!(Selector::parse("video ::cue"), None);
+        assert_eq!(Selector::parse("audio::cue"), None);
+        assert_eq!(Selector::parse("::cue b"), None);
+        assert_eq!(Selector::parse("::cue(span)"), None);
+        assert_eq!(
+            Selector::parse(r#"::cue(v[title="X"])"#),
+            None
+        );
+        assert_eq!(
+            Selector::parse("::cue-region(.c)"),
+            None
+        );
+        assert_eq!(Selector::parse("p"), None);
+
+        let stylesheet = Stylesheet::parse(
+            "video ::cue, ::cue { color: red; } video::cue { color: lime; }",
+        );
+        assert_eq!(
+            stylesheet.to_string(),
+            "::cue { color: lime; }\n"
+        );
+    }
+
+    #[test]
+    fn parse_disallowed_properties() {
+        let stylesheet = Stylesheet::parse(
+            "::cue { position: absolute; COLOR: red; display: none; margin: 0; font-size: ; opacity: 1 }",
+        );
+        assert_eq!(
+            stylesheet.rules[0].declarations,
+            vec![
+                Declaration {
+                    property: Property::Color,
+                    value: "red".to_string(),
+                    important: false,
+                },
+                Declaration {
+                    property: Property::Opacity,
+                    value: "1".to_string(),
+                    important: false,
+                },
+            ]
+        );
+        assert_eq!(Property::from_name("width"), None);
+    }
+
+    #[test]
+    fn compute_cascade_order() {
+        let cue = VttCue {
+            identifier: Some("intro".to_string()),
+            timings: VttTimings::default(),
+            settings: None,
+            payload: vec!["<c.a.b>text</c>".to_string()],
+        };
+        let payload = cue.parse_payload();
+        let VttCueNode::Span(span) = &payload.nodes[0] else {
+            panic!("not a span");
+        };
+
+        // The more specific rule wins regardless of the order.
+        let stylesheet = Stylesheet::parse(
+            "::cue(c.a.b) { color: red; } ::cue(.a) { color: blue; } ::cue(c) { color: green; }",
+        );
+        assert_eq!(
+            stylesheet
+                .compute(&cue, &[span])
+                .get(Property::Color),
+            Some("red")
+        );
+
+        // The later rule wins with the same specificity.
+        let stylesheet = Stylesheet::parse(
+            "::cue(.a) { color: red; } ::cue(.b) { color: blue; }",
+        );
+        assert_eq!(
+            stylesheet
+                .compute(&cue, &[span])
+                .get(Property::Color),
+            Some("blue")
+        );
+
+        // `!important` wins over the specificity and the order.
+        let stylesheet = Stylesheet::parse(
+            "::cue(*) { color: red !important; } ::cue(c.a.b) { color: blue; }",
+        );
+        assert_eq!(
+            stylesheet
+                .compute(&cue, &[span])
+                .get(Property::Color),
+            Some("red")
+        );
+
+        // The identifier is more specific than `::cue` on the root of the cue.
+        let stylesheet = Stylesheet::parse(
+            "::cue(#intro) { color: red; } ::cue { color: blue; }",
+        );
+        assert_eq!(
+            stylesheet
+                .compute(&cue, &[])
+                .get(Property::Color),
+            Some("red")
+        );
+    }
+
+    #[test]
+    fn compute_inheritance() {
+        let cue = VttCue {
+            identifier: None,
+            timings: VttTimings::default(),
+            settings: None,
+            payload: vec!["<c.outer><b><i>text</i></b></c>".to_string()],
+        };
+        let payload = cue.parse_payload();
+        let VttCueNode::Span(outer) = &payload.nodes[0] else {
+            panic!("not a span");
+        };
+        let VttCueNode::Span(bold) = &outer.children[0] else {
+            panic!("not a span");
+        };
+        let VttCueNode::Span(italic) = &bold.children[0] else {
+            panic!("not a span");
+        };
+
+        let stylesheet = Stylesheet::parse(
+            r#"
+::cue { color: white; background-color: black; font-family: serif; }
+::cue(.outer) { color: yellow; opacity: 0.5; text-decoration: underline; outline: 1px solid; }
+::cue(b) { font-weight: bold; }
+::cue(i) { font-family: sans-serif; }
+"#,
+        );
+
+        let style = stylesheet.compute(&cue, &[outer]);
+        assert_eq!(
+            style.get(Property::Color),
+            Some("yellow")
+        );
+        assert_eq!(
+            style.get(Property::FontFamily),
+            Some("serif")
+        );
+        assert_eq!(
+            style.get(Property::BackgroundColor),
+            None
+        );
+
+        let style = stylesheet.compute(&cue, &[outer, bold, italic]);
+        assert_eq!(
+            style.get(Property::Color),
+            Some("yellow")
+        );
+        assert_eq!(
+            style.get(Property::FontWeight),
+            Some("bold")
+        );
+        assert_eq!(
+            style.get(Property::FontFamily),
+            Some("sans-serif")
+        );
+        assert_eq!(style.get(Property::Opacity), None);
+        assert_eq!(
+            style.get(Property::TextDecoration),
+            None
+        );
+        assert_eq!(style.get(Property::Outline), None);
+        assert_eq!(
+            style.get(Property::BackgroundColor),
+            None
+        );
+    }
+}
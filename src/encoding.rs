@@ -0,0 +1,388 @@
+//! Character encoding detection and decoding of subtitle files.
+//!
+//! The encoding of the input bytes is determined in order of:
+//!
+//! 1. The encoding specified by the caller.
+//! 2. The byte order mark of UTF-8, UTF-16LE or UTF-16BE.
+//! 3. UTF-8 if the input is valid UTF-8.
+//! 4. The guess from the content, e.g. Windows-1252, Shift_JIS, GB18030 or EUC-KR, where GBK is widened to GB18030.
+//!
+//! Malformed sequences are replaced with the replacement character `U+FFFD`.
+//! The output can be written back in the original encoding with the byte order mark by [`Decoded::render_bytes`].
+//!
+//! ## Example
+//! ```
+//! use subtp::encoding::EncodingSource;
+//! use subtp::encoding::SHIFT_JIS;
+//! use subtp::srt::SubRip;
+//!
+//! let text = "1\n00:00:01,000 --> 00:00:02,000\nこんにちは、世界！\n";
+//! let (bytes, _, _) = SHIFT_JIS.encode(text);
+//!
+//! let decoded = SubRip::parse_bytes_with_encoding(&bytes, SHIFT_JIS).unwrap();
+//! assert_eq!(decoded.encoding.encoding, SHIFT_JIS);
+//! assert_eq!(decoded.source, EncodingSource::Specified);
+//! assert_eq!(decoded.output.subtitles[0].text, vec!["こんにちは、世界！"]);
+//! assert_eq!(decoded.render_bytes().bytes, &*bytes);
+//! ```
+
+pub use encoding_rs::EUC_KR;
+pub use encoding_rs::Encoding;
+pub use encoding_rs::GB18030;
+pub use encoding_rs::GBK;
+pub use encoding_rs::SHIFT_JIS;
+pub use encoding_rs::UTF_8;
+pub use encoding_rs::UTF_16BE;
+pub use encoding_rs::UTF_16LE;
+pub use encoding_rs::WINDOWS_1252;
+
+use crate::ParseResult;
+use crate::SubtitleFormat;
+
+/// The output decoded from bytes with the encoding used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded<T> {
+    /// The output.
+    pub output: T,
+    /// The encoding used to decode the input.
+    pub encoding: TextEncoding,
+    /// How the encoding was determined.
+    pub source: EncodingSource,
+    /// Whether malformed sequences were replaced with the replacement character.
+    pub had_errors: bool,
+}
+
+impl<T: SubtitleFormat> Decoded<T> {
+    /// Renders the output and encodes it back in the original encoding.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::vtt::WebVtt;
+    ///
+    /// let bytes = b"\xEF\xBB\xBFWEBVTT\n\n00:01.000 --> 00:02.000\nHello, world!\n";
+    ///
+    /// let decoded = WebVtt::parse_bytes(bytes).unwrap();
+    /// assert!(decoded.encoding.bom);
+    /// assert!(decoded.render_bytes().bytes.starts_with(b"\xEF\xBB\xBFWEBVTT\n"));
+    /// ```
+    pub fn render_bytes(&self) -> Encoded {
+        self.encoding
+            .encode(&self.output.render())
+    }
+}
+
+/// How the encoding was determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EncodingSource {
+    /// Specified by the caller.
+    Specified,
+    /// Detected by the byte order mark.
+    ByteOrderMark,
+    /// Guessed from the content.
+    Guessed,
+}
+
+/// The text encoding of a subtitle file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextEncoding {
+    /// The encoding.
+    pub encoding: &'static Encoding,
+    /// Whether the byte order mark is at the start of the file.
+    pub bom: bool,
+}
+
+impl TextEncoding {
+    /// Creates the encoding without the byte order mark.
+    pub fn new(encoding: &'static Encoding) -> Self {
+        Self {
+            encoding,
+            bom: false,
+        }
+    }
+
+    /// Encodes the text with the byte order mark if any.
+    ///
+    /// UTF-16LE and UTF-16BE are encoded as they are,
+    /// and characters unmappable in other encodings are replaced with HTML numeric character references.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::encoding::TextEncoding;
+    /// use subtp::encoding::UTF_16LE;
+    ///
+    /// let encoding = TextEncoding {
+    ///     encoding: UTF_16LE,
+    ///     bom: true,
+    /// };
+    ///
+    /// assert_eq!(encoding.encode("A").bytes, vec![0xFF, 0xFE, 0x41, 0x00]);
+    /// ```
+    pub fn encode(
+        &self,
+        text: &str,
+    ) -> Encoded {
+        let mut bytes = Vec::new();
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let little_endian = self.encoding == UTF_16LE;
+            let bom = self.bom.then_some(0xFEFF);
+            for unit in bom
+                .into_iter()
+                .chain(text.encode_utf16())
+            {
+                if little_endian {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                } else {
+                    bytes.extend_from_slice(&unit.to_be_bytes());
+                }
+            }
+
+            return Encoded {
+                bytes,
+                had_unmappable: false,
+            };
+        }
+
+        if self.bom && self.encoding == UTF_8 {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let (encoded, _, had_unmappable) = self.encoding.encode(text);
+        bytes.extend_from_slice(&encoded);
+
+        Encoded {
+            bytes,
+            had_unmappable,
+        }
+    }
+}
+
+/// The bytes encoded from the text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Encoded {
+    /// The bytes.
+    pub bytes: Vec<u8>,
+    /// Whether characters unmappable in the encoding were replaced with HTML numeric character references.
+    pub had_unmappable: bool,
+}
+
+/// Decodes the bytes by detecting the encoding.
+///
+/// ## Example
+/// ```
+/// use subtp::encoding::EncodingSource;
+/// use subtp::encoding::UTF_16BE;
+///
+/// let decoded = subtp::encoding::decode(b"\xFE\xFF\x00W\x00E\x00B\x00V\x00T\x00T\x00\n");
+///
+/// assert_eq!(decoded.output, "WEBVTT\n");
+/// assert_eq!(decoded.encoding.encoding, UTF_16BE);
+/// assert!(decoded.encoding.bom);
+/// assert_eq!(decoded.source, EncodingSource::ByteOrderMark);
+/// ```
+pub fn decode(bytes: &[u8]) -> Decoded<String> {
+    if let Some((encoding, length)) = Encoding::for_bom(bytes) {
+        let (output, had_errors) =
+            encoding.decode_without_bom_handling(&bytes[length..]);
+        return Decoded {
+            output: output.into_owned(),
+            encoding: TextEncoding {
+                encoding,
+                bom: true,
+            },
+            source: EncodingSource::ByteOrderMark,
+            had_errors,
+        };
+    }
+
+    let encoding = if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        let mut detector = chardetng::EncodingDetector::new();
+        detector.feed(bytes, true);
+        match detector.guess(None, true) {
+            // Decodes identically but encodes the whole Unicode by the superset.
+            | guess if guess == GBK => GB18030,
+            | guess => guess,
+        }
+    };
+    let (output, had_errors) = encoding.decode_without_bom_handling(bytes);
+
+    Decoded {
+        output: output.into_owned(),
+        encoding: TextEncoding::new(encoding),
+        source: EncodingSource::Guessed,
+        had_errors,
+    }
+}
+
+/// Decodes the bytes with the encoding, where the byte order mark of the encoding is skipped if any.
+///
+/// ## Example
+/// ```
+/// use subtp::encoding::WINDOWS_1252;
+///
+/// let decoded = subtp::encoding::decode_with_encoding(b"Caf\xE9\n", WINDOWS_1252);
+///
+/// assert_eq!(decoded.output, "Café\n");
+/// assert!(!decoded.had_errors);
+/// ```
+pub fn decode_with_encoding(
+    bytes: &[u8],
+    encoding: &'static Encoding,
+) -> Decoded<String> {
+    let bom = Encoding::for_bom(bytes)
+        .is_some_and(|(detected, _)| detected == encoding);
+    let (output, had_errors) = encoding.decode_with_bom_removal(bytes);
+
+    Decoded {
+        output: output.into_owned(),
+        encoding: TextEncoding {
+            encoding,
+            bom,
+        },
+        source: EncodingSource::Specified,
+        had_errors,
+    }
+}
+
+/// Decodes the bytes with the encoding if any, or by detecting the encoding, and parses the text.
+pub(crate) fn parse_bytes<T>(
+    bytes: &[u8],
+    encoding: Option<&'static Encoding>,
+    parse: impl FnOnce(&str) -> ParseResult<T>,
+) -> ParseResult<Decoded<T>> {
+    let decoded = match encoding {
+        | Some(encoding) => decode_with_encoding(bytes, encoding),
+        | None => decode(bytes),
+    };
+
+    Ok(Decoded {
+        output: parse(&decoded.output)?,
+        encoding: decoded.encoding,
+        source: decoded.source,
+        had_errors: decoded.had_errors,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:02,000\r\n";
+
+    #[test]
+    fn detect() {
+        let cases = [
+            (
+                WINDOWS_1252,
+                "Il était une fois, à Noël, un garçon très curieux.\nÇa, c'est déjà l'été à Genève.\n",
+            ),
+            (
+                SHIFT_JIS,
+                "むかしむかし、ある村に、心のやさしい浦島太郎という若者がいました。\n",
+            ),
+            (
+                GB18030,
+                "很久很久以前，在一个美丽的村庄里，住着一位善良的老人和他的孙子。\n",
+            ),
+            (
+                EUC_KR,
+                "옛날 옛적에 어느 마을에 마음씨 착한 나무꾼이 살고 있었습니다.\n",
+            ),
+        ];
+
+        for (encoding, text) in cases {
+            let text = format!(
+                "1\n00:00:01,000 --> 00:00:02,000\n{}",
+                text
+            );
+            let (bytes, ..) = encoding.encode(&text);
+
+            let decoded = SubRip::parse_bytes(&bytes).unwrap();
+            assert_eq!(
+                decoded.encoding,
+                TextEncoding::new(encoding)
+            );
+            assert_eq!(decoded.source, EncodingSource::Guessed);
+            assert!(!decoded.had_errors);
+            assert_eq!(
+                decoded.output,
+                SubRip::parse(&text).unwrap()
+            );
+            assert_eq!(decoded.render_bytes().bytes, &*bytes);
+        }
+    }
+
+    #[test]
+    fn utf16_with_bom() {
+        let text = "WEBVTT\r\n\r\n00:01.000 --> 00:02.000\r\nこんにちは\r\n";
+
+        for encoding in [UTF_16LE, UTF_16BE] {
+            let encoding = TextEncoding {
+                encoding,
+                bom: true,
+            };
+            let bytes = encoding.encode(text).bytes;
+
+            let decoded = WebVtt::parse_bytes(&bytes).unwrap();
+            assert_eq!(decoded.encoding, encoding);
+            assert_eq!(
+                decoded.source,
+                EncodingSource::ByteOrderMark
+            );
+            assert_eq!(
+                decoded.output,
+                WebVtt::parse(text).unwrap()
+            );
+            assert_eq!(
+                decoded.render_bytes().bytes,
+                encoding
+                    .encode(&decoded.output.render())
+                    .bytes
+            );
+        }
+    }
+
+    #[test]
+    fn utf8_with_bom() {
+        let text = "\u{feff}WEBVTT\n\n00:01.000 --> 00:02.000\nHello\n";
+
+        let vtt = WebVtt::parse(text).unwrap();
+        let decoded = WebVtt::parse_bytes(text.as_bytes()).unwrap();
+        assert_eq!(decoded.output, vtt);
+        assert_eq!(
+            decoded.encoding,
+            TextEncoding {
+                encoding: UTF_8,
+                bom: true,
+            }
+        );
+
+        let decoded =
+            WebVtt::parse_bytes_with_encoding(text.as_bytes(), UTF_8).unwrap();
+        assert_eq!(decoded.output, vtt);
+        assert!(decoded.encoding.bom);
+        assert_eq!(
+            decoded.source,
+            EncodingSource::Specified
+        );
+
+        let srt = format!("\u{feff}{}Hello\r\n", SRT);
+        assert!(SubRip::parse(&srt).is_ok());
+        assert!(SubRip::parse_lenient(&srt).is_clean());
+        assert!(WebVtt::parse_lenient(text).is_clean());
+    }
+
+    #[test]
+    fn unmappable() {
+        let encoded = TextEncoding::new(SHIFT_JIS).encode("Café 😀");
+
+        assert!(encoded.had_unmappable);
+        assert_eq!(encoded.bytes, b"Caf&#233; &#128512;");
+
+        let decoded = decode(b"Caf\xE9 \xFF\xFE");
+        assert_eq!(decoded.source, EncodingSource::Guessed);
+        assert_ne!(decoded.encoding.encoding, UTF_8);
+    }
+}
@@ -1,20 +1,293 @@
 //! Error types for parsing.
 
+use std::fmt::Display;
+
 /// The error of parsing.
-#[derive(Debug, thiserror::Error)]
-#[error("Parse error at {location}: expected {expected}")]
+///
+/// ## Example
+/// ```
+/// use subtp::srt::SubRip;
+/// use subtp::ParseErrorKind;
+///
+/// let text = "1\n00:00:01,000 -> 00:00:02,000\nHello, world!\n";
+///
+/// let error = SubRip::parse(text).unwrap_err();
+/// assert_eq!(error.line, 2);
+/// assert_eq!(error.column, 14);
+/// assert_eq!(error.offset, 15);
+/// assert_eq!(error.block_index, Some(0));
+/// assert_eq!(error.snippet, "00:00:01,000 -> 00:00:02,000");
+/// assert!(matches!(
+///     error.kind,
+///     ParseErrorKind::UnexpectedCharacter { found: '-', .. }
+/// ));
+/// assert_eq!(
+///     error.render_excerpt(),
+///     "2 | 00:00:01,000 -> 00:00:02,000\n  |              ^\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Parse error at {line}:{column}: {kind}")]
 pub struct ParseError {
-    /// The furthest position the parser reached in the input before failing.
-    pub location: String,
-    /// The set of literals that failed to match at that position.
-    pub expected: String,
+    /// The line number starting from 1 of the furthest position the parser reached before failing.
+    pub line: usize,
+    /// The column number in characters starting from 1 of the position.
+    pub column: usize,
+    /// The byte offset from the start of the input of the position.
+    pub offset: usize,
+    /// The kind of error.
+    pub kind: ParseErrorKind,
+    /// The index of the block containing the position, where blocks are separated by blank lines,
+    /// or `None` if the position is before the first block.
+    pub block_index: Option<usize>,
+    /// The line of the input containing the position without the newline.
+    pub snippet: String,
 }
 
-impl From<peg::error::ParseError<peg::str::LineCol>> for ParseError {
-    fn from(err: peg::error::ParseError<peg::str::LineCol>) -> Self {
+impl ParseError {
+    /// Creates the error from the error of the parser with the input.
+    pub(crate) fn from_peg(
+        input: &str,
+        err: peg::error::ParseError<peg::str::LineCol>,
+    ) -> Self {
+        let offset = err.location.offset;
+        let expected = err
+            .expected
+            .tokens()
+            .map(|token| token.to_string())
+            .collect();
+        let kind = match input[offset..].chars().next() {
+            | Some(found) => {
+                ParseErrorKind::UnexpectedCharacter {
+                    found,
+                    expected,
+                }
+            },
+            | None => {
+                ParseErrorKind::UnexpectedEndOfInput {
+                    expected,
+                }
+            },
+        };
+
+        let line_start = input[..offset]
+            .rfind('\n')
+            .map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |i| offset + i);
+        let snippet = input[line_start..line_end]
+            .trim_end_matches('\r')
+            .to_string();
+
         ParseError {
-            location: format!("{}", err.location),
-            expected: format!("{}", err.expected),
+            line: err.location.line,
+            column: err.location.column,
+            offset,
+            kind,
+            block_index: block_index(&input[..line_end]),
+            snippet,
+        }
+    }
+
+    /// Moves the position from the block to the whole input
+    /// by the line number and the byte offset of the start of the block.
+    pub(crate) fn in_block(
+        mut self,
+        line: usize,
+        offset: usize,
+        block_index: usize,
+    ) -> Self {
+        self.line += line - 1;
+        self.offset += offset;
+        self.block_index = Some(block_index);
+        self
+    }
+
+    /// Renders the excerpt of the input with a caret at the position.
+    pub fn render_excerpt(&self) -> String {
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let padding = self
+            .snippet
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| {
+                if c == '\t' {
+                    '\t'
+                } else {
+                    ' '
+                }
+            })
+            .collect::<String>();
+
+        format!(
+            "{} | {}\n{} | {}^\n",
+            number, self.snippet, gutter, padding
+        )
+    }
+}
+
+/// Counts the index of the block containing the last line of the input,
+/// or the block before it if the line is blank.
+fn block_index(input: &str) -> Option<usize> {
+    let mut blocks: usize = 0;
+    let mut in_block = false;
+    for line in input.split('\n') {
+        if line.trim().is_empty() {
+            in_block = false;
+        } else if !in_block {
+            in_block = true;
+            blocks += 1;
+        }
+    }
+
+    blocks.checked_sub(1)
+}
+
+/// The kind of parse error.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseErrorKind {
+    /// The input ended while the tokens were expected.
+    UnexpectedEndOfInput {
+        /// The tokens expected at the position.
+        expected: Vec<String>,
+    },
+    /// The character was found while the tokens were expected.
+    UnexpectedCharacter {
+        /// The character found at the position.
+        found: char,
+        /// The tokens expected at the position.
+        expected: Vec<String>,
+    },
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        let (found, expected) = match self {
+            | Self::UnexpectedEndOfInput {
+                expected,
+            } => ("end of input".to_string(), expected),
+            | Self::UnexpectedCharacter {
+                found,
+                expected,
+            } => (format!("{:?}", found), expected),
+        };
+
+        match expected.as_slice() {
+            | [] => write!(f, "unexpected {}", found),
+            | [token] => {
+                write!(
+                    f,
+                    "unexpected {}, expected {}",
+                    found, token
+                )
+            },
+            | tokens => {
+                write!(
+                    f,
+                    "unexpected {}, expected one of {}",
+                    found,
+                    tokens.join(", ")
+                )
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::ParseErrorKind;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn block_index() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\nA\n\n2\n00:00:03,000 --> 00:00:04.000\nB\n";
+        let error = SubRip::parse(text).unwrap_err();
+
+        assert_eq!(error.line, 6);
+        assert_eq!(error.column, 26);
+        assert_eq!(error.offset, 62);
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnexpectedCharacter {
+                found: '.',
+                expected: vec!["\",\"".to_string()],
+            }
+        );
+        assert_eq!(error.block_index, Some(1));
+        assert_eq!(
+            error.snippet,
+            "00:00:03,000 --> 00:00:04.000"
+        );
+        assert_eq!(
+            error.render_excerpt(),
+            "6 | 00:00:03,000 --> 00:00:04.000\n  |                          ^\n"
+        );
+
+        let text = "WEBVTT\n\n00:01.000 --> 00:02.000\nHello\n\n\n00:03.000 --> 00:0x.000\nWorld\n";
+        let error = WebVtt::parse(text).unwrap_err();
+
+        assert_eq!(error.line, 8);
+        assert_eq!(error.block_index, Some(2));
+        assert_eq!(error.snippet, "World");
+    }
+
+    #[test]
+    fn end_of_input() {
+        let error =
+            SubRip::parse("1\r\n00:00:01,000 --> 00:00:02,000").unwrap_err();
+
+        assert_eq!(error.line, 2);
+        assert_eq!(error.column, 30);
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnexpectedEndOfInput {
+                expected: vec![
+                    "\"\\n\"".to_string(),
+                    "\"\\r\"".to_string(),
+                    "\"\\r\\n\"".to_string(),
+                    "[' ' | '\\t']".to_string(),
+                ],
+            }
+        );
+        assert_eq!(error.block_index, Some(0));
+        assert_eq!(
+            error.snippet,
+            "00:00:01,000 --> 00:00:02,000"
+        );
+        assert_eq!(
+            error.to_string(),
+            "Parse error at 2:30: unexpected end of input, expected one of \"\\n\", \"\\r\", \"\\r\\n\", [' ' | '\\t']"
+        );
+    }
+
+    #[test]
+    fn render_excerpt_with_tabs() {
+        let error = SubRip::parse("1\n00:00:01,000\t-->\t00:00:02.000\nA\n")
+            .unwrap_err();
+
+        assert_eq!(error.column, 26);
+        assert_eq!(
+            error.render_excerpt(),
+            format!(
+                "2 | 00:00:01,000\t-->\t00:00:02.000\n  | {}\t{}\t{}^\n",
+                " ".repeat(12),
+                " ".repeat(3),
+                " ".repeat(8)
+            )
+        );
+
+        // The column is edited to zero.
+        let mut error = error;
+        error.column = 0;
+        assert_eq!(
+            error.render_excerpt(),
+            "2 | 00:00:01,000\t-->\t00:00:02.000\n  | ^\n"
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! A format-agnostic interface of the subtitle formats provided by [`subtp::SubtitleFormat`](SubtitleFormat)
+//! with the common cue model of [`subtp::format::Cue`](Cue).
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::SubtitleFormat;
+//! use subtp::srt::SubRip;
+//! use subtp::vtt::WebVtt;
+//!
+//! /// Delays all cues of any subtitle format.
+//! fn delay<T: SubtitleFormat>(
+//!     subtitle: &T,
+//!     delay: Duration,
+//! ) -> T {
+//!     let cues = subtitle
+//!         .cues()
+//!         .into_iter()
+//!         .map(|mut cue| {
+//!             cue.start += delay;
+//!             cue.end += delay;
+//!             cue
+//!         })
+//!         .collect();
+//!     T::from_cues(cues)
+//! }
+//!
+//! let srt = SubRip::parse(
+//!     "1\n00:00:01,000 --> 00:00:02,000\n<i>Hello</i>, world!\n",
+//! )
+//! .unwrap();
+//! let delayed = delay(&srt, Duration::from_secs(1));
+//! assert_eq!(
+//!     delayed.render(),
+//!     "1\n00:00:02,000 --> 00:00:03,000\n<i>Hello</i>, world!\n"
+//! );
+//!
+//! let vtt: WebVtt = srt.convert();
+//! assert_eq!(
+//!     vtt.render(),
+//!     "WEBVTT\n\n1\n00:00:01.000 --> 00:00:02.000\n<i>Hello</i>, world!\n"
+//! );
+//! ```
+
+use std::time::Duration;
+
+use crate::ParseResult;
+use crate::convert::DEFAULT_COLOR_CLASSES;
+use crate::transcript::Transcript;
+use crate::transcript::TranscriptOptions;
+use crate::vtt::Alignment;
+use crate::vtt::CueSettings;
+use crate::vtt::Line;
+use crate::vtt::LineAlignment;
+use crate::vtt::Percentage;
+use crate::vtt::Position;
+use crate::vtt::PositionAlignment;
+
+/// The format-agnostic interface of the subtitle formats.
+///
+/// The cues are mapped to the common cue model of [`Cue`],
+/// so that tools such as shifting, linting and conversion can be written once for all formats.
+pub trait SubtitleFormat: Sized {
+    /// Parses the format from the given text.
+    fn parse(text: &str) -> ParseResult<Self>;
+
+    /// Renders the text of the format.
+    fn render(&self) -> String;
+
+    /// Gets the cues in the common cue model.
+    fn cues(&self) -> Vec<Cue>;
+
+    /// Creates the format from the cues in the common cue model.
+    fn from_cues(cues: Vec<Cue>) -> Self;
+
+    /// Iterates the cues in the common cue model.
+    fn iter_cues(&self) -> std::vec::IntoIter<Cue> {
+        self.cues().into_iter()
+    }
+
+    /// Converts to another format through the common cue model.
+    ///
+    /// The data that the common cue model cannot represent is dropped,
+    /// see [`subtp::convert`](crate::convert) for the conversions between the SubRip Subtitle and the WebVTT with a report.
+    fn convert<T: SubtitleFormat>(&self) -> T {
+        T::from_cues(self.cues())
+    }
+
+    /// Creates the readable transcript of the cues, see [`subtp::transcript`](crate::transcript) for details.
+    fn transcript(
+        &self,
+        options: &TranscriptOptions,
+    ) -> Transcript {
+        Transcript::new(self.iter_cues(), options)
+    }
+}
+
+/// The cue in the common cue model.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use subtp::format::Cue;
+/// use subtp::format::CueSpan;
+///
+/// let cue = Cue {
+///     start: Duration::from_secs(1),
+///     end: Duration::from_secs(2),
+///     spans: vec![
+///         CueSpan {
+///             text: "Hello, ".to_string(),
+///             ..Default::default()
+///         },
+///         CueSpan {
+///             text: "world!\nSecond line".to_string(),
+///             italic: true,
+///             ..Default::default()
+///         },
+///     ],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(cue.plain_text(), "Hello, world!\nSecond line");
+/// assert_eq!(cue.duration(), Duration::from_secs(1));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cue {
+    /// The identifier.
+    pub identifier: Option<String>,
+    /// The start time.
+    pub start: Duration,
+    /// The end time.
+    pub end: Duration,
+    /// The text spans, where lines are separated by `\n` in the texts.
+    pub spans: Vec<CueSpan>,
+    /// The position.
+    pub position: Option<CuePosition>,
+    /// The reference to a style defined in the format, e.g. a style name of the Advanced SubStation Alpha.
+    pub style: Option<String>,
+}
+
+impl Cue {
+    /// Gets the plain text of the spans.
+    pub fn plain_text(&self) -> String {
+        self.spans
+            .iter()
+            .map(|span| span.text.as_str())
+            .collect()
+    }
+
+    /// Gets the lines of the plain text.
+    pub fn lines(&self) -> Vec<String> {
+        self.plain_text()
+            .split('\n')
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// Gets the duration from the start to the end.
+    pub fn duration(&self) -> Duration {
+        self.end
+            .saturating_sub(self.start)
+    }
+}
+
+/// The text span with the formatting in the common cue model.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CueSpan {
+    /// The text, where lines are separated by `\n`.
+    pub text: String,
+    /// The start time of the span on the timeline, e.g. for karaoke.
+    pub start: Option<Duration>,
+    /// Whether the text is italic.
+    pub italic: bool,
+    /// Whether the text is bold.
+    pub bold: bool,
+    /// Whether the text is underlined.
+    pub underline: bool,
+    /// The color as a CSS color, e.g. `red` or `#ff8000`.
+    pub color: Option<String>,
+    /// The voice, i.e. the name of the speaker.
+    pub voice: Option<String>,
+    /// The other classes of the span.
+    pub classes: Vec<String>,
+}
+
+impl CueSpan {
+    /// Checks whether the span has the same formatting as the other span.
+    pub(crate) fn has_same_format(
+        &self,
+        other: &Self,
+    ) -> bool {
+        self.start == other.start
+            && self.italic == other.italic
+            && self.bold == other.bold
+            && self.underline == other.underline
+            && self.color == other.color
+            && self.voice == other.voice
+            && self.classes == other.classes
+    }
+}
+
+/// Pushes the span by merging into the last span with the same formatting.
+pub(crate) fn push_span(
+    spans: &mut Vec<CueSpan>,
+    span: CueSpan,
+) {
+    if span.text.is_empty() {
+        return;
+    }
+
+    match spans.last_mut() {
+        | Some(last) if last.has_same_format(&span) => {
+            last.text.push_str(&span.text)
+        },
+        | _ => spans.push(span),
+    }
+}
+
+/// The position of the cue box in the common cue model.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CuePosition {
+    /// The vertical position of the cue box.
+    pub line: Option<CueLine>,
+    /// The edge of the cue box placed at the line, or `None` for the start edge.
+    pub line_anchor: Option<CueAnchor>,
+    /// The horizontal position of the cue box in percentage of the frame width.
+    pub position: Option<f32>,
+    /// The edge of the cue box placed at the position, or `None` for the default of the format.
+    pub position_anchor: Option<CueAnchor>,
+    /// The width of the cue box in percentage of the frame width.
+    pub size: Option<f32>,
+    /// The alignment of the text in the cue box.
+    pub align: Option<CueAlignment>,
+}
+
+/// The vertical position of the cue box in the common cue model.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CueLine {
+    /// The position from the top in percentage of the frame height.
+    Percentage(f32),
+    /// The line number from the top counting from `0`, or from the bottom counting from `-1`.
+    Number(i32),
+}
+
+/// The edge of the cue box placed at the line or the position in the common cue model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CueAnchor {
+    /// The top or the left edge.
+    Start,
+    /// The center.
+    Center,
+    /// The bottom or the right edge.
+    End,
+}
+
+/// The alignment of the text in the common cue model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CueAlignment {
+    /// The left alignment.
+    Left,
+    /// The center alignment.
+    #[default]
+    Center,
+    /// The right alignment.
+    Right,
+}
+
+/// Converts the WebVTT cue settings to the position.
+pub(crate) fn position_from_settings(
+    settings: &CueSettings
+) -> Option<CuePosition> {
+    let (line, line_alignment) = match settings.line {
+        | Some(Line::Percentage(percentage, alignment)) => {
+            (
+                Some(CueLine::Percentage(percentage.value)),
+                alignment,
+            )
+        },
+        | Some(Line::LineNumber(n, alignment)) => {
+            (Some(CueLine::Number(n)), alignment)
+        },
+        | None => (None, None),
+    };
+    let position = CuePosition {
+        line,
+        line_anchor: line_alignment.map(|alignment| {
+            match alignment {
+                | LineAlignment::Start => CueAnchor::Start,
+                | LineAlignment::Center => CueAnchor::Center,
+                | LineAlignment::End => CueAnchor::End,
+            }
+        }),
+        position: settings
+            .position
+            .map(|position| position.value.value),
+        position_anchor: settings
+            .position
+            .and_then(|position| position.alignment)
+            .map(|alignment| {
+                match alignment {
+                    | PositionAlignment::LineLeft => CueAnchor::Start,
+                    | PositionAlignment::Center => CueAnchor::Center,
+                    | PositionAlignment::LineRight => CueAnchor::End,
+                }
+            }),
+        size: settings
+            .size
+            .map(|size| size.value),
+        align: match settings.align {
+            | Some(Alignment::Start | Alignment::Left) => {
+                Some(CueAlignment::Left)
+            },
+            | Some(Alignment::Center) => Some(CueAlignment::Center),
+            | Some(Alignment::End | Alignment::Right) => {
+                Some(CueAlignment::Right)
+            },
+            | None => None,
+        },
+    };
+
+    if position == CuePosition::default() {
+        None
+    } else {
+        Some(position)
+    }
+}
+
+/// Converts the position to the WebVTT cue settings.
+pub(crate) fn settings_from_position(position: &CuePosition) -> CueSettings {
+    let line_alignment = position
+        .line_anchor
+        .map(|anchor| {
+            match anchor {
+                | CueAnchor::Start => LineAlignment::Start,
+                | CueAnchor::Center => LineAlignment::Center,
+                | CueAnchor::End => LineAlignment::End,
+            }
+        });
+
+    CueSettings {
+        line: position.line.map(|line| {
+            match line {
+                | CueLine::Percentage(value) => {
+                    Line::Percentage(
+                        Percentage {
+                            value,
+                        },
+                        line_alignment,
+                    )
+                },
+                | CueLine::Number(n) => Line::LineNumber(n, line_alignment),
+            }
+        }),
+        position: position
+            .position
+            .map(|value| {
+                Position {
+                    value: Percentage {
+                        value,
+                    },
+                    alignment: position
+                        .position_anchor
+                        .map(|anchor| {
+                            match anchor {
+                                | CueAnchor::Start => {
+                                    PositionAlignment::LineLeft
+                                },
+                                | CueAnchor::Center => {
+                                    PositionAlignment::Center
+                                },
+                                | CueAnchor::End => {
+                                    PositionAlignment::LineRight
+                                },
+                            }
+                        }),
+                }
+            }),
+        size: position.size.map(|value| {
+            Percentage {
+                value,
+            }
+        }),
+        align: position.align.map(|align| {
+            match align {
+                | CueAlignment::Left => Alignment::Left,
+                | CueAlignment::Center => Alignment::Center,
+                | CueAlignment::Right => Alignment::Right,
+            }
+        }),
+        ..Default::default()
+    }
+}
+
+/// Gets the red, green and blue of a CSS color in `#rrggbb`, `#rgb` or a default color class name.
+pub(crate) fn color_rgb(color: &str) -> Option<(u8, u8, u8)> {
+    let color = color
+        .trim()
+        .to_ascii_lowercase();
+    let hex = match color.strip_prefix('#') {
+        | Some(hex) if hex.len() == 3 => {
+            hex.chars()
+                .flat_map(|c| [c, c])
+                .collect()
+        },
+        | Some(hex) => hex.to_string(),
+        | None => {
+            DEFAULT_COLOR_CLASSES
+                .iter()
+                .find(|(name, _)| *name == color)?
+                .1
+                .to_string()
+        },
+    };
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let value = u32::from_str_radix(&hex, 16).ok()?;
+    Some((
+        (value >> 16) as u8,
+        (value >> 8) as u8,
+        value as u8,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_spans() {
+        let mut spans = Vec::new();
+        push_span(
+            &mut spans,
+            CueSpan {
+                text: "Hello, ".to_string(),
+                ..Default::default()
+            },
+        );
+        push_span(
+            &mut spans,
+            CueSpan {
+                text: "world".to_string(),
+                ..Default::default()
+            },
+        );
+        push_span(
+            &mut spans,
+            CueSpan {
+                text: "!".to_string(),
+                bold: true,
+                ..Default::default()
+            },
+        );
+        push_span(&mut spans, CueSpan::default());
+        assert_eq!(
+            spans,
+            vec![
+                CueSpan {
+                    text: "Hello, world".to_string(),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "!".to_string(),
+                    bold: true,
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn position_and_settings() {
+        let position = CuePosition {
+            line: Some(CueLine::Percentage(10.0)),
+            position: Some(50.0),
+            size: Some(80.0),
+            align: Some(CueAlignment::Left),
+            ..Default::default()
+        };
+        let settings = settings_from_position(&position);
+        assert_eq!(
+            settings.to_string(),
+            "line:10% position:50% size:80% align:left"
+        );
+        assert_eq!(
+            position_from_settings(&settings),
+            Some(position)
+        );
+
+        // Line numbers and anchors are kept as they are.
+        for text in [
+            "line:5",
+            "line:-2,end position:10%,line-right",
+            "line:0,center position:90%,center",
+        ] {
+            let settings = crate::vtt_parser::cue_settings(text).unwrap();
+            let position = position_from_settings(&settings).unwrap();
+            assert_eq!(
+                settings_from_position(&position).to_string(),
+                text
+            );
+        }
+        assert_eq!(
+            position_from_settings(
+                &crate::vtt_parser::cue_settings("line:5").unwrap()
+            )
+            .and_then(|position| position.line),
+            Some(CueLine::Number(5))
+        );
+        assert_eq!(
+            position_from_settings(&CueSettings::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn color() {
+        assert_eq!(
+            color_rgb("#FF8000"),
+            Some((0xFF, 0x80, 0x00))
+        );
+        assert_eq!(
+            color_rgb("#f80"),
+            Some((0xFF, 0x88, 0x00))
+        );
+        assert_eq!(
+            color_rgb("lime"),
+            Some((0x00, 0xFF, 0x00))
+        );
+        assert_eq!(color_rgb("orange"), None);
+    }
+}
@@ -0,0 +1,469 @@
+//! Indexed queries of cues by time for playback and seeking.
+//!
+//! The [`CueIndex`] is an immutable view of the cues sorted by the start
+//! with an implicit interval tree augmented by the maximum end of each subtree,
+//! so that the active cues at a time and the cues intersecting a range are found in `O(log n + k)`
+//! and the next and the previous cues in `O(log n)` for `n` cues and `k` results, including overlapping cues.
+//!
+//! The cues of WebVTT keep the internal timestamps in the cue text within the timings,
+//! which change the past and the future parts of the cue text while the cue is active.
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::index::CueIndex;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r#"WEBVTT
+//!
+//! 00:01.000 --> 00:04.000
+//! Hello, <00:02.000>world!
+//!
+//! 00:03.000 --> 00:05.000
+//! This is a test.
+//! "#;
+//!
+//! let vtt = WebVtt::parse(text).unwrap();
+//! let index = CueIndex::from(&vtt);
+//!
+//! let active = index.active_at(Duration::from_millis(3_500));
+//! assert_eq!(active.len(), 2);
+//! assert_eq!(active[0].cue.payload, vec!["Hello, <00:02.000>world!"]);
+//! assert_eq!(active[0].timestamps, vec![Duration::from_secs(2)]);
+//!
+//! assert_eq!(index.next_after(Duration::from_secs(1)).unwrap().index, 1);
+//! assert_eq!(
+//!     index.next_change_after(Duration::from_secs(1)),
+//!     Some(Duration::from_secs(2))
+//! );
+//! ```
+
+use std::time::Duration;
+
+use crate::srt::SrtSubtitle;
+use crate::srt::SubRip;
+use crate::vtt::VttBlock;
+use crate::vtt::VttCue;
+use crate::vtt::VttCueNode;
+use crate::vtt::WebVtt;
+
+/// The cue in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexedCue<'a, T> {
+    /// The index of the cue in order of the document starting from 0.
+    pub index: usize,
+    /// The start.
+    pub start: Duration,
+    /// The end.
+    pub end: Duration,
+    /// The sorted internal timestamps after the start and before the end.
+    pub timestamps: Vec<Duration>,
+    /// The cue.
+    pub cue: &'a T,
+}
+
+/// The immutable index of cues by time.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CueIndex<'a, T> {
+    /// The cues sorted by the start and the index.
+    cues: Vec<IndexedCue<'a, T>>,
+    /// The maximum end of the subtree rooted at each cue.
+    max_ends: Vec<Duration>,
+}
+
+impl<'a, T> CueIndex<'a, T> {
+    /// Creates the index of the cues.
+    pub fn new(mut cues: Vec<IndexedCue<'a, T>>) -> Self {
+        cues.sort_by_key(|cue| (cue.start, cue.index));
+        let mut max_ends = vec![Duration::ZERO; cues.len()];
+        build_max_ends(&cues, &mut max_ends, 0, cues.len());
+
+        Self {
+            cues,
+            max_ends,
+        }
+    }
+
+    /// The number of cues.
+    pub fn len(&self) -> usize {
+        self.cues.len()
+    }
+
+    /// Checks whether the index has no cues.
+    pub fn is_empty(&self) -> bool {
+        self.cues.is_empty()
+    }
+
+    /// The cues sorted by the start.
+    pub fn cues(&self) -> &[IndexedCue<'a, T>] {
+        &self.cues
+    }
+
+    /// Finds the cues active at the time, i.e. `start <= time < end`, sorted by the start.
+    pub fn active_at(
+        &self,
+        time: Duration,
+    ) -> Vec<&IndexedCue<'a, T>> {
+        let mut found = Vec::new();
+        self.search(
+            0,
+            self.cues.len(),
+            &|start| start <= time,
+            time,
+            &mut found,
+        );
+        found
+    }
+
+    /// Finds the cues intersecting the range from the start to the end exclusive, i.e. `start < to && end > from`,
+    /// sorted by the start.
+    pub fn intersecting(
+        &self,
+        from: Duration,
+        to: Duration,
+    ) -> Vec<&IndexedCue<'a, T>> {
+        let mut found = Vec::new();
+        self.search(
+            0,
+            self.cues.len(),
+            &|start| start < to,
+            from,
+            &mut found,
+        );
+        found
+    }
+
+    /// Finds the first cue starting after the time.
+    pub fn next_after(
+        &self,
+        time: Duration,
+    ) -> Option<&IndexedCue<'a, T>> {
+        let i = self
+            .cues
+            .partition_point(|cue| cue.start <= time);
+        self.cues.get(i)
+    }
+
+    /// Finds the last cue starting before the time.
+    pub fn previous_before(
+        &self,
+        time: Duration,
+    ) -> Option<&IndexedCue<'a, T>> {
+        let i = self
+            .cues
+            .partition_point(|cue| cue.start < time);
+        i.checked_sub(1)
+            .map(|i| &self.cues[i])
+    }
+
+    /// Finds the next time after the time when the active cues or their internal timestamps change.
+    pub fn next_change_after(
+        &self,
+        time: Duration,
+    ) -> Option<Duration> {
+        let next_start = self
+            .next_after(time)
+            .map(|cue| cue.start);
+
+        self.active_at(time)
+            .into_iter()
+            .flat_map(|cue| {
+                cue.timestamps
+                    .iter()
+                    .copied()
+                    .chain([cue.end])
+            })
+            .filter(|timestamp| *timestamp > time)
+            .chain(next_start)
+            .min()
+    }
+
+    /// Searches the subtree of the range for the cues with the start satisfying the condition
+    /// and the end after the time.
+    fn search<'s>(
+        &'s self,
+        low: usize,
+        high: usize,
+        starts_before: &dyn Fn(Duration) -> bool,
+        ends_after: Duration,
+        found: &mut Vec<&'s IndexedCue<'a, T>>,
+    ) {
+        if low >= high {
+            return;
+        }
+
+        let middle = low + (high - low) / 2;
+        // No cues in the subtree end after the time.
+        if self.max_ends[middle] <= ends_after {
+            return;
+        }
+
+        self.search(
+            low,
+            middle,
+            starts_before,
+            ends_after,
+            found,
+        );
+        let cue = &self.cues[middle];
+        // The cues in the right subtree start after the cue.
+        if starts_before(cue.start) {
+            if cue.end > ends_after {
+                found.push(cue);
+            }
+            self.search(
+                middle + 1,
+                high,
+                starts_before,
+                ends_after,
+                found,
+            );
+        }
+    }
+}
+
+/// Builds the maximum end of the subtree of the range and returns it.
+fn build_max_ends<T>(
+    cues: &[IndexedCue<'_, T>],
+    max_ends: &mut [Duration],
+    low: usize,
+    high: usize,
+) -> Duration {
+    if low >= high {
+        return Duration::ZERO;
+    }
+
+    let middle = low + (high - low) / 2;
+    let max_end = cues[middle]
+        .end
+        .max(build_max_ends(
+            cues, max_ends, low, middle,
+        ))
+        .max(build_max_ends(
+            cues,
+            max_ends,
+            middle + 1,
+            high,
+        ));
+    max_ends[middle] = max_end;
+
+    max_end
+}
+
+impl<'a> From<&'a SubRip> for CueIndex<'a, SrtSubtitle> {
+    fn from(srt: &'a SubRip) -> Self {
+        Self::new(
+            srt.subtitles
+                .iter()
+                .enumerate()
+                .map(|(index, subtitle)| {
+                    IndexedCue {
+                        index,
+                        start: subtitle.start.into(),
+                        end: subtitle.end.into(),
+                        timestamps: Vec::new(),
+                        cue: subtitle,
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<'a> From<&'a WebVtt> for CueIndex<'a, VttCue> {
+    fn from(vtt: &'a WebVtt) -> Self {
+        Self::new(
+            vtt.blocks
+                .iter()
+                .filter_map(|block| {
+                    match block {
+                        | VttBlock::Que(cue) => Some(cue),
+                        | _ => None,
+                    }
+                })
+                .enumerate()
+                .map(|(index, cue)| {
+                    let start = cue.timings.start.into();
+                    let end = cue.timings.end.into();
+                    let mut timestamps = Vec::new();
+                    collect_timestamps(
+                        &cue.parse_payload().nodes,
+                        &mut timestamps,
+                    );
+                    timestamps.retain(|timestamp| {
+                        start < *timestamp && *timestamp < end
+                    });
+                    timestamps.sort();
+                    timestamps.dedup();
+
+                    IndexedCue {
+                        index,
+                        start,
+                        end,
+                        timestamps,
+                        cue,
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Collects the internal timestamps in the nodes.
+fn collect_timestamps(
+    nodes: &[VttCueNode],
+    timestamps: &mut Vec<Duration>,
+) {
+    for node in nodes {
+        match node {
+            | VttCueNode::Timestamp(timestamp) => {
+                timestamps.push((*timestamp).into())
+            },
+            | VttCueNode::Span(span) => {
+                collect_timestamps(&span.children, timestamps)
+            },
+            | VttCueNode::Text(_)
+            | VttCueNode::Raw {
+                ..
+            } => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    fn indices<T>(cues: Vec<&IndexedCue<'_, T>>) -> Vec<usize> {
+        cues.into_iter()
+            .map(|cue| cue.index)
+            .collect()
+    }
+
+    /// Checks the queries against the linear scan.
+    #[test]
+    fn overlapping() {
+        let timings = [
+            (5, 30),
+            (0, 2),
+            (1, 4),
+            (3, 6),
+            (3, 3),
+            (8, 9),
+            (10, 12),
+            (11, 20),
+            (13, 14),
+            (25, 26),
+        ];
+        let values = timings
+            .iter()
+            .map(|&(start, end)| (secs(start), secs(end)))
+            .collect::<Vec<_>>();
+        let index = CueIndex::new(
+            values
+                .iter()
+                .enumerate()
+                .map(|(index, (start, end))| {
+                    IndexedCue {
+                        index,
+                        start: *start,
+                        end: *end,
+                        timestamps: Vec::new(),
+                        cue: &values[index],
+                    }
+                })
+                .collect(),
+        );
+
+        for time in 0..32 {
+            let time = secs(time) + Duration::from_millis(500 * (time % 2));
+            let mut expected = values
+                .iter()
+                .enumerate()
+                .filter(|(_, (start, end))| *start <= time && time < *end)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let mut active = indices(index.active_at(time));
+            expected.sort();
+            active.sort();
+            assert_eq!(active, expected, "at {:?}", time);
+        }
+
+        let mut intersecting = indices(index.intersecting(secs(4), secs(11)));
+        intersecting.sort();
+        assert_eq!(intersecting, vec![0, 3, 5, 6]);
+
+        assert_eq!(
+            index
+                .next_after(secs(3))
+                .unwrap()
+                .index,
+            0
+        );
+        assert_eq!(index.next_after(secs(25)), None);
+        assert_eq!(
+            index
+                .previous_before(secs(3))
+                .unwrap()
+                .index,
+            2
+        );
+        assert_eq!(index.previous_before(secs(0)), None);
+        assert_eq!(
+            index.next_change_after(secs(21)),
+            Some(secs(25))
+        );
+        assert_eq!(index.next_change_after(secs(30)), None);
+    }
+
+    #[test]
+    fn subrip() {
+        let text = "1\n00:00:01,000 --> 00:00:03,000\nA\n\n2\n00:00:02,000 --> 00:00:04,000\nB\n";
+        let srt = SubRip::parse(text).unwrap();
+        let index = CueIndex::from(&srt);
+
+        assert_eq!(index.len(), 2);
+        assert_eq!(
+            index
+                .active_at(Duration::from_millis(2_500))
+                .into_iter()
+                .map(|cue| cue.cue.text[0].as_str())
+                .collect::<Vec<_>>(),
+            vec!["A", "B"]
+        );
+        assert_eq!(
+            index.next_change_after(Duration::from_millis(2_500)),
+            Some(secs(3))
+        );
+    }
+
+    #[test]
+    fn internal_timestamps() {
+        let text = r#"WEBVTT
+
+NOTE Not a cue.
+
+00:01.000 --> 00:05.000
+<00:00.500>Out of <c><00:03.000>range<00:02.000> and</c> <00:06.000>sorted.
+"#;
+        let vtt = WebVtt::parse(text).unwrap();
+        let index = CueIndex::from(&vtt);
+
+        assert_eq!(
+            index.cues()[0].timestamps,
+            vec![secs(2), secs(3)]
+        );
+        assert_eq!(
+            index.next_change_after(secs(2)),
+            Some(secs(3))
+        );
+        assert_eq!(
+            index.next_change_after(secs(3)),
+            Some(secs(5))
+        );
+    }
+}
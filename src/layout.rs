@@ -0,0 +1,1442 @@
+//! Layout of the WebVTT cues on the screen.
+//!
+//! The layout follows the rules to apply the WebVTT cue settings and to lay out the regions of the WebVTT,
+//! i.e. the writing direction, the position, the size and the line of the cues with and without snap-to-lines
+//! including the steps to avoid overlapping the other cues, and the regions stacking the lines of their cues
+//! from the bottom and scrolling them up.
+//!
+//! The text is not shaped, where the lines are the lines of the payload wrapped by the width of a character if any,
+//! and every line has the height of the line.
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::layout::LayoutOptions;
+//! use subtp::layout::WritingDirection;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r#"WEBVTT
+//!
+//! 00:01.000 --> 00:04.000
+//! Hello, world!
+//!
+//! 00:02.000 --> 00:05.000 line:0 align:start
+//! At the top.
+//!
+//! 00:03.000 --> 00:05.000
+//! Above the first cue.
+//! "#;
+//!
+//! let vtt = WebVtt::parse(text).unwrap();
+//! let options = LayoutOptions {
+//!     width: 1280.0,
+//!     height: 720.0,
+//!     line_height: 40.0,
+//!     ..Default::default()
+//! };
+//!
+//! let layout = vtt.layout_at(Duration::from_millis(3_500), &options);
+//! assert_eq!(layout.cues.len(), 3);
+//! assert_eq!(layout.cues[0].direction, WritingDirection::Horizontal);
+//! assert_eq!(layout.cues[0].rect.y, 680.0);
+//! assert_eq!(layout.cues[1].rect.y, 0.0);
+//! assert_eq!(layout.cues[2].rect.y, 640.0);
+//! ```
+
+use std::time::Duration;
+
+use crate::vtt::Alignment;
+use crate::vtt::CueSettings;
+use crate::vtt::Line;
+use crate::vtt::LineAlignment;
+use crate::vtt::PositionAlignment;
+use crate::vtt::Scroll;
+use crate::vtt::Vertical;
+use crate::vtt::VttBlock;
+use crate::vtt::VttCue;
+use crate::vtt::VttRegion;
+use crate::vtt::WebVtt;
+
+/// The duration of scrolling up the lines of a region by a new cue.
+pub const SCROLL_DURATION: Duration = Duration::from_millis(433);
+
+/// The tolerance of comparing the coordinates.
+const EPSILON: f32 = 0.001;
+
+/// The options of the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayoutOptions {
+    /// The width of the viewport.
+    pub width: f32,
+    /// The height of the viewport.
+    pub height: f32,
+    /// The height of a line, or the width of a line of the vertical cues.
+    pub line_height: f32,
+    /// The width of a character to wrap the lines, or `None` not to wrap.
+    pub character_width: Option<f32>,
+}
+
+impl Default for LayoutOptions {
+    fn default() -> Self {
+        Self {
+            width: 1280.0,
+            height: 720.0,
+            line_height: 43.2,
+            character_width: None,
+        }
+    }
+}
+
+/// The rectangle in the viewport.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rect {
+    /// The left.
+    pub x: f32,
+    /// The top.
+    pub y: f32,
+    /// The width.
+    pub width: f32,
+    /// The height.
+    pub height: f32,
+}
+
+impl Rect {
+    /// The right.
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+
+    /// The bottom.
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Whether the rectangle overlaps the other one.
+    pub fn intersects(
+        &self,
+        other: &Rect,
+    ) -> bool {
+        self.x < other.right() - EPSILON
+            && other.x < self.right() - EPSILON
+            && self.y < other.bottom() - EPSILON
+            && other.y < self.bottom() - EPSILON
+    }
+
+    /// Whether the rectangle contains the other one entirely.
+    pub fn contains(
+        &self,
+        other: &Rect,
+    ) -> bool {
+        other.x >= self.x - EPSILON
+            && other.y >= self.y - EPSILON
+            && other.right() <= self.right() + EPSILON
+            && other.bottom() <= self.bottom() + EPSILON
+    }
+
+    /// The ratio of the area outside of the other rectangle.
+    fn outside_ratio(
+        &self,
+        other: &Rect,
+    ) -> f32 {
+        let area = self.width * self.height;
+        if area <= 0.0 {
+            return 0.0;
+        }
+
+        let width = (self
+            .right()
+            .min(other.right())
+            - self.x.max(other.x))
+        .max(0.0);
+        let height = (self
+            .bottom()
+            .min(other.bottom())
+            - self.y.max(other.y))
+        .max(0.0);
+        1.0 - width * height / area
+    }
+
+    /// Moves the rectangle along the block direction of the writing direction.
+    fn moved(
+        &self,
+        direction: WritingDirection,
+        distance: f32,
+    ) -> Self {
+        match direction {
+            | WritingDirection::Horizontal => {
+                Self {
+                    y: self.y + distance,
+                    ..*self
+                }
+            },
+            | _ => {
+                Self {
+                    x: self.x + distance,
+                    ..*self
+                }
+            },
+        }
+    }
+}
+
+/// The writing direction of a cue.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum WritingDirection {
+    /// Horizontal lines growing down.
+    #[default]
+    Horizontal,
+    /// Vertical lines growing left by `vertical:rl`.
+    VerticalGrowingLeft,
+    /// Vertical lines growing right by `vertical:lr`.
+    VerticalGrowingRight,
+}
+
+impl From<Option<Vertical>> for WritingDirection {
+    fn from(vertical: Option<Vertical>) -> Self {
+        match vertical {
+            | None => Self::Horizontal,
+            | Some(Vertical::Rl) => Self::VerticalGrowingLeft,
+            | Some(Vertical::Lr) => Self::VerticalGrowingRight,
+        }
+    }
+}
+
+/// The layout of the cues and the regions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layout<'a> {
+    /// The cues in order of the start and the end latest first.
+    pub cues: Vec<CueBox<'a>>,
+    /// The regions of the cues.
+    pub regions: Vec<RegionBox<'a>>,
+}
+
+/// The box of a cue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueBox<'a> {
+    /// The cue.
+    pub cue: &'a VttCue,
+    /// The writing direction.
+    pub direction: WritingDirection,
+    /// The rectangle.
+    pub rect: Rect,
+    /// The lines in order of the block direction.
+    pub lines: Vec<LineBox>,
+    /// The index of the region in the layout if the cue is in a region.
+    pub region: Option<usize>,
+}
+
+/// The box of a line of a cue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    /// The plain text.
+    pub text: String,
+    /// The rectangle aligned by the text alignment.
+    pub rect: Rect,
+    /// Whether the line is visible, which is false if clipped out of the region.
+    pub visible: bool,
+}
+
+/// The box of a region.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegionBox<'a> {
+    /// The region.
+    pub region: &'a VttRegion,
+    /// The rectangle.
+    pub rect: Rect,
+    /// The offset of the lines down not scrolled up yet by the last cue.
+    pub scroll_offset: f32,
+}
+
+/// Lays out the cues all showing with the regions defined.
+///
+/// ## Example
+/// ```
+/// use subtp::layout::layout;
+/// use subtp::layout::LayoutOptions;
+/// use subtp::layout::WritingDirection;
+/// use subtp::vtt::CueSettings;
+/// use subtp::vtt::Vertical;
+/// use subtp::vtt::VttCue;
+///
+/// let cue = VttCue {
+///     settings: Some(CueSettings {
+///         vertical: Some(Vertical::Rl),
+///         ..Default::default()
+///     }),
+///     payload: vec!["縦書き".to_string()],
+///     ..Default::default()
+/// };
+/// let options = LayoutOptions {
+///     line_height: 40.0,
+///     ..Default::default()
+/// };
+///
+/// let layout = layout(&[&cue], &[], &options);
+/// assert_eq!(layout.cues[0].direction, WritingDirection::VerticalGrowingLeft);
+/// assert_eq!(layout.cues[0].rect.x, 0.0);
+/// assert_eq!(layout.cues[0].rect.width, 40.0);
+/// ```
+pub fn layout<'a>(
+    cues: &[&'a VttCue],
+    regions: &[&'a VttRegion],
+    options: &LayoutOptions,
+) -> Layout<'a> {
+    layout_cues(cues, regions, options, None)
+}
+
+/// Lays out the cues of the WebVTT active at the time.
+pub(crate) fn layout_at<'a>(
+    vtt: &'a WebVtt,
+    time: Duration,
+    options: &LayoutOptions,
+) -> Layout<'a> {
+    let mut cues = Vec::new();
+    let mut regions = Vec::new();
+    for block in &vtt.blocks {
+        match block {
+            | VttBlock::Que(cue) => {
+                let start: Duration = cue.timings.start.into();
+                let end: Duration = cue.timings.end.into();
+                if start <= time && time < end {
+                    cues.push(cue);
+                }
+            },
+            | VttBlock::Region(region) => regions.push(region),
+            | _ => {},
+        }
+    }
+
+    layout_cues(&cues, &regions, options, Some(time))
+}
+
+/// Lays out the cues, where the time scrolls up the regions.
+fn layout_cues<'a>(
+    cues: &[&'a VttCue],
+    regions: &[&'a VttRegion],
+    options: &LayoutOptions,
+    time: Option<Duration>,
+) -> Layout<'a> {
+    let mut cues = cues.to_vec();
+    cues.sort_by(|a, b| {
+        a.timings
+            .start
+            .cmp(&b.timings.start)
+            .then(
+                b.timings
+                    .end
+                    .cmp(&a.timings.end),
+            )
+    });
+
+    let title = Rect {
+        x: 0.0,
+        y: 0.0,
+        width: options.width,
+        height: options.height,
+    };
+    let mut layout = Layout::default();
+    let mut output = Vec::new();
+
+    for cue in cues {
+        let settings = cue
+            .settings
+            .clone()
+            .unwrap_or_default();
+
+        if let Some(region) = region_of(&settings, regions) {
+            let index = match layout
+                .regions
+                .iter()
+                .position(|found| std::ptr::eq(found.region, region))
+            {
+                | Some(index) => index,
+                | None => {
+                    let rect = region_rect(region, options);
+                    output.push(rect);
+                    layout
+                        .regions
+                        .push(RegionBox {
+                            region,
+                            rect,
+                            scroll_offset: 0.0,
+                        });
+                    layout.regions.len() - 1
+                },
+            };
+
+            let region_rect = layout.regions[index].rect;
+            let (x, width) = inline_extent(&settings, region_rect.width);
+            let texts = wrap_payload(cue, width, options);
+            let rect = Rect {
+                x: region_rect.x + x,
+                y: 0.0,
+                width,
+                height: texts.len() as f32 * options.line_height,
+            };
+            layout.cues.push(CueBox {
+                cue,
+                direction: WritingDirection::Horizontal,
+                rect,
+                lines: line_boxes(
+                    texts,
+                    rect,
+                    WritingDirection::Horizontal,
+                    &settings,
+                    options,
+                ),
+                region: Some(index),
+            });
+            continue;
+        }
+
+        let direction = WritingDirection::from(settings.vertical);
+        let full = match direction {
+            | WritingDirection::Horizontal => options.width,
+            | _ => options.height,
+        };
+        let (offset, extent) = inline_extent(&settings, full);
+        let texts = wrap_payload(cue, extent, options);
+        let block = texts.len() as f32 * options.line_height;
+        let rect = match direction {
+            | WritingDirection::Horizontal => {
+                Rect {
+                    x: offset,
+                    y: 0.0,
+                    width: extent,
+                    height: block,
+                }
+            },
+            | _ => {
+                Rect {
+                    x: 0.0,
+                    y: offset,
+                    width: block,
+                    height: extent,
+                }
+            },
+        };
+
+        let rect = match settings.line {
+            | Some(Line::Percentage(percentage, alignment)) => {
+                position_by_percentage(
+                    rect,
+                    direction,
+                    percentage.value,
+                    alignment,
+                    title,
+                    &output,
+                )
+            },
+            | Some(Line::LineNumber(line, _)) => {
+                snap_to_lines(
+                    rect,
+                    direction,
+                    line as f32,
+                    title,
+                    &output,
+                    options,
+                )
+            },
+            | None => {
+                snap_to_lines(
+                    rect, direction, -1.0, title, &output, options,
+                )
+            },
+        };
+
+        output.push(rect);
+        layout.cues.push(CueBox {
+            cue,
+            direction,
+            rect,
+            lines: line_boxes(
+                texts, rect, direction, &settings, options,
+            ),
+            region: None,
+        });
+    }
+
+    for index in 0..layout.regions.len() {
+        stack_region(&mut layout, index, time);
+    }
+
+    layout
+}
+
+/// Finds the region of the cue, where the cue with the line, the size or the vertical setting is not in the region.
+fn region_of<'a>(
+    settings: &CueSettings,
+    regions: &[&'a VttRegion],
+) -> Option<&'a VttRegion> {
+    let id = settings.region.as_ref()?;
+    if settings.vertical.is_some()
+        || settings.line.is_some()
+        || settings
+            .size
+            .map_or(false, |size| size.value != 100.0)
+    {
+        return None;
+    }
+
+    regions
+        .iter()
+        .rev()
+        .find(|region| region.id.as_ref() == Some(id))
+        .copied()
+}
+
+/// Computes the rectangle of the region.
+fn region_rect(
+    region: &VttRegion,
+    options: &LayoutOptions,
+) -> Rect {
+    let width = region
+        .width
+        .map_or(100.0, |width| width.value)
+        * options.width
+        / 100.0;
+    let height = region.lines.unwrap_or(3) as f32 * options.line_height;
+    let region_anchor = region
+        .region_anchor
+        .unwrap_or_default();
+    let viewport_anchor = region
+        .viewport_anchor
+        .unwrap_or_default();
+
+    Rect {
+        x: viewport_anchor.x.value * options.width / 100.0
+            - region_anchor.x.value * width / 100.0,
+        y: viewport_anchor.y.value * options.height / 100.0
+            - region_anchor.y.value * height / 100.0,
+        width,
+        height,
+    }
+}
+
+/// Stacks the lines of the cues in the region from the bottom, scrolling up by the last cue at the time.
+fn stack_region(
+    layout: &mut Layout,
+    index: usize,
+    time: Option<Duration>,
+) {
+    let region = &layout.regions[index];
+    let rect = region.rect;
+    let cues = layout
+        .cues
+        .iter()
+        .enumerate()
+        .filter(|(_, cue)| cue.region == Some(index))
+        .map(|(position, _)| position)
+        .collect::<Vec<_>>();
+
+    let mut scroll_offset = 0.0;
+    if let (Some(Scroll::Up), Some(time), [_, .., last]) = (
+        region.region.scroll,
+        time,
+        cues.as_slice(),
+    ) {
+        let last = &layout.cues[*last];
+        let start: Duration = last.cue.timings.start.into();
+        let elapsed = time.saturating_sub(start);
+        if elapsed < SCROLL_DURATION {
+            scroll_offset = last.rect.height
+                * (1.0 - elapsed.as_secs_f32() / SCROLL_DURATION.as_secs_f32());
+        }
+    }
+    layout.regions[index].scroll_offset = scroll_offset;
+
+    let height = cues
+        .iter()
+        .map(|position| {
+            layout.cues[*position]
+                .rect
+                .height
+        })
+        .sum::<f32>();
+    let mut y = rect.bottom() - height + scroll_offset;
+    for position in cues {
+        let cue = &mut layout.cues[position];
+        let distance = y - cue.rect.y;
+        cue.rect.y = y;
+        for line in &mut cue.lines {
+            line.rect.y += distance;
+            line.visible = line.rect.intersects(&rect);
+        }
+        y += cue.rect.height;
+    }
+}
+
+/// Computes the offset and the extent along the inline direction by the position, the alignment and the size.
+fn inline_extent(
+    settings: &CueSettings,
+    full: f32,
+) -> (f32, f32) {
+    let align = settings
+        .align
+        .unwrap_or(Alignment::Center);
+    let position = settings
+        .position
+        .map(|position| position.value.value)
+        .unwrap_or(match align {
+            | Alignment::Start | Alignment::Left => 0.0,
+            | Alignment::Center => 50.0,
+            | Alignment::End | Alignment::Right => 100.0,
+        });
+    let alignment = settings
+        .position
+        .and_then(|position| position.alignment)
+        .unwrap_or(match align {
+            | Alignment::Start | Alignment::Left => PositionAlignment::LineLeft,
+            | Alignment::Center => PositionAlignment::Center,
+            | Alignment::End | Alignment::Right => PositionAlignment::LineRight,
+        });
+
+    let maximum = match alignment {
+        | PositionAlignment::LineLeft => 100.0 - position,
+        | PositionAlignment::LineRight => position,
+        | PositionAlignment::Center if position <= 50.0 => position * 2.0,
+        | PositionAlignment::Center => (100.0 - position) * 2.0,
+    };
+    let size = settings
+        .size
+        .map_or(100.0, |size| size.value)
+        .min(maximum)
+        .max(0.0);
+    let offset = match alignment {
+        | PositionAlignment::LineLeft => position,
+        | PositionAlignment::Center => position - size / 2.0,
+        | PositionAlignment::LineRight => position - size,
+    };
+
+    (
+        offset * full / 100.0,
+        size * full / 100.0,
+    )
+}
+
+/// Positions the rectangle by the line of the percentage without snap-to-lines,
+/// moving it to the closest position to avoid the output within the title area if any.
+fn position_by_percentage(
+    rect: Rect,
+    direction: WritingDirection,
+    percentage: f32,
+    alignment: Option<LineAlignment>,
+    title: Rect,
+    output: &[Rect],
+) -> Rect {
+    let percentage = if (0.0..=100.0).contains(&percentage) {
+        percentage
+    } else {
+        100.0
+    };
+    let factor = match alignment.unwrap_or(LineAlignment::Start) {
+        | LineAlignment::Start => 0.0,
+        | LineAlignment::Center => 0.5,
+        | LineAlignment::End => 1.0,
+    };
+
+    let rect = match direction {
+        | WritingDirection::Horizontal => {
+            Rect {
+                y: percentage * title.height / 100.0 - factor * rect.height,
+                ..rect
+            }
+        },
+        | _ => {
+            Rect {
+                x: percentage * title.width / 100.0 - factor * rect.width,
+                ..rect
+            }
+        },
+    };
+
+    closest_free_position(rect, title, output).unwrap_or(Rect {
+        x: rect
+            .x
+            .min(title.right() - rect.width)
+            .max(title.x),
+        y: rect
+            .y
+            .min(title.bottom() - rect.height)
+            .max(title.y),
+        ..rect
+    })
+}
+
+/// Finds the closest position of the rectangle within the title area not overlapping the output,
+/// preferring the highest and then the leftmost one of the same distance, or `None` if not found.
+fn closest_free_position(
+    rect: Rect,
+    title: Rect,
+    output: &[Rect],
+) -> Option<Rect> {
+    // The closest position is at the current position or on an edge of the title area or another box.
+    let mut xs = vec![
+        rect.x
+            .min(title.right() - rect.width)
+            .max(title.x),
+        title.x,
+        title.right() - rect.width,
+    ];
+    let mut ys = vec![
+        rect.y
+            .min(title.bottom() - rect.height)
+            .max(title.y),
+        title.y,
+        title.bottom() - rect.height,
+    ];
+    for other in output {
+        xs.extend([
+            other.x - rect.width,
+            other.right(),
+        ]);
+        ys.extend([
+            other.y - rect.height,
+            other.bottom(),
+        ]);
+    }
+
+    let mut best: Option<(f32, Rect)> = None;
+    for &y in &ys {
+        for &x in &xs {
+            let candidate = Rect {
+                x,
+                y,
+                ..rect
+            };
+            if !title.contains(&candidate)
+                || output
+                    .iter()
+                    .any(|other| other.intersects(&candidate))
+            {
+                continue;
+            }
+
+            let distance = (x - rect.x).hypot(y - rect.y);
+            let closer = best.map_or(true, |(closest, best)| {
+                distance < closest - EPSILON
+                    || (distance < closest + EPSILON
+                        && (y, x) < (best.y, best.x))
+            });
+            if closer {
+                best = Some((distance, candidate));
+            }
+        }
+    }
+
+    best.map(|(_, rect)| rect)
+}
+
+/// Positions the rectangle by the line number with snap-to-lines, moving it line by line to avoid the output.
+fn snap_to_lines(
+    rect: Rect,
+    direction: WritingDirection,
+    line: f32,
+    title: Rect,
+    output: &[Rect],
+    options: &LayoutOptions,
+) -> Rect {
+    let mut step = options.line_height;
+    if step <= 0.0 {
+        return rect;
+    }
+
+    let full = match direction {
+        | WritingDirection::Horizontal => title.height,
+        | _ => title.width,
+    };
+    let mut line = (line + 0.5).floor();
+    if direction == WritingDirection::VerticalGrowingLeft {
+        line = -(line + 1.0);
+    }
+    let mut position = step * line;
+    if direction == WritingDirection::VerticalGrowingLeft {
+        position = position - rect.width + step;
+    }
+    if line < 0.0 {
+        position += full;
+        step = -step;
+    }
+
+    let specified = rect.moved(direction, position);
+    let mut current = specified;
+    let mut best: Option<(f32, Rect)> = None;
+    let mut switched = false;
+
+    loop {
+        if title.contains(&current)
+            && !output
+                .iter()
+                .any(|other| other.intersects(&current))
+        {
+            return current;
+        }
+
+        let score = current.outside_ratio(&title);
+        if best.map_or(true, |(best, _)| score < best) {
+            best = Some((score, current));
+        }
+
+        let (start, end) = match direction {
+            | WritingDirection::Horizontal => {
+                (
+                    current.y,
+                    current.y + options.line_height,
+                )
+            },
+            | WritingDirection::VerticalGrowingLeft => {
+                (
+                    current.right() - options.line_height,
+                    current.right(),
+                )
+            },
+            | WritingDirection::VerticalGrowingRight => {
+                (
+                    current.x,
+                    current.x + options.line_height,
+                )
+            },
+        };
+        let beyond = (step < 0.0 && start < -EPSILON)
+            || (step > 0.0 && end > full + EPSILON);
+        if !beyond {
+            current = current.moved(direction, step);
+            continue;
+        }
+
+        if switched {
+            return best.map_or(current, |(_, rect)| rect);
+        }
+        current = specified;
+        step = -step;
+        switched = true;
+    }
+}
+
+/// Wraps the lines of the plain text of the payload by the extent.
+fn wrap_payload(
+    cue: &VttCue,
+    extent: f32,
+    options: &LayoutOptions,
+) -> Vec<String> {
+    let maximum = options
+        .character_width
+        .filter(|width| *width > 0.0)
+        .map(|width| ((extent / width).floor() as usize).max(1));
+
+    cue.parse_payload()
+        .plain_text()
+        .split('\n')
+        .flat_map(|line| wrap(line, maximum))
+        .collect()
+}
+
+/// Wraps the line by the maximum characters at the whitespaces, breaking the longer words.
+fn wrap(
+    line: &str,
+    maximum: Option<usize>,
+) -> Vec<String> {
+    let Some(maximum) = maximum else {
+        return vec![line.to_string()];
+    };
+
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in line.split_whitespace() {
+        let mut word = word.to_string();
+        while word.chars().count() > maximum {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            let split = word
+                .char_indices()
+                .nth(maximum)
+                .map_or(word.len(), |(index, _)| index);
+            let rest = word.split_off(split);
+            lines.push(word);
+            word = rest;
+        }
+
+        if current.is_empty() {
+            current = word;
+        } else if current.chars().count() + 1 + word.chars().count() <= maximum
+        {
+            current.push(' ');
+            current.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut current, word));
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+/// Computes the boxes of the lines in the rectangle aligned by the text alignment.
+fn line_boxes(
+    texts: Vec<String>,
+    rect: Rect,
+    direction: WritingDirection,
+    settings: &CueSettings,
+    options: &LayoutOptions,
+) -> Vec<LineBox> {
+    let factor = match settings
+        .align
+        .unwrap_or(Alignment::Center)
+    {
+        | Alignment::Start | Alignment::Left => 0.0,
+        | Alignment::Center => 0.5,
+        | Alignment::End | Alignment::Right => 1.0,
+    };
+    let line_height = options.line_height;
+
+    texts
+        .into_iter()
+        .enumerate()
+        .map(|(index, text)| {
+            let full = match direction {
+                | WritingDirection::Horizontal => rect.width,
+                | _ => rect.height,
+            };
+            let extent = options
+                .character_width
+                .map_or(full, |width| {
+                    (width * text.chars().count() as f32).min(full)
+                });
+            let start = factor * (full - extent);
+            let index = index as f32;
+
+            let rect = match direction {
+                | WritingDirection::Horizontal => {
+                    Rect {
+                        x: rect.x + start,
+                        y: rect.y + index * line_height,
+                        width: extent,
+                        height: line_height,
+                    }
+                },
+                | WritingDirection::VerticalGrowingLeft => {
+                    Rect {
+                        x: rect.right() - (index + 1.0) * line_height,
+                        y: rect.y + start,
+                        width: line_height,
+                        height: extent,
+                    }
+                },
+                | WritingDirection::VerticalGrowingRight => {
+                    Rect {
+                        x: rect.x + index * line_height,
+                        y: rect.y + start,
+                        width: line_height,
+                        height: extent,
+                    }
+                },
+            };
+
+            LineBox {
+                text,
+                rect,
+                visible: true,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::Percentage;
+    use crate::vtt::Position;
+
+    const OPTIONS: LayoutOptions = LayoutOptions {
+        width: 1000.0,
+        height: 500.0,
+        line_height: 50.0,
+        character_width: Some(25.0),
+    };
+
+    fn cue(
+        settings: CueSettings,
+        payload: &str,
+    ) -> VttCue {
+        VttCue {
+            settings: Some(settings),
+            payload: payload
+                .lines()
+                .map(String::from)
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn position_and_size() {
+        let first = cue(
+            CueSettings {
+                position: Some(Position {
+                    value: Percentage {
+                        value: 30.0,
+                    },
+                    alignment: None,
+                }),
+                size: Some(Percentage {
+                    value: 50.0,
+                }),
+                ..Default::default()
+            },
+            "A long line to be wrapped",
+        );
+        let second = cue(
+            CueSettings {
+                line: Some(Line::Percentage(
+                    Percentage {
+                        value: 50.0,
+                    },
+                    Some(LineAlignment::Center),
+                )),
+                align: Some(Alignment::Start),
+                ..Default::default()
+            },
+            "Middle",
+        );
+
+        let layout = layout(&[&first, &second], &[], &OPTIONS);
+        let first = &layout.cues[0];
+        assert_eq!(
+            first.rect,
+            Rect {
+                x: 50.0,
+                y: 400.0,
+                width: 500.0,
+                height: 100.0,
+            }
+        );
+        assert_eq!(first.lines[0].text, "A long line to be");
+        assert_eq!(first.lines[1].text, "wrapped");
+        assert_eq!(
+            first.lines[0].rect,
+            Rect {
+                x: 87.5,
+                y: 400.0,
+                width: 425.0,
+                height: 50.0,
+            }
+        );
+
+        let second = &layout.cues[1];
+        assert_eq!(
+            second.rect,
+            Rect {
+                x: 0.0,
+                y: 225.0,
+                width: 1000.0,
+                height: 50.0,
+            }
+        );
+        assert_eq!(
+            second.lines[0].rect,
+            Rect {
+                x: 0.0,
+                y: 225.0,
+                width: 150.0,
+                height: 50.0,
+            }
+        );
+    }
+
+    #[test]
+    fn snap_to_lines() {
+        let cues = [
+            cue(CueSettings::default(), "First"),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(-1, None)),
+                    ..Default::default()
+                },
+                "Second\nTwo lines",
+            ),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(0, None)),
+                    ..Default::default()
+                },
+                "Top",
+            ),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(20, None)),
+                    ..Default::default()
+                },
+                "Beyond",
+            ),
+        ];
+        let tops = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        )
+        .cues
+        .iter()
+        .map(|cue| cue.rect.y)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            tops,
+            vec![
+                450.0, 350.0, 0.0, 300.0
+            ]
+        );
+
+        let cues = [
+            cue(
+                CueSettings {
+                    vertical: Some(Vertical::Rl),
+                    line: Some(Line::LineNumber(0, None)),
+                    align: Some(Alignment::Start),
+                    ..Default::default()
+                },
+                "Right",
+            ),
+            cue(
+                CueSettings {
+                    vertical: Some(Vertical::Lr),
+                    align: Some(Alignment::End),
+                    ..Default::default()
+                },
+                "Left",
+            ),
+        ];
+        let layout = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        );
+
+        assert_eq!(layout.cues[0].rect.x, 950.0);
+        assert_eq!(layout.cues[1].rect.x, 900.0);
+        assert_eq!(
+            layout.cues[0].lines[0].rect,
+            Rect {
+                x: 950.0,
+                y: 0.0,
+                width: 50.0,
+                height: 125.0,
+            }
+        );
+        assert_eq!(layout.cues[1].lines[0].rect.y, 400.0);
+    }
+
+    #[test]
+    fn region() {
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\nREGION\nid:fred\nwidth:40%\nlines:2\nregionanchor:0%,100%\nviewportanchor:10%,90%\nscroll:up\n\n00:01.000 --> 00:09.000 region:fred align:left\nOne\n\n00:02.000 --> 00:09.000 region:fred align:left\nTwo\n\n00:03.000 --> 00:09.000 region:fred align:left\nThree\n\n00:03.000 --> 00:09.000 region:fred line:0\nNot in the region\n",
+        )
+        .unwrap();
+
+        let layout = vtt.layout_at(Duration::from_secs(4), &OPTIONS);
+        assert_eq!(layout.regions.len(), 1);
+        assert_eq!(
+            layout.regions[0].rect,
+            Rect {
+                x: 100.0,
+                y: 350.0,
+                width: 400.0,
+                height: 100.0,
+            }
+        );
+        assert_eq!(layout.cues.len(), 4);
+        let regions = layout
+            .cues
+            .iter()
+            .map(|cue| cue.region)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            regions,
+            vec![
+                Some(0),
+                Some(0),
+                Some(0),
+                None
+            ]
+        );
+        let lines = layout
+            .cues
+            .iter()
+            .take(3)
+            .map(|cue| (cue.rect.y, cue.lines[0].visible))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (300.0, false),
+                (350.0, true),
+                (400.0, true),
+            ]
+        );
+        assert_eq!(layout.cues[0].lines[0].rect.x, 100.0);
+        assert_eq!(layout.cues[3].rect.y, 0.0);
+
+        let layout = vtt.layout_at(Duration::from_millis(3_100), &OPTIONS);
+        let offset = layout.regions[0].scroll_offset;
+        assert!(offset > 38.0 && offset < 39.0);
+        assert!((layout.cues[2].rect.y - 400.0 - offset).abs() < EPSILON);
+        assert!(layout.cues[1].lines[0].visible);
+    }
+
+    #[test]
+    fn percentage_overlap() {
+        let line = |value: f32| {
+            Some(Line::Percentage(
+                Percentage {
+                    value,
+                },
+                None,
+            ))
+        };
+        let narrow = |position: f32, value: f32| {
+            CueSettings {
+                line: line(value),
+                position: Some(Position {
+                    value: Percentage {
+                        value: position,
+                    },
+                    alignment: None,
+                }),
+                size: Some(Percentage {
+                    value: 10.0,
+                }),
+                ..Default::default()
+            }
+        };
+        let cues = [
+            cue(
+                CueSettings {
+                    line: line(50.0),
+                    ..Default::default()
+                },
+                "First",
+            ),
+            cue(
+                CueSettings {
+                    line: line(50.0),
+                    ..Default::default()
+                },
+                "Moved up",
+            ),
+            cue(
+                CueSettings {
+                    line: line(0.0),
+                    ..Default::default()
+                },
+                "Top",
+            ),
+            // Moved right by the shorter distance, and up rather than right by the same distance.
+            cue(narrow(50.0, 80.0), "A"),
+            cue(narrow(58.0, 80.0), "B"),
+            cue(narrow(25.0, 80.0), "C"),
+            cue(narrow(30.0, 80.0), "D"),
+        ];
+        let rects = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        )
+        .cues
+        .iter()
+        .map(|cue| (cue.rect.x, cue.rect.y))
+        .collect::<Vec<_>>();
+        assert_eq!(
+            rects,
+            vec![
+                (0.0, 250.0),
+                (0.0, 200.0),
+                (0.0, 0.0),
+                (450.0, 400.0),
+                (550.0, 400.0),
+                (200.0, 400.0),
+                (250.0, 350.0),
+            ]
+        );
+
+        // Overlaps without any free position in the title area.
+        let cues = [
+            cue(
+                CueSettings {
+                    line: line(0.0),
+                    ..Default::default()
+                },
+                "1\n2\n3\n4\n5\n6\n7\n8\n9\n10",
+            ),
+            cue(
+                CueSettings {
+                    line: line(100.0),
+                    ..Default::default()
+                },
+                "Overlapped",
+            ),
+        ];
+        let layout = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        );
+        assert_eq!(layout.cues[1].rect.y, 450.0);
+    }
+
+    #[test]
+    fn negative_line_numbers() {
+        let cues = [
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(-2, None)),
+                    ..Default::default()
+                },
+                "Second from the bottom",
+            ),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(-2, None)),
+                    ..Default::default()
+                },
+                "Moved up",
+            ),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(-1, None)),
+                    ..Default::default()
+                },
+                "Bottom\nTwo lines",
+            ),
+            cue(
+                CueSettings {
+                    line: Some(Line::LineNumber(-20, None)),
+                    ..Default::default()
+                },
+                "Beyond",
+            ),
+        ];
+        let tops = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        )
+        .cues
+        .iter()
+        .map(|cue| cue.rect.y)
+        .collect::<Vec<_>>();
+        assert_eq!(
+            tops,
+            vec![
+                400.0, 350.0, 250.0, 0.0
+            ]
+        );
+
+        let cues = [
+            cue(
+                CueSettings {
+                    vertical: Some(Vertical::Rl),
+                    line: Some(Line::LineNumber(-1, None)),
+                    ..Default::default()
+                },
+                "Left",
+            ),
+            cue(
+                CueSettings {
+                    vertical: Some(Vertical::Lr),
+                    line: Some(Line::LineNumber(-1, None)),
+                    ..Default::default()
+                },
+                "Right",
+            ),
+        ];
+        let lefts = layout(
+            &cues
+                .iter()
+                .collect::<Vec<_>>(),
+            &[],
+            &OPTIONS,
+        )
+        .cues
+        .iter()
+        .map(|cue| cue.rect.x)
+        .collect::<Vec<_>>();
+        assert_eq!(lefts, vec![0.0, 950.0]);
+    }
+
+    #[test]
+    fn region_scroll() {
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\nREGION\nid:fred\nlines:2\nscroll:up\n\nREGION\nid:bill\nlines:2\n\n00:01.000 --> 00:09.000 region:fred\nOne\n\n00:02.000 --> 00:09.000 region:fred\nTwo\n\n00:01.000 --> 00:09.000 region:bill\nThree\n\n00:02.000 --> 00:09.000 region:bill\nFour\n",
+        )
+        .unwrap();
+
+        let tops = |layout: &Layout| {
+            layout
+                .cues
+                .iter()
+                .map(|cue| cue.rect.y)
+                .collect::<Vec<_>>()
+        };
+
+        // The last cue starts below the region to scroll up.
+        let layout = vtt.layout_at(Duration::from_secs(2), &OPTIONS);
+        assert_eq!(layout.regions[0].scroll_offset, 50.0);
+        assert_eq!(layout.regions[1].scroll_offset, 0.0);
+        assert_eq!(
+            tops(&layout),
+            vec![
+                450.0, 400.0, 500.0, 450.0
+            ]
+        );
+        assert!(layout.cues[0].lines[0].visible);
+        assert!(!layout.cues[2].lines[0].visible);
+
+        // The lines have scrolled up after the duration.
+        let layout = vtt.layout_at(
+            Duration::from_secs(2) + SCROLL_DURATION,
+            &OPTIONS,
+        );
+        assert_eq!(layout.regions[0].scroll_offset, 0.0);
+        assert_eq!(
+            tops(&layout),
+            vec![
+                400.0, 400.0, 450.0, 450.0
+            ]
+        );
+
+        // Without the time, the lines are not scrolling.
+        let cues = vtt
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => Some(cue),
+                    | _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let regions = vtt
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Region(region) => Some(region),
+                    | _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        let layout = super::layout(&cues, &regions, &OPTIONS);
+        assert_eq!(layout.regions[0].scroll_offset, 0.0);
+    }
+}
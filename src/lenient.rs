@@ -0,0 +1,1090 @@
+//! Lenient parsing of the SubRip Subtitle (`.srt`) and the WebVTT (`.vtt`) formats
+//! by [`SubRip::parse_lenient`] and [`WebVtt::parse_lenient`].
+//!
+//! The input is split into blocks by blank lines,
+//! and each block that the strict parser rejects is repaired or skipped
+//! with a [`ParseWarning`] of the span, the cause and the fix applied.
+//!
+//! ## Example
+//! ```
+//! use subtp::lenient::ParseFix;
+//! use subtp::lenient::ParseWarningKind;
+//! use subtp::srt::SubRip;
+//!
+//! let text = r#"1
+//! 00:00:01.000 --> 00:00:02,000
+//! Hello, world!
+//!
+//! 00:00:03,000 --> 00:00:04,000
+//! This is a test.
+//! "#;
+//!
+//! let lenient = SubRip::parse_lenient(text);
+//! assert_eq!(lenient.output.subtitles.len(), 2);
+//!
+//! let warning = &lenient.warnings[0];
+//! assert_eq!(warning.span.start.line, 2);
+//! assert_eq!(warning.span.start.column, 1);
+//! assert_eq!(
+//!     warning.kind,
+//!     ParseWarningKind::NonStandardTimestamp("00:00:01.000".to_string())
+//! );
+//! assert_eq!(
+//!     warning.fix,
+//!     ParseFix::TimestampNormalized("00:00:01,000".to_string())
+//! );
+//!
+//! let warning = &lenient.warnings[1];
+//! assert_eq!(warning.kind, ParseWarningKind::MissingSequence);
+//! assert_eq!(warning.fix, ParseFix::SequenceAssigned(2));
+//! ```
+
+use std::fmt::Display;
+
+use crate::srt::LinePosition;
+use crate::srt::SrtSubtitle;
+use crate::srt::SrtTimestamp;
+use crate::srt::SubRip;
+use crate::str_parser;
+use crate::vtt::CueSettings;
+use crate::vtt::VttBlock;
+use crate::vtt::VttCue;
+use crate::vtt::VttHeader;
+use crate::vtt::VttTimestamp;
+use crate::vtt::VttTimings;
+use crate::vtt::WebVtt;
+use crate::vtt_parser;
+
+/// The output of lenient parsing with the warnings of the repairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lenient<T> {
+    /// The output.
+    pub output: T,
+    /// The warnings in order of the input.
+    pub warnings: Vec<ParseWarning>,
+}
+
+impl<T> Lenient<T> {
+    /// Checks whether the input was parsed without any repairs.
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty()
+    }
+}
+
+/// The position in the input text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct TextPosition {
+    /// The line number starting from 1.
+    pub line: usize,
+    /// The column number in characters starting from 1.
+    pub column: usize,
+    /// The byte offset from the start of the input.
+    pub offset: usize,
+}
+
+impl Display for TextPosition {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The span in the input text, where the end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TextSpan {
+    /// The start position.
+    pub start: TextPosition,
+    /// The end position.
+    pub end: TextPosition,
+}
+
+impl Display for TextSpan {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// The warning of a malformed part of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    /// The span of the malformed part.
+    pub span: TextSpan,
+    /// The kind of warning.
+    pub kind: ParseWarningKind,
+    /// The fix applied.
+    pub fix: ParseFix,
+}
+
+impl Display for ParseWarning {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}, {}",
+            self.span.start.line, self.span.start.column, self.kind, self.fix
+        )
+    }
+}
+
+/// The kind of parse warning.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseWarningKind {
+    /// The `WEBVTT` header is missing.
+    MissingHeader,
+    /// The header is malformed.
+    InvalidHeader,
+    /// The sequence number is missing.
+    MissingSequence,
+    /// The sequence number is not a number.
+    InvalidSequence(String),
+    /// The timestamp is written in a non-standard form,
+    /// e.g. `.` instead of `,` in the SubRip Subtitle or single-digit hours.
+    NonStandardTimestamp(String),
+    /// The timestamp cannot be read.
+    InvalidTimestamp(String),
+    /// The timings line is missing.
+    MissingTimings,
+    /// The text is missing.
+    MissingText,
+    /// The unofficial line position of the SubRip Subtitle is malformed.
+    InvalidLinePosition(String),
+    /// The cue setting of the WebVTT is malformed.
+    InvalidCueSetting(String),
+    /// The lines before the sequence number or the identifier are unexpected.
+    UnexpectedLines,
+    /// The blank line splits the text of a subtitle.
+    UnexpectedBlankLine,
+    /// The block is malformed.
+    InvalidBlock,
+}
+
+impl Display for ParseWarningKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::MissingHeader => write!(f, "missing the WEBVTT header"),
+            | Self::InvalidHeader => write!(f, "invalid header"),
+            | Self::MissingSequence => write!(f, "missing sequence number"),
+            | Self::InvalidSequence(sequence) => {
+                write!(
+                    f,
+                    "invalid sequence number `{}`",
+                    sequence
+                )
+            },
+            | Self::NonStandardTimestamp(timestamp) => {
+                write!(
+                    f,
+                    "non-standard timestamp `{}`",
+                    timestamp
+                )
+            },
+            | Self::InvalidTimestamp(timestamp) => {
+                write!(f, "invalid timestamp `{}`", timestamp)
+            },
+            | Self::MissingTimings => write!(f, "missing timings"),
+            | Self::MissingText => write!(f, "missing text"),
+            | Self::InvalidLinePosition(position) => {
+                write!(
+                    f,
+                    "invalid line position `{}`",
+                    position
+                )
+            },
+            | Self::InvalidCueSetting(setting) => {
+                write!(f, "invalid cue setting `{}`", setting)
+            },
+            | Self::UnexpectedLines => {
+                write!(f, "unexpected lines before the timings")
+            },
+            | Self::UnexpectedBlankLine => {
+                write!(f, "unexpected blank line in the text")
+            },
+            | Self::InvalidBlock => write!(f, "invalid block"),
+        }
+    }
+}
+
+/// The fix applied to a malformed part of the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseFix {
+    /// The header is assumed.
+    HeaderInserted,
+    /// The sequence number is assigned next to the previous one.
+    SequenceAssigned(u32),
+    /// The timestamp is normalized to the standard form.
+    TimestampNormalized(String),
+    /// The lines are appended to the text of the previous subtitle.
+    AppendedToPrevious,
+    /// The part is dropped.
+    Dropped,
+    /// The whole block is skipped.
+    BlockSkipped,
+}
+
+impl Display for ParseFix {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::HeaderInserted => write!(f, "assumed the header"),
+            | Self::SequenceAssigned(sequence) => {
+                write!(
+                    f,
+                    "assigned sequence number {}",
+                    sequence
+                )
+            },
+            | Self::TimestampNormalized(timestamp) => {
+                write!(f, "normalized to `{}`", timestamp)
+            },
+            | Self::AppendedToPrevious => {
+                write!(f, "appended to the previous subtitle")
+            },
+            | Self::Dropped => write!(f, "dropped"),
+            | Self::BlockSkipped => write!(f, "skipped the block"),
+        }
+    }
+}
+
+/// The line of the input without the newline.
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    /// The line number starting from 1.
+    number: usize,
+    /// The byte offset from the start of the input.
+    offset: usize,
+    /// The text.
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    /// Gets the position of the byte index in the line.
+    fn position(
+        &self,
+        index: usize,
+    ) -> TextPosition {
+        TextPosition {
+            line: self.number,
+            column: self.text[..index]
+                .chars()
+                .count()
+                + 1,
+            offset: self.offset + index,
+        }
+    }
+
+    /// Gets the span of the sub-slice of the line.
+    fn span_of(
+        &self,
+        part: &str,
+    ) -> TextSpan {
+        let start = part.as_ptr() as usize - self.text.as_ptr() as usize;
+        TextSpan {
+            start: self.position(start),
+            end: self.position(start + part.len()),
+        }
+    }
+
+    /// Checks whether the line has only whitespaces.
+    fn is_blank(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+/// Splits the input into the lines by `\r\n`, `\n` or `\r`.
+fn split_lines(text: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    // Skips the byte order mark.
+    let mut start = if text.starts_with('\u{feff}') {
+        '\u{feff}'.len_utf8()
+    } else {
+        0
+    };
+    let bytes = text.as_bytes();
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'\n' || bytes[i] == b'\r' {
+            lines.push(Line {
+                number: lines.len() + 1,
+                offset: start,
+                text: &text[start..i],
+            });
+            if bytes[i] == b'\r' && bytes.get(i + 1) == Some(&b'\n') {
+                i += 1;
+            }
+            start = i + 1;
+        }
+        i += 1;
+    }
+    if start < text.len() {
+        lines.push(Line {
+            number: lines.len() + 1,
+            offset: start,
+            text: &text[start..],
+        });
+    }
+
+    lines
+}
+
+/// Splits the lines into the blocks separated by blank lines.
+fn split_blocks<'a, 'b>(lines: &'b [Line<'a>]) -> Vec<&'b [Line<'a>]> {
+    lines
+        .split(|line| line.is_blank())
+        .filter(|block| !block.is_empty())
+        .collect()
+}
+
+/// Joins the lines of the block with the trailing newline for the strict parser.
+fn block_text(block: &[Line]) -> String {
+    let mut text = String::new();
+    for line in block {
+        text.push_str(line.text);
+        text.push('\n');
+    }
+    text
+}
+
+/// Gets the span of the lines from the first to the last.
+fn block_span(block: &[Line]) -> TextSpan {
+    let first = &block[0];
+    let last = &block[block.len() - 1];
+    TextSpan {
+        start: first.position(0),
+        end: last.position(last.text.len()),
+    }
+}
+
+/// The timestamp read leniently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LenientTimestamp {
+    hours: u32,
+    minutes: u8,
+    seconds: u8,
+    milliseconds: u16,
+}
+
+/// Reads the timestamp leniently with any digits of hours,
+/// one or two digits of minutes and seconds,
+/// `,` or `.` as the decimal separator and any digits of the fraction.
+fn lenient_timestamp(text: &str) -> Option<LenientTimestamp> {
+    let (clock, fraction) = match text.rfind([',', '.']) {
+        | Some(i) => (&text[..i], &text[i + 1..]),
+        | None => (text, ""),
+    };
+    let is_digits = |s: &str| {
+        s.bytes()
+            .all(|b| b.is_ascii_digit())
+    };
+    if !is_digits(fraction) {
+        return None;
+    }
+
+    let fields = clock
+        .split(':')
+        .map(|field| {
+            if field.is_empty() || !is_digits(field) {
+                None
+            } else {
+                field.parse::<u32>().ok()
+            }
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let (hours, minutes, seconds) = match fields[..] {
+        | [
+            hours,
+            minutes,
+            seconds,
+        ] => (hours, minutes, seconds),
+        | [minutes, seconds] => (0, minutes, seconds),
+        | _ => return None,
+    };
+    if minutes >= 60 || seconds >= 60 {
+        return None;
+    }
+
+    let milliseconds = format!("{:0<3}", fraction)[..3]
+        .parse::<u16>()
+        .ok()?;
+
+    Some(LenientTimestamp {
+        hours,
+        minutes: minutes as u8,
+        seconds: seconds as u8,
+        milliseconds,
+    })
+}
+
+/// The timings line split into the start, the end and the rest.
+struct TimingsLine<'a> {
+    start: &'a str,
+    end: &'a str,
+    rest: &'a str,
+}
+
+/// Splits the timings line at `-->`.
+fn split_timings_line(text: &str) -> Option<TimingsLine<'_>> {
+    let arrow = text.find("-->")?;
+    let start = text[..arrow].trim();
+    let after = text[arrow + 3..].trim_start();
+    let end_len = after
+        .find(char::is_whitespace)
+        .unwrap_or(after.len());
+
+    Some(TimingsLine {
+        start,
+        end: &after[..end_len],
+        rest: after[end_len..].trim(),
+    })
+}
+
+/// Reads the timestamp leniently with the warning if not in the standard form.
+fn read_timestamp<T>(
+    line: &Line,
+    text: &str,
+    strict: impl Fn(&str) -> bool,
+    convert: impl Fn(LenientTimestamp) -> T,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<T>
+where
+    T: Display,
+{
+    let span = line.span_of(text);
+    let Some(timestamp) = lenient_timestamp(text) else {
+        warnings.push(ParseWarning {
+            span,
+            kind: ParseWarningKind::InvalidTimestamp(text.to_string()),
+            fix: ParseFix::BlockSkipped,
+        });
+        return None;
+    };
+
+    let timestamp = convert(timestamp);
+    if !strict(text) {
+        warnings.push(ParseWarning {
+            span,
+            kind: ParseWarningKind::NonStandardTimestamp(text.to_string()),
+            fix: ParseFix::TimestampNormalized(timestamp.to_string()),
+        });
+    }
+
+    Some(timestamp)
+}
+
+/// Parses the SubRip Subtitle format leniently.
+pub(crate) fn parse_subrip(text: &str) -> Lenient<SubRip> {
+    let lines = split_lines(text);
+    let mut subtitles: Vec<SrtSubtitle> = Vec::new();
+    let mut warnings = Vec::new();
+
+    for block in split_blocks(&lines) {
+        if let Ok(subtitle) = str_parser::subtitle(&block_text(block)) {
+            subtitles.push(subtitle);
+            continue;
+        }
+
+        let Some(timings_index) = block
+            .iter()
+            .position(|line| line.text.contains("-->"))
+        else {
+            let is_sequence = block[0]
+                .text
+                .trim()
+                .parse::<u32>()
+                .is_ok();
+            match subtitles.last_mut() {
+                | Some(last) if !is_sequence => {
+                    last.text.extend(
+                        block
+                            .iter()
+                            .map(|line| line.text.trim().to_string()),
+                    );
+                    warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::UnexpectedBlankLine,
+                        fix: ParseFix::AppendedToPrevious,
+                    });
+                },
+                | _ => {
+                    warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::MissingTimings,
+                        fix: ParseFix::BlockSkipped,
+                    });
+                },
+            }
+            continue;
+        };
+
+        let mut block_warnings = Vec::new();
+        let next_sequence = subtitles
+            .last()
+            .map_or(1, |last| last.sequence + 1);
+        let sequence = if timings_index == 0 {
+            block_warnings.push(ParseWarning {
+                span: block[0].span_of(block[0].text),
+                kind: ParseWarningKind::MissingSequence,
+                fix: ParseFix::SequenceAssigned(next_sequence),
+            });
+            next_sequence
+        } else {
+            if timings_index > 1 {
+                block_warnings.push(ParseWarning {
+                    span: block_span(&block[..timings_index - 1]),
+                    kind: ParseWarningKind::UnexpectedLines,
+                    fix: ParseFix::Dropped,
+                });
+            }
+            let line = &block[timings_index - 1];
+            let text = line.text.trim();
+            match text.parse::<u32>() {
+                | Ok(sequence) => sequence,
+                | Err(_) => {
+                    block_warnings.push(ParseWarning {
+                        span: line.span_of(text),
+                        kind: ParseWarningKind::InvalidSequence(
+                            text.to_string(),
+                        ),
+                        fix: ParseFix::SequenceAssigned(next_sequence),
+                    });
+                    next_sequence
+                },
+            }
+        };
+
+        let timings_line = &block[timings_index];
+        let Some(subtitle) =
+            read_subrip_timings(timings_line, &mut block_warnings).and_then(
+                |(start, end, line_position)| {
+                    let text = block[timings_index + 1..]
+                        .iter()
+                        .map(|line| line.text.trim().to_string())
+                        .collect::<Vec<_>>();
+                    if text.is_empty() {
+                        block_warnings.push(ParseWarning {
+                            span: block_span(block),
+                            kind: ParseWarningKind::MissingText,
+                            fix: ParseFix::BlockSkipped,
+                        });
+                        return None;
+                    }
+
+                    Some(SrtSubtitle {
+                        sequence,
+                        start,
+                        end,
+                        text,
+                        line_position,
+                    })
+                },
+            )
+        else {
+            warnings.extend(
+                block_warnings
+                    .into_iter()
+                    .filter(|warning| warning.fix == ParseFix::BlockSkipped),
+            );
+            continue;
+        };
+
+        warnings.extend(block_warnings);
+        subtitles.push(subtitle);
+    }
+
+    Lenient {
+        output: SubRip {
+            subtitles,
+        },
+        warnings,
+    }
+}
+
+/// Reads the timings line of the SubRip Subtitle leniently.
+fn read_subrip_timings(
+    line: &Line,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<(
+    SrtTimestamp,
+    SrtTimestamp,
+    Option<LinePosition>,
+)> {
+    let timings = split_timings_line(line.text)?;
+
+    let strict = |text: &str| str_parser::timestamp(text).is_ok();
+    let convert = |timestamp: LenientTimestamp| {
+        SrtTimestamp {
+            hours: timestamp.hours,
+            minutes: timestamp.minutes,
+            seconds: timestamp.seconds,
+            milliseconds: timestamp.milliseconds,
+        }
+    };
+    let start = read_timestamp(
+        line,
+        timings.start,
+        strict,
+        convert,
+        warnings,
+    );
+    let end = read_timestamp(
+        line,
+        timings.end,
+        strict,
+        convert,
+        warnings,
+    );
+
+    let line_position = if timings.rest.is_empty() {
+        None
+    } else {
+        match str_parser::line_position(timings.rest) {
+            | Ok(line_position) => Some(line_position),
+            | Err(_) => {
+                warnings.push(ParseWarning {
+                    span: line.span_of(timings.rest),
+                    kind: ParseWarningKind::InvalidLinePosition(
+                        timings.rest.to_string(),
+                    ),
+                    fix: ParseFix::Dropped,
+                });
+                None
+            },
+        }
+    };
+
+    Some((start?, end?, line_position))
+}
+
+/// Parses the WebVTT format leniently.
+pub(crate) fn parse_webvtt(text: &str) -> Lenient<WebVtt> {
+    let lines = split_lines(text);
+    let mut blocks = split_blocks(&lines).into_iter();
+    let mut warnings = Vec::new();
+
+    let mut header = VttHeader::default();
+    let mut first = None;
+    match blocks.next() {
+        | Some(block)
+            if block[0]
+                .text
+                .starts_with("WEBVTT") =>
+        {
+            match vtt_parser::header(&block_text(block)) {
+                | Ok(parsed) => header = parsed,
+                | Err(_) => {
+                    warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::InvalidHeader,
+                        fix: ParseFix::Dropped,
+                    });
+                },
+            }
+        },
+        | block => {
+            let position = TextPosition {
+                line: 1,
+                column: 1,
+                offset: 0,
+            };
+            warnings.push(ParseWarning {
+                span: TextSpan {
+                    start: position,
+                    end: position,
+                },
+                kind: ParseWarningKind::MissingHeader,
+                fix: ParseFix::HeaderInserted,
+            });
+            first = block;
+        },
+    }
+
+    let mut vtt_blocks: Vec<VttBlock> = Vec::new();
+    for block in first
+        .into_iter()
+        .chain(blocks)
+    {
+        if let Ok(parsed) = vtt_parser::block(&block_text(block)) {
+            vtt_blocks.push(parsed);
+            continue;
+        }
+
+        let is_cue = !matches!(
+            block[0]
+                .text
+                .split_whitespace()
+                .next(),
+            Some("NOTE" | "STYLE" | "REGION")
+        );
+        if !is_cue {
+            warnings.push(ParseWarning {
+                span: block_span(block),
+                kind: ParseWarningKind::InvalidBlock,
+                fix: ParseFix::BlockSkipped,
+            });
+            continue;
+        }
+
+        let Some(timings_index) = block
+            .iter()
+            .position(|line| line.text.contains("-->"))
+        else {
+            match vtt_blocks.last_mut() {
+                | Some(VttBlock::Que(last)) => {
+                    last.payload.extend(
+                        block
+                            .iter()
+                            .map(|line| line.text.trim().to_string()),
+                    );
+                    warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::UnexpectedBlankLine,
+                        fix: ParseFix::AppendedToPrevious,
+                    });
+                },
+                | _ => {
+                    warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::MissingTimings,
+                        fix: ParseFix::BlockSkipped,
+                    });
+                },
+            }
+            continue;
+        };
+
+        let mut block_warnings = Vec::new();
+        let identifier = if timings_index == 0 {
+            None
+        } else {
+            if timings_index > 1 {
+                block_warnings.push(ParseWarning {
+                    span: block_span(&block[..timings_index - 1]),
+                    kind: ParseWarningKind::UnexpectedLines,
+                    fix: ParseFix::Dropped,
+                });
+            }
+            Some(
+                block[timings_index - 1]
+                    .text
+                    .trim()
+                    .to_string(),
+            )
+        };
+
+        let timings_line = &block[timings_index];
+        let cue = read_webvtt_timings(timings_line, &mut block_warnings)
+            .and_then(|(timings, settings)| {
+                let payload = block[timings_index + 1..]
+                    .iter()
+                    .map(|line| line.text.trim().to_string())
+                    .collect::<Vec<_>>();
+                if payload.is_empty() {
+                    block_warnings.push(ParseWarning {
+                        span: block_span(block),
+                        kind: ParseWarningKind::MissingText,
+                        fix: ParseFix::BlockSkipped,
+                    });
+                    return None;
+                }
+
+                Some(VttCue {
+                    identifier,
+                    timings,
+                    settings,
+                    payload,
+                })
+            });
+        match cue {
+            | Some(cue) => {
+                warnings.extend(block_warnings);
+                vtt_blocks.push(cue.into());
+            },
+            | None => {
+                warnings.extend(
+                    block_warnings
+                        .into_iter()
+                        .filter(|warning| {
+                            warning.fix == ParseFix::BlockSkipped
+                        }),
+                );
+            },
+        }
+    }
+
+    Lenient {
+        output: WebVtt {
+            header,
+            blocks: vtt_blocks,
+        },
+        warnings,
+    }
+}
+
+/// Reads the timings line of the WebVTT leniently
+/// with the cue settings dropping the malformed ones.
+fn read_webvtt_timings(
+    line: &Line,
+    warnings: &mut Vec<ParseWarning>,
+) -> Option<(VttTimings, Option<CueSettings>)> {
+    let timings = split_timings_line(line.text)?;
+
+    let strict = |text: &str| vtt_parser::timestamp(text).is_ok();
+    let convert = |timestamp: LenientTimestamp| {
+        VttTimestamp {
+            hours: timestamp.hours,
+            minutes: timestamp.minutes,
+            seconds: timestamp.seconds,
+            milliseconds: timestamp.milliseconds,
+        }
+    };
+    let start = read_timestamp(
+        line,
+        timings.start,
+        strict,
+        convert,
+        warnings,
+    );
+    let end = read_timestamp(
+        line,
+        timings.end,
+        strict,
+        convert,
+        warnings,
+    );
+
+    let settings = if timings.rest.is_empty() {
+        None
+    } else if let Ok(settings) = vtt_parser::cue_settings(timings.rest) {
+        Some(settings)
+    } else {
+        let mut valid = Vec::new();
+        for setting in timings
+            .rest
+            .split_whitespace()
+        {
+            if vtt_parser::cue_settings(setting).is_ok() {
+                valid.push(setting);
+            } else {
+                warnings.push(ParseWarning {
+                    span: line.span_of(setting),
+                    kind: ParseWarningKind::InvalidCueSetting(
+                        setting.to_string(),
+                    ),
+                    fix: ParseFix::Dropped,
+                });
+            }
+        }
+        if valid.is_empty() {
+            None
+        } else {
+            vtt_parser::cue_settings(&valid.join(" ")).ok()
+        }
+    };
+
+    Some((
+        VttTimings {
+            start: start?,
+            end: end?,
+        },
+        settings,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timestamp() {
+        assert_eq!(
+            lenient_timestamp("1:02:03.4"),
+            Some(LenientTimestamp {
+                hours: 1,
+                minutes: 2,
+                seconds: 3,
+                milliseconds: 400,
+            })
+        );
+        assert_eq!(
+            lenient_timestamp("02:03,0045"),
+            Some(LenientTimestamp {
+                hours: 0,
+                minutes: 2,
+                seconds: 3,
+                milliseconds: 4,
+            })
+        );
+        assert_eq!(
+            lenient_timestamp("00:00:01"),
+            Some(LenientTimestamp {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                milliseconds: 0,
+            })
+        );
+        assert_eq!(lenient_timestamp("00:60:00,000"), None);
+        assert_eq!(lenient_timestamp("00:00:0a,000"), None);
+        assert_eq!(lenient_timestamp("00::00,000"), None);
+        assert_eq!(
+            lenient_timestamp("300:00:00,000").map(|timestamp| timestamp.hours),
+            Some(300)
+        );
+        assert_eq!(lenient_timestamp("4294967296:00:00,000"), None);
+    }
+
+    #[test]
+    fn clean() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000 X1:1 X2:2 Y1:3 Y2:4\nHello, world!\n\n2\n00:00:03,000 --> 00:00:04,000\nThis is a test.\nSecond line.\n";
+        let lenient = SubRip::parse_lenient(srt);
+        assert!(lenient.is_clean());
+        assert_eq!(
+            lenient.output,
+            SubRip::parse(srt).unwrap()
+        );
+
+        let vtt = r#"WEBVTT - Title
+
+STYLE
+::cue { color: red; }
+
+NOTE
+A comment.
+
+REGION
+id:fred
+width:40%
+
+intro
+00:01.000 --> 00:00:04.000 line:0 region:fred
+<v Alice>Hello, world!
+Second line.
+"#;
+        let lenient = WebVtt::parse_lenient(vtt);
+        assert!(lenient.is_clean());
+        assert_eq!(
+            lenient.output,
+            WebVtt::parse(vtt).unwrap()
+        );
+    }
+
+    #[test]
+    fn repair_subrip() {
+        let text = "\r\n1\r\n0:00:01.000 --> 00:00:02,000 X1:bad\r\nHello,\r\n\r\nworld!\r\n\r\n00:00:03,000 --> 00:00:04,000\r\nMissing sequence.\r\n\r\n3\r\n\r\nfoo\r\nx\r\n00:00:05,000 --> 00:00:06,000\r\nUnexpected line.\r\n\r\n5\r\n00:00:07,000 --> 00:00:xx,000\r\nInvalid timestamp.\r\n\r\n6\r\n00:00:08,000 --> 00:00:09,000\r\n";
+        let lenient = SubRip::parse_lenient(text);
+
+        assert_eq!(
+            lenient.output.render(),
+            r#"1
+00:00:01,000 --> 00:00:02,000
+Hello,
+world!
+
+2
+00:00:03,000 --> 00:00:04,000
+Missing sequence.
+
+3
+00:00:05,000 --> 00:00:06,000
+Unexpected line.
+"#
+        );
+
+        let warnings = lenient
+            .warnings
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "line 3, column 1: non-standard timestamp `0:00:01.000`, normalized to `00:00:01,000`",
+                "line 3, column 30: invalid line position `X1:bad`, dropped",
+                "line 6, column 1: unexpected blank line in the text, appended to the previous subtitle",
+                "line 8, column 1: missing sequence number, assigned sequence number 2",
+                "line 11, column 1: missing timings, skipped the block",
+                "line 13, column 1: unexpected lines before the timings, dropped",
+                "line 14, column 1: invalid sequence number `x`, assigned sequence number 3",
+                "line 19, column 18: invalid timestamp `00:00:xx,000`, skipped the block",
+                "line 22, column 1: missing text, skipped the block",
+            ]
+        );
+
+        let warning = &lenient.warnings[0];
+        assert_eq!(
+            warning.span,
+            TextSpan {
+                start: TextPosition {
+                    line: 3,
+                    column: 1,
+                    offset: 5,
+                },
+                end: TextPosition {
+                    line: 3,
+                    column: 12,
+                    offset: 16,
+                },
+            }
+        );
+        assert_eq!(
+            &text[warning.span.start.offset..warning.span.end.offset],
+            "0:00:01.000"
+        );
+    }
+
+    #[test]
+    fn repair_webvtt() {
+        let text = r#"1
+00:01,000 --> 00:02.000 align:middle size:50%
+Hello,
+
+world!
+
+REGION
+id:fred
+width:400%
+
+00:03.000 --> 00:04.000
+"#;
+        let lenient = WebVtt::parse_lenient(text);
+
+        assert_eq!(
+            lenient.output.render(),
+            r#"WEBVTT
+
+1
+00:00:01.000 --> 00:00:02.000 size:50%
+Hello,
+world!
+"#
+        );
+
+        let warnings = lenient
+            .warnings
+            .iter()
+            .map(|warning| warning.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "line 1, column 1: missing the WEBVTT header, assumed the header",
+                "line 2, column 1: non-standard timestamp `00:01,000`, normalized to `00:00:01.000`",
+                "line 2, column 25: invalid cue setting `align:middle`, dropped",
+                "line 5, column 1: unexpected blank line in the text, appended to the previous subtitle",
+                "line 7, column 1: invalid block, skipped the block",
+                "line 11, column 1: missing text, skipped the block",
+            ]
+        );
+    }
+}
@@ -1,19 +1,64 @@
 //! # subtp
-//! A parser for subtitle files such as the SubRip Subtitle (.srt) and the WebVTT (.vtt).
+//! A parser for subtitle files such as the SubRip Subtitle (.srt), the WebVTT (.vtt), the Advanced SubStation Alpha (.ass) and the Timed Text Markup Language (.ttml).
 //!
 //! - [SubRip Subtitle (.srt)](`crate::srt::SubRip`)
 //! - [WebVTT (.vtt)](`crate::vtt::WebVtt`)
+//! - [Advanced SubStation Alpha (.ass) and SubStation Alpha (.ssa)](`crate::ass::SubStationAlpha`)
+//! - [Timed Text Markup Language (.ttml, .dfxp)](`crate::ttml::Ttml`)
+//! - [LRC (.lrc)](`crate::lrc::Lrc`)
+//! - [YouTube SubViewer (.sbv)](`crate::sbv::Sbv`)
+//! - [YouTube timed text (srv3, json3)](`crate::youtube`)
+//!
+//! Large SubRip Subtitle (.srt) and WebVTT (.vtt) inputs can be parsed block by block by [`stream`](`crate::stream`).
+//!
+//! All formats implement the format-agnostic [`SubtitleFormat`](`crate::SubtitleFormat`) with the common cue model of [`Cue`](`crate::format::Cue`).
+//!
+//! ## Features
+//! No features are enabled by default.
+//!
+//! - `encoding`: Detects and decodes the encodings of bytes by `subtp::encoding`, e.g. `subtp = { version = "0.2.0", features = ["encoding"] }`.
+//! - `serde`: Implements `Serialize` and `Deserialize` for the types of [`srt`](`crate::srt`) and [`vtt`](`crate::vtt`).
+//! - `cli`: Builds the `subtp` command-line tool, which enables `encoding`.
 
 // Re-exports.
 pub use error::ParseError;
+pub use error::ParseErrorKind;
+pub use format::SubtitleFormat;
 pub use result::ParseResult;
 
 // Public modules.
+pub mod ass;
+pub mod convert;
+pub mod css;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+pub mod format;
+pub mod index;
+pub mod layout;
+pub mod lenient;
+pub mod lint;
+pub mod lrc;
+pub mod lossless;
+pub mod render;
+pub mod sbv;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod srt;
+pub mod stream;
+pub mod timeline;
+pub mod transcript;
+pub mod ttml;
+pub mod validate;
 pub mod vtt;
+pub mod youtube;
 
 // Internal modules.
+mod ass_parser;
 mod error;
+mod lrc_parser;
 mod result;
+mod sbv_parser;
 mod str_parser;
+mod ttml_parser;
 mod vtt_parser;
+mod youtube_parser;
@@ -0,0 +1,451 @@
+//! Quality linting of subtitle cues by broadcast-style rules.
+//!
+//! The [`Lint`] is implemented for [`SubRip`](crate::srt::SubRip) and [`WebVtt`](crate::vtt::WebVtt),
+//! where cues are indexed from 0 in order of the document and the other blocks of WebVTT are not counted.
+//!
+//! The text of cues is checked without markup,
+//! i.e. `<...>` tags and `{\...}` overrides of the SubRip Subtitle, and tags, timestamps and ruby texts of the WebVTT.
+//! The length of lines is measured by [`CharacterWidth`] and the characters per second count all characters except line breaks.
+//!
+//! ## Example
+//! ```
+//! use subtp::lint::Lint;
+//! use subtp::lint::LintRules;
+//! use subtp::lint::ViolationKind;
+//! use subtp::srt::SubRip;
+//!
+//! let text = r#"1
+//! 00:00:01,000 --> 00:00:02,000
+//! <i>This line is much longer than forty-two characters.</i>
+//!
+//! 2
+//! 00:00:03,000 --> 00:00:04,000
+//! Short.
+//! "#;
+//!
+//! let srt = SubRip::parse(text).unwrap();
+//!
+//! let violations = srt.lint(&LintRules::NETFLIX);
+//! assert_eq!(violations.len(), 2);
+//! assert_eq!(violations[0].cue, 0);
+//! assert!(matches!(
+//!     violations[0].kind,
+//!     ViolationKind::LineTooLong { line: 0, length: 51, max: 42 }
+//! ));
+//! assert!(matches!(violations[1].kind, ViolationKind::TooFast { .. }));
+//! ```
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use unicode_width::UnicodeWidthStr;
+
+/// How to measure the length of lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CharacterWidth {
+    /// Counts characters.
+    #[default]
+    Characters,
+    /// Counts the display width in half-width columns,
+    /// where wide characters such as CJK count as 2.
+    HalfWidth,
+}
+
+impl CharacterWidth {
+    /// Measures the length of the text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::lint::CharacterWidth;
+    ///
+    /// assert_eq!(CharacterWidth::Characters.measure("字幕 abc"), 6);
+    /// assert_eq!(CharacterWidth::HalfWidth.measure("字幕 abc"), 8);
+    /// ```
+    pub fn measure(
+        &self,
+        text: &str,
+    ) -> usize {
+        match self {
+            | Self::Characters => text.chars().count(),
+            | Self::HalfWidth => text.width(),
+        }
+    }
+}
+
+/// The rules of linting, where `None` disables the rule.
+///
+/// The presets approximate the public style guides, so that they can be adjusted for the guide in use, e.g.
+/// ```
+/// use subtp::lint::LintRules;
+///
+/// let rules = LintRules {
+///     max_characters_per_second: Some(17.),
+///     ..LintRules::NETFLIX
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LintRules {
+    /// The maximum length of a line measured by the width.
+    pub max_line_length: Option<usize>,
+    /// The maximum number of lines in a cue.
+    pub max_lines: Option<usize>,
+    /// The maximum characters per second.
+    pub max_characters_per_second: Option<f64>,
+    /// The minimum duration of a cue.
+    pub min_duration: Option<Duration>,
+    /// The maximum duration of a cue.
+    pub max_duration: Option<Duration>,
+    /// How to measure the length of lines.
+    pub width: CharacterWidth,
+}
+
+impl LintRules {
+    /// The rules of the BBC subtitle guidelines:
+    /// 37 characters per line, 2 lines, about 180 words per minute and 1 to 7 seconds.
+    pub const BBC: Self = Self {
+        max_line_length: Some(37),
+        max_lines: Some(2),
+        max_characters_per_second: Some(15.),
+        min_duration: Some(Duration::from_secs(1)),
+        max_duration: Some(Duration::from_secs(7)),
+        width: CharacterWidth::Characters,
+    };
+
+    /// The rules for English of the Netflix timed text style guide:
+    /// 42 characters per line, 2 lines, 20 characters per second and 5/6 to 7 seconds.
+    pub const NETFLIX: Self = Self {
+        max_line_length: Some(42),
+        max_lines: Some(2),
+        max_characters_per_second: Some(20.),
+        min_duration: Some(Duration::from_millis(833)),
+        max_duration: Some(Duration::from_secs(7)),
+        width: CharacterWidth::Characters,
+    };
+
+    /// The rules for Japanese of the Netflix timed text style guide:
+    /// 13 full-width characters per line, 2 lines, 4 characters per second and 5/6 to 7 seconds.
+    pub const NETFLIX_JAPANESE: Self = Self {
+        max_line_length: Some(26),
+        max_lines: Some(2),
+        max_characters_per_second: Some(4.),
+        min_duration: Some(Duration::from_millis(833)),
+        max_duration: Some(Duration::from_secs(7)),
+        width: CharacterWidth::HalfWidth,
+    };
+
+    /// Checks the cue at the index by the rules.
+    pub fn check(
+        &self,
+        index: usize,
+        cue: &LintCue,
+    ) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        let mut push = |kind: ViolationKind| {
+            violations.push(Violation {
+                cue: index,
+                kind,
+            })
+        };
+
+        if let Some(max) = self.max_line_length {
+            for (line, text) in cue.lines.iter().enumerate() {
+                let length = self.width.measure(text);
+                if length > max {
+                    push(ViolationKind::LineTooLong {
+                        line,
+                        length,
+                        max,
+                    });
+                }
+            }
+        }
+
+        if let Some(max) = self.max_lines {
+            if cue.lines.len() > max {
+                push(ViolationKind::TooManyLines {
+                    lines: cue.lines.len(),
+                    max,
+                });
+            }
+        }
+
+        let duration = cue
+            .end
+            .saturating_sub(cue.start);
+        if let Some(max) = self.max_characters_per_second {
+            let characters = cue
+                .lines
+                .iter()
+                .map(|line| line.chars().count())
+                .sum::<usize>();
+            if !duration.is_zero() {
+                let characters_per_second =
+                    characters as f64 / duration.as_secs_f64();
+                if characters_per_second > max {
+                    push(ViolationKind::TooFast {
+                        characters_per_second,
+                        max,
+                    });
+                }
+            }
+        }
+
+        if let Some(min) = self.min_duration {
+            if duration < min {
+                push(ViolationKind::TooShort {
+                    duration,
+                    min,
+                });
+            }
+        }
+
+        if let Some(max) = self.max_duration {
+            if duration > max {
+                push(ViolationKind::TooLong {
+                    duration,
+                    max,
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+/// The cue to lint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LintCue {
+    /// The start.
+    pub start: Duration,
+    /// The end.
+    pub end: Duration,
+    /// The lines of the text without markup and surrounding whitespaces.
+    pub lines: Vec<String>,
+}
+
+/// The violation of the rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Violation {
+    /// The index of the cue starting from 0.
+    pub cue: usize,
+    /// The kind of violation.
+    pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "cue {}: {}", self.cue, self.kind)
+    }
+}
+
+/// The kind of violation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViolationKind {
+    /// The line is longer than the maximum.
+    LineTooLong {
+        /// The index of the line starting from 0.
+        line: usize,
+        /// The length of the line.
+        length: usize,
+        /// The maximum length.
+        max: usize,
+    },
+    /// The cue has more lines than the maximum.
+    TooManyLines {
+        /// The number of lines.
+        lines: usize,
+        /// The maximum number of lines.
+        max: usize,
+    },
+    /// The characters per second are more than the maximum.
+    TooFast {
+        /// The characters per second.
+        characters_per_second: f64,
+        /// The maximum characters per second.
+        max: f64,
+    },
+    /// The duration is shorter than the minimum.
+    TooShort {
+        /// The duration.
+        duration: Duration,
+        /// The minimum duration.
+        min: Duration,
+    },
+    /// The duration is longer than the maximum.
+    TooLong {
+        /// The duration.
+        duration: Duration,
+        /// The maximum duration.
+        max: Duration,
+    },
+}
+
+impl Display for ViolationKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::LineTooLong {
+                line,
+                length,
+                max,
+            } => {
+                write!(
+                    f,
+                    "line {} has length {}, more than {}",
+                    line, length, max
+                )
+            },
+            | Self::TooManyLines {
+                lines,
+                max,
+            } => {
+                write!(f, "{} lines, more than {}", lines, max)
+            },
+            | Self::TooFast {
+                characters_per_second,
+                max,
+            } => {
+                write!(
+                    f,
+                    "{:.1} characters per second, more than {}",
+                    characters_per_second, max
+                )
+            },
+            | Self::TooShort {
+                duration,
+                min,
+            } => {
+                write!(
+                    f,
+                    "duration {:?}, shorter than {:?}",
+                    duration, min
+                )
+            },
+            | Self::TooLong {
+                duration,
+                max,
+            } => {
+                write!(
+                    f,
+                    "duration {:?}, longer than {:?}",
+                    duration, max
+                )
+            },
+        }
+    }
+}
+
+/// Quality linting of subtitle cues.
+pub trait Lint {
+    /// The cues to lint in order of the document.
+    fn lint_cues(&self) -> Vec<LintCue>;
+
+    /// Checks all cues by the rules and returns the violations in order of the cues.
+    fn lint(
+        &self,
+        rules: &LintRules,
+    ) -> Vec<Violation> {
+        self.lint_cues()
+            .iter()
+            .enumerate()
+            .flat_map(|(index, cue)| rules.check(index, cue))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn check() {
+        let cue = LintCue {
+            start: Duration::from_secs(1),
+            end: Duration::from_millis(1_500),
+            lines: vec![
+                "字幕の一行目はとても長くて十三文字を超える".to_string(),
+                "二行目".to_string(),
+                "三行目".to_string(),
+            ],
+        };
+
+        assert_eq!(
+            LintRules::NETFLIX_JAPANESE.check(3, &cue),
+            vec![
+                Violation {
+                    cue: 3,
+                    kind: ViolationKind::LineTooLong {
+                        line: 0,
+                        length: 42,
+                        max: 26,
+                    },
+                },
+                Violation {
+                    cue: 3,
+                    kind: ViolationKind::TooManyLines {
+                        lines: 3,
+                        max: 2,
+                    },
+                },
+                Violation {
+                    cue: 3,
+                    kind: ViolationKind::TooFast {
+                        characters_per_second: 54.,
+                        max: 4.,
+                    },
+                },
+                Violation {
+                    cue: 3,
+                    kind: ViolationKind::TooShort {
+                        duration: Duration::from_millis(500),
+                        min: Duration::from_millis(833),
+                    },
+                },
+            ]
+        );
+        assert!(
+            LintRules::default()
+                .check(0, &cue)
+                .is_empty()
+        );
+
+        let cue = LintCue {
+            start: Duration::ZERO,
+            end: Duration::from_secs(8),
+            lines: vec!["Hello.".to_string()],
+        };
+        assert_eq!(
+            LintRules::BBC
+                .check(0, &cue)
+                .iter()
+                .map(|violation| violation.to_string())
+                .collect::<Vec<_>>(),
+            vec!["cue 0: duration 8s, longer than 7s"]
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let text = r#"WEBVTT
+
+00:00.000 --> 00:02.000
+<v Alice><b>Hello</b>, &amp; <ruby>漢字<rt>かんじ</rt></ruby>!
+"#;
+        let vtt = WebVtt::parse(text).unwrap();
+
+        assert_eq!(
+            vtt.lint_cues(),
+            vec![LintCue {
+                start: Duration::ZERO,
+                end: Duration::from_secs(2),
+                lines: vec!["Hello, & 漢字!".to_string()],
+            }]
+        );
+    }
+}
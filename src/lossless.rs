@@ -0,0 +1,504 @@
+//! Lossless parsing of the SubRip Subtitle (`.srt`) and the WebVTT (`.vtt`) formats
+//! that keeps the original formatting.
+//!
+//! The input is split into blocks separated by blank lines,
+//! and each block keeps its source text and the trivia before it,
+//! i.e. the byte order mark, blank lines and whitespace, with the original line endings.
+//! Rendering an unedited document gives the byte-identical output of the input,
+//! and an edited block is rendered in the normalized form with the line ending of the input
+//! while the other blocks are kept as they are.
+//!
+//! ## Example
+//! ```
+//! use subtp::srt::SubRip;
+//!
+//! let text = "1\r\n00:00:01,000-->00:00:02,000\r\nHello,   \r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nworld!\r\n";
+//!
+//! let mut srt = SubRip::parse_lossless(text).unwrap();
+//! assert_eq!(srt.render(), text);
+//!
+//! srt.subtitles[1].value.text = vec!["everyone!".to_string()];
+//! assert_eq!(
+//!     srt.render(),
+//!     "1\r\n00:00:01,000-->00:00:02,000\r\nHello,   \r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\neveryone!\r\n"
+//! );
+//! ```
+
+use std::fmt::Display;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::srt::SrtSubtitle;
+use crate::srt::SubRip;
+use crate::str_parser;
+use crate::vtt::VttBlock;
+use crate::vtt::VttHeader;
+use crate::vtt::WebVtt;
+use crate::vtt_parser;
+
+/// The block with the source text and the trivia before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LosslessBlock<T> {
+    /// The trivia before the block, or one blank line if `None`.
+    pub leading: Option<String>,
+    /// The parsed block, which can be edited.
+    pub value: T,
+    /// The source text of the block and the parsed block when parsed.
+    source: Option<(String, T)>,
+}
+
+impl<T: Display> LosslessBlock<T> {
+    /// Creates the new block without the source, which is rendered in the normalized form.
+    pub fn new(value: T) -> Self {
+        Self {
+            leading: None,
+            value,
+            source: None,
+        }
+    }
+
+    /// The source text of the block if parsed.
+    pub fn source(&self) -> Option<&str> {
+        self.source
+            .as_ref()
+            .map(|(source, _)| source.as_str())
+    }
+
+    /// Checks whether the block is edited or created after parsing.
+    pub fn is_edited(&self) -> bool {
+        match &self.source {
+            | Some((_, original)) => {
+                original.to_string() != self.value.to_string()
+            },
+            | None => true,
+        }
+    }
+
+    /// Renders the block with the trivia before it into the output.
+    fn render_into(
+        &self,
+        output: &mut String,
+        newline: &str,
+        first: bool,
+    ) {
+        match &self.leading {
+            | Some(leading) => output.push_str(leading),
+            | None if first => {},
+            | None => output.push_str(newline),
+        }
+
+        match &self.source {
+            | Some((source, _)) if !self.is_edited() => output.push_str(source),
+            | _ => {
+                output.push_str(
+                    &self
+                        .value
+                        .to_string()
+                        .replace('\n', newline),
+                )
+            },
+        }
+    }
+}
+
+/// The SubRip Subtitle keeping the original formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LosslessSubRip {
+    /// The subtitles.
+    pub subtitles: Vec<LosslessBlock<SrtSubtitle>>,
+    /// The trivia after the last subtitle.
+    pub trailing: String,
+    /// The line ending of the input.
+    newline: &'static str,
+}
+
+impl LosslessSubRip {
+    /// Parses the SubRip Subtitle format keeping the original formatting.
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        let (blocks, trailing) = split_blocks(text);
+        let subtitles = blocks
+            .into_iter()
+            .map(|block| block.parse(str_parser::subtitle))
+            .collect::<ParseResult<_>>()?;
+
+        Ok(Self {
+            subtitles,
+            trailing: trailing.to_string(),
+            newline: detect_newline(text),
+        })
+    }
+
+    /// Renders the text keeping the original formatting of the unedited subtitles.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        for (index, subtitle) in self
+            .subtitles
+            .iter()
+            .enumerate()
+        {
+            subtitle.render_into(&mut output, self.newline, index == 0);
+        }
+        output.push_str(&self.trailing);
+
+        output
+    }
+
+    /// Converts into the normalized [`SubRip`].
+    pub fn into_subrip(self) -> SubRip {
+        SubRip {
+            subtitles: self
+                .subtitles
+                .into_iter()
+                .map(|subtitle| subtitle.value)
+                .collect(),
+        }
+    }
+}
+
+/// The WebVTT keeping the original formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LosslessWebVtt {
+    /// The header.
+    pub header: LosslessBlock<VttHeader>,
+    /// The blocks.
+    pub blocks: Vec<LosslessBlock<VttBlock>>,
+    /// The trivia after the last block.
+    pub trailing: String,
+    /// The line ending of the input.
+    newline: &'static str,
+}
+
+impl LosslessWebVtt {
+    /// Parses the WebVTT format keeping the original formatting.
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        let (blocks, trailing) = split_blocks(text);
+        let mut blocks = blocks.into_iter();
+        let header = match blocks.next() {
+            | Some(block) => block.parse(vtt_parser::header)?,
+            | None => {
+                RawBlock {
+                    leading: text,
+                    source: "",
+                    line: 1,
+                    offset: 0,
+                    index: 0,
+                }
+                .parse(vtt_parser::header)?
+            },
+        };
+        let blocks = blocks
+            .map(|block| block.parse(vtt_parser::block))
+            .collect::<ParseResult<_>>()?;
+
+        Ok(Self {
+            header,
+            blocks,
+            trailing: trailing.to_string(),
+            newline: detect_newline(text),
+        })
+    }
+
+    /// Renders the text keeping the original formatting of the unedited blocks.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        self.header
+            .render_into(&mut output, self.newline, true);
+        for block in &self.blocks {
+            block.render_into(&mut output, self.newline, false);
+        }
+        output.push_str(&self.trailing);
+
+        output
+    }
+
+    /// Converts into the normalized [`WebVtt`].
+    pub fn into_webvtt(self) -> WebVtt {
+        WebVtt {
+            header: self.header.value,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(|block| block.value)
+                .collect(),
+        }
+    }
+}
+
+/// The block of the input with the trivia before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RawBlock<'a> {
+    /// The trivia before the block.
+    leading: &'a str,
+    /// The lines of the block with the original line endings.
+    source: &'a str,
+    /// The line number starting from 1 of the first line in the input.
+    line: usize,
+    /// The byte offset of the first line in the input.
+    offset: usize,
+    /// The index of the block in the input.
+    index: usize,
+}
+
+impl<'a> RawBlock<'a> {
+    /// Parses the block by the rule with the error positioned in the whole input.
+    fn parse<T>(
+        self,
+        rule: fn(&str) -> Result<T, peg::error::ParseError<peg::str::LineCol>>,
+    ) -> ParseResult<LosslessBlock<T>>
+    where
+        T: Clone,
+    {
+        let mut text = self.source.to_string();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+
+        let value = rule(&text).map_err(|err| {
+            ParseError::from_peg(&text, err).in_block(
+                self.line,
+                self.offset,
+                self.index,
+            )
+        })?;
+
+        Ok(LosslessBlock {
+            leading: Some(self.leading.to_string()),
+            value: value.clone(),
+            source: Some((self.source.to_string(), value)),
+        })
+    }
+}
+
+/// Splits the text into the blocks separated by blank lines and the trivia after the last block.
+fn split_blocks(text: &str) -> (Vec<RawBlock<'_>>, &str) {
+    let mut blocks = Vec::new();
+    // The start of the trivia before the next block.
+    let mut trivia = 0;
+    // The start of the current block.
+    let mut block: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (number, line) in split_lines_inclusive(text)
+        .into_iter()
+        .enumerate()
+    {
+        let start = offset;
+        offset += line.len();
+
+        let content = if start == 0 {
+            line.trim_start_matches('\u{feff}')
+        } else {
+            line
+        };
+        if content.trim().is_empty() {
+            if let Some((block_start, block_line)) = block.take() {
+                blocks.push(RawBlock {
+                    leading: &text[trivia..block_start],
+                    source: &text[block_start..start],
+                    line: block_line,
+                    offset: block_start,
+                    index: blocks.len(),
+                });
+                trivia = start;
+            }
+            continue;
+        }
+
+        if block.is_none() {
+            let block_start = start + line.len() - content.len();
+            block = Some((block_start, number + 1));
+        }
+    }
+
+    if let Some((block_start, block_line)) = block {
+        blocks.push(RawBlock {
+            leading: &text[trivia..block_start],
+            source: &text[block_start..],
+            line: block_line,
+            offset: block_start,
+            index: blocks.len(),
+        });
+        trivia = text.len();
+    }
+
+    (blocks, &text[trivia..])
+}
+
+/// Splits the text into the lines including the line endings of `\r\n`, `\n` or `\r`.
+fn split_lines_inclusive(text: &str) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let bytes = text.as_bytes();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\n' || bytes[i] == b'\r' {
+            if bytes[i] == b'\r' && bytes.get(i + 1) == Some(&b'\n') {
+                i += 1;
+            }
+            lines.push(&text[start..=i]);
+            start = i + 1;
+        }
+        i += 1;
+    }
+    if start < text.len() {
+        lines.push(&text[start..]);
+    }
+    lines
+}
+
+/// Detects the line ending of the text by the first line.
+fn detect_newline(text: &str) -> &'static str {
+    match text.find(['\r', '\n']) {
+        | Some(index) if text[index..].starts_with("\r\n") => "\r\n",
+        | Some(index) if text[index..].starts_with('\r') => "\r",
+        | _ => "\n",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srt::LinePosition;
+    use crate::vtt::VttComment;
+
+    #[test]
+    fn srt_round_trip() {
+        let texts = [
+            "",
+            "\n\n",
+            "1\n00:00:01,000 --> 00:00:02,000\nHello",
+            "\u{feff}\n \n1\r\n00:00:01,000  -->  00:00:02,000   X1:1 X2:2 Y1:3 Y2:4\r\n  Hello,\t\r\n world!\r\n\r\n \r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nBye.\r\n\r\n\r\n",
+        ];
+
+        for text in texts {
+            let srt = LosslessSubRip::parse(text).unwrap();
+            assert_eq!(srt.render(), text);
+        }
+
+        let srt = LosslessSubRip::parse(texts[3])
+            .unwrap()
+            .into_subrip();
+        assert_eq!(srt.subtitles.len(), 2);
+        assert_eq!(
+            srt.subtitles[0].text,
+            vec!["Hello,", "world!"]
+        );
+        assert_eq!(
+            srt.subtitles[0].line_position,
+            Some(LinePosition {
+                x1: 1,
+                x2: 2,
+                y1: 3,
+                y2: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn srt_edit() {
+        let text = "\u{feff}1\r\n00:00:01,000-->00:00:02,000 X1:1 X2:2 Y1:3 Y2:4\r\nHello,  \r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nworld!";
+        let mut srt = LosslessSubRip::parse(text).unwrap();
+
+        srt.subtitles[0]
+            .value
+            .sequence = 1;
+        assert!(!srt.subtitles[0].is_edited());
+
+        srt.subtitles[0]
+            .value
+            .line_position = Some(LinePosition {
+            x1: 5,
+            ..Default::default()
+        });
+        assert!(srt.subtitles[0].is_edited());
+        srt.subtitles[1].value.text = vec!["everyone!".to_string()];
+        srt.subtitles
+            .push(LosslessBlock::new(SrtSubtitle {
+                sequence: 3,
+                text: vec!["New".to_string()],
+                ..Default::default()
+            }));
+
+        assert_eq!(
+            srt.render(),
+            "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000 X1:5 X2:0 Y1:0 Y2:0\r\nHello,\r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\neveryone!\r\n\r\n3\r\n00:00:00,000 --> 00:00:00,000\r\nNew\r\n"
+        );
+    }
+
+    #[test]
+    fn srt_carriage_return() {
+        let text = "1\r00:00:01,000 --> 00:00:02,000\rHello\r\r2\r00:00:03,000 --> 00:00:04,000\rworld!\r";
+        let mut srt = LosslessSubRip::parse(text).unwrap();
+        assert_eq!(srt.render(), text);
+        assert_eq!(srt.subtitles.len(), 2);
+        assert_eq!(
+            srt.subtitles[1].value.text,
+            vec!["world!"]
+        );
+
+        srt.subtitles[1].value.text = vec!["everyone!".to_string()];
+        assert_eq!(
+            srt.render(),
+            "1\r00:00:01,000 --> 00:00:02,000\rHello\r\r2\r00:00:03,000 --> 00:00:04,000\reveryone!\r"
+        );
+
+        let text =
+            "WEBVTT\r\r00:01.000 --> 00:02.000\rHello\r\rNOTE A comment.\r";
+        let vtt = LosslessWebVtt::parse(text).unwrap();
+        assert_eq!(vtt.render(), text);
+        assert_eq!(vtt.blocks.len(), 2);
+    }
+
+    #[test]
+    fn srt_error() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000\nA\n\n\n2\n00:00:03,000 --> 00:00:04.000\nB\n";
+        let error = LosslessSubRip::parse(text).unwrap_err();
+
+        assert_eq!(error.line, 7);
+        assert_eq!(error.block_index, Some(1));
+        assert_eq!(
+            &text[error.offset..error.offset + 1],
+            "."
+        );
+    }
+
+    #[test]
+    fn vtt_round_trip() {
+        let text = "WEBVTT - A description.\r\n\r\nSTYLE\r\n::cue { color: red; }\r\n\r\nNOTE\r\nA comment.\r\n\r\n\r\n00:01.000   -->   00:04.000 align:start\r\nHello <b>world</b>!  \r\n\r\ncue_id\r\n01:00:01.000 --> 01:00:02.000\r\nBye.";
+        let vtt = LosslessWebVtt::parse(text).unwrap();
+
+        assert_eq!(vtt.render(), text);
+        assert_eq!(
+            vtt.into_webvtt(),
+            WebVtt::parse(&format!("{}\r\n", text)).unwrap()
+        );
+        assert!(LosslessWebVtt::parse("").is_err());
+        assert_eq!(
+            LosslessWebVtt::parse("WEBVTT")
+                .unwrap()
+                .render(),
+            "WEBVTT"
+        );
+    }
+
+    #[test]
+    fn vtt_edit() {
+        let text = "WEBVTT\n\n\n00:01.000 --> 00:04.000\nHello\n\nNOTE  Keep  spacing.\n";
+        let mut vtt = LosslessWebVtt::parse(text).unwrap();
+
+        if let VttBlock::Que(cue) = &mut vtt.blocks[0].value {
+            cue.payload = vec!["Hi".to_string()];
+        }
+        vtt.blocks.insert(
+            1,
+            LosslessBlock::new(VttBlock::Comment(VttComment::Side(
+                "Inserted.".to_string(),
+            ))),
+        );
+
+        assert_eq!(
+            vtt.render(),
+            "WEBVTT\n\n\n00:00:01.000 --> 00:00:04.000\nHi\n\nNOTE Inserted.\n\nNOTE  Keep  spacing.\n"
+        );
+    }
+}
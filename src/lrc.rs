@@ -0,0 +1,700 @@
+//! A parser for the LRC (`.lrc`) lyrics format including the enhanced word tags provided by [`subtp::lrc::Lrc`](Lrc).
+//!
+//! The metadata tags such as `[ar:]`, `[ti:]` and `[offset:]` are kept in order,
+//! and the lines with one or more time tags of `[mm:ss.xx]` are resolved to [`LrcTimedLine`]s
+//! by [`Lrc::timed_lines`], where the offset is applied and the end of a line is inferred from the next line.
+//! The word tags of `<mm:ss.xx>` are mapped to the internal timestamps of the WebVTT by [`SubtitleFormat`].
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::SubtitleFormat;
+//! use subtp::lrc::Lrc;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r#"[ar:Artist]
+//! [ti:Title]
+//! [offset:+500]
+//! [00:01.50]Hello
+//! [00:04.00][00:10.00]<00:04.00>La <00:04.50>la
+//! [00:06.00]
+//! "#;
+//!
+//! let lrc = Lrc::parse(text).unwrap();
+//! assert_eq!(lrc.get("ti"), Some("Title"));
+//! assert_eq!(lrc.offset(), 500);
+//! assert_eq!(lrc.render(), text);
+//!
+//! let lines = lrc.timed_lines();
+//! assert_eq!(lines[0].start, Duration::from_millis(1_000));
+//! assert_eq!(lines[0].end, Duration::from_millis(3_500));
+//!
+//! let vtt: WebVtt = lrc.convert();
+//! assert_eq!(
+//!     vtt.render(),
+//!     r#"WEBVTT
+//!
+//! 00:00:01.000 --> 00:00:03.500
+//! Hello
+//!
+//! 00:00:03.500 --> 00:00:05.500
+//! <00:00:03.500>La <00:00:04.000>la
+//!
+//! 00:00:09.500 --> 00:00:14.500
+//! <00:00:09.500>La <00:00:10.000>la
+//! "#
+//! );
+//! ```
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::format::Cue;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::format::push_span;
+use crate::lrc_parser;
+
+/// The duration of the last line without the `[length:]` tag after it.
+pub const LAST_LINE_DURATION: Duration = Duration::from_secs(5);
+
+/// The LRC lyrics.
+///
+/// Parses from text by [`Lrc::parse`](Lrc::parse)
+/// and renders to text by [`Lrc::render`](Lrc::render).
+///
+/// The metadata tags are rendered before the lines, and the blank lines are not preserved.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Lrc {
+    /// The metadata tags.
+    pub metadata: Vec<LrcMetadata>,
+    /// The lines with the time tags.
+    pub lines: Vec<LrcLine>,
+}
+
+impl Lrc {
+    /// Parses the LRC format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::lrc::Lrc;
+    ///
+    /// let text = r#"[ti:Title]
+    /// [00:12.00]Line one
+    /// [00:17.20][01:05.30]Chorus
+    /// "#;
+    ///
+    /// let lrc = Lrc::parse(text).unwrap();
+    /// assert_eq!(lrc.lines[1].times.len(), 2);
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        lrc_parser::lrc(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the LRC format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Gets the value of the first metadata tag by the key ignoring the ASCII case.
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Option<&str> {
+        self.metadata
+            .iter()
+            .find(|metadata| {
+                metadata
+                    .key
+                    .eq_ignore_ascii_case(key)
+            })
+            .map(|metadata| metadata.value.as_str())
+    }
+
+    /// Gets the title of `[ti:]`.
+    pub fn title(&self) -> Option<&str> {
+        self.get("ti")
+    }
+
+    /// Gets the artist of `[ar:]`.
+    pub fn artist(&self) -> Option<&str> {
+        self.get("ar")
+    }
+
+    /// Gets the offset in milliseconds of `[offset:]`, or zero if missing or invalid,
+    /// where a positive offset shows the lines earlier.
+    pub fn offset(&self) -> i64 {
+        self.get("offset")
+            .and_then(|offset| offset.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Gets the length of `[length:]`.
+    pub fn length(&self) -> Option<Duration> {
+        self.get("length")
+            .and_then(|length| lrc_parser::timestamp(length.trim()).ok())
+            .map(Duration::from)
+    }
+
+    /// Gets the lines for each time tag sorted by the start with the offset applied.
+    ///
+    /// A line ends at the start of the next line including the empty lines clearing the lyrics,
+    /// and the last line ends at the `[length:]` or after [`LAST_LINE_DURATION`].
+    /// The word tags of a line with multiple time tags are shifted by the difference from the first time tag.
+    pub fn timed_lines(&self) -> Vec<LrcTimedLine<'_>> {
+        let offset = self.offset();
+        let apply = |milliseconds: i64| {
+            Duration::from_millis(
+                milliseconds
+                    .saturating_sub(offset)
+                    .max(0) as u64,
+            )
+        };
+
+        let mut lines = Vec::new();
+        for line in &self.lines {
+            let Some(first) = line.times.first() else {
+                continue;
+            };
+            for time in &line.times {
+                let shift =
+                    time.total_milliseconds() - first.total_milliseconds();
+                lines.push(LrcTimedLine {
+                    start: apply(time.total_milliseconds()),
+                    end: Duration::ZERO,
+                    word_starts: line
+                        .words
+                        .iter()
+                        .map(|word| {
+                            word.start.map(|start| {
+                                apply(start.total_milliseconds() + shift)
+                            })
+                        })
+                        .collect(),
+                    line,
+                });
+            }
+        }
+        lines.sort_by_key(|line| line.start);
+
+        let length = self.length();
+        for i in 0..lines.len() {
+            let start = lines[i].start;
+            lines[i].end = lines[i + 1..]
+                .iter()
+                .map(|next| next.start)
+                .find(|next| *next > start)
+                .or(length.filter(|length| *length > start))
+                .unwrap_or(start + LAST_LINE_DURATION);
+        }
+
+        lines
+    }
+}
+
+impl Display for Lrc {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        for metadata in &self.metadata {
+            writeln!(f, "{}", metadata)?;
+        }
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SubtitleFormat for Lrc {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the non-empty timed lines, see [`Lrc::timed_lines`] for the timings.
+    fn cues(&self) -> Vec<Cue> {
+        self.timed_lines()
+            .into_iter()
+            .filter(|timed| {
+                !timed
+                    .line
+                    .text()
+                    .trim()
+                    .is_empty()
+            })
+            .map(|timed| {
+                let mut spans = Vec::new();
+                for (word, start) in timed
+                    .line
+                    .words
+                    .iter()
+                    .zip(&timed.word_starts)
+                {
+                    push_span(
+                        &mut spans,
+                        CueSpan {
+                            text: word.text.clone(),
+                            start: *start,
+                            ..Default::default()
+                        },
+                    );
+                }
+
+                Cue {
+                    start: timed.start,
+                    end: timed.end,
+                    spans,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Creates from the cues with a line of a time tag per cue and the word tags of the spans,
+    /// where the lines of a cue are joined by a space
+    /// and an empty line is inserted at the end of a cue not followed immediately by the next cue.
+    fn from_cues(mut cues: Vec<Cue>) -> Self {
+        cues.sort_by_key(|cue| cue.start);
+
+        let mut lines = Vec::new();
+        for (i, cue) in cues.iter().enumerate() {
+            let mut words: Vec<LrcWord> = Vec::new();
+            let mut start = None;
+            for span in &cue.spans {
+                let text = span.text.replace('\n', " ");
+                match words.last_mut() {
+                    | Some(last)
+                        if span.start.is_none() || span.start == start =>
+                    {
+                        last.text.push_str(&text)
+                    },
+                    | _ => {
+                        start = span.start;
+                        words.push(LrcWord {
+                            start: span
+                                .start
+                                .map(LrcTimestamp::from),
+                            text,
+                        });
+                    },
+                }
+            }
+
+            lines.push(LrcLine {
+                times: vec![cue.start.into()],
+                words,
+            });
+            if cues
+                .get(i + 1)
+                .map_or(true, |next| next.start > cue.end)
+            {
+                lines.push(LrcLine {
+                    times: vec![cue.end.into()],
+                    words: vec![],
+                });
+            }
+        }
+
+        Self {
+            metadata: vec![],
+            lines,
+        }
+    }
+}
+
+/// The metadata tag of `[key:value]`, e.g. `[ar:Artist]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LrcMetadata {
+    /// The key, e.g. `ar`, `ti`, `al`, `by`, `offset` and `length`.
+    pub key: String,
+    /// The value.
+    pub value: String,
+}
+
+impl Display for LrcMetadata {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "[{}:{}]", self.key, self.value)
+    }
+}
+
+/// The line with the time tags and the words.
+///
+/// ## Example
+/// ```
+/// use subtp::lrc::LrcLine;
+/// use subtp::lrc::LrcTimestamp;
+/// use subtp::lrc::LrcWord;
+///
+/// let line = LrcLine {
+///     times: vec![LrcTimestamp {
+///         minutes: 0,
+///         seconds: 12,
+///         milliseconds: 0,
+///     }],
+///     words: vec![
+///         LrcWord {
+///             start: None,
+///             text: "Hello ".to_string(),
+///         },
+///         LrcWord {
+///             start: Some(LrcTimestamp {
+///                 minutes: 0,
+///                 seconds: 12,
+///                 milliseconds: 500,
+///             }),
+///             text: "world".to_string(),
+///         },
+///     ],
+/// };
+///
+/// assert_eq!(line.text(), "Hello world");
+/// assert_eq!(line.to_string(), "[00:12.00]Hello <00:12.50>world");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LrcLine {
+    /// The time tags.
+    pub times: Vec<LrcTimestamp>,
+    /// The words split by the word tags, where the words are empty for a line clearing the lyrics.
+    pub words: Vec<LrcWord>,
+}
+
+impl LrcLine {
+    /// Gets the plain text of the words.
+    pub fn text(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| word.text.as_str())
+            .collect()
+    }
+}
+
+impl Display for LrcLine {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        for time in &self.times {
+            write!(f, "[{}]", time)?;
+        }
+        for word in &self.words {
+            write!(f, "{}", word)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The word with the word tag of `<mm:ss.xx>` in the enhanced format.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct LrcWord {
+    /// The start time of the word tag, or `None` for the text before the first word tag.
+    pub start: Option<LrcTimestamp>,
+    /// The text.
+    pub text: String,
+}
+
+impl Display for LrcWord {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        if let Some(start) = &self.start {
+            write!(f, "<{}>", start)?;
+        }
+
+        write!(f, "{}", self.text)
+    }
+}
+
+/// The line resolved for a time tag by [`Lrc::timed_lines`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LrcTimedLine<'a> {
+    /// The start time with the offset applied.
+    pub start: Duration,
+    /// The end time inferred from the next line.
+    pub end: Duration,
+    /// The start times of the words with the offset applied.
+    pub word_starts: Vec<Option<Duration>>,
+    /// The line.
+    pub line: &'a LrcLine,
+}
+
+/// The timestamp of the LRC in `mm:ss.xx`,
+/// which is rendered with the milliseconds if not in hundredths.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use subtp::lrc::LrcTimestamp;
+///
+/// let timestamp = LrcTimestamp::from(Duration::from_millis(65_300));
+/// assert_eq!(timestamp.to_string(), "01:05.30");
+///
+/// let timestamp = LrcTimestamp::from(Duration::from_millis(3_723_004));
+/// assert_eq!(timestamp.to_string(), "62:03.004");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct LrcTimestamp {
+    /// The minutes of two or more digits.
+    pub minutes: u32,
+    /// The seconds.
+    pub seconds: u8,
+    /// The milliseconds.
+    pub milliseconds: u16,
+}
+
+impl LrcTimestamp {
+    /// The maximum timestamp.
+    pub const MAX: Self = Self {
+        minutes: u32::MAX,
+        seconds: 59,
+        milliseconds: 999,
+    };
+
+    /// Gets the total milliseconds.
+    fn total_milliseconds(&self) -> i64 {
+        (self.minutes as i64 * 60 + self.seconds as i64) * 1_000
+            + self.milliseconds as i64
+    }
+}
+
+impl Display for LrcTimestamp {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        if self.milliseconds % 10 == 0 {
+            write!(
+                f,
+                "{:02}:{:02}.{:02}",
+                self.minutes,
+                self.seconds,
+                self.milliseconds / 10
+            )
+        } else {
+            write!(
+                f,
+                "{:02}:{:02}.{:03}",
+                self.minutes, self.seconds, self.milliseconds
+            )
+        }
+    }
+}
+
+/// Converts from the duration truncated to milliseconds, saturating at [`LrcTimestamp::MAX`].
+impl From<Duration> for LrcTimestamp {
+    fn from(duration: Duration) -> Self {
+        let seconds = duration.as_secs();
+        match u32::try_from(seconds / 60) {
+            | Ok(minutes) => {
+                Self {
+                    minutes,
+                    seconds: (seconds % 60) as u8,
+                    milliseconds: duration.subsec_millis() as u16,
+                }
+            },
+            | Err(_) => Self::MAX,
+        }
+    }
+}
+
+impl From<LrcTimestamp> for Duration {
+    fn from(timestamp: LrcTimestamp) -> Self {
+        Duration::from_millis(timestamp.total_milliseconds() as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::WebVtt;
+
+    fn timestamp(milliseconds: u64) -> LrcTimestamp {
+        Duration::from_millis(milliseconds).into()
+    }
+
+    #[test]
+    fn parse() {
+        let lrc = Lrc::parse(
+            "\u{feff}[ar: Artist ]\r\n[length:03:20]\r\n\r\n[00:12.00] Line one \r\n[00:17.2][01:05:30]Chorus\r\n[00:21]<00:21.10>Word <00:21.500>by <00:22.00>word<00:23.00>\r\n[00:25.00]\r\n[#:comment]",
+        )
+        .unwrap();
+
+        assert_eq!(
+            lrc.metadata,
+            vec![
+                LrcMetadata {
+                    key: "ar".to_string(),
+                    value: " Artist ".to_string(),
+                },
+                LrcMetadata {
+                    key: "length".to_string(),
+                    value: "03:20".to_string(),
+                },
+                LrcMetadata {
+                    key: "#".to_string(),
+                    value: "comment".to_string(),
+                },
+            ]
+        );
+        assert_eq!(lrc.artist(), Some(" Artist "));
+        assert_eq!(lrc.title(), None);
+        assert_eq!(
+            lrc.length(),
+            Some(Duration::from_secs(200))
+        );
+        assert_eq!(lrc.offset(), 0);
+
+        assert_eq!(
+            lrc.lines,
+            vec![
+                LrcLine {
+                    times: vec![timestamp(12_000)],
+                    words: vec![LrcWord {
+                        start: None,
+                        text: "Line one".to_string(),
+                    }],
+                },
+                LrcLine {
+                    times: vec![
+                        timestamp(17_200),
+                        timestamp(65_300)
+                    ],
+                    words: vec![LrcWord {
+                        start: None,
+                        text: "Chorus".to_string(),
+                    }],
+                },
+                LrcLine {
+                    times: vec![timestamp(21_000)],
+                    words: vec![
+                        LrcWord {
+                            start: Some(timestamp(21_100)),
+                            text: "Word ".to_string(),
+                        },
+                        LrcWord {
+                            start: Some(timestamp(21_500)),
+                            text: "by ".to_string(),
+                        },
+                        LrcWord {
+                            start: Some(timestamp(22_000)),
+                            text: "word".to_string(),
+                        },
+                        LrcWord {
+                            start: Some(timestamp(23_000)),
+                            text: "".to_string(),
+                        },
+                    ],
+                },
+                LrcLine {
+                    times: vec![timestamp(25_000)],
+                    words: vec![],
+                },
+            ]
+        );
+        assert_eq!(
+            lrc.render(),
+            "[ar: Artist ]\n[length:03:20]\n[#:comment]\n[00:12.00]Line one\n[00:17.20][01:05.30]Chorus\n[00:21.00]<00:21.10>Word <00:21.50>by <00:22.00>word<00:23.00>\n[00:25.00]\n"
+        );
+        assert_eq!(Lrc::parse("").unwrap(), Lrc::default());
+
+        assert!(Lrc::parse("Plain text\n").is_err());
+        assert!(Lrc::parse("[00:1.00]A\n").is_err());
+    }
+
+    #[test]
+    fn timed_lines() {
+        let lrc = Lrc::parse(
+            "[offset:-1000]\n[length:00:30]\n[00:10.00][00:01.00]<00:10.50>A\n[00:05.00]B\n[00:05.00]C\n",
+        )
+        .unwrap();
+
+        let lines = lrc.timed_lines();
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| {
+                    (
+                        line.start.as_millis(),
+                        line.end.as_millis(),
+                        line.line.text(),
+                        line.word_starts.clone(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    2_000,
+                    6_000,
+                    "A".to_string(),
+                    vec![Some(
+                        Duration::from_millis(2_500)
+                    )],
+                ),
+                (
+                    6_000,
+                    11_000,
+                    "B".to_string(),
+                    vec![None]
+                ),
+                (
+                    6_000,
+                    11_000,
+                    "C".to_string(),
+                    vec![None]
+                ),
+                (
+                    11_000,
+                    30_000,
+                    "A".to_string(),
+                    vec![Some(
+                        Duration::from_millis(11_500)
+                    )],
+                ),
+            ]
+        );
+
+        let lrc = Lrc::parse("[offset:+2000]\n[00:01.00]A\n").unwrap();
+        assert_eq!(
+            lrc.timed_lines()[0].start,
+            Duration::ZERO
+        );
+        assert_eq!(
+            lrc.timed_lines()[0].end,
+            LAST_LINE_DURATION
+        );
+    }
+
+    #[test]
+    fn convert() {
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\n00:01.000 --> 00:03.000\n<v Alice><i>Hello</i>,\nworld <00:02.005>again</v>\n\n00:03.000 --> 00:04.000\nB\n\n00:05.000 --> 00:06.000\nC\n",
+        )
+        .unwrap();
+
+        let lrc: Lrc = vtt.convert();
+        assert_eq!(
+            lrc.render(),
+            "[00:01.00]Hello, world <00:02.005>again\n[00:03.00]B\n[00:04.00]\n[00:05.00]C\n[00:06.00]\n"
+        );
+
+        let vtt: WebVtt = lrc.convert();
+        assert_eq!(
+            vtt.render(),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nHello, world <00:00:02.005>again\n\n00:00:03.000 --> 00:00:04.000\nB\n\n00:00:05.000 --> 00:00:06.000\nC\n"
+        );
+        assert_eq!(vtt.convert::<Lrc>(), lrc);
+    }
+}
@@ -0,0 +1,192 @@
+//! A parser implementation for the LRC format.
+
+pub(crate) use lrc_parser::lrc;
+pub(crate) use lrc_parser::timestamp;
+
+use crate::lrc::Lrc;
+use crate::lrc::LrcLine;
+use crate::lrc::LrcMetadata;
+
+/// A line of the LRC.
+enum Entry {
+    Metadata(LrcMetadata),
+    Line(LrcLine),
+    Blank,
+}
+
+/// Collects the entries into the LRC.
+fn lrc_from_entries(entries: Vec<Entry>) -> Lrc {
+    let mut lrc = Lrc::default();
+    for entry in entries {
+        match entry {
+            | Entry::Metadata(metadata) => lrc.metadata.push(metadata),
+            | Entry::Line(line) => lrc.lines.push(line),
+            | Entry::Blank => {},
+        }
+    }
+
+    lrc
+}
+
+peg::parser! {
+    /// The parser for LRC format.
+    grammar lrc_parser() for str {
+        use crate::lrc::LrcLine;
+        use crate::lrc::LrcMetadata;
+        use crate::lrc::LrcTimestamp;
+        use crate::lrc::LrcWord;
+        use super::Entry;
+        use super::lrc_from_entries;
+
+        /// Whitespace.
+        rule whitespace() = [' ' | '\t']
+
+        /// Newline.
+        rule newline() = "\r\n" / "\n" / "\r"
+
+        /// Any-digit minutes.
+        rule minutes() -> u32
+            = n:$(['0'..='9']+) {?
+                n.parse().or(Err("minutes in u32"))
+            }
+
+        /// Two-digit number.
+        rule two_number() -> u8
+            = n:$(['0'..='9']['0'..='9']) {?
+                n.parse().or(Err("two-digit number"))
+            }
+
+        /// Fraction of a second of one to three digits in milliseconds.
+        rule fraction() -> u16
+            = n:$(['0'..='9']*<1,3>) {?
+                n.parse::<u16>()
+                    .map(|fraction| fraction * 10u16.pow(3 - n.len() as u32))
+                    .or(Err("fraction of a second"))
+            }
+
+        /// Timestamp of `mm:ss.xx` with the fraction separated by `.` or `:`.
+        pub(crate) rule timestamp() -> LrcTimestamp
+            = minutes:minutes() ":" seconds:two_number() milliseconds:(("." / ":") f:fraction() { f })?
+            {
+                LrcTimestamp {
+                    minutes,
+                    seconds,
+                    milliseconds: milliseconds.unwrap_or(0),
+                }
+            }
+
+        /// Time tag.
+        rule time_tag() -> LrcTimestamp
+            = "[" whitespace()* t:timestamp() whitespace()* "]" { t }
+
+        /// Word tag of the enhanced format.
+        rule word_tag() -> LrcTimestamp
+            = "<" whitespace()* t:timestamp() whitespace()* ">" { t }
+
+        /// Text until a word tag or the end of the line.
+        rule text() -> &'input str
+            = $((!newline() !word_tag() [_])+)
+
+        /// Line with the time tags and the words.
+        rule line() -> LrcLine
+            = times:(t:time_tag() whitespace()* { t })+
+                first:text()?
+                rest:(start:word_tag() text:text()? { (start, text) })*
+            {
+                let mut words = first
+                    .map(|text| LrcWord {
+                        start: None,
+                        text: text.to_string(),
+                    })
+                    .into_iter()
+                    .chain(rest.into_iter().map(|(start, text)| LrcWord {
+                        start: Some(start),
+                        text: text.unwrap_or_default().to_string(),
+                    }))
+                    .collect::<Vec<_>>();
+                if let Some(last) = words.last_mut() {
+                    last.text.truncate(last.text.trim_end().len());
+                }
+                words.retain(|word| word.start.is_some() || !word.text.is_empty());
+
+                LrcLine {
+                    times,
+                    words,
+                }
+            }
+
+        /// Metadata tag with the key not starting with a digit.
+        rule metadata() -> LrcMetadata
+            = "[" key:$(!['0'..='9'] [^ ':' | ']' | '\r' | '\n']+) ":" value:$([^ ']' | '\r' | '\n']*) "]" whitespace()*
+            {
+                LrcMetadata {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                }
+            }
+
+        /// Line of the metadata tag, the time tags or the blank.
+        rule entry() -> Entry
+            = whitespace()*
+                entry:(
+                    line:line() { Entry::Line(line) }
+                    / metadata:metadata() { Entry::Metadata(metadata) }
+                    / whitespace()* { Entry::Blank }
+                )
+            {
+                entry
+            }
+
+        /// The entire LRC.
+        pub(crate) rule lrc() -> Lrc
+            = "\u{feff}"? entries:entry() ** newline()
+            {
+                lrc_from_entries(entries)
+            }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::lrc_parser;
+    use crate::lrc::*;
+
+    #[test]
+    fn parse_timestamp() {
+        assert_eq!(
+            lrc_parser::timestamp("01:05.30").unwrap(),
+            LrcTimestamp {
+                minutes: 1,
+                seconds: 5,
+                milliseconds: 300,
+            }
+        );
+        assert_eq!(
+            lrc_parser::timestamp("123:45.678").unwrap(),
+            LrcTimestamp {
+                minutes: 123,
+                seconds: 45,
+                milliseconds: 678,
+            }
+        );
+        assert_eq!(
+            lrc_parser::timestamp("00:07.5").unwrap(),
+            LrcTimestamp {
+                minutes: 0,
+                seconds: 7,
+                milliseconds: 500,
+            }
+        );
+        assert_eq!(
+            lrc_parser::timestamp("00:07").unwrap(),
+            LrcTimestamp {
+                minutes: 0,
+                seconds: 7,
+                milliseconds: 0,
+            }
+        );
+
+        assert!(lrc_parser::timestamp("0:7.00").is_err());
+        assert!(lrc_parser::timestamp("00:07.1234").is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Options of rendering the SubRip Subtitle (`.srt`) and the WebVTT (`.vtt`) formats.
+//!
+//! The [`RenderOptions`] control the line ending, the byte order mark, the hours of timestamps,
+//! the blank lines between blocks, the trailing newline and the sequence numbers,
+//! and are used by `render_with` and `write_to` of [`SubRip`](crate::srt::SubRip) and [`WebVtt`](crate::vtt::WebVtt).
+//!
+//! ## Example
+//! ```
+//! use subtp::render::LineEnding;
+//! use subtp::render::RenderOptions;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = "WEBVTT\n\n00:01.000 --> 00:02.000\nHello, world!\n";
+//! let vtt = WebVtt::parse(text).unwrap();
+//!
+//! let options = RenderOptions {
+//!     line_ending: LineEnding::CrLf,
+//!     byte_order_mark: true,
+//!     elide_hours: true,
+//!     ..Default::default()
+//! };
+//! assert_eq!(
+//!     vtt.render_with(&options),
+//!     "\u{feff}WEBVTT\r\n\r\n00:01.000 --> 00:02.000\r\nHello, world!\r\n"
+//! );
+//!
+//! let mut bytes = Vec::new();
+//! vtt.write_to(&mut bytes, &options).unwrap();
+//! assert_eq!(bytes, vtt.render_with(&options).into_bytes());
+//! ```
+
+use crate::vtt::VttCue;
+use crate::vtt::VttTimestamp;
+
+/// The line ending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    /// `\n`.
+    #[default]
+    Lf,
+    /// `\r\n`.
+    CrLf,
+}
+
+impl LineEnding {
+    /// The characters of the line ending.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            | Self::Lf => "\n",
+            | Self::CrLf => "\r\n",
+        }
+    }
+}
+
+/// The options of rendering.
+///
+/// The default options render the same text as `render`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RenderOptions {
+    /// The line ending.
+    pub line_ending: LineEnding,
+    /// Whether to write the UTF-8 byte order mark at the start.
+    pub byte_order_mark: bool,
+    /// Whether to omit the zero hours of the cue timings as `mm:ss.ttt`,
+    /// which only applies to the WebVTT since the SubRip Subtitle requires the hours.
+    pub elide_hours: bool,
+    /// The number of blank lines between blocks, clamped to at least one to be parsed again.
+    pub blank_lines: usize,
+    /// Whether to end the text with a line ending.
+    pub trailing_newline: bool,
+    /// Whether to renumber the sequence numbers from 1 in order,
+    /// which only applies to the SubRip Subtitle.
+    pub renumber: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            line_ending: LineEnding::Lf,
+            byte_order_mark: false,
+            elide_hours: false,
+            blank_lines: 1,
+            trailing_newline: true,
+            renumber: false,
+        }
+    }
+}
+
+/// Renders the blocks ending with `\n` by the options,
+/// where the line endings of `\r\n` written in the blocks are kept as is.
+pub(crate) fn render_blocks(
+    blocks: impl IntoIterator<Item = String>,
+    options: &RenderOptions,
+) -> String {
+    let separator = "\n".repeat(options.blank_lines.max(1));
+    let mut text = blocks
+        .into_iter()
+        .collect::<Vec<_>>()
+        .join(&separator);
+
+    if !options.trailing_newline && text.ends_with('\n') {
+        text.pop();
+        if text.ends_with('\r') {
+            text.pop();
+        }
+    }
+    if options.line_ending != LineEnding::Lf {
+        text = replace_line_feeds(&text, options.line_ending);
+    }
+    if options.byte_order_mark {
+        text.insert(0, '\u{feff}');
+    }
+
+    text
+}
+
+/// Replaces the line feeds not preceded by `\r` with the line ending.
+fn replace_line_feeds(
+    text: &str,
+    line_ending: LineEnding,
+) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut previous = None;
+    for c in text.chars() {
+        if c == '\n' && previous != Some('\r') {
+            replaced.push_str(line_ending.as_str());
+        } else {
+            replaced.push(c);
+        }
+        previous = Some(c);
+    }
+    replaced
+}
+
+/// Renders the cue of the WebVTT by the options.
+pub(crate) fn render_vtt_cue(
+    cue: &VttCue,
+    options: &RenderOptions,
+) -> String {
+    if !options.elide_hours {
+        return cue.to_string();
+    }
+
+    let mut text = String::new();
+    if let Some(identifier) = &cue.identifier {
+        text.push_str(identifier);
+        text.push('\n');
+    }
+
+    text.push_str(&format!(
+        "{} --> {}",
+        elide_hours(&cue.timings.start),
+        elide_hours(&cue.timings.end)
+    ));
+    if let Some(settings) = &cue.settings {
+        text.push_str(&format!(" {}", settings));
+    }
+    text.push_str(&format!(
+        "\n{}\n",
+        cue.payload.join("\n")
+    ));
+
+    text
+}
+
+/// Formats the timestamp without the hours if zero.
+fn elide_hours(timestamp: &VttTimestamp) -> String {
+    if timestamp.hours == 0 {
+        format!(
+            "{:02}:{:02}.{:03}",
+            timestamp.minutes, timestamp.seconds, timestamp.milliseconds
+        )
+    } else {
+        timestamp.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn default() {
+        let srt = SubRip::parse(
+            "1\n00:00:01,000 --> 00:00:02,000\nA\n\n2\n00:00:03,000 --> 00:00:04,000\nB\n",
+        )
+        .unwrap();
+        assert_eq!(
+            srt.render_with(&RenderOptions::default()),
+            srt.render()
+        );
+
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\nSTYLE\n::cue {\r\n  color: red;\r\n}\n\nNOTE A comment.\n\n01:00:01.000 --> 01:00:02.000 align:start\nA\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vtt.render_with(&RenderOptions::default()),
+            vtt.render()
+        );
+        assert_eq!(
+            vtt.render_with(&RenderOptions {
+                line_ending: LineEnding::CrLf,
+                ..Default::default()
+            }),
+            replace_line_feeds(&vtt.render(), LineEnding::CrLf)
+        );
+    }
+
+    #[test]
+    fn srt() {
+        let srt = SubRip::parse(
+            "3\n00:00:01,000 --> 00:00:02,000\nA\n\n5\n00:00:03,000 --> 00:00:04,000\nB\nC\n",
+        )
+        .unwrap();
+        let options = RenderOptions {
+            line_ending: LineEnding::CrLf,
+            byte_order_mark: true,
+            elide_hours: true,
+            blank_lines: 2,
+            trailing_newline: false,
+            renumber: true,
+        };
+
+        let rendered = srt.render_with(&options);
+        assert_eq!(
+            rendered,
+            "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\nA\r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nB\r\nC"
+        );
+        assert_eq!(
+            SubRip::parse(&format!("{}\r\n", rendered))
+                .unwrap()
+                .subtitles
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\nid\n00:01.000 --> 01:00:02.000 line:0\nA\n\n00:03.000 --> 00:04.000\nB <00:03.500>C\n",
+        )
+        .unwrap();
+        let options = RenderOptions {
+            elide_hours: true,
+            blank_lines: 0,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vtt.render_with(&options),
+            "WEBVTT\n\nid\n00:01.000 --> 01:00:02.000 line:0\nA\n\n00:03.000 --> 00:04.000\nB <00:03.500>C\n"
+        );
+        assert_eq!(
+            WebVtt::parse(&vtt.render_with(&options)).unwrap(),
+            vtt
+        );
+    }
+}
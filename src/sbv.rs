@@ -0,0 +1,350 @@
+//! A parser for the YouTube SubViewer (`.sbv`) format provided by [`subtp::sbv::Sbv`](Sbv).
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::sbv::Sbv;
+//!
+//! let text = r#"0:00:01.000,0:00:04.000
+//! Hello, world!
+//!
+//! 0:00:05.000,0:00:07.500
+//! This is a sample.
+//! Thank you for your reading.
+//! "#;
+//!
+//! let sbv = Sbv::parse(text).unwrap();
+//! assert_eq!(sbv.cues.len(), 2);
+//! assert_eq!(sbv.cues[0].text, vec!["Hello, world!"]);
+//! assert_eq!(
+//!     Duration::from(sbv.cues[1].end),
+//!     Duration::from_millis(7_500)
+//! );
+//!
+//! let rendered = sbv.render();
+//! assert_eq!(rendered, text);
+//! ```
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::format::Cue;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::sbv_parser;
+
+/// The YouTube SubViewer.
+///
+/// Parses from text by [`Sbv::parse`](Sbv::parse)
+/// and renders to text by [`Sbv::render`](Sbv::render).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Sbv {
+    /// The cues.
+    pub cues: Vec<SbvCue>,
+}
+
+impl Sbv {
+    /// Parses the YouTube SubViewer format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::sbv::Sbv;
+    ///
+    /// let text = r#"0:00:01.000,0:00:04.000
+    /// Hello, world!
+    /// "#;
+    ///
+    /// let sbv = Sbv::parse(text).unwrap();
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        sbv_parser::sbv(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the YouTube SubViewer format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Sbv {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        for (i, cue) in self.cues.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", cue)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SubtitleFormat for Sbv {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the plain text.
+    fn cues(&self) -> Vec<Cue> {
+        self.cues
+            .iter()
+            .map(|cue| {
+                Cue {
+                    start: cue.start.into(),
+                    end: cue.end.into(),
+                    spans: vec![CueSpan {
+                        text: cue.text.join("\n"),
+                        ..Default::default()
+                    }]
+                    .into_iter()
+                    .filter(|span| !span.text.is_empty())
+                    .collect(),
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    /// Creates from the plain text of the cues, dropping the formatting and the positions.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        Self {
+            cues: cues
+                .iter()
+                .map(|cue| {
+                    SbvCue {
+                        start: cue.start.into(),
+                        end: cue.end.into(),
+                        text: cue
+                            .lines()
+                            .into_iter()
+                            .filter(|line| !line.trim().is_empty())
+                            .collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The cue of the YouTube SubViewer.
+///
+/// ## Example
+/// ```
+/// use subtp::sbv::SbvCue;
+/// use subtp::sbv::SbvTimestamp;
+///
+/// let cue = SbvCue {
+///     start: SbvTimestamp {
+///         seconds: 1,
+///         ..Default::default()
+///     },
+///     end: SbvTimestamp {
+///         seconds: 2,
+///         milliseconds: 500,
+///         ..Default::default()
+///     },
+///     text: vec!["Hello, world!".to_string()],
+/// };
+///
+/// assert_eq!(
+///     cue.to_string(),
+///     "0:00:01.000,0:00:02.500\nHello, world!\n"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SbvCue {
+    /// The start timestamp.
+    pub start: SbvTimestamp,
+    /// The end timestamp.
+    pub end: SbvTimestamp,
+    /// The lines of text.
+    pub text: Vec<String>,
+}
+
+impl Display for SbvCue {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "{},{}", self.start, self.end)?;
+        for line in &self.text {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The timestamp of the YouTube SubViewer in `h:mm:ss.ttt`.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use subtp::sbv::SbvTimestamp;
+///
+/// let timestamp = SbvTimestamp::from(Duration::from_millis(3_723_004));
+/// assert_eq!(timestamp.to_string(), "1:02:03.004");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct SbvTimestamp {
+    /// The hours of one or more digits.
+    pub hours: u32,
+    /// The minutes.
+    pub minutes: u8,
+    /// The seconds.
+    pub seconds: u8,
+    /// The milliseconds.
+    pub milliseconds: u16,
+}
+
+impl SbvTimestamp {
+    /// The maximum timestamp.
+    pub const MAX: Self = Self {
+        hours: u32::MAX,
+        minutes: 59,
+        seconds: 59,
+        milliseconds: 999,
+    };
+}
+
+impl Display for SbvTimestamp {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{:02}:{:02}.{:03}",
+            self.hours, self.minutes, self.seconds, self.milliseconds
+        )
+    }
+}
+
+/// Converts from the duration truncated to milliseconds, saturating at [`SbvTimestamp::MAX`].
+impl From<Duration> for SbvTimestamp {
+    fn from(duration: Duration) -> Self {
+        let seconds = duration.as_secs();
+        match u32::try_from(seconds / 3600) {
+            | Ok(hours) => {
+                Self {
+                    hours,
+                    minutes: ((seconds % 3600) / 60) as u8,
+                    seconds: (seconds % 60) as u8,
+                    milliseconds: duration.subsec_millis() as u16,
+                }
+            },
+            | Err(_) => Self::MAX,
+        }
+    }
+}
+
+impl From<SbvTimestamp> for Duration {
+    fn from(timestamp: SbvTimestamp) -> Self {
+        Duration::new(
+            timestamp.hours as u64 * 3600
+                + timestamp.minutes as u64 * 60
+                + timestamp.seconds as u64,
+            timestamp.milliseconds as u32 * 1_000_000,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn parse() {
+        let sbv = Sbv::parse(
+            "\u{feff}0:00:01.000,0:00:04.000\r\nHello,\r\nworld!\r\n\r\n\r\n12:34:56.789 , 12:34:57.000  \r\n\r\n0:00:10.000,0:00:11.000\r\n>> Bye.",
+        )
+        .unwrap();
+
+        assert_eq!(
+            sbv,
+            Sbv {
+                cues: vec![
+                    SbvCue {
+                        start: SbvTimestamp {
+                            seconds: 1,
+                            ..Default::default()
+                        },
+                        end: SbvTimestamp {
+                            seconds: 4,
+                            ..Default::default()
+                        },
+                        text: vec![
+                            "Hello,".to_string(),
+                            "world!".to_string(),
+                        ],
+                    },
+                    SbvCue {
+                        start: SbvTimestamp {
+                            hours: 12,
+                            minutes: 34,
+                            seconds: 56,
+                            milliseconds: 789,
+                        },
+                        end: SbvTimestamp {
+                            hours: 12,
+                            minutes: 34,
+                            seconds: 57,
+                            milliseconds: 0,
+                        },
+                        text: vec![],
+                    },
+                    SbvCue {
+                        start: SbvTimestamp {
+                            seconds: 10,
+                            ..Default::default()
+                        },
+                        end: SbvTimestamp {
+                            seconds: 11,
+                            ..Default::default()
+                        },
+                        text: vec![">> Bye.".to_string()],
+                    },
+                ],
+            }
+        );
+
+        assert_eq!(Sbv::parse("").unwrap(), Sbv::default());
+        assert!(Sbv::parse("0:00:01,000,0:00:02.000\nA\n").is_err());
+        assert!(Sbv::parse("0:00:01.000 --> 0:00:02.000\nA\n").is_err());
+    }
+
+    #[test]
+    fn convert() {
+        let sbv = Sbv::parse(
+            "0:00:01.000,0:00:02.000\nHello,\nworld!\n\n1:00:00.000,1:00:01.500\nBye.\n",
+        )
+        .unwrap();
+
+        let vtt: WebVtt = sbv.convert();
+        assert_eq!(
+            vtt.render(),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nHello,\nworld!\n\n01:00:00.000 --> 01:00:01.500\nBye.\n"
+        );
+
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\n00:01.000 --> 00:02.000\n<v Alice><i>Hello</i> <00:01.500>world!</v>\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vtt.convert::<Sbv>().render(),
+            "0:00:01.000,0:00:02.000\nHello world!\n"
+        );
+        assert_eq!(sbv.convert::<Sbv>(), sbv);
+    }
+}
@@ -0,0 +1,111 @@
+//! A parser implementation for the YouTube SubViewer format.
+
+pub(crate) use sbv_parser::sbv;
+
+peg::parser! {
+    /// The parser for YouTube SubViewer format.
+    grammar sbv_parser() for str {
+        use crate::sbv::Sbv;
+        use crate::sbv::SbvCue;
+        use crate::sbv::SbvTimestamp;
+
+        /// Whitespace.
+        rule whitespace() = [' ' | '\t']
+
+        /// Newline.
+        rule newline() = "\r\n" / "\n" / "\r"
+
+        /// Blank line or the end of the text.
+        rule blank() = whitespace()* (newline() / ![_])
+
+        /// Any-digit hours.
+        rule hours() -> u32
+            = n:$(['0'..='9']+) {?
+                n.parse().or(Err("hours in u32"))
+            }
+
+        /// Two-digit number.
+        rule two_number() -> u8
+            = n:$(['0'..='9']['0'..='9']) {?
+                n.parse().or(Err("two-digit number"))
+            }
+
+        /// Three-digit number.
+        rule three_number() -> u16
+            = n:$(['0'..='9']['0'..='9']['0'..='9']) {?
+                n.parse().or(Err("three-digit number"))
+            }
+
+        /// Line of text.
+        rule line() -> String
+            = !blank() line:$((!newline() [_])+)
+            {
+                line.trim_end().to_string()
+            }
+
+        /// Timestamp.
+        pub(crate) rule timestamp() -> SbvTimestamp
+            = hours:hours() ":" minutes:two_number() ":" seconds:two_number() "." milliseconds:three_number()
+            {
+                SbvTimestamp {
+                    hours,
+                    minutes,
+                    seconds,
+                    milliseconds,
+                }
+            }
+
+        /// Single cue.
+        rule cue() -> SbvCue
+            = start:timestamp() whitespace()* "," whitespace()* end:timestamp() whitespace()*
+                text:(newline() line:line() { line })*
+            {
+                SbvCue {
+                    start,
+                    end,
+                    text,
+                }
+            }
+
+        /// The entire SBV.
+        pub(crate) rule sbv() -> Sbv
+            = "\u{feff}"? (whitespace() / newline())*
+                cues:cue() ** ((whitespace()* newline())+)
+                (whitespace() / newline())*
+            {
+                Sbv { cues }
+            }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::sbv_parser;
+    use crate::sbv::*;
+
+    #[test]
+    fn parse_timestamp() {
+        assert_eq!(
+            sbv_parser::timestamp("0:00:01.000").unwrap(),
+            SbvTimestamp {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                milliseconds: 0,
+            }
+        );
+        assert_eq!(
+            sbv_parser::timestamp("123:45:06.789").unwrap(),
+            SbvTimestamp {
+                hours: 123,
+                minutes: 45,
+                seconds: 6,
+                milliseconds: 789,
+            }
+        );
+
+        assert!(sbv_parser::timestamp("0:0:01.000").is_err());
+        assert!(sbv_parser::timestamp("0:00:01,000").is_err());
+        assert!(sbv_parser::timestamp("00:01.000").is_err());
+    }
+}
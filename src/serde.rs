@@ -0,0 +1,404 @@
+//! Serialization and deserialization of the subtitles by [serde](https://serde.rs),
+//! enabled by the `serde` feature.
+//!
+//! The types in [`srt`](crate::srt) and [`vtt`](crate::vtt) implement `Serialize` and `Deserialize`
+//! with the stable shape as follows in JSON:
+//!
+//! - Structs are objects with the same field names as the Rust fields, and `None` is `null`.
+//! - Timestamps are objects of `hours`, `minutes`, `seconds` and `milliseconds`.
+//! - Enums without values are strings of the snake case variant names, e.g. `"line_left"`.
+//! - Enums with values are objects with the single key of the snake case variant name,
+//!   e.g. `{"cue": {...}}` of [`VttBlock::Que`](crate::vtt::VttBlock::Que)
+//!   and `{"line_number": [-1, "start"]}` of [`Line::LineNumber`](crate::vtt::Line::LineNumber).
+//! - [`Percentage`](crate::vtt::Percentage) is the number of the value, e.g. `50.0`.
+//!
+//! The shape with the timestamps as the integers of the total milliseconds is also stable,
+//! chosen by [`Milliseconds`] for all the timestamps in a value,
+//! or by `#[serde(with = "subtp::serde::milliseconds")]` for a timestamp field of your own type.
+//! It differs from the default shape only in the timestamps.
+//!
+//! ## Example
+//! ```
+//! use subtp::serde::Milliseconds;
+//! use subtp::srt::SubRip;
+//!
+//! let text = "1\n00:00:01,000 --> 00:00:02,500\nHello, world!\n";
+//! let srt = SubRip::parse(text).unwrap();
+//!
+//! let json = serde_json::to_string(&srt).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"subtitles":[{"sequence":1,"start":{"hours":0,"minutes":0,"seconds":1,"milliseconds":0},"end":{"hours":0,"minutes":0,"seconds":2,"milliseconds":500},"text":["Hello, world!"],"line_position":null}]}"#
+//! );
+//! assert_eq!(serde_json::from_str::<SubRip>(&json).unwrap(), srt);
+//!
+//! let json = serde_json::to_string(&Milliseconds(&srt)).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"subtitles":[{"sequence":1,"start":1000,"end":2500,"text":["Hello, world!"],"line_position":null}]}"#
+//! );
+//! assert_eq!(serde_json::from_str::<Milliseconds<SubRip>>(&json).unwrap().0, srt);
+//! ```
+//!
+//! With the timestamps in milliseconds in your own type:
+//! ```
+//! use serde::Deserialize;
+//! use serde::Serialize;
+//! use subtp::vtt::VttTimestamp;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Chapter {
+//!     title: String,
+//!     #[serde(with = "subtp::serde::milliseconds")]
+//!     start: VttTimestamp,
+//! }
+//!
+//! let chapter = Chapter {
+//!     title: "Opening".to_string(),
+//!     start: VttTimestamp {
+//!         minutes: 1,
+//!         seconds: 2,
+//!         milliseconds: 345,
+//!         ..Default::default()
+//!     },
+//! };
+//!
+//! let json = serde_json::to_string(&chapter).unwrap();
+//! assert_eq!(json, r#"{"title":"Opening","start":62345}"#);
+//! assert_eq!(
+//!     serde_json::from_str::<Chapter>(&json).unwrap().start,
+//!     chapter.start
+//! );
+//! ```
+
+use std::cell::Cell;
+
+use ::serde::Deserialize;
+use ::serde::Deserializer;
+use ::serde::Serialize;
+use ::serde::Serializer;
+
+use crate::srt::SrtTimestamp;
+use crate::vtt::VttTimestamp;
+
+thread_local! {
+    /// Whether the timestamps are in milliseconds in the value being serialized or deserialized by [`Milliseconds`].
+    static IN_MILLISECONDS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The scope of [`IN_MILLISECONDS`] restoring the previous state on drop.
+struct MillisecondsScope {
+    previous: bool,
+}
+
+impl MillisecondsScope {
+    fn enter() -> Self {
+        Self {
+            previous: IN_MILLISECONDS.with(|flag| flag.replace(true)),
+        }
+    }
+}
+
+impl Drop for MillisecondsScope {
+    fn drop(&mut self) {
+        IN_MILLISECONDS.with(|flag| flag.set(self.previous));
+    }
+}
+
+/// The value with all the timestamps in it serialized and deserialized as the integers of the total milliseconds.
+///
+/// ## Example
+/// ```
+/// use subtp::serde::Milliseconds;
+/// use subtp::vtt::VttTimings;
+///
+/// let json = r#"{"start":1000,"end":62345}"#;
+/// let Milliseconds(timings) = serde_json::from_str::<Milliseconds<VttTimings>>(json).unwrap();
+/// assert_eq!(timings.end.to_string(), "00:01:02.345");
+/// assert_eq!(serde_json::to_string(&Milliseconds(timings)).unwrap(), json);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Milliseconds<T>(pub T);
+
+impl<T> Serialize for Milliseconds<T>
+where T: Serialize
+{
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let _scope = MillisecondsScope::enter();
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Milliseconds<T>
+where T: Deserialize<'de>
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        let _scope = MillisecondsScope::enter();
+        T::deserialize(deserializer).map(Self)
+    }
+}
+
+impl Serialize for SrtTimestamp {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if IN_MILLISECONDS.with(Cell::get) {
+            milliseconds::serialize(self, serializer)
+        } else {
+            Self::serialize(self, serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SrtTimestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        if IN_MILLISECONDS.with(Cell::get) {
+            milliseconds::deserialize(deserializer)
+        } else {
+            Self::deserialize(deserializer)
+        }
+    }
+}
+
+impl Serialize for VttTimestamp {
+    fn serialize<S>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if IN_MILLISECONDS.with(Cell::get) {
+            milliseconds::serialize(self, serializer)
+        } else {
+            Self::serialize(self, serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VttTimestamp {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        if IN_MILLISECONDS.with(Cell::get) {
+            milliseconds::deserialize(deserializer)
+        } else {
+            Self::deserialize(deserializer)
+        }
+    }
+}
+
+/// The timestamp as the integer of the total milliseconds for `#[serde(with = "subtp::serde::milliseconds")]`.
+///
+/// The deserialized timestamp saturates at the maximum of the timestamp.
+pub mod milliseconds {
+    use std::time::Duration;
+
+    use ::serde::Deserialize;
+    use ::serde::Deserializer;
+    use ::serde::Serializer;
+
+    /// Serializes the timestamp as the total milliseconds.
+    pub fn serialize<T, S>(
+        timestamp: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<Duration>,
+        S: Serializer,
+    {
+        let duration: Duration = (*timestamp).into();
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    /// Deserializes the timestamp from the total milliseconds.
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<Duration>,
+        D: Deserializer<'de>,
+    {
+        let milliseconds = u64::deserialize(deserializer)?;
+        Ok(Duration::from_millis(milliseconds).into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::serde::Deserialize;
+    use ::serde::Serialize;
+
+    use super::Milliseconds;
+    use crate::srt::SrtTimestamp;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn webvtt() {
+        let text = r#"WEBVTT
+
+STYLE
+::cue { color: red; }
+
+REGION
+id:region_id
+width:40%
+regionanchor:0%,100%
+scroll:up
+
+NOTE A comment.
+
+cue_id
+00:01.000 --> 00:04.000 vertical:rl line:-1,start position:10%,line-left align:start region:region_id
+<v Roger>Hello <00:02.000><ruby>world<rt>w</rt></ruby>!
+"#;
+        let vtt = WebVtt::parse(text).unwrap();
+
+        let json = serde_json::to_value(&vtt).unwrap();
+        assert_eq!(
+            json["header"],
+            serde_json::json!({"description": null})
+        );
+        assert_eq!(
+            json["blocks"][0],
+            serde_json::json!({"style": {"style": "::cue { color: red; }\n"}})
+        );
+        assert_eq!(
+            json["blocks"][1]["region"],
+            serde_json::json!({
+                "id": "region_id",
+                "width": 40.0,
+                "lines": null,
+                "region_anchor": {"x": 0.0, "y": 100.0},
+                "viewport_anchor": null,
+                "scroll": "up",
+            })
+        );
+        assert_eq!(
+            json["blocks"][2],
+            serde_json::json!({"comment": {"side": "A comment."}})
+        );
+        assert_eq!(
+            json["blocks"][3]["cue"]["settings"],
+            serde_json::json!({
+                "vertical": "rl",
+                "line": {"line_number": [-1, "start"]},
+                "position": {"value": 10.0, "alignment": "line_left"},
+                "size": null,
+                "align": "start",
+                "region": "region_id",
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<WebVtt>(json).unwrap(),
+            vtt
+        );
+
+        let cue_text = match &vtt.blocks[3] {
+            | crate::vtt::VttBlock::Que(cue) => cue.parse_payload(),
+            | _ => unreachable!(),
+        };
+        let json = serde_json::to_value(&cue_text).unwrap();
+        assert_eq!(
+            json["nodes"][0]["span"]["kind"],
+            "voice"
+        );
+        assert_eq!(
+            json["nodes"][0]["span"]["children"][1]["timestamp"],
+            serde_json::json!({"hours": 0, "minutes": 0, "seconds": 2, "milliseconds": 0})
+        );
+        assert_eq!(
+            serde_json::from_value::<crate::vtt::VttCueText>(json).unwrap(),
+            cue_text
+        );
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Timestamps {
+        #[serde(with = "super::milliseconds")]
+        start: SrtTimestamp,
+        end: SrtTimestamp,
+    }
+
+    #[test]
+    fn milliseconds() {
+        let timestamps = Timestamps {
+            start: SrtTimestamp {
+                hours: 1,
+                milliseconds: 1,
+                ..Default::default()
+            },
+            end: SrtTimestamp {
+                seconds: 2,
+                ..Default::default()
+            },
+        };
+
+        let json = serde_json::to_string(&timestamps).unwrap();
+        assert_eq!(
+            json,
+            r#"{"start":3600001,"end":{"hours":0,"minutes":0,"seconds":2,"milliseconds":0}}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Timestamps>(&json).unwrap(),
+            timestamps
+        );
+
+        let saturated = serde_json::from_str::<Timestamps>(
+            r#"{"start":18446744073709551615,"end":{"hours":0,"minutes":0,"seconds":0,"milliseconds":0}}"#,
+        )
+        .unwrap();
+        assert_eq!(saturated.start, SrtTimestamp::MAX);
+        assert!(
+            serde_json::from_str::<Timestamps>(r#"{"start":-1,"end":0}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn milliseconds_wrapper() {
+        let text =
+            "WEBVTT\n\n00:01.000 --> 01:00:02.500\nHello <00:01.500>world!\n";
+        let vtt = WebVtt::parse(text).unwrap();
+
+        let json = serde_json::to_value(Milliseconds(&vtt)).unwrap();
+        assert_eq!(
+            json["blocks"][0]["cue"]["timings"],
+            serde_json::json!({"start": 1000, "end": 3602500})
+        );
+        assert_eq!(
+            serde_json::from_value::<Milliseconds<WebVtt>>(json)
+                .unwrap()
+                .0,
+            vtt
+        );
+
+        let cue_text = match &vtt.blocks[0] {
+            | crate::vtt::VttBlock::Que(cue) => cue.parse_payload(),
+            | _ => unreachable!(),
+        };
+        let json = serde_json::to_value(Milliseconds(&cue_text)).unwrap();
+        assert_eq!(json["nodes"][1]["timestamp"], 1500);
+
+        // The default shape is restored after the wrapper, even by an error.
+        assert!(
+            serde_json::from_str::<Milliseconds<SrtTimestamp>>(
+                r#"{"hours":0}"#
+            )
+            .is_err()
+        );
+        assert_eq!(
+            serde_json::to_value(SrtTimestamp::default()).unwrap(),
+            serde_json::json!({"hours": 0, "minutes": 0, "seconds": 0, "milliseconds": 0})
+        );
+    }
+}
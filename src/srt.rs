@@ -67,7 +67,22 @@ use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::time::Duration;
 
+use crate::convert::subrip_to_webvtt;
+use crate::convert::webvtt_to_subrip;
+use crate::format::Cue;
+use crate::format::SubtitleFormat;
+use crate::lenient;
+use crate::lenient::Lenient;
+use crate::lint::Lint;
+use crate::lint::LintCue;
+use crate::lossless::LosslessSubRip;
+use crate::render::render_blocks;
+use crate::render::RenderOptions;
 use crate::str_parser;
+use crate::timeline::Timeline;
+use crate::validate::Validate;
+use crate::vtt::WebVtt;
+use crate::ParseError;
 use crate::ParseResult;
 
 /// The SubRip Subtitle (`.srt`) format.
@@ -109,6 +124,7 @@ use crate::ParseResult;
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubRip {
     /// The collection of subtitles.
     pub subtitles: Vec<SrtSubtitle>,
@@ -134,7 +150,79 @@ impl SubRip {
     /// let srt = SubRip::parse(text).unwrap();
     /// ```
     pub fn parse(text: &str) -> ParseResult<Self> {
-        str_parser::srt(text).map_err(|err| err.into())
+        str_parser::srt(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Parses the SubRip Subtitle format leniently by repairing or skipping malformed blocks,
+    /// see [`subtp::lenient`](crate::lenient) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::srt::SubRip;
+    ///
+    /// let text = r#"1
+    /// 0:00:01.000 --> 0:00:02.000
+    /// Hello, world!
+    ///
+    /// 2
+    /// broken timings
+    /// This is skipped.
+    /// "#;
+    ///
+    /// let lenient = SubRip::parse_lenient(text);
+    /// assert_eq!(lenient.output.subtitles.len(), 1);
+    /// for warning in &lenient.warnings {
+    ///     println!("{}", warning);
+    /// }
+    /// ```
+    pub fn parse_lenient(text: &str) -> Lenient<Self> {
+        lenient::parse_subrip(text)
+    }
+
+    /// Parses the SubRip Subtitle format keeping the original formatting,
+    /// see [`subtp::lossless`](crate::lossless) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::srt::SubRip;
+    ///
+    /// let text = "1\r\n00:00:01,000-->00:00:02,000\r\nHello, world!  \r\n";
+    ///
+    /// let lossless = SubRip::parse_lossless(text).unwrap();
+    /// assert_eq!(lossless.render(), text);
+    /// ```
+    pub fn parse_lossless(text: &str) -> ParseResult<LosslessSubRip> {
+        LosslessSubRip::parse(text)
+    }
+
+    /// Parses the SubRip Subtitle format from bytes by detecting the encoding,
+    /// see [`subtp::encoding`](crate::encoding) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::encoding::WINDOWS_1252;
+    /// use subtp::srt::SubRip;
+    ///
+    /// let bytes = b"1\r\n00:00:01,000 --> 00:00:02,000\r\nC'est d\xE9j\xE0 l'\xE9t\xE9 \xE0 Gen\xE8ve.\r\n";
+    ///
+    /// let decoded = SubRip::parse_bytes(bytes).unwrap();
+    /// assert_eq!(decoded.encoding.encoding, WINDOWS_1252);
+    /// assert_eq!(decoded.output.subtitles[0].text, vec!["C'est déjà l'été à Genève."]);
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn parse_bytes(
+        bytes: &[u8]
+    ) -> ParseResult<crate::encoding::Decoded<Self>> {
+        crate::encoding::parse_bytes(bytes, None, Self::parse)
+    }
+
+    /// Parses the SubRip Subtitle format from bytes in the encoding.
+    #[cfg(feature = "encoding")]
+    pub fn parse_bytes_with_encoding(
+        bytes: &[u8],
+        encoding: &'static crate::encoding::Encoding,
+    ) -> ParseResult<crate::encoding::Decoded<Self>> {
+        crate::encoding::parse_bytes(bytes, Some(encoding), Self::parse)
     }
 
     /// Renders the text from the SubRip Subtitle format.
@@ -172,6 +260,63 @@ impl SubRip {
     pub fn render(&self) -> String {
         self.to_string()
     }
+
+    /// Renders the text from the SubRip Subtitle format by the options,
+    /// see [`subtp::render`](crate::render) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::render::LineEnding;
+    /// use subtp::render::RenderOptions;
+    /// use subtp::srt::SubRip;
+    ///
+    /// let srt = SubRip::parse("5\n00:00:01,000 --> 00:00:02,000\nHello, world!\n").unwrap();
+    ///
+    /// let options = RenderOptions {
+    ///     line_ending: LineEnding::CrLf,
+    ///     byte_order_mark: true,
+    ///     renumber: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(
+    ///     srt.render_with(&options),
+    ///     "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\nHello, world!\r\n"
+    /// );
+    /// ```
+    pub fn render_with(
+        &self,
+        options: &RenderOptions,
+    ) -> String {
+        render_blocks(
+            self.subtitles
+                .iter()
+                .enumerate()
+                .map(|(index, subtitle)| {
+                    if options.renumber {
+                        SrtSubtitle {
+                            sequence: index as u32 + 1,
+                            ..subtitle.clone()
+                        }
+                        .to_string()
+                    } else {
+                        subtitle.to_string()
+                    }
+                }),
+            options,
+        )
+    }
+
+    /// Writes the text from the SubRip Subtitle format by the options to the writer.
+    pub fn write_to(
+        &self,
+        mut writer: impl std::io::Write,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        writer.write_all(
+            self.render_with(options)
+                .as_bytes(),
+        )
+    }
 }
 
 impl Default for SubRip {
@@ -216,6 +361,117 @@ impl Iterator for SubRip {
     }
 }
 
+impl SubtitleFormat for SubRip {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues through the conversion to the WebVTT, see [`subtp::convert::subrip_to_webvtt`](subrip_to_webvtt).
+    fn cues(&self) -> Vec<Cue> {
+        subrip_to_webvtt(self, &Default::default())
+            .output
+            .cues()
+    }
+
+    /// Creates from the cues through the conversion from the WebVTT, see [`subtp::convert::webvtt_to_subrip`](webvtt_to_subrip).
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        webvtt_to_subrip(&WebVtt::from_cues(cues)).output
+    }
+}
+
+impl Timeline for SubRip {
+    fn for_each_timestamp(
+        &mut self,
+        f: &mut dyn FnMut(Duration) -> Duration,
+    ) {
+        for subtitle in &mut self.subtitles {
+            subtitle.start = f(subtitle.start.into()).into();
+            subtitle.end = f(subtitle.end.into()).into();
+        }
+    }
+
+    fn cue_start(
+        &self,
+        index: usize,
+    ) -> Option<Duration> {
+        self.subtitles
+            .get(index)
+            .map(|subtitle| subtitle.start.into())
+    }
+}
+
+impl Validate for SubRip {
+    fn cue_timings(&self) -> Vec<(Duration, Duration)> {
+        self.subtitles
+            .iter()
+            .map(|subtitle| (subtitle.start.into(), subtitle.end.into()))
+            .collect()
+    }
+
+    fn set_cue_timings(
+        &mut self,
+        timings: &[(Duration, Duration)],
+    ) {
+        for (subtitle, (start, end)) in self
+            .subtitles
+            .iter_mut()
+            .zip(timings)
+        {
+            subtitle.start = (*start).into();
+            subtitle.end = (*end).into();
+        }
+    }
+
+    fn sequences(&self) -> Option<Vec<u32>> {
+        Some(
+            self.subtitles
+                .iter()
+                .map(|subtitle| subtitle.sequence)
+                .collect(),
+        )
+    }
+
+    fn sort_cues(&mut self) {
+        self.subtitles
+            .sort_by_key(|subtitle| (subtitle.start, subtitle.end));
+    }
+
+    fn renumber(&mut self) {
+        for (i, subtitle) in self
+            .subtitles
+            .iter_mut()
+            .enumerate()
+        {
+            subtitle.sequence = i as u32 + 1;
+        }
+    }
+}
+
+impl Lint for SubRip {
+    /// Gets the cues with the text without `<...>` tags and `{\...}` overrides.
+    fn lint_cues(&self) -> Vec<LintCue> {
+        self.subtitles
+            .iter()
+            .map(|subtitle| {
+                LintCue {
+                    start: subtitle.start.into(),
+                    end: subtitle.end.into(),
+                    lines: subtitle
+                        .parse_text()
+                        .plain_text()
+                        .split('\n')
+                        .map(|line| line.trim().to_string())
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+}
+
 /// The subtitle entry.
 ///
 /// ## Example
@@ -268,6 +524,7 @@ impl Iterator for SubRip {
 /// };
 /// ```
 #[derive(Debug, Clone, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SrtSubtitle {
     /// The sequence number.
     pub sequence: u32,
@@ -281,6 +538,31 @@ pub struct SrtSubtitle {
     pub line_position: Option<LinePosition>,
 }
 
+impl SrtSubtitle {
+    /// Parses the text into the formatting tags and the alignment overrides.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::srt::SrtSubtitle;
+    ///
+    /// let subtitle = SrtSubtitle {
+    ///     text: vec![
+    ///         "<b>Hello,".to_string(),
+    ///         "world!</b>".to_string(),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let text = subtitle.parse_text();
+    ///
+    /// assert_eq!(text.plain_text(), "Hello,\nworld!");
+    /// assert_eq!(text.to_lines(), subtitle.text);
+    /// ```
+    pub fn parse_text(&self) -> SrtText {
+        SrtText::parse(&self.text.join("\n"))
+    }
+}
+
 impl PartialEq<Self> for SrtSubtitle {
     fn eq(
         &self,
@@ -328,12 +610,91 @@ impl Display for SrtSubtitle {
     ) -> std::fmt::Result {
         write!(
             f,
-            "{}\n{} --> {}\n{}\n",
-            self.sequence,
-            self.start,
-            self.end,
-            self.text.join("\n"),
-        )
+            "{}\n{} --> {}",
+            self.sequence, self.start, self.end,
+        )?;
+
+        if let Some(line_position) = &self.line_position {
+            write!(f, " {}", line_position)?;
+        }
+
+        write!(f, "\n{}\n", self.text.join("\n"))
+    }
+}
+
+/// The SubRip Subtitle borrowing the text from the input.
+///
+/// This avoids allocating a string for each line of text,
+/// and is converted to [`SubRip`] by [`SubRipRef::into_owned`].
+///
+/// ## Example
+/// ```
+/// use subtp::srt::SubRipRef;
+///
+/// let text = r#"1
+/// 00:00:01,000 --> 00:00:02,000
+/// Hello, world!
+/// "#;
+///
+/// let srt = SubRipRef::parse(text).unwrap();
+/// assert_eq!(srt.subtitles[0].text, vec!["Hello, world!"]);
+///
+/// let owned = srt.into_owned();
+/// assert_eq!(owned.subtitles[0].text, vec!["Hello, world!".to_string()]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SubRipRef<'a> {
+    /// The collection of subtitles.
+    pub subtitles: Vec<SrtSubtitleRef<'a>>,
+}
+
+impl<'a> SubRipRef<'a> {
+    /// Parses the SubRip Subtitle format from the given text borrowing the text.
+    pub fn parse(text: &'a str) -> ParseResult<Self> {
+        str_parser::srt_ref(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Converts into the owned [`SubRip`].
+    pub fn into_owned(self) -> SubRip {
+        SubRip {
+            subtitles: self
+                .subtitles
+                .into_iter()
+                .map(SrtSubtitleRef::into_owned)
+                .collect(),
+        }
+    }
+}
+
+/// The subtitle entry borrowing the text from the input.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SrtSubtitleRef<'a> {
+    /// The sequence number.
+    pub sequence: u32,
+    /// The start timestamp.
+    pub start: SrtTimestamp,
+    /// The end timestamp.
+    pub end: SrtTimestamp,
+    /// The subtitle text.
+    pub text: Vec<&'a str>,
+    /// The unofficial line position.
+    pub line_position: Option<LinePosition>,
+}
+
+impl<'a> SrtSubtitleRef<'a> {
+    /// Converts into the owned [`SrtSubtitle`].
+    pub fn into_owned(self) -> SrtSubtitle {
+        SrtSubtitle {
+            sequence: self.sequence,
+            start: self.start,
+            end: self.end,
+            text: self
+                .text
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            line_position: self.line_position,
+        }
     }
 }
 
@@ -372,9 +733,11 @@ impl Display for SrtSubtitle {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self"))]
 pub struct SrtTimestamp {
-    /// The hours.
-    pub hours: u8,
+    /// The hours of two or more digits.
+    pub hours: u32,
     /// The minutes.
     pub minutes: u8,
     /// The seconds.
@@ -407,21 +770,43 @@ impl Display for SrtTimestamp {
     }
 }
 
-impl From<Duration> for SrtTimestamp {
-    fn from(duration: Duration) -> Self {
+impl SrtTimestamp {
+    /// The maximum timestamp.
+    pub const MAX: Self = Self {
+        hours: u32::MAX,
+        minutes: 59,
+        seconds: 59,
+        milliseconds: 999,
+    };
+
+    /// Converts from the duration truncated to milliseconds, or `None` if the hours overflow.
+    ///
+    /// ## Example
+    /// ```
+    /// use std::time::Duration;
+    /// use subtp::srt::SrtTimestamp;
+    ///
+    /// let timestamp = SrtTimestamp::checked_from_duration(Duration::from_secs(100 * 3600)).unwrap();
+    /// assert_eq!(timestamp.to_string(), "100:00:00,000");
+    ///
+    /// assert_eq!(SrtTimestamp::checked_from_duration(Duration::MAX), None);
+    /// ```
+    pub fn checked_from_duration(duration: Duration) -> Option<Self> {
         let seconds = duration.as_secs();
-        let milliseconds = duration.subsec_millis() as u16;
 
-        let hours = (seconds / 3600) as u8;
-        let minutes = ((seconds % 3600) / 60) as u8;
-        let seconds = (seconds % 60) as u8;
+        Some(Self {
+            hours: u32::try_from(seconds / 3600).ok()?,
+            minutes: ((seconds % 3600) / 60) as u8,
+            seconds: (seconds % 60) as u8,
+            milliseconds: duration.subsec_millis() as u16,
+        })
+    }
+}
 
-        Self {
-            hours,
-            minutes,
-            seconds,
-            milliseconds,
-        }
+/// Converts from the duration truncated to milliseconds, saturating at [`SrtTimestamp::MAX`].
+impl From<Duration> for SrtTimestamp {
+    fn from(duration: Duration) -> Self {
+        Self::checked_from_duration(duration).unwrap_or(Self::MAX)
     }
 }
 
@@ -438,6 +823,7 @@ impl Into<Duration> for SrtTimestamp {
 
 /// Unofficial line position settings.
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LinePosition {
     /// X1 of the line position.
     pub x1: u32,
@@ -473,6 +859,697 @@ impl Display for LinePosition {
     }
 }
 
+/// The text of a subtitle parsed into the formatting tags of `<i>`, `<b>`, `<u>` and `<font>`,
+/// and the alignment overrides of `{\anN}`.
+///
+/// Parses by [`SrtText::parse`](SrtText::parse) or [`SrtSubtitle::parse_text`](SrtSubtitle::parse_text).
+/// The tag names are case-insensitive and rendered in lowercase with the double-quoted attributes of the font,
+/// and other tags and overrides are kept as written.
+///
+/// ## Example
+/// ```
+/// use subtp::srt::SrtAlignment;
+/// use subtp::srt::SrtNode;
+/// use subtp::srt::SrtSpan;
+/// use subtp::srt::SrtSpanKind;
+/// use subtp::srt::SrtText;
+///
+/// let text = SrtText::parse("{\\an8}<i>Hello</i>, <font color=\"red\">world</font>!");
+///
+/// assert_eq!(
+///     text,
+///     SrtText {
+///         nodes: vec![
+///             SrtNode::Alignment(SrtAlignment::TopCenter),
+///             SrtNode::Span(SrtSpan {
+///                 kind: SrtSpanKind::Italic,
+///                 children: vec![SrtNode::Text("Hello".to_string())],
+///                 ..Default::default()
+///             }),
+///             SrtNode::Text(", ".to_string()),
+///             SrtNode::Span(SrtSpan {
+///                 kind: SrtSpanKind::Font,
+///                 color: Some("red".to_string()),
+///                 children: vec![SrtNode::Text("world".to_string())],
+///                 ..Default::default()
+///             }),
+///             SrtNode::Text("!".to_string()),
+///         ],
+///     }
+/// );
+///
+/// assert_eq!(text.plain_text(), "Hello, world!");
+/// assert_eq!(text.alignment(), Some(SrtAlignment::TopCenter));
+/// assert_eq!(
+///     text.render(),
+///     "{\\an8}<i>Hello</i>, <font color=\"red\">world</font>!"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SrtText {
+    /// The root nodes.
+    pub nodes: Vec<SrtNode>,
+}
+
+impl SrtText {
+    /// Parses the text from the given text.
+    ///
+    /// End tags closing an outer span also close the inner spans left open,
+    /// end tags that do not close any span are kept as markup,
+    /// and spans left open are closed at the end of the text.
+    pub fn parse(text: &str) -> Self {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<SrtSpan> = Vec::new();
+        let mut plain = String::new();
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            let line = rest
+                .find('\n')
+                .map_or(rest, |end| &rest[..end]);
+            let end = match c {
+                | '<' => line.find('>'),
+                | '{' if line.starts_with("{\\") => line.find('}'),
+                | _ => None,
+            };
+            let Some(end) = end else {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            };
+
+            let markup = &rest[..=end];
+            rest = &rest[end + 1..];
+            if !plain.is_empty() {
+                push_srt_node(
+                    &mut nodes,
+                    &mut stack,
+                    SrtNode::Text(std::mem::take(&mut plain)),
+                );
+            }
+
+            if c == '{' {
+                let node = markup
+                    .strip_prefix("{\\an")
+                    .and_then(|n| n.strip_suffix('}'))
+                    .and_then(|n| n.parse::<u8>().ok())
+                    .and_then(SrtAlignment::from_number)
+                    .map_or_else(
+                        || SrtNode::Markup(markup.to_string()),
+                        SrtNode::Alignment,
+                    );
+                push_srt_node(&mut nodes, &mut stack, node);
+                continue;
+            }
+
+            let (closing, name, attributes) = split_tag(markup);
+            let kind = SrtSpanKind::from_tag(&name.to_ascii_lowercase());
+
+            match (kind, closing) {
+                | (Some(kind), false) => {
+                    let mut span = SrtSpan {
+                        kind,
+                        closed: false,
+                        start_tag: Some(markup.to_string()),
+                        ..Default::default()
+                    };
+                    if kind == SrtSpanKind::Font {
+                        (span.color, span.face, span.size) =
+                            font_attributes(attributes);
+                    }
+                    if span.escaped_start_tag() == markup {
+                        span.start_tag = None;
+                    }
+                    stack.push(span);
+                },
+                | (Some(kind), true)
+                    if stack
+                        .iter()
+                        .any(|span| span.kind == kind) =>
+                {
+                    while stack
+                        .last()
+                        .map_or(false, |span| span.kind != kind)
+                    {
+                        close_srt_span(&mut nodes, &mut stack, None);
+                    }
+                    close_srt_span(&mut nodes, &mut stack, Some(markup));
+                },
+                | _ => {
+                    push_srt_node(
+                        &mut nodes,
+                        &mut stack,
+                        SrtNode::Markup(markup.to_string()),
+                    );
+                },
+            }
+        }
+
+        if !plain.is_empty() {
+            push_srt_node(
+                &mut nodes,
+                &mut stack,
+                SrtNode::Text(plain),
+            );
+        }
+        while !stack.is_empty() {
+            close_srt_span(&mut nodes, &mut stack, None);
+        }
+
+        Self {
+            nodes,
+        }
+    }
+
+    /// Renders the text.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders the text into lines of the subtitle.
+    pub fn to_lines(&self) -> Vec<String> {
+        self.render()
+            .split('\n')
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// Gets the plain text without tags and overrides.
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.nodes {
+            node.write_plain_text(&mut text);
+        }
+        text
+    }
+
+    /// Gets the alignment of the last `{\anN}` override.
+    pub fn alignment(&self) -> Option<SrtAlignment> {
+        fn find(nodes: &[SrtNode]) -> Option<SrtAlignment> {
+            nodes
+                .iter()
+                .rev()
+                .find_map(|node| {
+                    match node {
+                        | SrtNode::Alignment(alignment) => Some(*alignment),
+                        | SrtNode::Span(span) => find(&span.children),
+                        | _ => None,
+                    }
+                })
+        }
+
+        find(&self.nodes)
+    }
+}
+
+impl Display for SrtText {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        for node in &self.nodes {
+            write!(f, "{}", node)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Pushes the node into the current span or into the root.
+fn push_srt_node(
+    nodes: &mut Vec<SrtNode>,
+    stack: &mut [SrtSpan],
+    node: SrtNode,
+) {
+    if let Some(span) = stack.last_mut() {
+        span.children.push(node);
+    } else {
+        nodes.push(node);
+    }
+}
+
+/// Closes the current span by the end tag, or left open, and pushes it into its parent.
+fn close_srt_span(
+    nodes: &mut Vec<SrtNode>,
+    stack: &mut Vec<SrtSpan>,
+    end_tag: Option<&str>,
+) {
+    if let Some(mut span) = stack.pop() {
+        span.closed = end_tag.is_some();
+        span.end_tag = end_tag
+            .filter(|end_tag| *end_tag != span.escaped_end_tag())
+            .map(|end_tag| end_tag.to_string());
+        push_srt_node(nodes, stack, SrtNode::Span(span));
+    }
+}
+
+/// Splits the tag such as `<font color="red">` into whether it is an end tag, the name and the attributes.
+fn split_tag(tag: &str) -> (bool, &str, &str) {
+    let inner = tag[1..tag.len() - 1].trim_start();
+    let (closing, inner) = match inner.strip_prefix('/') {
+        | Some(inner) => (true, inner.trim_start()),
+        | None => (false, inner),
+    };
+    let name_end = inner
+        .find(char::is_whitespace)
+        .unwrap_or(inner.len());
+
+    (
+        closing,
+        &inner[..name_end],
+        &inner[name_end..],
+    )
+}
+
+/// Parses the attributes of a tag such as `color="red" face='Arial'`.
+fn parse_tag_attributes(text: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut rest = text.trim_start();
+
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_ascii_lowercase();
+        rest = rest[key_end..].trim_start();
+
+        let value = if let Some(value) = rest.strip_prefix('=') {
+            let value = value.trim_start();
+            let (value, remaining) = match value.chars().next() {
+                | Some(quote @ ('"' | '\'')) => {
+                    let value = &value[1..];
+                    let end = value
+                        .find(quote)
+                        .unwrap_or(value.len());
+                    (
+                        &value[..end],
+                        value
+                            .get(end + 1..)
+                            .unwrap_or(""),
+                    )
+                },
+                | _ => {
+                    let end = value
+                        .find(char::is_whitespace)
+                        .unwrap_or(value.len());
+                    (&value[..end], &value[end..])
+                },
+            };
+            rest = remaining.trim_start();
+            value.to_string()
+        } else {
+            String::new()
+        };
+
+        if !key.is_empty() {
+            attributes.push((key, value));
+        }
+    }
+
+    attributes
+}
+
+/// Gets the color, the face and the size from the attributes of a font tag.
+fn font_attributes(
+    text: &str
+) -> (
+    Option<String>,
+    Option<String>,
+    Option<String>,
+) {
+    let (mut color, mut face, mut size) = (None, None, None);
+    for (key, value) in parse_tag_attributes(text) {
+        match key.as_str() {
+            | "color" => color = Some(value),
+            | "face" => face = Some(value),
+            | "size" => size = Some(value),
+            | _ => {},
+        }
+    }
+    (color, face, size)
+}
+
+/// The node of the text of a subtitle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SrtNode {
+    /// The text as written.
+    Text(String),
+    /// The alignment override of `{\anN}`.
+    Alignment(SrtAlignment),
+    /// The span with tags.
+    Span(SrtSpan),
+    /// The unsupported tag or override, or the end tag not closing any span, as written.
+    Markup(String),
+}
+
+impl SrtNode {
+    fn write_plain_text(
+        &self,
+        text: &mut String,
+    ) {
+        match self {
+            | Self::Text(t) => {
+                text.push_str(t);
+            },
+            | Self::Span(span) => {
+                for child in &span.children {
+                    child.write_plain_text(text);
+                }
+            },
+            | Self::Alignment(_) | Self::Markup(_) => {},
+        }
+    }
+}
+
+impl Default for SrtNode {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl Display for SrtNode {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Text(text) => write!(f, "{}", text),
+            | Self::Alignment(alignment) => write!(f, "{}", alignment),
+            | Self::Span(span) => write!(f, "{}", span),
+            | Self::Markup(markup) => write!(f, "{}", markup),
+        }
+    }
+}
+
+/// The span of the text of a subtitle.
+///
+/// ## Example
+/// ```
+/// use subtp::srt::SrtNode;
+/// use subtp::srt::SrtSpan;
+/// use subtp::srt::SrtSpanKind;
+///
+/// let span = SrtSpan {
+///     kind: SrtSpanKind::Font,
+///     color: Some("#ff8000".to_string()),
+///     face: Some("Arial".to_string()),
+///     children: vec![SrtNode::Text("Hello".to_string())],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     span.to_string(),
+///     "<font color=\"#ff8000\" face=\"Arial\">Hello</font>".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SrtSpan {
+    /// The kind of span.
+    pub kind: SrtSpanKind,
+    /// The color of the font span.
+    pub color: Option<String>,
+    /// The face of the font span.
+    pub face: Option<String>,
+    /// The size of the font span.
+    pub size: Option<String>,
+    /// The child nodes.
+    pub children: Vec<SrtNode>,
+    /// Whether the span is closed by an end tag.
+    pub closed: bool,
+    /// The start tag as written if different from the escaped form, e.g. `<FONT color='red' foo=bar>`,
+    /// rendered while the kind, the color, the face and the size are not edited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start_tag: Option<String>,
+    /// The end tag as written if different from the escaped form, e.g. `</I>`,
+    /// rendered while the kind is not edited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end_tag: Option<String>,
+}
+
+impl Default for SrtSpan {
+    fn default() -> Self {
+        Self {
+            kind: SrtSpanKind::default(),
+            color: None,
+            face: None,
+            size: None,
+            children: vec![],
+            closed: true,
+            start_tag: None,
+            end_tag: None,
+        }
+    }
+}
+
+impl SrtSpan {
+    /// Renders the start tag with the values of the attributes quoted,
+    /// followed by the attributes other than the color, the face and the size as written.
+    fn escaped_start_tag(&self) -> String {
+        let others = parse_tag_attributes(
+            self.start_tag
+                .as_deref()
+                .map_or("", |start_tag| split_tag(start_tag).2),
+        )
+        .into_iter()
+        .filter(|(key, _)| !matches!(key.as_str(), "color" | "face" | "size"))
+        .map(|(key, value)| (key, Some(value)));
+
+        let mut start_tag = format!("<{}", self.kind);
+        for (key, value) in [
+            ("color".to_string(), self.color.clone()),
+            ("face".to_string(), self.face.clone()),
+            ("size".to_string(), self.size.clone()),
+        ]
+        .into_iter()
+        .chain(others)
+        {
+            if let Some(value) = value {
+                start_tag.push_str(&format!(
+                    " {}={}",
+                    key,
+                    quote_attribute(&value)
+                ));
+            }
+        }
+        start_tag.push('>');
+        start_tag
+    }
+
+    /// Renders the end tag in the escaped form.
+    fn escaped_end_tag(&self) -> String {
+        format!("</{}>", self.kind)
+    }
+
+    /// Checks whether the start tag as written stands for the kind, the color, the face and the size.
+    fn matches_start_tag(
+        &self,
+        start_tag: &str,
+    ) -> bool {
+        let (closing, name, attributes) = split_tag(start_tag);
+        let (color, face, size) = match self.kind {
+            | SrtSpanKind::Font => font_attributes(attributes),
+            | _ => (None, None, None),
+        };
+
+        !closing
+            && SrtSpanKind::from_tag(&name.to_ascii_lowercase())
+                == Some(self.kind)
+            && (&color, &face, &size) == (&self.color, &self.face, &self.size)
+    }
+
+    /// Checks whether the end tag as written stands for the kind.
+    fn matches_end_tag(
+        &self,
+        end_tag: &str,
+    ) -> bool {
+        let (closing, name, _) = split_tag(end_tag);
+
+        closing
+            && SrtSpanKind::from_tag(&name.to_ascii_lowercase())
+                == Some(self.kind)
+    }
+}
+
+impl Display for SrtSpan {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        match &self.start_tag {
+            | Some(start_tag) if self.matches_start_tag(start_tag) => {
+                write!(f, "{}", start_tag)?
+            },
+            | _ => write!(f, "{}", self.escaped_start_tag())?,
+        }
+
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+
+        if self.closed {
+            match &self.end_tag {
+                | Some(end_tag) if self.matches_end_tag(end_tag) => {
+                    write!(f, "{}", end_tag)?
+                },
+                | _ => write!(f, "{}", self.escaped_end_tag())?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quotes the value of an attribute by `"`, or by `'` if the value contains `"`.
+fn quote_attribute(value: &str) -> String {
+    if !value.contains('"') {
+        format!("\"{}\"", value)
+    } else if !value.contains('\'') {
+        format!("'{}'", value)
+    } else {
+        format!("\"{}\"", value.replace('"', "&quot;"))
+    }
+}
+
+/// The kind of span in the text of a subtitle.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SrtSpanKind {
+    /// The italics span `<i>`.
+    Italic,
+    /// The bold span `<b>`.
+    Bold,
+    /// The underline span `<u>`.
+    Underline,
+    /// The font span `<font>`.
+    Font,
+}
+
+impl SrtSpanKind {
+    /// Gets the kind from the tag name in lowercase.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            | "i" => Some(Self::Italic),
+            | "b" => Some(Self::Bold),
+            | "u" => Some(Self::Underline),
+            | "font" => Some(Self::Font),
+            | _ => None,
+        }
+    }
+
+    /// Gets the tag name.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            | Self::Italic => "i",
+            | Self::Bold => "b",
+            | Self::Underline => "u",
+            | Self::Font => "font",
+        }
+    }
+}
+
+impl Default for SrtSpanKind {
+    fn default() -> Self {
+        Self::Italic
+    }
+}
+
+impl Display for SrtSpanKind {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
+/// The alignment of the `{\anN}` override by the numeric keypad.
+///
+/// ## Example
+/// ```
+/// use subtp::srt::SrtAlignment;
+///
+/// let alignment = SrtAlignment::from_number(7).unwrap();
+///
+/// assert_eq!(alignment, SrtAlignment::TopLeft);
+/// assert_eq!(alignment.to_string(), "{\\an7}".to_string());
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SrtAlignment {
+    /// `{\an1}`.
+    BottomLeft,
+    /// `{\an2}`.
+    BottomCenter,
+    /// `{\an3}`.
+    BottomRight,
+    /// `{\an4}`.
+    MiddleLeft,
+    /// `{\an5}`.
+    MiddleCenter,
+    /// `{\an6}`.
+    MiddleRight,
+    /// `{\an7}`.
+    TopLeft,
+    /// `{\an8}`.
+    TopCenter,
+    /// `{\an9}`.
+    TopRight,
+}
+
+impl SrtAlignment {
+    /// Gets the alignment from the number from 1 to 9.
+    pub fn from_number(number: u8) -> Option<Self> {
+        match number {
+            | 1 => Some(Self::BottomLeft),
+            | 2 => Some(Self::BottomCenter),
+            | 3 => Some(Self::BottomRight),
+            | 4 => Some(Self::MiddleLeft),
+            | 5 => Some(Self::MiddleCenter),
+            | 6 => Some(Self::MiddleRight),
+            | 7 => Some(Self::TopLeft),
+            | 8 => Some(Self::TopCenter),
+            | 9 => Some(Self::TopRight),
+            | _ => None,
+        }
+    }
+
+    /// Gets the number from 1 to 9.
+    pub fn number(&self) -> u8 {
+        match self {
+            | Self::BottomLeft => 1,
+            | Self::BottomCenter => 2,
+            | Self::BottomRight => 3,
+            | Self::MiddleLeft => 4,
+            | Self::MiddleCenter => 5,
+            | Self::MiddleRight => 6,
+            | Self::TopLeft => 7,
+            | Self::TopCenter => 8,
+            | Self::TopRight => 9,
+        }
+    }
+}
+
+impl Default for SrtAlignment {
+    fn default() -> Self {
+        Self::BottomCenter
+    }
+}
+
+impl Display for SrtAlignment {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{{\\an{}}}", self.number())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -745,6 +1822,32 @@ This is a test.
         let displayed = format!("{}", subtitle);
         let expected = "1\n00:00:01,000 --> 00:00:02,000\nHello, world!\nThis is the test.\n";
         assert_eq!(displayed, expected);
+
+        let subtitle = SrtSubtitle {
+            sequence: 1,
+            start: SrtTimestamp {
+                seconds: 1,
+                ..Default::default()
+            },
+            end: SrtTimestamp {
+                seconds: 2,
+                ..Default::default()
+            },
+            text: vec!["Hello, world!".to_string()],
+            line_position: Some(LinePosition {
+                x1: 10,
+                x2: 20,
+                y1: 30,
+                y2: 40,
+            }),
+        };
+        let displayed = format!("{}", subtitle);
+        let expected = "1\n00:00:01,000 --> 00:00:02,000 X1:10 X2:20 Y1:30 Y2:40\nHello, world!\n";
+        assert_eq!(displayed, expected);
+        assert_eq!(
+            SubRip::parse(&displayed).unwrap().subtitles[0].line_position,
+            subtitle.line_position
+        );
     }
 
     #[test]
@@ -836,6 +1939,15 @@ This is a test.
                 milliseconds: 500,
             }
         );
+
+        let duration = Duration::from_secs(360_000);
+        let timestamp: SrtTimestamp = duration.into();
+        assert_eq!(timestamp.hours, 100);
+        assert_eq!(Into::<Duration>::into(timestamp), duration);
+
+        // Saturates without wrapping.
+        let timestamp: SrtTimestamp = Duration::MAX.into();
+        assert_eq!(timestamp, SrtTimestamp::MAX);
     }
 
     #[test]
@@ -923,4 +2035,161 @@ This is a test.
         };
         assert!(timestamp1 < timestamp2);
     }
+
+    #[test]
+    fn subtitle_format() {
+        let srt = SubRip::parse(
+            "1\n00:00:01,000 --> 00:00:02,000\n{\\an8}<b>Hello</b>,\nworld!\n",
+        )
+        .unwrap();
+        let cues = srt.cues();
+
+        assert_eq!(cues.len(), 1);
+        assert_eq!(
+            cues[0].identifier,
+            Some("1".to_string())
+        );
+        assert_eq!(cues[0].start, Duration::from_secs(1));
+        assert_eq!(cues[0].end, Duration::from_secs(2));
+        assert_eq!(cues[0].plain_text(), "Hello,\nworld!");
+        assert!(cues[0].spans[0].bold);
+        assert_eq!(
+            cues[0]
+                .position
+                .and_then(|position| position.line),
+            Some(crate::format::CueLine::Number(0))
+        );
+
+        assert_eq!(SubRip::from_cues(cues), srt);
+        assert_eq!(
+            SubRip::from_cues(srt.cues()).render(),
+            srt.render()
+        );
+    }
+
+    #[test]
+    fn parse_ref() {
+        let text = "1\n00:00:01,000 --> 00:00:02,000 X1:1 X2:2 Y1:3 Y2:4\n  Hello,  \nworld!\n\n2\n00:00:03,000 --> 00:00:04,000\nBye.\n";
+        let srt = SubRipRef::parse(text).unwrap();
+
+        assert_eq!(srt.subtitles.len(), 2);
+        assert_eq!(
+            srt.subtitles[0].text,
+            vec!["Hello,", "world!"]
+        );
+        assert!(text
+            .as_bytes()
+            .as_ptr_range()
+            .contains(&srt.subtitles[0].text[1].as_ptr()));
+        assert_eq!(
+            srt.subtitles[0].line_position,
+            Some(LinePosition {
+                x1: 1,
+                x2: 2,
+                y1: 3,
+                y2: 4,
+            })
+        );
+        assert_eq!(
+            srt.clone().into_owned(),
+            SubRip::parse(text).unwrap()
+        );
+        assert_eq!(
+            srt.into_owned().render(),
+            SubRip::parse(text).unwrap().render()
+        );
+
+        let invalid = "1\n00:00:01,000 -> 00:00:02,000\nHello\n";
+        assert_eq!(
+            SubRipRef::parse(invalid).unwrap_err(),
+            SubRip::parse(invalid).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn text() {
+        let text = SrtText::parse(
+            "{\\an8}<I>Hello</I>, <font color=red face='Arial'>world</font>!",
+        );
+        assert_eq!(text.plain_text(), "Hello, world!");
+        assert_eq!(
+            text.alignment(),
+            Some(SrtAlignment::TopCenter)
+        );
+        assert_eq!(
+            text.render(),
+            "{\\an8}<I>Hello</I>, <font color=red face='Arial'>world</font>!"
+        );
+
+        // Tags are rendered as written until the kind is edited.
+        let mut text = SrtText::parse("<I>a</I> <B >b</ b> <U>c");
+        assert_eq!(
+            text.render(),
+            "<I>a</I> <B >b</ b> <U>c"
+        );
+        let SrtNode::Span(span) = &mut text.nodes[0] else {
+            unreachable!()
+        };
+        span.kind = SrtSpanKind::Bold;
+        assert_eq!(
+            text.render(),
+            "<b>a</b> <B >b</ b> <U>c"
+        );
+
+        // Attributes are rendered as written until edited.
+        let mut text = SrtText::parse(
+            "<font color='a\"b' foo=bar>x</font><font size=\"1\">y</font>",
+        );
+        assert_eq!(
+            text.render(),
+            "<font color='a\"b' foo=bar>x</font><font size=\"1\">y</font>"
+        );
+        let SrtNode::Span(span) = &mut text.nodes[0] else {
+            unreachable!()
+        };
+        assert_eq!(span.color, Some("a\"b".to_string()));
+        span.face = Some("Arial".to_string());
+        let SrtNode::Span(span) = &mut text.nodes[1] else {
+            unreachable!()
+        };
+        span.size = Some("it's \"2\"".to_string());
+        assert_eq!(
+            text.render(),
+            "<font color='a\"b' face=\"Arial\" foo=\"bar\">x</font><font size=\"it's &quot;2&quot;\">y</font>"
+        );
+
+        let text = SrtText::parse("<b><i>Misnested</b> text</i> <u>unclosed\n1 < 2 {3} {\\pos(1,2)}");
+        assert_eq!(
+            text.nodes[0],
+            SrtNode::Span(SrtSpan {
+                kind: SrtSpanKind::Bold,
+                children: vec![SrtNode::Span(SrtSpan {
+                    kind: SrtSpanKind::Italic,
+                    children: vec![SrtNode::Text("Misnested".to_string())],
+                    closed: false,
+                    ..Default::default()
+                })],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            text.nodes[2],
+            SrtNode::Markup("</i>".to_string())
+        );
+        assert_eq!(
+            text.plain_text(),
+            "Misnested text unclosed\n1 < 2 {3} "
+        );
+        assert_eq!(text.alignment(), None);
+        assert_eq!(
+            text.to_lines(),
+            vec![
+                "<b><i>Misnested</b> text</i> <u>unclosed",
+                "1 < 2 {3} {\\pos(1,2)}"
+            ]
+        );
+
+        let text = SrtText::parse("a <b\n> c");
+        assert_eq!(text.plain_text(), "a <b\n> c");
+    }
 }
@@ -1,13 +1,19 @@
 //! A parser implementation for the SubRip Subtitle format.
 
+pub(crate) use srt_parser::line_position;
 pub(crate) use srt_parser::srt;
+pub(crate) use srt_parser::srt_ref;
+pub(crate) use srt_parser::subtitle;
+pub(crate) use srt_parser::timestamp;
 
 peg::parser! {
     /// The parser for SubRip Subtitle format.
     grammar srt_parser() for str {
         use crate::srt::SrtTimestamp;
         use crate::srt::SubRip;
+        use crate::srt::SubRipRef;
         use crate::srt::SrtSubtitle;
+        use crate::srt::SrtSubtitleRef;
         use crate::srt::LinePosition;
 
         /// Whitespace.
@@ -31,6 +37,12 @@ peg::parser! {
                 n.parse().or(Err("two-digit number"))
             }
 
+        /// Hours of two or more digits.
+        rule hours() -> u32
+            = n:$(['0'..='9']*<2,>) {?
+                n.parse().or(Err("hours in u32"))
+            }
+
         /// Three-digit number.
         rule three_number() -> u16
             = n:$(['0'..='9']['0'..='9']['0'..='9']) {?
@@ -38,18 +50,18 @@ peg::parser! {
             }
 
         /// Multiple lines block of text.
-        rule multiline() -> Vec<String>
+        rule multiline() -> Vec<&'input str>
             = !(whitespace() / newline()) lines:$(!(whitespace()+ newline()) (!newline() [_])+ newline()) ++ ()
             {
                 lines
                     .iter()
-                    .map(|l| l.to_string().trim().to_string())
+                    .map(|l| l.trim())
                     .collect()
             }
 
         /// Timestamp.
         pub(crate) rule timestamp() -> SrtTimestamp
-            = hours:two_number() ":" minutes:two_number() ":" seconds:two_number() "," milliseconds:three_number()
+            = hours:hours() ":" minutes:two_number() ":" seconds:two_number() "," milliseconds:three_number()
             {
                 SrtTimestamp {
                     hours,
@@ -75,14 +87,18 @@ peg::parser! {
 
         /// Single subtitle entry.
         pub(crate) rule subtitle() -> SrtSubtitle
+            = subtitle:subtitle_ref() { subtitle.into_owned() }
+
+        /// Single subtitle entry borrowing the text.
+        rule subtitle_ref() -> SrtSubtitleRef<'input>
             = subtitle_with_line_position() / subtitle_without_line_position()
 
-        rule subtitle_without_line_position() -> SrtSubtitle
+        rule subtitle_without_line_position() -> SrtSubtitleRef<'input>
             = sequence:number() separator()
                 start:timestamp() separator()* "-->" separator()* end:timestamp() separator()
                 text:multiline()
             {
-                SrtSubtitle {
+                SrtSubtitleRef {
                     sequence,
                     start,
                     end,
@@ -91,12 +107,12 @@ peg::parser! {
                 }
             }
 
-        rule subtitle_with_line_position() -> SrtSubtitle
+        rule subtitle_with_line_position() -> SrtSubtitleRef<'input>
             = sequence:number() separator()
                 start:timestamp() separator()* "-->" separator()* end:timestamp() separator()+ line_position:line_position() separator()
                 text:multiline()
             {
-                SrtSubtitle {
+                SrtSubtitleRef {
                     sequence,
                     start,
                     end,
@@ -107,11 +123,15 @@ peg::parser! {
 
         /// The entire SRT.
         pub(crate) rule srt() -> SubRip
-            = (whitespace() / newline())*
-                subtitles:subtitle() ** (newline()+)
+            = srt:srt_ref() { srt.into_owned() }
+
+        /// The entire SRT borrowing the text.
+        pub(crate) rule srt_ref() -> SubRipRef<'input>
+            = "\u{feff}"? (whitespace() / newline())*
+                subtitles:subtitle_ref() ** (newline()+)
                 (whitespace() / newline())*
             {
-                SubRip { subtitles, }
+                SubRipRef { subtitles, }
             }
     }
 }
@@ -169,8 +189,26 @@ mod test {
             }
         );
 
+        // Hours of two or more digits.
+        assert_eq!(
+            srt_parser::timestamp("123:45:06,789").unwrap(),
+            SrtTimestamp {
+                hours: 123,
+                minutes: 45,
+                seconds: 6,
+                milliseconds: 789,
+            }
+        );
+        assert_eq!(
+            srt_parser::timestamp("4294967295:00:00,000")
+                .unwrap()
+                .hours,
+            u32::MAX
+        );
+        assert!(srt_parser::timestamp("4294967296:00:00,000").is_err());
+
         // Invalid digits.
-        assert!(srt_parser::timestamp("000:00:00,000").is_err());
+        assert!(srt_parser::timestamp("0:00:00,000").is_err());
         assert!(srt_parser::timestamp("00:000:00,000").is_err());
         assert!(srt_parser::timestamp("00:00:000,000").is_err());
         assert!(srt_parser::timestamp("00:00:00,0000").is_err());
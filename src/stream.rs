@@ -0,0 +1,439 @@
+//! Streaming parsers of the SubRip Subtitle (`.srt`) and the WebVTT (`.vtt`) formats.
+//!
+//! The readers read the input from any [`BufRead`] block by block, where blocks are separated by blank lines,
+//! and yield the parsed blocks one at a time, so that the memory is bounded by the size of a block.
+//! Each block is parsed by the same grammar as [`SubRip::parse`](`crate::srt::SubRip::parse`)
+//! and [`WebVtt::parse`](`crate::vtt::WebVtt::parse`),
+//! and an invalid block is reported as an error without stopping the following blocks.
+//!
+//! Lines are separated by `\n` or `\r\n`, and a byte order mark at the start of the input is skipped.
+//! Wrap any [`std::io::Read`] with [`std::io::BufReader`] to stream it.
+//!
+//! ## Example
+//! ```
+//! use subtp::stream::SrtReader;
+//!
+//! let text = r#"1
+//! 00:00:01,000 --> 00:00:02,000
+//! Hello, world!
+//!
+//! 2
+//! 00:00:03,000 --> 00:00:04.000
+//! Invalid timestamp.
+//!
+//! 3
+//! 00:00:05,000 --> 00:00:06,000
+//! This is a test.
+//! "#;
+//!
+//! let results = SrtReader::new(text.as_bytes()).collect::<Vec<_>>();
+//! assert_eq!(results.len(), 3);
+//! assert_eq!(results[0].as_ref().unwrap().text, vec!["Hello, world!"]);
+//! assert!(results[1].is_err());
+//! assert_eq!(results[2].as_ref().unwrap().sequence, 3);
+//! ```
+
+use std::io::BufRead;
+
+use crate::ParseError;
+use crate::srt::SrtSubtitle;
+use crate::str_parser;
+use crate::vtt::VttBlock;
+use crate::vtt::VttHeader;
+use crate::vtt_parser;
+
+/// The error of streaming.
+#[derive(Debug, thiserror::Error)]
+pub enum StreamError {
+    /// Reading the input failed, e.g. by an invalid UTF-8 sequence.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Parsing a block failed, with the position in the whole input.
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+/// The streaming parser of the SubRip Subtitle format.
+///
+/// ## Example
+/// ```
+/// use std::io::BufReader;
+/// use subtp::stream::SrtReader;
+///
+/// let text = "1\r\n00:00:01,000 --> 00:00:02,000\r\nHello, world!\r\n";
+/// let reader = SrtReader::new(BufReader::new(text.as_bytes()));
+///
+/// for subtitle in reader {
+///     let subtitle = subtitle.unwrap();
+///     assert_eq!(subtitle.text, vec!["Hello, world!"]);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SrtReader<R> {
+    blocks: BlockReader<R>,
+}
+
+impl<R: BufRead> SrtReader<R> {
+    /// Creates the streaming parser reading from the reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            blocks: BlockReader::new(reader),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SrtReader<R> {
+    type Item = Result<SrtSubtitle, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = match self.blocks.next_block()? {
+            | Ok(block) => block,
+            | Err(err) => return Some(Err(err.into())),
+        };
+
+        Some(block.parse(str_parser::subtitle))
+    }
+}
+
+/// The streaming parser of the WebVTT format.
+///
+/// The header is parsed when the parser is created, and the following blocks are yielded one at a time.
+///
+/// ## Example
+/// ```
+/// use subtp::stream::VttReader;
+/// use subtp::vtt::VttBlock;
+///
+/// let text = r#"WEBVTT
+///
+/// NOTE This is a comment.
+///
+/// 00:01.000 --> 00:02.000
+/// Hello, world!
+/// "#;
+///
+/// let mut reader = VttReader::new(text.as_bytes()).unwrap();
+/// assert_eq!(reader.header().description, None);
+/// assert!(matches!(reader.next(), Some(Ok(VttBlock::Comment(_)))));
+/// assert!(matches!(reader.next(), Some(Ok(VttBlock::Que(_)))));
+/// assert!(reader.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct VttReader<R> {
+    header: VttHeader,
+    blocks: BlockReader<R>,
+}
+
+impl<R: BufRead> VttReader<R> {
+    /// Creates the streaming parser reading from the reader and parses the header.
+    pub fn new(reader: R) -> Result<Self, StreamError> {
+        let mut blocks = BlockReader::new(reader);
+        let header = match blocks.next_block() {
+            | Some(block) => block?.parse(vtt_parser::header)?,
+            | None => Block::default().parse(vtt_parser::header)?,
+        };
+
+        Ok(Self {
+            header,
+            blocks,
+        })
+    }
+
+    /// The header of the input.
+    pub fn header(&self) -> &VttHeader {
+        &self.header
+    }
+}
+
+impl<R: BufRead> Iterator for VttReader<R> {
+    type Item = Result<VttBlock, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let block = match self.blocks.next_block()? {
+            | Ok(block) => block,
+            | Err(err) => return Some(Err(err.into())),
+        };
+
+        Some(block.parse(vtt_parser::block))
+    }
+}
+
+/// A block of lines read from the input.
+#[derive(Debug)]
+struct Block {
+    /// The lines with the original newlines and a trailing newline.
+    text: String,
+    /// The line number starting from 1 of the first line in the input.
+    line: usize,
+    /// The byte offset of the first line in the input.
+    offset: usize,
+    /// The index of the block in the input.
+    index: usize,
+}
+
+impl Default for Block {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            line: 1,
+            offset: 0,
+            index: 0,
+        }
+    }
+}
+
+impl Block {
+    /// Parses the block by the rule with the error positioned in the whole input.
+    fn parse<T>(
+        self,
+        rule: fn(&str) -> Result<T, peg::error::ParseError<peg::str::LineCol>>,
+    ) -> Result<T, StreamError> {
+        rule(&self.text).map_err(|err| {
+            ParseError::from_peg(&self.text, err)
+                .in_block(self.line, self.offset, self.index)
+                .into()
+        })
+    }
+}
+
+/// The reader of blocks separated by blank lines.
+#[derive(Debug)]
+struct BlockReader<R> {
+    reader: R,
+    /// The number of lines read.
+    lines: usize,
+    /// The number of bytes read.
+    offset: usize,
+    /// The number of blocks read.
+    blocks: usize,
+    /// Whether the input has ended or failed.
+    done: bool,
+}
+
+impl<R: BufRead> BlockReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            lines: 0,
+            offset: 0,
+            blocks: 0,
+            done: false,
+        }
+    }
+
+    /// Reads the next block, or `None` at the end of the input.
+    fn next_block(&mut self) -> Option<std::io::Result<Block>> {
+        let mut block: Option<Block> = None;
+        let mut line = String::new();
+
+        while !self.done {
+            line.clear();
+            let read = match self
+                .reader
+                .read_line(&mut line)
+            {
+                | Ok(read) => read,
+                | Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                },
+            };
+            if read == 0 {
+                self.done = true;
+                break;
+            }
+
+            let mut text = line.as_str();
+            let mut offset = self.offset;
+            if self.offset == 0 {
+                if let Some(stripped) = text.strip_prefix('\u{feff}') {
+                    text = stripped;
+                    offset += '\u{feff}'.len_utf8();
+                }
+            }
+            self.lines += 1;
+            self.offset += read;
+
+            if text.trim().is_empty() {
+                if block.is_some() {
+                    break;
+                }
+                continue;
+            }
+
+            block
+                .get_or_insert_with(|| {
+                    Block {
+                        text: String::new(),
+                        line: self.lines,
+                        offset,
+                        index: self.blocks,
+                    }
+                })
+                .text
+                .push_str(text);
+        }
+
+        let mut block = block?;
+        if !block.text.ends_with('\n') {
+            block.text.push('\n');
+        }
+        self.blocks += 1;
+
+        Some(Ok(block))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ParseErrorKind;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn srt() {
+        let text = "\u{feff}1\r\n00:00:01,000 --> 00:00:02,000\r\nHello,\r\nworld!\r\n\r\n\r\n2\r\n00:00:03,000 --> 00:00:04,000\r\nThis is a test.";
+        let subtitles = SrtReader::new(text.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(
+            subtitles,
+            SubRip::parse(&format!(
+                "{}\r\n",
+                text.trim_start_matches('\u{feff}')
+            ))
+            .unwrap()
+            .subtitles
+        );
+    }
+
+    #[test]
+    fn srt_error_per_block() {
+        let text = "1\r\n00:00:01,000 --> 00:00:02,000\r\nA\r\n\r\n2\r\n00:00:03,000 --> 00:00:04.000\r\nB\r\n\r\n3\r\n00:00:05,000 --> 00:00:06,000\r\nC\r\n";
+        let results = SrtReader::new(text.as_bytes()).collect::<Vec<_>>();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0]
+                .as_ref()
+                .unwrap()
+                .text,
+            vec!["A"]
+        );
+        assert_eq!(
+            results[2]
+                .as_ref()
+                .unwrap()
+                .text,
+            vec!["C"]
+        );
+
+        let Err(StreamError::Parse(error)) = &results[1] else {
+            panic!(
+                "expected a parse error: {:?}",
+                results[1]
+            );
+        };
+        assert_eq!(error.line, 6);
+        assert_eq!(error.column, 26);
+        assert_eq!(error.offset, 67);
+        assert_eq!(
+            &text[error.offset..error.offset + 1],
+            "."
+        );
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnexpectedCharacter {
+                found: '.',
+                expected: vec!["\",\"".to_string()],
+            }
+        );
+        assert_eq!(error.block_index, Some(1));
+        assert_eq!(
+            error.snippet,
+            "00:00:03,000 --> 00:00:04.000"
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let text = r#"WEBVTT This is a header.
+
+STYLE
+::cue {
+  color: yellow;
+}
+
+1
+00:01.000 --> 00:02.000 line:0
+Hello, world!
+
+NOTE
+This is a comment.
+"#;
+        let mut reader = VttReader::new(text.as_bytes()).unwrap();
+        let vtt = WebVtt::parse(text).unwrap();
+
+        assert_eq!(reader.header(), &vtt.header);
+        assert_eq!(
+            reader
+                .by_ref()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vtt.blocks
+        );
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn vtt_errors() {
+        let error = VttReader::new("WEBVT\n".as_bytes()).unwrap_err();
+        assert!(matches!(
+            error,
+            StreamError::Parse(ParseError {
+                line: 1,
+                block_index: Some(0),
+                ..
+            })
+        ));
+
+        let error = VttReader::new("".as_bytes()).unwrap_err();
+        assert!(matches!(
+            error,
+            StreamError::Parse(ParseError {
+                kind: ParseErrorKind::UnexpectedEndOfInput { .. },
+                ..
+            })
+        ));
+
+        let text = "WEBVTT\n\n00:01.000 --> 00:02.000\nA\n\n00:03.000 -> 00:04.000\nB\n";
+        let results = VttReader::new(text.as_bytes())
+            .unwrap()
+            .collect::<Vec<_>>();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        let Err(StreamError::Parse(error)) = &results[1] else {
+            panic!(
+                "expected a parse error: {:?}",
+                results[1]
+            );
+        };
+        assert_eq!(error.line, 7);
+        assert_eq!(error.block_index, Some(2));
+    }
+
+    #[test]
+    fn io_error() {
+        let text = b"1\n00:00:01,000 --> 00:00:02,000\nA\n\n2\n00:00:03,000 --> 00:00:04,000\n\xff\n";
+        let mut reader = SrtReader::new(&text[..]);
+
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(
+            reader.next(),
+            Some(Err(StreamError::Io(_)))
+        ));
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,594 @@
+//! Timeline editing of all timestamps in a subtitle document.
+//!
+//! The [`Timeline`] is implemented for [`SubRip`](crate::srt::SubRip) and [`WebVtt`](crate::vtt::WebVtt),
+//! where the timestamps of WebVTT include the internal timestamps in the cue text.
+//!
+//! Timestamps are edited in milliseconds, and a timestamp that would go negative or overflow
+//! is clamped or raises an error by [`OutOfRange`] without modifying the document.
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::srt::SubRip;
+//! use subtp::timeline::Framerate;
+//! use subtp::timeline::OutOfRange;
+//! use subtp::timeline::Timeline;
+//!
+//! let text = r#"1
+//! 00:00:01,000 --> 00:00:02,000
+//! Hello, world!
+//!
+//! 2
+//! 00:00:25,000 --> 00:00:30,000
+//! This is a test.
+//! "#;
+//!
+//! let mut srt = SubRip::parse(text).unwrap();
+//!
+//! srt.shift(-1_500, OutOfRange::Clamp).unwrap();
+//! assert_eq!(srt.subtitles[0].start.to_string(), "00:00:00,000");
+//! assert_eq!(srt.subtitles[0].end.to_string(), "00:00:00,500");
+//!
+//! srt.convert_framerate(Framerate::PAL, Framerate::FILM, OutOfRange::Error).unwrap();
+//! assert_eq!(srt.subtitles[1].start.to_string(), "00:00:24,479");
+//! ```
+
+use std::time::Duration;
+
+/// The maximum timestamp representable by subtitle documents.
+pub const MAX_TIMESTAMP: Duration =
+    Duration::from_millis(((u32::MAX as u64 * 60 + 59) * 60 + 59) * 1_000 + 999);
+
+/// How to handle a timestamp that would go negative or overflow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OutOfRange {
+    /// Clamps the timestamp to zero or [`MAX_TIMESTAMP`].
+    #[default]
+    Clamp,
+    /// Raises an error without modifying the document.
+    Error,
+}
+
+/// The error of timeline editing.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TimelineError {
+    /// A timestamp would go negative or overflow.
+    #[error("Timestamp of {milliseconds} ms is out of range")]
+    OutOfRange {
+        /// The timestamp in milliseconds.
+        milliseconds: i64,
+    },
+    /// The scale factor is not positive and finite.
+    #[error("Invalid scale factor: {0}")]
+    InvalidFactor(f64),
+    /// The framerate is zero.
+    #[error("Invalid framerate: {0}")]
+    InvalidFramerate(Framerate),
+    /// The cue of the index was not found.
+    #[error("Cue not found at index {0}")]
+    CueNotFound(usize),
+    /// The sync points do not map different cue times to different times in the same order.
+    #[error("Invalid sync points: {first:?} and {second:?}")]
+    InvalidSyncPoints {
+        /// The first sync point.
+        first: SyncPoint,
+        /// The second sync point.
+        second: SyncPoint,
+    },
+}
+
+/// The framerate of video as a ratio of frames per second.
+///
+/// ## Example
+/// ```
+/// use subtp::timeline::Framerate;
+///
+/// assert_eq!(Framerate::FILM_NTSC, Framerate::new(24_000, 1_001));
+/// assert_eq!(Framerate::FILM_NTSC.to_string(), "23.976");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Framerate {
+    /// The numerator of frames per second.
+    pub numerator: u32,
+    /// The denominator of frames per second.
+    pub denominator: u32,
+}
+
+impl Framerate {
+    /// 24 fps of film.
+    pub const FILM: Self = Self::new(24, 1);
+    /// 23.976 fps of film on NTSC video.
+    pub const FILM_NTSC: Self = Self::new(24_000, 1_001);
+    /// 29.97 fps of NTSC video.
+    pub const NTSC: Self = Self::new(30_000, 1_001);
+    /// 25 fps of PAL video.
+    pub const PAL: Self = Self::new(25, 1);
+
+    /// Creates the framerate of `numerator / denominator` frames per second.
+    pub const fn new(
+        numerator: u32,
+        denominator: u32,
+    ) -> Self {
+        Self {
+            numerator,
+            denominator,
+        }
+    }
+}
+
+impl std::fmt::Display for Framerate {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if self.denominator == 1 {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(
+                f,
+                "{:.3}",
+                self.numerator as f64 / self.denominator as f64
+            )
+        }
+    }
+}
+
+/// The point to map the start of the cue to the time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SyncPoint {
+    /// The index of the cue starting from 0.
+    pub cue: usize,
+    /// The time to map the start of the cue to.
+    pub time: Duration,
+}
+
+/// Timeline editing of all timestamps in a subtitle document.
+pub trait Timeline {
+    /// Replaces each timestamp with the result of the function in order of the document.
+    fn for_each_timestamp(
+        &mut self,
+        f: &mut dyn FnMut(Duration) -> Duration,
+    );
+
+    /// The start of the cue at the index, or `None` if not found.
+    fn cue_start(
+        &self,
+        index: usize,
+    ) -> Option<Duration>;
+
+    /// Maps each timestamp in milliseconds by the function.
+    fn retime(
+        &mut self,
+        map: impl Fn(i64) -> i64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>
+    where
+        Self: Sized,
+    {
+        let max = MAX_TIMESTAMP.as_millis() as i64;
+
+        if out_of_range == OutOfRange::Error {
+            let mut error = None;
+            self.for_each_timestamp(&mut |timestamp| {
+                let milliseconds = map(timestamp.as_millis() as i64);
+                if error.is_none() && !(0..=max).contains(&milliseconds) {
+                    error = Some(TimelineError::OutOfRange {
+                        milliseconds,
+                    });
+                }
+                timestamp
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+        }
+
+        self.for_each_timestamp(&mut |timestamp| {
+            let milliseconds = map(timestamp.as_millis() as i64).clamp(0, max);
+            Duration::from_millis(milliseconds as u64)
+        });
+
+        Ok(())
+    }
+
+    /// Shifts all timestamps by the milliseconds, backward if negative.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::timeline::OutOfRange;
+    /// use subtp::timeline::Timeline;
+    /// use subtp::timeline::TimelineError;
+    /// use subtp::vtt::WebVtt;
+    ///
+    /// let text = "WEBVTT\n\n00:01.000 --> 00:03.000\nHello, <00:02.000>world!\n";
+    /// let mut vtt = WebVtt::parse(text).unwrap();
+    ///
+    /// assert_eq!(
+    ///     vtt.shift(-2_000, OutOfRange::Error),
+    ///     Err(TimelineError::OutOfRange { milliseconds: -1_000 })
+    /// );
+    ///
+    /// vtt.shift(1_500, OutOfRange::Error).unwrap();
+    /// assert_eq!(
+    ///     vtt.render(),
+    ///     "WEBVTT\n\n00:00:02.500 --> 00:00:04.500\nHello, <00:00:03.500>world!\n"
+    /// );
+    /// ```
+    fn shift(
+        &mut self,
+        milliseconds: i64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>
+    where
+        Self: Sized,
+    {
+        self.retime(
+            |timestamp| timestamp.saturating_add(milliseconds),
+            out_of_range,
+        )
+    }
+
+    /// Scales all timestamps from zero by the factor.
+    fn scale(
+        &mut self,
+        factor: f64,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>
+    where
+        Self: Sized,
+    {
+        if !factor.is_finite() || factor <= 0. {
+            return Err(TimelineError::InvalidFactor(factor));
+        }
+
+        self.retime(
+            |timestamp| (timestamp as f64 * factor).round() as i64,
+            out_of_range,
+        )
+    }
+
+    /// Converts all timestamps for the video sped up or slowed down from the framerate to the other,
+    /// e.g. from 23.976 fps to 25 fps of the PAL speedup.
+    fn convert_framerate(
+        &mut self,
+        from: Framerate,
+        to: Framerate,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>
+    where
+        Self: Sized,
+    {
+        for framerate in [from, to] {
+            if framerate.numerator == 0 || framerate.denominator == 0 {
+                return Err(TimelineError::InvalidFramerate(
+                    framerate,
+                ));
+            }
+        }
+
+        // The frame at the time `t` is at `t * from / to` after conversion.
+        let numerator = from.numerator as i128 * to.denominator as i128;
+        let denominator = from.denominator as i128 * to.numerator as i128;
+        self.retime(
+            |timestamp| {
+                divide_rounded(
+                    timestamp as i128 * numerator,
+                    denominator,
+                )
+            },
+            out_of_range,
+        )
+    }
+
+    /// Maps all timestamps linearly so that the starts of the two cues are at the times of the sync points.
+    ///
+    /// ## Example
+    /// ```
+    /// use std::time::Duration;
+    /// use subtp::srt::SubRip;
+    /// use subtp::timeline::OutOfRange;
+    /// use subtp::timeline::SyncPoint;
+    /// use subtp::timeline::Timeline;
+    ///
+    /// let text = r#"1
+    /// 00:00:10,000 --> 00:00:12,000
+    /// First.
+    ///
+    /// 2
+    /// 00:00:20,000 --> 00:00:22,000
+    /// Second.
+    ///
+    /// 3
+    /// 00:00:30,000 --> 00:00:32,000
+    /// Third.
+    /// "#;
+    /// let mut srt = SubRip::parse(text).unwrap();
+    ///
+    /// srt.resync(
+    ///     SyncPoint { cue: 0, time: Duration::from_secs(11) },
+    ///     SyncPoint { cue: 2, time: Duration::from_secs(51) },
+    ///     OutOfRange::Error,
+    /// ).unwrap();
+    /// assert_eq!(srt.subtitles[1].start.to_string(), "00:00:31,000");
+    /// assert_eq!(srt.subtitles[1].end.to_string(), "00:00:35,000");
+    /// ```
+    fn resync(
+        &mut self,
+        first: SyncPoint,
+        second: SyncPoint,
+        out_of_range: OutOfRange,
+    ) -> Result<(), TimelineError>
+    where
+        Self: Sized,
+    {
+        let start = |point: SyncPoint| {
+            self.cue_start(point.cue)
+                .map(|start| start.as_millis() as i128)
+                .ok_or(TimelineError::CueNotFound(point.cue))
+        };
+        let (first_start, second_start) = (start(first)?, start(second)?);
+        let first_time = first.time.as_millis() as i128;
+        let second_time = second.time.as_millis() as i128;
+
+        let numerator = second_time - first_time;
+        let denominator = second_start - first_start;
+        if numerator == 0
+            || denominator == 0
+            || (numerator < 0) != (denominator < 0)
+        {
+            return Err(TimelineError::InvalidSyncPoints {
+                first,
+                second,
+            });
+        }
+
+        self.retime(
+            |timestamp| {
+                let offset = divide_rounded(
+                    (timestamp as i128 - first_start) * numerator,
+                    denominator,
+                );
+                (first_time as i64).saturating_add(offset)
+            },
+            out_of_range,
+        )
+    }
+}
+
+/// Divides with rounding half away from zero, saturating to `i64`.
+fn divide_rounded(
+    numerator: i128,
+    denominator: i128,
+) -> i64 {
+    let (numerator, denominator) = if denominator < 0 {
+        (-numerator, -denominator)
+    } else {
+        (numerator, denominator)
+    };
+    let quotient = if numerator < 0 {
+        (numerator - denominator / 2) / denominator
+    } else {
+        (numerator + denominator / 2) / denominator
+    };
+
+    quotient.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    const SRT: &str = r#"1
+00:00:01,000 --> 00:00:02,000
+Hello, world!
+
+2
+01:00:00,000 --> 01:00:02,500
+This is a test.
+"#;
+
+    fn times(srt: &SubRip) -> Vec<String> {
+        srt.subtitles
+            .iter()
+            .flat_map(|subtitle| {
+                [
+                    subtitle.start.to_string(),
+                    subtitle.end.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shift() {
+        let mut srt = SubRip::parse(SRT).unwrap();
+
+        srt.shift(-1_200, OutOfRange::Clamp)
+            .unwrap();
+        assert_eq!(
+            times(&srt),
+            vec![
+                "00:00:00,000",
+                "00:00:00,800",
+                "00:59:58,800",
+                "01:00:01,300"
+            ]
+        );
+
+        let original = srt.clone();
+        assert_eq!(
+            srt.shift(i64::MAX, OutOfRange::Error),
+            Err(TimelineError::OutOfRange {
+                milliseconds: i64::MAX,
+            })
+        );
+        assert_eq!(srt, original);
+
+        srt.shift(i64::MAX, OutOfRange::Clamp)
+            .unwrap();
+        assert_eq!(
+            srt.subtitles[0]
+                .start
+                .to_string(),
+            "4294967295:59:59,999"
+        );
+    }
+
+    #[test]
+    fn scale() {
+        let mut srt = SubRip::parse(SRT).unwrap();
+
+        srt.scale(1.5, OutOfRange::Error)
+            .unwrap();
+        assert_eq!(
+            times(&srt),
+            vec![
+                "00:00:01,500",
+                "00:00:03,000",
+                "01:30:00,000",
+                "01:30:03,750"
+            ]
+        );
+
+        for factor in [
+            0.,
+            -1.,
+            f64::NAN,
+            f64::INFINITY,
+        ] {
+            assert!(matches!(
+                srt.scale(factor, OutOfRange::Clamp),
+                Err(TimelineError::InvalidFactor(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn convert_framerate() {
+        let mut srt = SubRip::parse(SRT).unwrap();
+
+        srt.convert_framerate(
+            Framerate::FILM_NTSC,
+            Framerate::PAL,
+            OutOfRange::Error,
+        )
+        .unwrap();
+        assert_eq!(
+            times(&srt),
+            vec![
+                "00:00:00,959",
+                "00:00:01,918",
+                "00:57:32,547",
+                "00:57:34,945"
+            ]
+        );
+
+        srt.convert_framerate(
+            Framerate::PAL,
+            Framerate::FILM_NTSC,
+            OutOfRange::Error,
+        )
+        .unwrap();
+        assert_eq!(
+            times(&srt),
+            times(&SubRip::parse(SRT).unwrap())
+        );
+
+        assert_eq!(
+            srt.convert_framerate(
+                Framerate::new(25, 0),
+                Framerate::PAL,
+                OutOfRange::Error,
+            ),
+            Err(TimelineError::InvalidFramerate(
+                Framerate::new(25, 0)
+            ))
+        );
+    }
+
+    #[test]
+    fn resync() {
+        let mut srt = SubRip::parse(SRT).unwrap();
+        let first = SyncPoint {
+            cue: 0,
+            time: Duration::from_secs(2),
+        };
+        let second = SyncPoint {
+            cue: 1,
+            time: Duration::from_secs(7_201),
+        };
+
+        srt.resync(first, second, OutOfRange::Error)
+            .unwrap();
+        assert_eq!(
+            times(&srt),
+            vec![
+                "00:00:02,000",
+                "00:00:04,000",
+                "02:00:01,000",
+                "02:00:06,001"
+            ]
+        );
+
+        let missing = SyncPoint {
+            cue: 2,
+            ..second
+        };
+        assert_eq!(
+            srt.resync(first, missing, OutOfRange::Error),
+            Err(TimelineError::CueNotFound(2))
+        );
+
+        let reversed = SyncPoint {
+            cue: 1,
+            time: Duration::from_secs(1),
+        };
+        assert_eq!(
+            srt.resync(first, reversed, OutOfRange::Error),
+            Err(TimelineError::InvalidSyncPoints {
+                first,
+                second: reversed,
+            })
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let text = r#"WEBVTT
+
+NOTE 00:00:01.000 is not a timestamp.
+
+intro
+00:01.000 --> 00:02.000 align:left
+<c.a>Hello,</c> <00:01.500><b>world!</b> <1:2.3>
+
+00:03.000 --> 00:04.000
+This is a test.
+"#;
+        let mut vtt = WebVtt::parse(text).unwrap();
+
+        vtt.shift(1_000, OutOfRange::Error)
+            .unwrap();
+        assert_eq!(
+            vtt.render(),
+            r#"WEBVTT
+
+NOTE 00:00:01.000 is not a timestamp.
+
+intro
+00:00:02.000 --> 00:00:03.000 align:left
+<c.a>Hello,</c> <00:00:02.500><b>world!</b> <1:2.3>
+
+00:00:04.000 --> 00:00:05.000
+This is a test.
+"#
+        );
+        assert_eq!(
+            vtt.cue_start(1),
+            Some(Duration::from_secs(4))
+        );
+        assert_eq!(vtt.cue_start(2), None);
+    }
+}
@@ -0,0 +1,424 @@
+//! Readable transcripts of the subtitle formats built on the cues of [`SubtitleFormat`](crate::SubtitleFormat).
+//!
+//! The cues are turned into the lines of `Speaker: text` by the voices of the spans without markup,
+//! where the cues split in the middle of a sentence are joined back together,
+//! the lines repeated from the previous cue by rolling captions are removed,
+//! and optionally the paragraphs are broken by long gaps and the lines are marked with timestamps at intervals.
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::SubtitleFormat;
+//! use subtp::transcript::TranscriptOptions;
+//! use subtp::vtt::WebVtt;
+//!
+//! let text = r#"WEBVTT
+//!
+//! 00:01.000 --> 00:03.000
+//! <v Alice>I was going to</v>
+//!
+//! 00:03.000 --> 00:05.000
+//! <v Alice>the <i>store</i>.</v>
+//!
+//! 00:05.000 --> 00:07.000
+//! <v Bob>Me too!</v>
+//!
+//! 00:20.000 --> 00:22.000
+//! Later that day.
+//! "#;
+//!
+//! let vtt = WebVtt::parse(text).unwrap();
+//! let options = TranscriptOptions {
+//!     paragraph_gap: Some(Duration::from_secs(5)),
+//!     ..Default::default()
+//! };
+//!
+//! assert_eq!(
+//!     vtt.transcript(&options).render(),
+//!     "Alice: I was going to the store.\nBob: Me too!\n\nLater that day.\n"
+//! );
+//! ```
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::format::Cue;
+
+/// The options of the transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TranscriptOptions {
+    /// Whether to join the cues of the same speaker not ending a sentence into a line.
+    pub join_sentences: bool,
+    /// Whether to remove the lines repeated from the previous cue, e.g. by rolling captions.
+    pub remove_duplicates: bool,
+    /// The minimum gap between cues to break the paragraph, or `None` for a single paragraph.
+    pub paragraph_gap: Option<Duration>,
+    /// The interval of the timestamps marking the first line starting in each interval, or `None` for no timestamps.
+    pub timestamp_interval: Option<Duration>,
+}
+
+impl Default for TranscriptOptions {
+    fn default() -> Self {
+        Self {
+            join_sentences: true,
+            remove_duplicates: true,
+            paragraph_gap: None,
+            timestamp_interval: None,
+        }
+    }
+}
+
+/// The transcript of the paragraphs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Transcript {
+    /// The paragraphs.
+    pub paragraphs: Vec<TranscriptParagraph>,
+}
+
+impl Transcript {
+    /// Creates the transcript from the cues.
+    pub fn new(
+        cues: impl IntoIterator<Item = Cue>,
+        options: &TranscriptOptions,
+    ) -> Self {
+        let mut cues = cues
+            .into_iter()
+            .collect::<Vec<_>>();
+        cues.sort_by_key(|cue| cue.start);
+
+        let mut transcript = Self::default();
+        let mut previous_lines: Vec<String> = Vec::new();
+        let mut previous_end: Option<Duration> = None;
+        let mut next_timestamp = Duration::ZERO;
+
+        for cue in cues {
+            let lines = cue_lines(&cue);
+            let texts = lines
+                .iter()
+                .map(|(_, text)| text.clone())
+                .collect::<Vec<_>>();
+            let skip = if options.remove_duplicates {
+                overlap(&previous_lines, &texts)
+            } else {
+                0
+            };
+            previous_lines = texts;
+
+            let paragraph_break = match (options.paragraph_gap, previous_end) {
+                | (Some(gap), Some(end)) => {
+                    cue.start.saturating_sub(end) >= gap
+                },
+                | _ => false,
+            };
+            previous_end =
+                Some(previous_end.map_or(cue.end, |end| end.max(cue.end)));
+            if paragraph_break
+                || transcript
+                    .paragraphs
+                    .is_empty()
+            {
+                transcript
+                    .paragraphs
+                    .push(TranscriptParagraph::default());
+            }
+            let paragraph = transcript
+                .paragraphs
+                .last_mut()
+                .expect("pushed above");
+            let mut first = true;
+
+            for (speaker, text) in lines.into_iter().skip(skip) {
+                match paragraph.lines.last_mut() {
+                    | Some(last)
+                        if last.speaker == speaker
+                            && (!first
+                                || options.join_sentences
+                                    && !ends_sentence(&last.text)) =>
+                    {
+                        join(&mut last.text, &text);
+                        last.end = cue.end;
+                    },
+                    | _ => {
+                        let timestamp = match options.timestamp_interval {
+                            | Some(interval)
+                                if !interval.is_zero()
+                                    && cue.start >= next_timestamp =>
+                            {
+                                let intervals = cue.start.as_nanos()
+                                    / interval.as_nanos()
+                                    + 1;
+                                next_timestamp = interval * intervals as u32;
+                                Some(cue.start)
+                            },
+                            | _ => None,
+                        };
+                        paragraph
+                            .lines
+                            .push(TranscriptLine {
+                                start: cue.start,
+                                end: cue.end,
+                                speaker,
+                                text,
+                                timestamp,
+                            });
+                    },
+                }
+                first = false;
+            }
+        }
+
+        transcript
+            .paragraphs
+            .retain(|paragraph| !paragraph.lines.is_empty());
+        transcript
+    }
+
+    /// Renders the transcript with the lines ending with `\n` and the paragraphs separated by a blank line.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Transcript {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for (index, paragraph) in self
+            .paragraphs
+            .iter()
+            .enumerate()
+        {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            for line in &paragraph.lines {
+                writeln!(f, "{}", line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The paragraph of the lines.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TranscriptParagraph {
+    /// The lines.
+    pub lines: Vec<TranscriptLine>,
+}
+
+/// The line of a speaker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct TranscriptLine {
+    /// The start of the first cue.
+    pub start: Duration,
+    /// The end of the last cue.
+    pub end: Duration,
+    /// The speaker by the voice.
+    pub speaker: Option<String>,
+    /// The plain text.
+    pub text: String,
+    /// The timestamp to mark the line with.
+    pub timestamp: Option<Duration>,
+}
+
+impl Display for TranscriptLine {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if let Some(timestamp) = self.timestamp {
+            let seconds = timestamp.as_secs();
+            write!(
+                f,
+                "[{:02}:{:02}:{:02}] ",
+                seconds / 3600,
+                seconds / 60 % 60,
+                seconds % 60
+            )?;
+        }
+        if let Some(speaker) = &self.speaker {
+            write!(f, "{}: ", speaker)?;
+        }
+
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Gets the lines of the cue with the speakers, where the spaces are collapsed.
+fn cue_lines(cue: &Cue) -> Vec<(Option<String>, String)> {
+    let mut lines: Vec<(Option<String>, String)> = vec![(None, String::new())];
+    for span in &cue.spans {
+        for (index, text) in span
+            .text
+            .split('\n')
+            .enumerate()
+        {
+            if index > 0 {
+                lines.push((None, String::new()));
+            }
+            let line = lines
+                .last_mut()
+                .expect("pushed above");
+            if line.1.trim().is_empty() {
+                line.0 = span.voice.clone();
+            } else if line.0 != span.voice && !text.trim().is_empty() {
+                lines.push((span.voice.clone(), String::new()));
+            }
+            lines
+                .last_mut()
+                .expect("pushed above")
+                .1
+                .push_str(text);
+        }
+    }
+
+    lines
+        .into_iter()
+        .map(|(speaker, text)| {
+            (
+                speaker,
+                text.split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            )
+        })
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+/// Counts the lines at the start of the current lines repeating the end of the previous lines.
+fn overlap(
+    previous: &[String],
+    current: &[String],
+) -> usize {
+    (1..=previous
+        .len()
+        .min(current.len()))
+        .rev()
+        .find(|length| {
+            previous[previous.len() - length..] == current[..*length]
+        })
+        .unwrap_or(0)
+}
+
+/// Checks whether the text ends a sentence, where an ellipsis continues the sentence.
+fn ends_sentence(text: &str) -> bool {
+    let text = text.trim_end_matches([
+        '"', '\'', ')', ']', '”', '’', '」', '』',
+    ]);
+    !text.ends_with("...")
+        && text.ends_with([
+            '.', '!', '?', '。', '！', '？',
+        ])
+}
+
+/// Joins the text with a space, removing the ellipses continuing the sentence.
+fn join(
+    text: &mut String,
+    next: &str,
+) {
+    let trimmed = text
+        .trim_end_matches("...")
+        .trim_end_matches('…')
+        .trim_end()
+        .len();
+    text.truncate(trimmed);
+
+    let next = next
+        .trim_start_matches("...")
+        .trim_start_matches('…')
+        .trim_start();
+    if !text.is_empty() && !next.is_empty() {
+        text.push(' ');
+    }
+    text.push_str(next);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SubtitleFormat;
+    use crate::srt::SubRip;
+    use crate::vtt::WebVtt;
+
+    #[test]
+    fn srt() {
+        let srt = SubRip::parse(
+            r#"1
+00:00:01,000 --> 00:00:02,000
+<i>I was going</i>
+
+2
+00:00:02,000 --> 00:00:03,000
+<i>I was going</i>
+{\an8}to the store...
+
+3
+00:00:03,000 --> 00:00:04,000
+to the store...
+...but it was closed.
+
+4
+00:00:04,000 --> 00:00:05,000
+So I came back!
+
+5
+00:01:10,000 --> 00:01:12,000
+The end.
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            srt.transcript(&Default::default())
+                .render(),
+            "I was going to the store but it was closed.\nSo I came back!\nThe end.\n"
+        );
+
+        let options = TranscriptOptions {
+            join_sentences: false,
+            remove_duplicates: false,
+            paragraph_gap: Some(Duration::from_secs(60)),
+            timestamp_interval: Some(Duration::from_secs(30)),
+        };
+        assert_eq!(
+            srt.transcript(&options)
+                .render(),
+            "[00:00:01] I was going\nI was going to the store...\nto the store but it was closed.\nSo I came back!\n\n[00:01:10] The end.\n"
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let vtt = WebVtt::parse(
+            r#"WEBVTT
+
+00:00:01.000 --> 00:00:03.000
+<v Alice>Hello,</v>
+<v Bob>Hi!</v>
+
+00:00:03.000 --> 00:00:05.000
+<v Bob>How are</v>
+
+00:00:05.000 --> 00:00:06.000
+<v Bob>you?</v> <v Alice>Fine.</v>
+
+00:00:06.000 --> 00:00:07.000
+<v Alice>Thanks &amp; <b>bye</b>.</v>
+"#,
+        )
+        .unwrap();
+
+        let transcript = vtt.transcript(&Default::default());
+        assert_eq!(
+            transcript.render(),
+            "Alice: Hello,\nBob: Hi!\nBob: How are you?\nAlice: Fine.\nAlice: Thanks & bye.\n"
+        );
+        assert_eq!(
+            transcript.paragraphs[0].lines[2].end,
+            Duration::from_secs(6)
+        );
+    }
+}
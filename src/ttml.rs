@@ -0,0 +1,2170 @@
+//! A parser for the Timed Text Markup Language (`.ttml`, `.dfxp`, `.xml`) format including the IMSC1 text profile provided by [`subtp::ttml::Ttml`](Ttml).
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::ttml::Ttml;
+//!
+//! let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+//! <tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:frameRate="25">
+//!   <body>
+//!     <div>
+//!       <p begin="00:00:01.000" end="00:00:02.500">Hello,<br/>world!</p>
+//!       <p begin="75f" dur="2s">This is a subtitle.</p>
+//!     </div>
+//!   </body>
+//! </tt>
+//! "#;
+//!
+//! let ttml = Ttml::parse(text).unwrap();
+//!
+//! let paragraphs = ttml.timed_paragraphs();
+//! assert_eq!(paragraphs.len(), 2);
+//! assert_eq!(paragraphs[0].start, Duration::from_millis(1000));
+//! assert_eq!(paragraphs[0].end, Some(Duration::from_millis(2500)));
+//! assert_eq!(paragraphs[0].paragraph.plain_text(), "Hello,\nworld!");
+//! assert_eq!(paragraphs[1].start, Duration::from_secs(3));
+//! assert_eq!(paragraphs[1].end, Some(Duration::from_secs(5)));
+//!
+//! let rendered = ttml.render();
+//! assert_eq!(rendered, text);
+//! ```
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::format::Cue;
+use crate::format::CueAlignment;
+use crate::format::CueAnchor;
+use crate::format::CueLine;
+use crate::format::CuePosition;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::format::push_span;
+use crate::ttml_parser;
+
+/// The Timed Text Markup Language document.
+///
+/// Parses from text by [`Ttml::parse`](Ttml::parse)
+/// and renders to text by [`Ttml::render`](Ttml::render).
+///
+/// Elements are matched by the local name regardless of the namespace prefix,
+/// and unknown elements such as `<metadata>` are passed through as raw text.
+/// Comments and processing instructions are not preserved.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Ttml {
+    /// The attributes of the `<tt>` element other than the timing parameters,
+    /// e.g. `xmlns` and `xml:lang`.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The timing parameters of the `<tt>` element.
+    pub parameters: TtmlParameters,
+    /// The `<head>` element.
+    pub head: Option<TtmlHead>,
+    /// The `<body>` element.
+    pub body: Option<TtmlBody>,
+}
+
+impl Ttml {
+    /// Parses the Timed Text Markup Language format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::ttml::Ttml;
+    ///
+    /// let text = r#"<tt xmlns="http://www.w3.org/ns/ttml">
+    ///   <body>
+    ///     <div>
+    ///       <p begin="1s" end="2s">Hello, world!</p>
+    ///     </div>
+    ///   </body>
+    /// </tt>
+    /// "#;
+    ///
+    /// let ttml = Ttml::parse(text).unwrap();
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        ttml_parser::ttml(text).map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the Timed Text Markup Language format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Gets the style defined in the styling of the head by the identifier.
+    pub fn style(
+        &self,
+        id: &str,
+    ) -> Option<&TtmlStyle> {
+        self.head
+            .as_ref()?
+            .styling
+            .as_ref()?
+            .styles
+            .iter()
+            .find(|style| style.id.as_deref() == Some(id))
+    }
+
+    /// Gets the region defined in the layout of the head by the identifier.
+    pub fn region(
+        &self,
+        id: &str,
+    ) -> Option<&TtmlRegion> {
+        self.head
+            .as_ref()?
+            .layout
+            .as_ref()?
+            .regions
+            .iter()
+            .find(|region| region.id.as_deref() == Some(id))
+    }
+
+    /// Gets the paragraphs in document order with the timings resolved to the document time.
+    ///
+    /// The timings are resolved in the parallel time containment of the body and the divisions,
+    /// that is, `begin` and `end` are offsets from the begin of the parent element,
+    /// `dur` is an offset from the begin of the element itself,
+    /// and the active interval of an element is clipped by its parent.
+    pub fn timed_paragraphs(&self) -> Vec<TtmlTimedParagraph<'_>> {
+        let mut paragraphs = Vec::new();
+        if let Some(body) = &self.body {
+            let (start, end) =
+                body.timing
+                    .resolve(&self.parameters, Duration::ZERO, None);
+            collect_timed_paragraphs(
+                &body.children,
+                &self.parameters,
+                start,
+                end,
+                &mut paragraphs,
+            );
+        }
+        paragraphs
+    }
+}
+
+/// Collects the paragraphs in the blocks with the resolved timings.
+fn collect_timed_paragraphs<'a>(
+    blocks: &'a [TtmlBlock],
+    parameters: &TtmlParameters,
+    parent_start: Duration,
+    parent_end: Option<Duration>,
+    paragraphs: &mut Vec<TtmlTimedParagraph<'a>>,
+) {
+    for block in blocks {
+        match block {
+            | TtmlBlock::Div(div) => {
+                let (start, end) =
+                    div.timing
+                        .resolve(parameters, parent_start, parent_end);
+                collect_timed_paragraphs(
+                    &div.children,
+                    parameters,
+                    start,
+                    end,
+                    paragraphs,
+                );
+            },
+            | TtmlBlock::Paragraph(paragraph) => {
+                let (start, end) = paragraph.timing.resolve(
+                    parameters,
+                    parent_start,
+                    parent_end,
+                );
+                paragraphs.push(TtmlTimedParagraph {
+                    start,
+                    end,
+                    paragraph,
+                });
+            },
+            | TtmlBlock::Other(_) => {},
+        }
+    }
+}
+
+impl Display for Ttml {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(
+            f,
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>"
+        )?;
+        write!(f, "<tt")?;
+        write_attributes(f, &self.attributes)?;
+        write!(f, "{}", self.parameters)?;
+
+        if self.head.is_none() && self.body.is_none() {
+            return writeln!(f, "/>");
+        }
+
+        writeln!(f, ">")?;
+        if let Some(head) = &self.head {
+            head.write_xml(f, 1)?;
+        }
+        if let Some(body) = &self.body {
+            body.write_xml(f, 1)?;
+        }
+        writeln!(f, "</tt>")
+    }
+}
+
+impl SubtitleFormat for Ttml {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the timed paragraphs.
+    ///
+    /// The italic, the bold, the underline and the color are resolved from the styling attributes and the referential styles,
+    /// and the position is resolved from the `tts:textAlign` and the region with the `tts:origin` and the `tts:extent` in percentages.
+    /// A paragraph active indefinitely ends at the start.
+    fn cues(&self) -> Vec<Cue> {
+        self.timed_paragraphs()
+            .into_iter()
+            .map(|timed| {
+                let paragraph = timed.paragraph;
+                let format = span_format(
+                    self,
+                    &CueSpan::default(),
+                    &paragraph.attributes,
+                    &paragraph.styles,
+                );
+                let mut spans = Vec::new();
+                collect_cue_spans(
+                    self,
+                    &paragraph.content,
+                    &format,
+                    timed.start,
+                    timed.end,
+                    &mut spans,
+                );
+
+                Cue {
+                    identifier: paragraph.id.clone(),
+                    start: timed.start,
+                    end: timed
+                        .end
+                        .unwrap_or(timed.start),
+                    spans: collapse_whitespace(spans),
+                    position: cue_position(self, paragraph),
+                    style: paragraph
+                        .styles
+                        .first()
+                        .cloned(),
+                }
+            })
+            .collect()
+    }
+
+    /// Creates from the cues with a region for each position and the styling attributes on the spans,
+    /// where the line numbers other than `0` at the top are regarded as the default line.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        let mut styles: Vec<TtmlStyle> = Vec::new();
+        let mut regions: Vec<(CuePosition, TtmlRegion)> = Vec::new();
+        let mut paragraphs = Vec::new();
+
+        for cue in cues {
+            if let Some(style) = &cue.style {
+                if !styles
+                    .iter()
+                    .any(|s| s.id.as_ref() == Some(style))
+                {
+                    styles.push(TtmlStyle {
+                        id: Some(style.clone()),
+                        ..Default::default()
+                    });
+                }
+            }
+
+            let mut attributes = Vec::new();
+            let mut region = None;
+            if let Some(position) = &cue.position {
+                if let Some(align) = position.align {
+                    attributes.push(TtmlAttribute {
+                        name: "tts:textAlign".to_string(),
+                        value: match align {
+                            | CueAlignment::Left => "left",
+                            | CueAlignment::Center => "center",
+                            | CueAlignment::Right => "right",
+                        }
+                        .to_string(),
+                    });
+                }
+                if position.line.is_some()
+                    || position.position.is_some()
+                    || position.size.is_some()
+                {
+                    let key = CuePosition {
+                        align: None,
+                        ..*position
+                    };
+                    let index = match regions
+                        .iter()
+                        .position(|(p, _)| *p == key)
+                    {
+                        | Some(index) => index,
+                        | None => {
+                            let id = format!("r{}", regions.len() + 1);
+                            regions.push((key, region_from_position(id, &key)));
+                            regions.len() - 1
+                        },
+                    };
+                    region = regions[index].1.id.clone();
+                }
+            }
+
+            paragraphs.push(TtmlBlock::Paragraph(TtmlParagraph {
+                id: cue
+                    .identifier
+                    .filter(|identifier| is_xml_id(identifier)),
+                styles: cue
+                    .style
+                    .into_iter()
+                    .collect(),
+                region,
+                timing: TtmlTiming {
+                    begin: Some(cue.start.into()),
+                    end: Some(cue.end.into()),
+                    dur: None,
+                },
+                attributes,
+                content: inlines_from_spans(&cue.spans, cue.start),
+            }));
+        }
+
+        let head = if styles.is_empty() && regions.is_empty() {
+            None
+        } else {
+            Some(TtmlHead {
+                styling: Some(TtmlStyling {
+                    styles,
+                    ..Default::default()
+                })
+                .filter(|styling| !styling.styles.is_empty()),
+                layout: Some(TtmlLayout {
+                    regions: regions
+                        .into_iter()
+                        .map(|(_, region)| region)
+                        .collect(),
+                    ..Default::default()
+                })
+                .filter(|layout| !layout.regions.is_empty()),
+                ..Default::default()
+            })
+        };
+
+        Self {
+            attributes: vec![
+                TtmlAttribute {
+                    name: "xmlns".to_string(),
+                    value: "http://www.w3.org/ns/ttml".to_string(),
+                },
+                TtmlAttribute {
+                    name: "xmlns:tts".to_string(),
+                    value: "http://www.w3.org/ns/ttml#styling".to_string(),
+                },
+            ],
+            parameters: TtmlParameters::default(),
+            head,
+            body: Some(TtmlBody {
+                children: vec![TtmlBlock::Div(
+                    TtmlDiv {
+                        children: paragraphs,
+                        ..Default::default()
+                    },
+                )],
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// Finds the value of the styling attribute by the local name
+/// in the attributes and then in the referential styles.
+fn styling_attribute<'a>(
+    ttml: &'a Ttml,
+    attributes: &'a [TtmlAttribute],
+    styles: &'a [String],
+    local: &str,
+) -> Option<&'a str> {
+    let find = |attributes: &'a [TtmlAttribute]| {
+        attributes
+            .iter()
+            .find(|attribute| {
+                attribute
+                    .name
+                    .starts_with("tts:")
+                    && attribute.name[4..] == *local
+            })
+            .map(|attribute| attribute.value.as_str())
+    };
+    if let Some(value) = find(attributes) {
+        return Some(value);
+    }
+
+    let mut visited = Vec::new();
+    let mut pending = styles
+        .iter()
+        .rev()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    while let Some(id) = pending.pop() {
+        if visited.contains(&id) {
+            continue;
+        }
+        visited.push(id);
+
+        if let Some(style) = ttml.style(id) {
+            if let Some(value) = find(&style.attributes) {
+                return Some(value);
+            }
+            pending.extend(
+                style
+                    .styles
+                    .iter()
+                    .rev()
+                    .map(String::as_str),
+            );
+        }
+    }
+
+    None
+}
+
+/// Resolves the formatting of the span from the styling attributes over the parent formatting.
+fn span_format(
+    ttml: &Ttml,
+    parent: &CueSpan,
+    attributes: &[TtmlAttribute],
+    styles: &[String],
+) -> CueSpan {
+    let value = |local| styling_attribute(ttml, attributes, styles, local);
+    let mut format = parent.clone();
+
+    if let Some(style) = value("fontStyle") {
+        format.italic = style == "italic" || style == "oblique";
+    }
+    if let Some(weight) = value("fontWeight") {
+        format.bold = weight == "bold";
+    }
+    if let Some(decoration) = value("textDecoration") {
+        let mut decorations = decoration.split_whitespace();
+        if decorations
+            .clone()
+            .any(|d| d == "underline")
+        {
+            format.underline = true;
+        } else if decorations.any(|d| d == "noUnderline" || d == "none") {
+            format.underline = false;
+        }
+    }
+    if let Some(color) = value("color") {
+        format.color = Some(color.to_string());
+    }
+
+    format
+}
+
+/// Collects the spans of the inline content with the formatting of the parent.
+fn collect_cue_spans(
+    ttml: &Ttml,
+    content: &[TtmlInline],
+    format: &CueSpan,
+    parent_start: Duration,
+    parent_end: Option<Duration>,
+    spans: &mut Vec<CueSpan>,
+) {
+    for inline in content {
+        match inline {
+            | TtmlInline::Text(text) => {
+                push_span(
+                    spans,
+                    CueSpan {
+                        text: text.replace(['\n', '\r', '\t'], " "),
+                        ..format.clone()
+                    },
+                );
+            },
+            | TtmlInline::Break => {
+                push_span(
+                    spans,
+                    CueSpan {
+                        text: "\n".to_string(),
+                        ..format.clone()
+                    },
+                );
+            },
+            | TtmlInline::Span(span) => {
+                let (start, end) = span.timing.resolve(
+                    &ttml.parameters,
+                    parent_start,
+                    parent_end,
+                );
+                let mut format = span_format(
+                    ttml,
+                    format,
+                    &span.attributes,
+                    &span.styles,
+                );
+                if span.timing.begin.is_some() {
+                    format.start = Some(start);
+                }
+                collect_cue_spans(
+                    ttml,
+                    &span.content,
+                    &format,
+                    start,
+                    end,
+                    spans,
+                );
+            },
+            | TtmlInline::Other(_) => {},
+        }
+    }
+}
+
+/// Collapses the whitespace of the spans as the default `xml:space`
+/// and trims the whitespace around the line breaks.
+fn collapse_whitespace(spans: Vec<CueSpan>) -> Vec<CueSpan> {
+    let pop_space = |collapsed: &mut Vec<CueSpan>, text: &mut String| {
+        if text.ends_with(' ') {
+            text.pop();
+        } else if let Some(last) = collapsed.last_mut() {
+            last.text.pop();
+            if last.text.is_empty() {
+                collapsed.pop();
+            }
+        }
+    };
+
+    let mut collapsed = Vec::new();
+    let mut line_start = true;
+    let mut last_space = false;
+    for span in spans {
+        let mut text = String::new();
+        for c in span.text.chars() {
+            match c {
+                | ' ' => {
+                    if !line_start && !last_space {
+                        text.push(' ');
+                        last_space = true;
+                    }
+                },
+                | '\n' => {
+                    if last_space {
+                        pop_space(&mut collapsed, &mut text);
+                        last_space = false;
+                    }
+                    text.push('\n');
+                    line_start = true;
+                },
+                | c => {
+                    text.push(c);
+                    line_start = false;
+                    last_space = false;
+                },
+            }
+        }
+        push_span(
+            &mut collapsed,
+            CueSpan {
+                text,
+                ..span
+            },
+        );
+    }
+    if last_space {
+        pop_space(&mut collapsed, &mut String::new());
+    }
+
+    collapsed
+}
+
+/// Resolves the position of the paragraph from the `tts:textAlign` and the region.
+///
+/// A region displayed at the after or the center edge is regarded as the default line.
+fn cue_position(
+    ttml: &Ttml,
+    paragraph: &TtmlParagraph,
+) -> Option<CuePosition> {
+    let region = paragraph
+        .region
+        .as_deref()
+        .or_else(|| {
+            ttml.body
+                .as_ref()?
+                .region
+                .as_deref()
+        })
+        .and_then(|id| ttml.region(id));
+
+    let align = styling_attribute(
+        ttml,
+        &paragraph.attributes,
+        &paragraph.styles,
+        "textAlign",
+    )
+    .or_else(|| {
+        let region = region?;
+        styling_attribute(
+            ttml,
+            &region.attributes,
+            &region.styles,
+            "textAlign",
+        )
+    });
+    let mut position = CuePosition {
+        align: match align {
+            | Some("left" | "start") => Some(CueAlignment::Left),
+            | Some("center") => Some(CueAlignment::Center),
+            | Some("right" | "end") => Some(CueAlignment::Right),
+            | _ => None,
+        },
+        ..Default::default()
+    };
+
+    if let Some(region) = region {
+        let value = |local| {
+            styling_attribute(
+                ttml,
+                &region.attributes,
+                &region.styles,
+                local,
+            )
+        };
+        if let (Some((x, y)), Some((width, _))) = (
+            value("origin").and_then(parse_percentages),
+            value("extent").and_then(parse_percentages),
+        ) {
+            position.position = Some(x + width / 2.0);
+            position.position_anchor = Some(CueAnchor::Center);
+            position.size = Some(width);
+            if matches!(
+                value("displayAlign"),
+                None | Some("before")
+            ) {
+                position.line = Some(CueLine::Percentage(y));
+            }
+        }
+    }
+
+    if position == CuePosition::default() {
+        None
+    } else {
+        Some(position)
+    }
+}
+
+/// Parses the pair of percentages such as `10% 80%`.
+fn parse_percentages(text: &str) -> Option<(f32, f32)> {
+    let mut values = text
+        .split_whitespace()
+        .map(|value| {
+            value
+                .strip_suffix('%')?
+                .parse::<f32>()
+                .ok()
+        });
+    let pair = (values.next()??, values.next()??);
+    if values.next().is_some() {
+        return None;
+    }
+    Some(pair)
+}
+
+/// Creates the region of the position.
+fn region_from_position(
+    id: String,
+    position: &CuePosition,
+) -> TtmlRegion {
+    let size = position.size.unwrap_or(100.0);
+    let offset = match position.position_anchor {
+        | Some(CueAnchor::Start) => 0.0,
+        | Some(CueAnchor::End) => size,
+        | Some(CueAnchor::Center) | None => size / 2.0,
+    };
+    let x = (position
+        .position
+        .unwrap_or(50.0)
+        - offset)
+        .max(0.0);
+    let (y, height, display_align) = match (position.line, position.line_anchor)
+    {
+        | (Some(CueLine::Percentage(line)), None | Some(CueAnchor::Start)) => {
+            (line, 100.0 - line, "before")
+        },
+        | (Some(CueLine::Percentage(line)), Some(CueAnchor::Center)) => {
+            let half = line.min(100.0 - line);
+            (line - half, half * 2.0, "center")
+        },
+        | (Some(CueLine::Percentage(line)), Some(CueAnchor::End)) => {
+            (0.0, line, "after")
+        },
+        | (Some(CueLine::Number(0)), _) => (0.0, 100.0, "before"),
+        | _ => (0.0, 100.0, "after"),
+    };
+
+    TtmlRegion {
+        id: Some(id),
+        attributes: [
+            ("tts:origin", format!("{}% {}%", x, y)),
+            (
+                "tts:extent",
+                format!("{}% {}%", size, height),
+            ),
+            (
+                "tts:displayAlign",
+                display_align.to_string(),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| {
+            TtmlAttribute {
+                name: name.to_string(),
+                value,
+            }
+        })
+        .collect(),
+        ..Default::default()
+    }
+}
+
+/// Checks whether the identifier is valid as the `xml:id`.
+fn is_xml_id(identifier: &str) -> bool {
+    let mut chars = identifier.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Converts the spans to the inline content with the styling attributes on the `<span>` elements.
+fn inlines_from_spans(
+    spans: &[CueSpan],
+    cue_start: Duration,
+) -> Vec<TtmlInline> {
+    let mut content = Vec::new();
+    for span in spans {
+        let mut inlines = Vec::new();
+        for (i, line) in span
+            .text
+            .split('\n')
+            .enumerate()
+        {
+            if i > 0 {
+                inlines.push(TtmlInline::Break);
+            }
+            if !line.is_empty() {
+                inlines.push(TtmlInline::Text(line.to_string()));
+            }
+        }
+
+        let attributes = [
+            (span.italic, "tts:fontStyle", "italic"),
+            (span.bold, "tts:fontWeight", "bold"),
+            (
+                span.underline,
+                "tts:textDecoration",
+                "underline",
+            ),
+        ]
+        .into_iter()
+        .filter(|(enabled, ..)| *enabled)
+        .map(|(_, name, value)| (name, value.to_string()))
+        .chain(
+            span.color
+                .clone()
+                .map(|color| ("tts:color", color)),
+        )
+        .map(|(name, value)| {
+            TtmlAttribute {
+                name: name.to_string(),
+                value,
+            }
+        })
+        .collect::<Vec<_>>();
+
+        if attributes.is_empty() && span.start.is_none() {
+            content.extend(inlines);
+        } else {
+            content.push(TtmlInline::Span(TtmlSpan {
+                timing: TtmlTiming {
+                    begin: span.start.map(|start| {
+                        start
+                            .saturating_sub(cue_start)
+                            .into()
+                    }),
+                    ..Default::default()
+                },
+                attributes,
+                content: inlines,
+                ..Default::default()
+            }));
+        }
+    }
+
+    content
+}
+
+/// The paragraph with the timings resolved to the document time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TtmlTimedParagraph<'a> {
+    /// The start time.
+    pub start: Duration,
+    /// The end time, or `None` if the paragraph is active indefinitely.
+    pub end: Option<Duration>,
+    /// The paragraph.
+    pub paragraph: &'a TtmlParagraph,
+}
+
+/// The attribute written as `name="value"`.
+///
+/// The name is qualified with the namespace prefix as written, e.g. `tts:color`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TtmlAttribute {
+    /// The qualified name.
+    pub name: String,
+    /// The value with the entities decoded.
+    pub value: String,
+}
+
+impl Display for TtmlAttribute {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(
+            f,
+            "{}=\"{}\"",
+            self.name,
+            escape_attribute(&self.value)
+        )
+    }
+}
+
+/// The timing parameters of the `<tt>` element.
+///
+/// ## Example
+/// ```
+/// use subtp::ttml::TtmlParameters;
+///
+/// let parameters = TtmlParameters {
+///     frame_rate: Some(30),
+///     frame_rate_multiplier: Some((1000, 1001)),
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(parameters.effective_frame_rate(), 30000.0 / 1001.0);
+/// assert_eq!(parameters.effective_tick_rate(), 30.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TtmlParameters {
+    /// The `ttp:frameRate`.
+    pub frame_rate: Option<u32>,
+    /// The `ttp:frameRateMultiplier` as the numerator and the denominator.
+    pub frame_rate_multiplier: Option<(u32, u32)>,
+    /// The `ttp:subFrameRate`.
+    pub sub_frame_rate: Option<u32>,
+    /// The `ttp:tickRate`.
+    pub tick_rate: Option<u32>,
+}
+
+impl TtmlParameters {
+    /// Gets the effective frame rate in frames per second,
+    /// which is the frame rate multiplied by the frame rate multiplier.
+    ///
+    /// The frame rate is `30` if not specified.
+    pub fn effective_frame_rate(&self) -> f64 {
+        let frame_rate = self.frame_rate.unwrap_or(30) as f64;
+        match self.frame_rate_multiplier {
+            | Some((numerator, denominator)) if denominator != 0 => {
+                frame_rate * numerator as f64 / denominator as f64
+            },
+            | _ => frame_rate,
+        }
+    }
+
+    /// Gets the effective sub-frame rate in sub-frames per frame.
+    ///
+    /// The sub-frame rate is `1` if not specified.
+    pub fn effective_sub_frame_rate(&self) -> f64 {
+        self.sub_frame_rate
+            .unwrap_or(1) as f64
+    }
+
+    /// Gets the effective tick rate in ticks per second.
+    ///
+    /// The tick rate is the frame rate multiplied by the sub-frame rate if not specified but the frame rate is,
+    /// otherwise `1`.
+    pub fn effective_tick_rate(&self) -> f64 {
+        match (self.tick_rate, self.frame_rate) {
+            | (Some(tick_rate), _) => tick_rate as f64,
+            | (None, Some(frame_rate)) => {
+                frame_rate as f64 * self.effective_sub_frame_rate()
+            },
+            | (None, None) => 1.0,
+        }
+    }
+}
+
+impl Display for TtmlParameters {
+    /// Renders the parameters as the attributes with the leading spaces.
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        if let Some(frame_rate) = self.frame_rate {
+            write!(f, " ttp:frameRate=\"{}\"", frame_rate)?;
+        }
+        if let Some((numerator, denominator)) = self.frame_rate_multiplier {
+            write!(
+                f,
+                " ttp:frameRateMultiplier=\"{} {}\"",
+                numerator, denominator
+            )?;
+        }
+        if let Some(sub_frame_rate) = self.sub_frame_rate {
+            write!(
+                f,
+                " ttp:subFrameRate=\"{}\"",
+                sub_frame_rate
+            )?;
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            write!(f, " ttp:tickRate=\"{}\"", tick_rate)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The `<head>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlHead {
+    /// The attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The `<styling>` element.
+    pub styling: Option<TtmlStyling>,
+    /// The `<layout>` element.
+    pub layout: Option<TtmlLayout>,
+    /// The other elements such as `<metadata>` kept as raw text.
+    pub others: Vec<String>,
+}
+
+impl TtmlHead {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<head")?;
+        write_attributes(f, &self.attributes)?;
+
+        if self.styling.is_none()
+            && self.layout.is_none()
+            && self.others.is_empty()
+        {
+            return writeln!(f, "/>");
+        }
+
+        writeln!(f, ">")?;
+        write_raw_elements(f, &self.others, depth + 1)?;
+        if let Some(styling) = &self.styling {
+            styling.write_xml(f, depth + 1)?;
+        }
+        if let Some(layout) = &self.layout {
+            layout.write_xml(f, depth + 1)?;
+        }
+        write_indent(f, depth)?;
+        writeln!(f, "</head>")
+    }
+}
+
+/// The `<styling>` element.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TtmlStyling {
+    /// The attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The `<style>` elements.
+    pub styles: Vec<TtmlStyle>,
+    /// The other elements kept as raw text.
+    pub others: Vec<String>,
+}
+
+impl TtmlStyling {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<styling")?;
+        write_attributes(f, &self.attributes)?;
+
+        if self.styles.is_empty() && self.others.is_empty() {
+            return writeln!(f, "/>");
+        }
+
+        writeln!(f, ">")?;
+        write_raw_elements(f, &self.others, depth + 1)?;
+        for style in &self.styles {
+            write_indent(f, depth + 1)?;
+            writeln!(f, "{}", style)?;
+        }
+        write_indent(f, depth)?;
+        writeln!(f, "</styling>")
+    }
+}
+
+/// The `<style>` element.
+///
+/// ## Example
+/// ```
+/// use subtp::ttml::TtmlAttribute;
+/// use subtp::ttml::TtmlStyle;
+///
+/// let style = TtmlStyle {
+///     id: Some("s1".to_string()),
+///     attributes: vec![TtmlAttribute {
+///         name: "tts:color".to_string(),
+///         value: "yellow".to_string(),
+///     }],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(style.attribute("tts:color"), Some("yellow"));
+/// assert_eq!(
+///     style.to_string(),
+///     "<style xml:id=\"s1\" tts:color=\"yellow\"/>".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct TtmlStyle {
+    /// The `xml:id`.
+    pub id: Option<String>,
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The other attributes such as the `tts:*` styling attributes.
+    pub attributes: Vec<TtmlAttribute>,
+}
+
+impl TtmlStyle {
+    /// Gets the value of the attribute by the qualified name.
+    pub fn attribute(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+}
+
+impl Display for TtmlStyle {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "<style")?;
+        write_common_attributes(
+            f,
+            &self.id,
+            &self.styles,
+            &None,
+            &TtmlTiming::default(),
+        )?;
+        write_attributes(f, &self.attributes)?;
+        write!(f, "/>")
+    }
+}
+
+/// The `<layout>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlLayout {
+    /// The attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The `<region>` elements.
+    pub regions: Vec<TtmlRegion>,
+    /// The other elements kept as raw text.
+    pub others: Vec<String>,
+}
+
+impl TtmlLayout {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<layout")?;
+        write_attributes(f, &self.attributes)?;
+
+        if self.regions.is_empty() && self.others.is_empty() {
+            return writeln!(f, "/>");
+        }
+
+        writeln!(f, ">")?;
+        write_raw_elements(f, &self.others, depth + 1)?;
+        for region in &self.regions {
+            region.write_xml(f, depth + 1)?;
+        }
+        write_indent(f, depth)?;
+        writeln!(f, "</layout>")
+    }
+}
+
+/// The `<region>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlRegion {
+    /// The `xml:id`.
+    pub id: Option<String>,
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The timing attributes.
+    pub timing: TtmlTiming,
+    /// The other attributes such as `tts:origin` and `tts:extent`.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The inline `<style>` elements.
+    pub inline_styles: Vec<TtmlStyle>,
+    /// The other elements kept as raw text.
+    pub others: Vec<String>,
+}
+
+impl TtmlRegion {
+    /// Gets the value of the attribute by the qualified name.
+    pub fn attribute(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<region")?;
+        write_common_attributes(
+            f,
+            &self.id,
+            &self.styles,
+            &None,
+            &self.timing,
+        )?;
+        write_attributes(f, &self.attributes)?;
+
+        if self.inline_styles.is_empty() && self.others.is_empty() {
+            return writeln!(f, "/>");
+        }
+
+        writeln!(f, ">")?;
+        write_raw_elements(f, &self.others, depth + 1)?;
+        for style in &self.inline_styles {
+            write_indent(f, depth + 1)?;
+            writeln!(f, "{}", style)?;
+        }
+        write_indent(f, depth)?;
+        writeln!(f, "</region>")
+    }
+}
+
+/// The `<body>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlBody {
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The `region`.
+    pub region: Option<String>,
+    /// The timing attributes.
+    pub timing: TtmlTiming,
+    /// The other attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The children, which are usually the `<div>` elements.
+    pub children: Vec<TtmlBlock>,
+}
+
+impl TtmlBody {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<body")?;
+        write_common_attributes(
+            f,
+            &None,
+            &self.styles,
+            &self.region,
+            &self.timing,
+        )?;
+        write_attributes(f, &self.attributes)?;
+        write_blocks(f, "body", &self.children, depth)
+    }
+}
+
+/// The block content of the `<body>` and the `<div>` elements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TtmlBlock {
+    /// The `<div>` element.
+    Div(TtmlDiv),
+    /// The `<p>` element.
+    Paragraph(TtmlParagraph),
+    /// The other element such as `<metadata>` kept as raw text.
+    Other(String),
+}
+
+impl TtmlBlock {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Div(div) => div.write_xml(f, depth),
+            | Self::Paragraph(paragraph) => {
+                write_indent(f, depth)?;
+                writeln!(f, "{}", paragraph)
+            },
+            | Self::Other(raw) => {
+                write_indent(f, depth)?;
+                writeln!(f, "{}", raw)
+            },
+        }
+    }
+}
+
+/// The `<div>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlDiv {
+    /// The `xml:id`.
+    pub id: Option<String>,
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The `region`.
+    pub region: Option<String>,
+    /// The timing attributes.
+    pub timing: TtmlTiming,
+    /// The other attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The children.
+    pub children: Vec<TtmlBlock>,
+}
+
+impl TtmlDiv {
+    fn write_xml(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        depth: usize,
+    ) -> std::fmt::Result {
+        write_indent(f, depth)?;
+        write!(f, "<div")?;
+        write_common_attributes(
+            f,
+            &self.id,
+            &self.styles,
+            &self.region,
+            &self.timing,
+        )?;
+        write_attributes(f, &self.attributes)?;
+        write_blocks(f, "div", &self.children, depth)
+    }
+}
+
+/// The `<p>` element.
+///
+/// ## Example
+/// ```
+/// use subtp::ttml::TtmlInline;
+/// use subtp::ttml::TtmlParagraph;
+/// use subtp::ttml::TtmlSpan;
+/// use subtp::ttml::TtmlTime;
+/// use subtp::ttml::TtmlTimeMetric;
+/// use subtp::ttml::TtmlTiming;
+///
+/// let paragraph = TtmlParagraph {
+///     timing: TtmlTiming {
+///         begin: Some(TtmlTime::OffsetTime {
+///             value: 1.0,
+///             metric: TtmlTimeMetric::Seconds,
+///         }),
+///         end: Some(TtmlTime::OffsetTime {
+///             value: 2.5,
+///             metric: TtmlTimeMetric::Seconds,
+///         }),
+///         ..Default::default()
+///     },
+///     content: vec![
+///         TtmlInline::Text("Hello,".to_string()),
+///         TtmlInline::Break,
+///         TtmlInline::Span(TtmlSpan {
+///             styles: vec!["italic".to_string()],
+///             content: vec![TtmlInline::Text("world!".to_string())],
+///             ..Default::default()
+///         }),
+///     ],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     paragraph.to_string(),
+///     "<p begin=\"1s\" end=\"2.5s\">Hello,<br/><span style=\"italic\">world!</span></p>".to_string()
+/// );
+/// assert_eq!(paragraph.plain_text(), "Hello,\nworld!");
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlParagraph {
+    /// The `xml:id`.
+    pub id: Option<String>,
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The `region`.
+    pub region: Option<String>,
+    /// The timing attributes.
+    pub timing: TtmlTiming,
+    /// The other attributes such as the `tts:*` styling attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The inline content.
+    pub content: Vec<TtmlInline>,
+}
+
+impl TtmlParagraph {
+    /// Gets the value of the attribute by the qualified name.
+    pub fn attribute(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+
+    /// Gets the plain text without markup.
+    ///
+    /// Whitespace is collapsed as the default `xml:space`
+    /// and each `<br/>` is converted to a newline.
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        push_plain_text(&self.content, &mut text);
+        text.split('\n')
+            .map(|line| {
+                line.split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Pushes the plain text of the inline content.
+fn push_plain_text(
+    content: &[TtmlInline],
+    text: &mut String,
+) {
+    for inline in content {
+        match inline {
+            | TtmlInline::Text(t) => text.push_str(&t.replace('\n', " ")),
+            | TtmlInline::Span(span) => push_plain_text(&span.content, text),
+            | TtmlInline::Break => text.push('\n'),
+            | TtmlInline::Other(_) => {},
+        }
+    }
+}
+
+impl Display for TtmlParagraph {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "<p")?;
+        write_common_attributes(
+            f,
+            &self.id,
+            &self.styles,
+            &self.region,
+            &self.timing,
+        )?;
+        write_attributes(f, &self.attributes)?;
+        write_inlines(f, "p", &self.content)
+    }
+}
+
+/// The inline content of the `<p>` and the `<span>` elements.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TtmlInline {
+    /// The text with the entities decoded.
+    Text(String),
+    /// The `<span>` element.
+    Span(TtmlSpan),
+    /// The `<br/>` element.
+    Break,
+    /// The other element such as `<set>` kept as raw text.
+    Other(String),
+}
+
+impl Display for TtmlInline {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Text(text) => {
+                write!(f, "{}", escape_text(text))
+            },
+            | Self::Span(span) => {
+                write!(f, "{}", span)
+            },
+            | Self::Break => {
+                write!(f, "<br/>")
+            },
+            | Self::Other(raw) => {
+                write!(f, "{}", raw)
+            },
+        }
+    }
+}
+
+/// The `<span>` element.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlSpan {
+    /// The `xml:id`.
+    pub id: Option<String>,
+    /// The referential styles of the `style` attribute.
+    pub styles: Vec<String>,
+    /// The `region`.
+    pub region: Option<String>,
+    /// The timing attributes.
+    pub timing: TtmlTiming,
+    /// The other attributes such as the `tts:*` styling attributes.
+    pub attributes: Vec<TtmlAttribute>,
+    /// The inline content.
+    pub content: Vec<TtmlInline>,
+}
+
+impl TtmlSpan {
+    /// Gets the value of the attribute by the qualified name.
+    pub fn attribute(
+        &self,
+        name: &str,
+    ) -> Option<&str> {
+        find_attribute(&self.attributes, name)
+    }
+}
+
+impl Display for TtmlSpan {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "<span")?;
+        write_common_attributes(
+            f,
+            &self.id,
+            &self.styles,
+            &self.region,
+            &self.timing,
+        )?;
+        write_attributes(f, &self.attributes)?;
+        write_inlines(f, "span", &self.content)
+    }
+}
+
+/// The timing attributes of `begin`, `end` and `dur`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TtmlTiming {
+    /// The `begin` relative to the begin of the parent element.
+    pub begin: Option<TtmlTime>,
+    /// The `end` relative to the begin of the parent element.
+    pub end: Option<TtmlTime>,
+    /// The `dur` relative to the begin of the element.
+    pub dur: Option<TtmlTime>,
+}
+
+impl TtmlTiming {
+    /// Resolves the begin and the end to the document time in the parallel time containment.
+    fn resolve(
+        &self,
+        parameters: &TtmlParameters,
+        parent_start: Duration,
+        parent_end: Option<Duration>,
+    ) -> (Duration, Option<Duration>) {
+        let start = parent_start
+            + self
+                .begin
+                .as_ref()
+                .map(|begin| begin.to_duration(parameters))
+                .unwrap_or_default();
+        let by_end = self
+            .end
+            .as_ref()
+            .map(|end| parent_start + end.to_duration(parameters));
+        let by_dur = self
+            .dur
+            .as_ref()
+            .map(|dur| start + dur.to_duration(parameters));
+
+        let end = [
+            by_end, by_dur, parent_end,
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        let start = match parent_end {
+            | Some(parent_end) => start.min(parent_end),
+            | None => start,
+        };
+        let end = end.map(|end| end.max(start));
+
+        (start, end)
+    }
+}
+
+/// The time expression.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use subtp::ttml::TtmlParameters;
+/// use subtp::ttml::TtmlTime;
+///
+/// let parameters = TtmlParameters {
+///     frame_rate: Some(25),
+///     tick_rate: Some(10_000_000),
+///     ..Default::default()
+/// };
+///
+/// let clock = TtmlTime::parse("00:00:01:12").unwrap();
+/// assert_eq!(clock.to_duration(&parameters), Duration::from_millis(1480));
+///
+/// let ticks = TtmlTime::parse("15000000t").unwrap();
+/// assert_eq!(ticks.to_duration(&parameters), Duration::from_millis(1500));
+/// assert_eq!(ticks.to_string(), "15000000t");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum TtmlTime {
+    /// The clock time of `hh:mm:ss` with optional fraction such as `00:00:01.500`.
+    ClockTime {
+        /// The hours.
+        hours: u32,
+        /// The minutes.
+        minutes: u8,
+        /// The seconds.
+        seconds: u8,
+        /// The digits of the fraction of seconds.
+        fraction: Option<String>,
+    },
+    /// The clock time of `hh:mm:ss:ff` with optional sub-frames such as `00:00:01:12.1`.
+    ClockTimeFrames {
+        /// The hours.
+        hours: u32,
+        /// The minutes.
+        minutes: u8,
+        /// The seconds.
+        seconds: u8,
+        /// The frames.
+        frames: u32,
+        /// The sub-frames.
+        sub_frames: Option<u32>,
+    },
+    /// The offset time such as `1.5s`, `40f` or `10000000t`.
+    OffsetTime {
+        /// The value.
+        value: f64,
+        /// The metric.
+        metric: TtmlTimeMetric,
+    },
+}
+
+impl TtmlTime {
+    /// Parses the time expression from the given text.
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        ttml_parser::time_expression(text)
+            .map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Converts the time expression to the duration with the timing parameters.
+    pub fn to_duration(
+        &self,
+        parameters: &TtmlParameters,
+    ) -> Duration {
+        let seconds = match self {
+            | Self::ClockTime {
+                hours,
+                minutes,
+                seconds,
+                fraction,
+            } => {
+                let fraction = fraction
+                    .as_ref()
+                    .and_then(|fraction| {
+                        format!("0.{}", fraction)
+                            .parse::<f64>()
+                            .ok()
+                    })
+                    .unwrap_or_default();
+                clock_seconds(*hours, *minutes, *seconds) + fraction
+            },
+            | Self::ClockTimeFrames {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                sub_frames,
+            } => {
+                let frames = *frames as f64
+                    + sub_frames.unwrap_or_default() as f64
+                        / parameters.effective_sub_frame_rate();
+                clock_seconds(*hours, *minutes, *seconds)
+                    + frames / parameters.effective_frame_rate()
+            },
+            | Self::OffsetTime {
+                value,
+                metric,
+            } => {
+                match metric {
+                    | TtmlTimeMetric::Hours => value * 3600.0,
+                    | TtmlTimeMetric::Minutes => value * 60.0,
+                    | TtmlTimeMetric::Seconds => *value,
+                    | TtmlTimeMetric::Milliseconds => value / 1000.0,
+                    | TtmlTimeMetric::Frames => {
+                        value / parameters.effective_frame_rate()
+                    },
+                    | TtmlTimeMetric::Ticks => {
+                        value / parameters.effective_tick_rate()
+                    },
+                }
+            },
+        };
+
+        Duration::from_nanos((seconds.max(0.0) * 1e9).round() as u64)
+    }
+}
+
+/// Gets the seconds of the clock time.
+fn clock_seconds(
+    hours: u32,
+    minutes: u8,
+    seconds: u8,
+) -> f64 {
+    hours as f64 * 3600.0 + minutes as f64 * 60.0 + seconds as f64
+}
+
+impl Display for TtmlTime {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::ClockTime {
+                hours,
+                minutes,
+                seconds,
+                fraction,
+            } => {
+                write!(
+                    f,
+                    "{:02}:{:02}:{:02}",
+                    hours, minutes, seconds
+                )?;
+                if let Some(fraction) = fraction {
+                    write!(f, ".{}", fraction)?;
+                }
+                Ok(())
+            },
+            | Self::ClockTimeFrames {
+                hours,
+                minutes,
+                seconds,
+                frames,
+                sub_frames,
+            } => {
+                write!(
+                    f,
+                    "{:02}:{:02}:{:02}:{:02}",
+                    hours, minutes, seconds, frames
+                )?;
+                if let Some(sub_frames) = sub_frames {
+                    write!(f, ".{}", sub_frames)?;
+                }
+                Ok(())
+            },
+            | Self::OffsetTime {
+                value,
+                metric,
+            } => {
+                write!(f, "{}{}", value, metric)
+            },
+        }
+    }
+}
+
+impl From<Duration> for TtmlTime {
+    /// Converts the duration to the clock time with milliseconds.
+    fn from(duration: Duration) -> Self {
+        let seconds = duration.as_secs();
+        Self::ClockTime {
+            hours: (seconds / 3600) as u32,
+            minutes: ((seconds % 3600) / 60) as u8,
+            seconds: (seconds % 60) as u8,
+            fraction: Some(format!(
+                "{:03}",
+                duration.subsec_millis()
+            )),
+        }
+    }
+}
+
+/// The metric of the offset time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TtmlTimeMetric {
+    /// The `h`.
+    Hours,
+    /// The `m`.
+    Minutes,
+    /// The `s`.
+    #[default]
+    Seconds,
+    /// The `ms`.
+    Milliseconds,
+    /// The `f`.
+    Frames,
+    /// The `t`.
+    Ticks,
+}
+
+impl Display for TtmlTimeMetric {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Hours => write!(f, "h"),
+            | Self::Minutes => write!(f, "m"),
+            | Self::Seconds => write!(f, "s"),
+            | Self::Milliseconds => write!(f, "ms"),
+            | Self::Frames => write!(f, "f"),
+            | Self::Ticks => write!(f, "t"),
+        }
+    }
+}
+
+/// Finds the value of the attribute by the qualified name.
+fn find_attribute<'a>(
+    attributes: &'a [TtmlAttribute],
+    name: &str,
+) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attribute| attribute.name == name)
+        .map(|attribute| attribute.value.as_str())
+}
+
+/// Escapes the text content.
+pub(crate) fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Escapes the attribute value.
+fn escape_attribute(value: &str) -> String {
+    escape_text(value).replace('"', "&quot;")
+}
+
+/// Writes the indent of two spaces per depth.
+fn write_indent(
+    f: &mut std::fmt::Formatter<'_>,
+    depth: usize,
+) -> std::fmt::Result {
+    write!(f, "{}", "  ".repeat(depth))
+}
+
+/// Writes the attributes with the leading spaces.
+fn write_attributes(
+    f: &mut std::fmt::Formatter<'_>,
+    attributes: &[TtmlAttribute],
+) -> std::fmt::Result {
+    for attribute in attributes {
+        write!(f, " {}", attribute)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the common attributes with the leading spaces.
+fn write_common_attributes(
+    f: &mut std::fmt::Formatter<'_>,
+    id: &Option<String>,
+    styles: &[String],
+    region: &Option<String>,
+    timing: &TtmlTiming,
+) -> std::fmt::Result {
+    if let Some(id) = id {
+        write!(
+            f,
+            " xml:id=\"{}\"",
+            escape_attribute(id)
+        )?;
+    }
+    if let Some(begin) = &timing.begin {
+        write!(f, " begin=\"{}\"", begin)?;
+    }
+    if let Some(end) = &timing.end {
+        write!(f, " end=\"{}\"", end)?;
+    }
+    if let Some(dur) = &timing.dur {
+        write!(f, " dur=\"{}\"", dur)?;
+    }
+    if !styles.is_empty() {
+        write!(
+            f,
+            " style=\"{}\"",
+            escape_attribute(&styles.join(" "))
+        )?;
+    }
+    if let Some(region) = region {
+        write!(
+            f,
+            " region=\"{}\"",
+            escape_attribute(region)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes the raw elements on each line.
+fn write_raw_elements(
+    f: &mut std::fmt::Formatter<'_>,
+    raw_elements: &[String],
+    depth: usize,
+) -> std::fmt::Result {
+    for raw in raw_elements {
+        write_indent(f, depth)?;
+        writeln!(f, "{}", raw)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the rest of the start tag, the block children and the end tag.
+fn write_blocks(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    children: &[TtmlBlock],
+    depth: usize,
+) -> std::fmt::Result {
+    if children.is_empty() {
+        return writeln!(f, "/>");
+    }
+
+    writeln!(f, ">")?;
+    for child in children {
+        child.write_xml(f, depth + 1)?;
+    }
+    write_indent(f, depth)?;
+    writeln!(f, "</{}>", name)
+}
+
+/// Writes the rest of the start tag, the inline content and the end tag.
+fn write_inlines(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    content: &[TtmlInline],
+) -> std::fmt::Result {
+    if content.is_empty() {
+        return write!(f, "/>");
+    }
+
+    write!(f, ">")?;
+    for inline in content {
+        write!(f, "{}", inline)?;
+    }
+    write!(f, "</{}>", name)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const TEXT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" xml:lang="en" ttp:frameRate="25" ttp:tickRate="10000000">
+  <head>
+    <metadata><ttm:title xmlns:ttm="http://www.w3.org/ns/ttml#metadata">Sample</ttm:title></metadata>
+    <styling>
+      <style xml:id="s1" tts:color="white" tts:fontStyle="italic"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 20%"/>
+    </layout>
+  </head>
+  <body region="bottom">
+    <div begin="10s">
+      <p xml:id="c1" begin="00:00:01.000" end="00:00:02.500">Hello, <span style="s1">world</span>!<br/>Second &amp; line</p>
+      <p begin="00:00:03:05" dur="20000000t" tts:textAlign="center">Frames and ticks</p>
+    </div>
+  </body>
+</tt>
+"#;
+
+    #[test]
+    fn parse() {
+        let ttml = Ttml::parse(TEXT).unwrap();
+
+        assert_eq!(
+            ttml.style("s1")
+                .unwrap()
+                .attribute("tts:color"),
+            Some("white")
+        );
+        assert_eq!(
+            ttml.region("bottom")
+                .unwrap()
+                .attribute("tts:extent"),
+            Some("80% 20%")
+        );
+        assert!(ttml.style("s2").is_none());
+
+        let paragraphs = ttml.timed_paragraphs();
+        assert_eq!(paragraphs.len(), 2);
+        assert_eq!(
+            paragraphs[0].start,
+            Duration::from_secs(11)
+        );
+        assert_eq!(
+            paragraphs[0].end,
+            Some(Duration::from_millis(12500))
+        );
+        assert_eq!(
+            paragraphs[0]
+                .paragraph
+                .plain_text(),
+            "Hello, world!\nSecond & line"
+        );
+        assert_eq!(
+            paragraphs[1].start,
+            Duration::from_millis(13200)
+        );
+        assert_eq!(
+            paragraphs[1].end,
+            Some(Duration::from_millis(15200))
+        );
+        assert_eq!(
+            paragraphs[1]
+                .paragraph
+                .attribute("tts:textAlign"),
+            Some("center")
+        );
+    }
+
+    #[test]
+    fn render() {
+        let ttml = Ttml::parse(TEXT).unwrap();
+        assert_eq!(ttml.render(), TEXT);
+
+        assert_eq!(
+            Ttml::default().render(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tt/>\n"
+        );
+    }
+
+    #[test]
+    fn resolve_timing() {
+        let parameters = TtmlParameters::default();
+        let seconds = |value: f64| {
+            Some(TtmlTime::OffsetTime {
+                value,
+                metric: TtmlTimeMetric::Seconds,
+            })
+        };
+
+        let timing = TtmlTiming {
+            begin: seconds(1.0),
+            end: seconds(5.0),
+            dur: seconds(2.0),
+        };
+        assert_eq!(
+            timing.resolve(
+                &parameters,
+                Duration::from_secs(10),
+                None
+            ),
+            (
+                Duration::from_secs(11),
+                Some(Duration::from_secs(13))
+            )
+        );
+
+        let timing = TtmlTiming {
+            begin: seconds(1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            timing.resolve(&parameters, Duration::ZERO, None),
+            (Duration::from_secs(1), None)
+        );
+        assert_eq!(
+            timing.resolve(
+                &parameters,
+                Duration::ZERO,
+                Some(Duration::from_secs(4))
+            ),
+            (
+                Duration::from_secs(1),
+                Some(Duration::from_secs(4))
+            )
+        );
+    }
+
+    #[test]
+    fn time_to_duration() {
+        let parameters = TtmlParameters {
+            frame_rate: Some(30),
+            frame_rate_multiplier: Some((1000, 1001)),
+            sub_frame_rate: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            TtmlTime::parse("01:02:03.5")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_millis(3_723_500)
+        );
+        assert_eq!(
+            TtmlTime::parse("00:00:00:30")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_nanos(1_001_000_000)
+        );
+        assert_eq!(
+            TtmlTime::parse("00:00:00:00.1")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_nanos(16_683_333)
+        );
+        assert_eq!(
+            TtmlTime::parse("1.5h")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_secs(5400)
+        );
+        assert_eq!(
+            TtmlTime::parse("250ms")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            TtmlTime::parse("60t")
+                .unwrap()
+                .to_duration(&parameters),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            TtmlTime::parse("10t")
+                .unwrap()
+                .to_duration(&TtmlParameters::default()),
+            Duration::from_secs(10)
+        );
+    }
+
+    #[test]
+    fn from_duration_to_time() {
+        let time: TtmlTime = Duration::from_millis(3_723_045).into();
+        assert_eq!(time.to_string(), "01:02:03.045");
+    }
+
+    #[test]
+    fn subtitle_format() {
+        let text = r#"<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+  <head>
+    <styling>
+      <style xml:id="s1" tts:fontStyle="italic"/>
+      <style xml:id="s2" style="s1" tts:color="yellow"/>
+    </styling>
+    <layout>
+      <region xml:id="top" tts:origin="10% 5%" tts:extent="80% 20%"/>
+    </layout>
+  </head>
+  <body>
+    <div>
+      <p xml:id="c1" begin="1s" end="2s" region="top" tts:textAlign="left">
+        Hello, <span style="s2">world</span>
+        <br/>
+        <span begin="0.5s" tts:fontWeight="bold">again</span>
+      </p>
+    </div>
+  </body>
+</tt>
+"#;
+        let ttml = Ttml::parse(text).unwrap();
+        let cues = ttml.cues();
+
+        let expected = vec![Cue {
+            identifier: Some("c1".to_string()),
+            start: Duration::from_secs(1),
+            end: Duration::from_secs(2),
+            spans: vec![
+                CueSpan {
+                    text: "Hello, ".to_string(),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "world".to_string(),
+                    italic: true,
+                    color: Some("yellow".to_string()),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "\n".to_string(),
+                    ..Default::default()
+                },
+                CueSpan {
+                    text: "again".to_string(),
+                    start: Some(Duration::from_millis(1500)),
+                    bold: true,
+                    ..Default::default()
+                },
+            ],
+            position: Some(CuePosition {
+                line: Some(CueLine::Percentage(5.0)),
+                line_anchor: None,
+                position: Some(50.0),
+                position_anchor: Some(CueAnchor::Center),
+                size: Some(80.0),
+                align: Some(CueAlignment::Left),
+            }),
+            style: None,
+        }];
+        assert_eq!(cues, expected);
+
+        let ttml = Ttml::from_cues(cues);
+        assert_eq!(
+            ttml.render(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling">
+  <head>
+    <layout>
+      <region xml:id="r1" tts:origin="10% 5%" tts:extent="80% 95%" tts:displayAlign="before"/>
+    </layout>
+  </head>
+  <body>
+    <div>
+      <p xml:id="c1" begin="00:00:01.000" end="00:00:02.000" region="r1" tts:textAlign="left">Hello, <span tts:fontStyle="italic" tts:color="yellow">world</span><br/><span begin="00:00:00.500" tts:fontWeight="bold">again</span></p>
+    </div>
+  </body>
+</tt>
+"#
+        );
+        assert_eq!(ttml.cues(), expected);
+    }
+}
@@ -0,0 +1,880 @@
+//! A parser implementation for the Timed Text Markup Language format.
+
+pub(crate) use ttml_parser::time_expression;
+pub(crate) use ttml_parser::ttml;
+
+use crate::ttml::TtmlAttribute;
+use crate::ttml::TtmlLayout;
+use crate::ttml::TtmlParameters;
+use crate::ttml::TtmlStyling;
+use crate::ttml::TtmlTime;
+use crate::ttml::TtmlTiming;
+
+/// The maximum nesting depth of elements, beyond which parsing fails
+/// instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Gets the local name of an element without the namespace prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':')
+        .next()
+        .unwrap_or(name)
+}
+
+/// Decodes the predefined entities and the character references of XML.
+pub(crate) fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded_char = rest
+            .find(';')
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match decoded_char {
+            | Some((c, end)) => {
+                decoded.push(c);
+                rest = &rest[end + 1..];
+            },
+            | None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes an entity name or a character reference without `&` and `;`.
+fn decode_entity(name: &str) -> Option<char> {
+    match name {
+        | "amp" => Some('&'),
+        | "lt" => Some('<'),
+        | "gt" => Some('>'),
+        | "quot" => Some('"'),
+        | "apos" => Some('\''),
+        | _ => {
+            let code = if let Some(hex) = name
+                .strip_prefix("#x")
+                .or_else(|| name.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                name.strip_prefix('#')?
+                    .parse()
+                    .ok()?
+            };
+            char::from_u32(code)
+        },
+    }
+}
+
+/// Takes the value of the attribute by the name out of the attributes.
+fn take_attribute(
+    attributes: &mut Vec<TtmlAttribute>,
+    name: &str,
+) -> Option<String> {
+    let index = attributes
+        .iter()
+        .position(|attribute| attribute.name == name)?;
+    Some(attributes.remove(index).value)
+}
+
+/// Takes the time expression of the attribute by the name out of the attributes.
+fn take_time(
+    attributes: &mut Vec<TtmlAttribute>,
+    name: &str,
+) -> Result<Option<TtmlTime>, &'static str> {
+    take_attribute(attributes, name)
+        .map(|value| time_expression(value.trim()).or(Err("time expression")))
+        .transpose()
+}
+
+/// The attributes commonly used by the content and the layout elements.
+pub(crate) struct CommonAttributes {
+    pub(crate) id: Option<String>,
+    pub(crate) styles: Vec<String>,
+    pub(crate) region: Option<String>,
+    pub(crate) timing: TtmlTiming,
+    pub(crate) attributes: Vec<TtmlAttribute>,
+}
+
+impl CommonAttributes {
+    /// Splits the common attributes from the other attributes.
+    fn from_attributes(
+        mut attributes: Vec<TtmlAttribute>
+    ) -> Result<Self, &'static str> {
+        let id = take_attribute(&mut attributes, "xml:id");
+        let styles = take_attribute(&mut attributes, "style")
+            .map(|styles| {
+                styles
+                    .split_whitespace()
+                    .map(|style| style.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let region = take_attribute(&mut attributes, "region");
+        let timing = TtmlTiming {
+            begin: take_time(&mut attributes, "begin")?,
+            end: take_time(&mut attributes, "end")?,
+            dur: take_time(&mut attributes, "dur")?,
+        };
+
+        Ok(Self {
+            id,
+            styles,
+            region,
+            timing,
+            attributes,
+        })
+    }
+}
+
+/// Splits the timing parameters from the other attributes of the root element.
+fn parameters_from_attributes(
+    attributes: &mut Vec<TtmlAttribute>
+) -> Result<TtmlParameters, &'static str> {
+    let mut number = |name: &str| {
+        take_attribute(attributes, name)
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<u32>()
+                    .or(Err("positive integer"))
+            })
+            .transpose()
+    };
+    let frame_rate = number("ttp:frameRate")?;
+    let sub_frame_rate = number("ttp:subFrameRate")?;
+    let tick_rate = number("ttp:tickRate")?;
+    let frame_rate_multiplier =
+        take_attribute(attributes, "ttp:frameRateMultiplier")
+            .map(|value| {
+                let mut values = value.split_whitespace();
+                match (
+                    values
+                        .next()
+                        .and_then(|v| v.parse::<u32>().ok()),
+                    values
+                        .next()
+                        .and_then(|v| v.parse::<u32>().ok()),
+                    values.next(),
+                ) {
+                    | (Some(numerator), Some(denominator), None) => {
+                        Ok((numerator, denominator))
+                    },
+                    | _ => Err("frame rate multiplier"),
+                }
+            })
+            .transpose()?;
+
+    Ok(TtmlParameters {
+        frame_rate,
+        frame_rate_multiplier,
+        sub_frame_rate,
+        tick_rate,
+    })
+}
+
+/// The child of the head element.
+enum HeadChild {
+    Styling(TtmlStyling),
+    Layout(TtmlLayout),
+    Other(String),
+}
+
+peg::parser! {
+    /// The parser for the Timed Text Markup Language format.
+    grammar ttml_parser() for str {
+        use crate::ttml::Ttml;
+        use crate::ttml::TtmlBlock;
+        use crate::ttml::TtmlBody;
+        use crate::ttml::TtmlDiv;
+        use crate::ttml::TtmlHead;
+        use crate::ttml::TtmlInline;
+        use crate::ttml::TtmlParagraph;
+        use crate::ttml::TtmlRegion;
+        use crate::ttml::TtmlSpan;
+        use crate::ttml::TtmlStyle;
+        use crate::ttml::TtmlTimeMetric;
+        use super::CommonAttributes;
+        use super::HeadChild;
+        use super::MAX_DEPTH;
+        use super::TtmlAttribute;
+        use super::TtmlParameters;
+        use super::TtmlTime;
+        use super::decode_entities;
+        use super::local_name;
+        use super::parameters_from_attributes;
+        use super::split_results;
+        use super::take_attribute;
+
+        /// Whitespace including newlines.
+        rule whitespace() = [' ' | '\t' | '\r' | '\n']
+
+        /// Any-digit number.
+        rule number() -> u32
+            = n:$(['0'..='9']+) {?
+                n.parse().or(Err("number in u32"))
+            }
+
+        /// Two-digit number.
+        rule two_number() -> u8
+            = n:$(['0'..='9']['0'..='9']) {?
+                n.parse().or(Err("two-digit number"))
+            }
+
+        /// Clock time.
+        rule clock_time() -> TtmlTime
+            = hours:number() ":" minutes:two_number() ":" seconds:two_number()
+                ":" frames:number() sub_frames:("." s:number() { s })?
+            {
+                TtmlTime::ClockTimeFrames {
+                    hours,
+                    minutes,
+                    seconds,
+                    frames,
+                    sub_frames,
+                }
+            }
+            / hours:number() ":" minutes:two_number() ":" seconds:two_number()
+                fraction:("." f:$(['0'..='9']+) { f.to_string() })?
+            {
+                TtmlTime::ClockTime {
+                    hours,
+                    minutes,
+                    seconds,
+                    fraction,
+                }
+            }
+
+        /// Metric of offset time.
+        rule time_metric() -> TtmlTimeMetric
+            = "h" { TtmlTimeMetric::Hours }
+            / "ms" { TtmlTimeMetric::Milliseconds }
+            / "m" { TtmlTimeMetric::Minutes }
+            / "s" { TtmlTimeMetric::Seconds }
+            / "f" { TtmlTimeMetric::Frames }
+            / "t" { TtmlTimeMetric::Ticks }
+
+        /// Offset time.
+        rule offset_time() -> TtmlTime
+            = value:$(['0'..='9']+ ("." ['0'..='9']+)?) metric:time_metric()
+            {?
+                Ok(TtmlTime::OffsetTime {
+                    value: value.parse().or(Err("offset time value"))?,
+                    metric,
+                })
+            }
+
+        /// Time expression.
+        pub(crate) rule time_expression() -> TtmlTime
+            = clock_time() / offset_time()
+
+        /// Name of an element or an attribute.
+        rule name() -> &'input str
+            = $(['a'..='z' | 'A'..='Z' | '_' | ':']
+                ['a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':' | '-' | '.']*)
+
+        /// Attribute.
+        rule attribute() -> TtmlAttribute
+            = whitespace()+ name:name() whitespace()* "=" whitespace()*
+                value:("\"" v:$([^'"']*) "\"" { v } / "'" v:$([^'\'']*) "'" { v })
+            {
+                TtmlAttribute {
+                    name: name.to_string(),
+                    value: decode_entities(value),
+                }
+            }
+
+        /// Comment.
+        rule comment()
+            = "<!--" (!"-->" [_])* "-->"
+
+        /// Processing instruction including the XML declaration.
+        rule processing_instruction()
+            = "<?" (!"?>" [_])* "?>"
+
+        /// Document type declaration.
+        rule doctype()
+            = "<!DOCTYPE" [^'>']* ">"
+
+        /// Nodes ignored between elements.
+        rule ignorable()
+            = (whitespace() / comment() / processing_instruction())*
+
+        /// Character data section.
+        rule cdata() -> &'input str
+            = "<![CDATA[" t:$((!"]]>" [_])*) "]]>" { t }
+
+        /// Character data.
+        rule text() -> String
+            = t:$([^'<']+) { decode_entities(t) }
+
+        /// Start tag of an element with the local name until the end of attributes.
+        rule start_tag(local: &'static str) -> Vec<TtmlAttribute>
+            = "<" n:name() ({?
+                if local_name(n) == local {
+                    Ok(())
+                } else {
+                    Err(local)
+                }
+            }) attributes:attribute()* whitespace()*
+            {
+                attributes
+            }
+
+        /// End tag of an element with the local name.
+        rule end_tag(local: &'static str)
+            = "</" n:name() whitespace()* ">" {?
+                if local_name(n) == local {
+                    Ok(())
+                } else {
+                    Err(local)
+                }
+            }
+
+        /// Start tag with the common attributes.
+        rule start_tag_common(local: &'static str) -> CommonAttributes
+            = attributes:start_tag(local) {?
+                CommonAttributes::from_attributes(attributes)
+            }
+
+        /// Element-only children of an element.
+        rule children<T>(local: &'static str, child: rule<T>) -> Vec<T>
+            = "/>" { vec![] }
+            / ">" ignorable() children:(c:child() ignorable() { c })* end_tag(local)
+            {
+                children
+            }
+
+        /// Any element kept as the raw text.
+        rule raw_element(depth: usize) -> String
+            = raw:$(any_element(depth)) { raw.to_string() }
+
+        /// Any element except the typed ones kept as the raw text.
+        rule other_element(typed: &'static [&'static str], depth: usize) -> String
+            = &("<" n:name() {?
+                if typed.contains(&local_name(n)) {
+                    Err("typed element")
+                } else {
+                    Ok(())
+                }
+            })
+            raw:raw_element(depth)
+            {
+                raw
+            }
+
+        /// Nesting of the depth up to the maximum.
+        rule nesting(depth: usize)
+            = "" {?
+                if depth < MAX_DEPTH {
+                    Ok(())
+                } else {
+                    Err("nesting depth of at most 128")
+                }
+            }
+
+        /// Any element.
+        rule any_element(depth: usize)
+            = nesting(depth) "<" n:name() attribute()* whitespace()*
+                (
+                    "/>"
+                    / ">"
+                        (any_element(depth + 1) / comment() / processing_instruction() / cdata() / [^'<']+)*
+                        "</" e:name() whitespace()* ">" {?
+                            if e == n {
+                                Ok(())
+                            } else {
+                                Err("matching end tag")
+                            }
+                        }
+                )
+
+        /// Style element.
+        rule style() -> TtmlStyle
+            = attributes:start_tag("style") ("/>" / ">" ignorable() end_tag("style"))
+            {
+                let mut attributes = attributes;
+                TtmlStyle {
+                    id: take_attribute(&mut attributes, "xml:id"),
+                    styles: take_attribute(&mut attributes, "style")
+                        .map(|styles| {
+                            styles
+                                .split_whitespace()
+                                .map(|style| style.to_string())
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    attributes,
+                }
+            }
+
+        /// Child of the styling element.
+        rule styling_child(depth: usize) -> Result<TtmlStyle, String>
+            = s:style() { Ok(s) }
+            / r:other_element(&["style"], depth) { Err(r) }
+
+        /// Styling element.
+        rule styling() -> TtmlStyling
+            = attributes:start_tag("styling") children:children("styling", <styling_child(3)>)
+            {
+                let (styles, others) = split_results(children);
+                TtmlStyling {
+                    attributes,
+                    styles,
+                    others,
+                }
+            }
+
+        /// Region element.
+        rule region() -> TtmlRegion
+            = common:start_tag_common("region") children:children("region", <styling_child(4)>)
+            {
+                let (styles, others) = split_results(children);
+                TtmlRegion {
+                    id: common.id,
+                    styles: common.styles,
+                    timing: common.timing,
+                    attributes: common.attributes,
+                    inline_styles: styles,
+                    others,
+                }
+            }
+
+        /// Child of the layout element.
+        rule layout_child() -> Result<TtmlRegion, String>
+            = r:region() { Ok(r) }
+            / r:other_element(&["region"], 3) { Err(r) }
+
+        /// Layout element.
+        rule layout() -> TtmlLayout
+            = attributes:start_tag("layout") children:children("layout", <layout_child()>)
+            {
+                let (regions, others) = split_results(children);
+                TtmlLayout {
+                    attributes,
+                    regions,
+                    others,
+                }
+            }
+
+        /// Child of the head element.
+        rule head_child() -> HeadChild
+            = s:styling() { HeadChild::Styling(s) }
+            / l:layout() { HeadChild::Layout(l) }
+            / r:other_element(&["styling", "layout"], 2) { HeadChild::Other(r) }
+
+        /// Head element.
+        rule head() -> TtmlHead
+            = attributes:start_tag("head") children:children("head", <head_child()>)
+            {
+                let mut head = TtmlHead {
+                    attributes,
+                    ..Default::default()
+                };
+                for child in children {
+                    match child {
+                        | HeadChild::Styling(styling) => head.styling = Some(styling),
+                        | HeadChild::Layout(layout) => head.layout = Some(layout),
+                        | HeadChild::Other(other) => head.others.push(other),
+                    }
+                }
+                head
+            }
+
+        /// Inline content of the paragraph and the span elements.
+        rule inline(depth: usize) -> Option<TtmlInline>
+            = s:span(depth) { Some(TtmlInline::Span(s)) }
+            / br() { Some(TtmlInline::Break) }
+            / t:cdata() { Some(TtmlInline::Text(t.to_string())) }
+            / comment() { None }
+            / processing_instruction() { None }
+            / r:other_element(&["span", "br"], depth) { Some(TtmlInline::Other(r)) }
+            / t:text() { Some(TtmlInline::Text(t)) }
+
+        /// Inline children of an element.
+        rule inline_children(local: &'static str, depth: usize) -> Vec<TtmlInline>
+            = "/>" { vec![] }
+            / ">" content:inline(depth)* end_tag(local)
+            {
+                content.into_iter().flatten().collect()
+            }
+
+        /// Line break element.
+        rule br()
+            = start_tag("br") ("/>" / ">" ignorable() end_tag("br"))
+
+        /// Span element.
+        rule span(depth: usize) -> TtmlSpan
+            = nesting(depth) common:start_tag_common("span") content:inline_children("span", depth + 1)
+            {
+                TtmlSpan {
+                    id: common.id,
+                    styles: common.styles,
+                    region: common.region,
+                    timing: common.timing,
+                    attributes: common.attributes,
+                    content,
+                }
+            }
+
+        /// Paragraph element.
+        rule paragraph(depth: usize) -> TtmlParagraph
+            = nesting(depth) common:start_tag_common("p") content:inline_children("p", depth + 1)
+            {
+                TtmlParagraph {
+                    id: common.id,
+                    styles: common.styles,
+                    region: common.region,
+                    timing: common.timing,
+                    attributes: common.attributes,
+                    content,
+                }
+            }
+
+        /// Division element.
+        rule div(depth: usize) -> TtmlDiv
+            = nesting(depth) common:start_tag_common("div") children:children("div", <block(depth + 1)>)
+            {
+                TtmlDiv {
+                    id: common.id,
+                    styles: common.styles,
+                    region: common.region,
+                    timing: common.timing,
+                    attributes: common.attributes,
+                    children,
+                }
+            }
+
+        /// Block content of the body and the division elements.
+        rule block(depth: usize) -> TtmlBlock
+            = d:div(depth) { TtmlBlock::Div(d) }
+            / p:paragraph(depth) { TtmlBlock::Paragraph(p) }
+            / r:other_element(&["div", "p"], depth) { TtmlBlock::Other(r) }
+
+        /// Body element.
+        rule body() -> TtmlBody
+            = common:start_tag_common("body") children:children("body", <block(2)>)
+            {
+                TtmlBody {
+                    styles: common.styles,
+                    region: common.region,
+                    timing: common.timing,
+                    attributes: common.attributes,
+                    children,
+                }
+            }
+
+        /// Start tag of the root element with the parameters.
+        rule tt_start_tag() -> (Vec<TtmlAttribute>, TtmlParameters)
+            = attributes:start_tag("tt") {?
+                let mut attributes = attributes;
+                let parameters = parameters_from_attributes(&mut attributes)?;
+                Ok((attributes, parameters))
+            }
+
+        /// Root element.
+        rule tt() -> Ttml
+            = start:tt_start_tag()
+                content:(
+                    "/>" { (None, None) }
+                    / ">" ignorable() head:head()? ignorable() body:body()? ignorable() end_tag("tt")
+                    { (head, body) }
+                )
+            {
+                Ttml {
+                    attributes: start.0,
+                    parameters: start.1,
+                    head: content.0,
+                    body: content.1,
+                }
+            }
+
+        /// The entire Timed Text Markup Language document.
+        pub(crate) rule ttml() -> Ttml
+            = "\u{feff}"? (whitespace() / comment() / processing_instruction() / doctype())*
+                tt:tt()
+                (whitespace() / comment() / processing_instruction())*
+            {
+                tt
+            }
+    }
+}
+
+/// Splits the parsed children into the typed ones and the raw ones.
+fn split_results<T>(children: Vec<Result<T, String>>) -> (Vec<T>, Vec<String>) {
+    let mut typed = Vec::new();
+    let mut raw = Vec::new();
+    for child in children {
+        match child {
+            | Ok(child) => typed.push(child),
+            | Err(child) => raw.push(child),
+        }
+    }
+    (typed, raw)
+}
+
+#[cfg(test)]
+mod test {
+    use super::ttml_parser;
+    use crate::ttml::*;
+
+    #[test]
+    fn time_expression() {
+        assert_eq!(
+            ttml_parser::time_expression("00:00:01.500").unwrap(),
+            TtmlTime::ClockTime {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                fraction: Some("500".to_string()),
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("100:00:01").unwrap(),
+            TtmlTime::ClockTime {
+                hours: 100,
+                minutes: 0,
+                seconds: 1,
+                fraction: None,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("00:00:01:12").unwrap(),
+            TtmlTime::ClockTimeFrames {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                frames: 12,
+                sub_frames: None,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("00:00:01:12.1").unwrap(),
+            TtmlTime::ClockTimeFrames {
+                hours: 0,
+                minutes: 0,
+                seconds: 1,
+                frames: 12,
+                sub_frames: Some(1),
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("1.5s").unwrap(),
+            TtmlTime::OffsetTime {
+                value: 1.5,
+                metric: TtmlTimeMetric::Seconds,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("250ms").unwrap(),
+            TtmlTime::OffsetTime {
+                value: 250.0,
+                metric: TtmlTimeMetric::Milliseconds,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("2m").unwrap(),
+            TtmlTime::OffsetTime {
+                value: 2.0,
+                metric: TtmlTimeMetric::Minutes,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("40f").unwrap(),
+            TtmlTime::OffsetTime {
+                value: 40.0,
+                metric: TtmlTimeMetric::Frames,
+            }
+        );
+        assert_eq!(
+            ttml_parser::time_expression("10000000t").unwrap(),
+            TtmlTime::OffsetTime {
+                value: 10000000.0,
+                metric: TtmlTimeMetric::Ticks,
+            }
+        );
+        assert!(ttml_parser::time_expression("00:01").is_err());
+        assert!(ttml_parser::time_expression("1.5").is_err());
+        assert!(ttml_parser::time_expression("1x").is_err());
+    }
+
+    #[test]
+    fn ttml() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- comment -->
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" xmlns:tts="http://www.w3.org/ns/ttml#styling" ttp:frameRate="25" ttp:frameRateMultiplier="1000 1001" xml:lang="en">
+  <head>
+    <metadata><ttm:title xmlns:ttm="http://www.w3.org/ns/ttml#metadata">Sample</ttm:title></metadata>
+    <styling>
+      <style xml:id="s1" tts:color="white" tts:fontStyle="italic"/>
+    </styling>
+    <layout>
+      <region xml:id="bottom" tts:origin="10% 80%" tts:extent="80% 20%"/>
+    </layout>
+  </head>
+  <body region="bottom">
+    <div>
+      <p xml:id="c1" begin="00:00:01.000" end="00:00:02.500">Hello, <span style="s1">world</span>!<br/>Second &amp; line</p>
+      <p begin="75f" dur="1s"><![CDATA[<raw>]]></p>
+    </div>
+  </body>
+</tt>
+"#;
+        let ttml = ttml_parser::ttml(text).unwrap();
+
+        assert_eq!(
+            ttml.parameters,
+            TtmlParameters {
+                frame_rate: Some(25),
+                frame_rate_multiplier: Some((1000, 1001)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(ttml.attributes.len(), 4);
+
+        let head = ttml.head.unwrap();
+        assert_eq!(head.others.len(), 1);
+        assert_eq!(
+            head.styling.unwrap().styles,
+            vec![TtmlStyle {
+                id: Some("s1".to_string()),
+                attributes: vec![
+                    TtmlAttribute {
+                        name: "tts:color".to_string(),
+                        value: "white".to_string(),
+                    },
+                    TtmlAttribute {
+                        name: "tts:fontStyle".to_string(),
+                        value: "italic".to_string(),
+                    },
+                ],
+                ..Default::default()
+            }]
+        );
+        assert_eq!(
+            head.layout.unwrap().regions[0].id,
+            Some("bottom".to_string())
+        );
+
+        let body = ttml.body.unwrap();
+        assert_eq!(body.region, Some("bottom".to_string()));
+        let div = match &body.children[0] {
+            | TtmlBlock::Div(div) => div,
+            | _ => panic!("Expected a div"),
+        };
+        assert_eq!(div.children.len(), 2);
+        assert_eq!(
+            div.children[0],
+            TtmlBlock::Paragraph(TtmlParagraph {
+                id: Some("c1".to_string()),
+                timing: TtmlTiming {
+                    begin: Some(TtmlTime::ClockTime {
+                        hours: 0,
+                        minutes: 0,
+                        seconds: 1,
+                        fraction: Some("000".to_string()),
+                    }),
+                    end: Some(TtmlTime::ClockTime {
+                        hours: 0,
+                        minutes: 0,
+                        seconds: 2,
+                        fraction: Some("500".to_string()),
+                    }),
+                    dur: None,
+                },
+                content: vec![
+                    TtmlInline::Text("Hello, ".to_string()),
+                    TtmlInline::Span(TtmlSpan {
+                        styles: vec!["s1".to_string()],
+                        content: vec![TtmlInline::Text(
+                            "world".to_string()
+                        )],
+                        ..Default::default()
+                    }),
+                    TtmlInline::Text("!".to_string()),
+                    TtmlInline::Break,
+                    TtmlInline::Text("Second & line".to_string()),
+                ],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            div.children[1],
+            TtmlBlock::Paragraph(TtmlParagraph {
+                timing: TtmlTiming {
+                    begin: Some(TtmlTime::OffsetTime {
+                        value: 75.0,
+                        metric: TtmlTimeMetric::Frames,
+                    }),
+                    end: None,
+                    dur: Some(TtmlTime::OffsetTime {
+                        value: 1.0,
+                        metric: TtmlTimeMetric::Seconds,
+                    }),
+                },
+                content: vec![TtmlInline::Text(
+                    "<raw>".to_string()
+                )],
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn prefixed_elements() {
+        let text = r#"<tt:tt xmlns:tt="http://www.w3.org/ns/ttml"><tt:body><tt:div><tt:p begin="1s" end="2s">Hello</tt:p></tt:div></tt:body></tt:tt>"#;
+        let ttml = ttml_parser::ttml(text).unwrap();
+        assert!(ttml.head.is_none());
+        assert_eq!(
+            ttml.body
+                .unwrap()
+                .children
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(ttml_parser::ttml("").is_err());
+        assert!(ttml_parser::ttml("<tt><body></tt>").is_err());
+        assert!(
+            ttml_parser::ttml(
+                r#"<tt><body><div><p begin="soon">Hello</p></div></body></tt>"#
+            )
+            .is_err()
+        );
+        assert!(ttml_parser::ttml(r#"<tt ttp:frameRate="fast"/>"#).is_err());
+        assert!(
+            ttml_parser::ttml(
+                r#"<tt><body><div><p>Hello</span></p></div></body></tt>"#
+            )
+            .is_err()
+        );
+
+        // Deep nesting fails instead of overflowing the stack.
+        let nested = |element: &str, depth| {
+            format!(
+                "<tt><body><div><p>{}Hello{}</p></div></body></tt>",
+                format!("<{}>", element).repeat(depth),
+                format!("</{}>", element).repeat(depth)
+            )
+        };
+        for element in ["span", "x"] {
+            assert!(ttml_parser::ttml(&nested(element, 100)).is_ok());
+            assert!(ttml_parser::ttml(&nested(element, 2_000)).is_err());
+        }
+        assert!(
+            ttml_parser::ttml(&format!(
+                "<tt><body>{}{}</body></tt>",
+                "<div>".repeat(2_000),
+                "</div>".repeat(2_000)
+            ))
+            .is_err()
+        );
+    }
+}
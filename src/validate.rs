@@ -0,0 +1,561 @@
+//! Validation of the timeline of cues with auto-fixes.
+//!
+//! The [`Validate`] is implemented for [`SubRip`](crate::srt::SubRip) and [`WebVtt`](crate::vtt::WebVtt),
+//! where cues are indexed from 0 in order of the document and the other blocks of WebVTT are not counted.
+//!
+//! Each cue is checked against the start of the previous cue
+//! and the latest end of all the previous cues in order of the document.
+//!
+//! ## Example
+//! ```
+//! use subtp::srt::SubRip;
+//! use subtp::validate::FindingKind;
+//! use subtp::validate::Validate;
+//!
+//! let text = r#"1
+//! 00:00:01,000 --> 00:00:03,000
+//! Hello, world!
+//!
+//! 3
+//! 00:00:02,500 --> 00:00:04,000
+//! This is a test.
+//! "#;
+//!
+//! let mut srt = SubRip::parse(text).unwrap();
+//!
+//! let findings = srt.validate(&Default::default());
+//! assert_eq!(findings.len(), 2);
+//! assert_eq!(findings[0].cue, 1);
+//! assert!(matches!(findings[0].kind, FindingKind::NonSequentialSequence { expected: 2, found: 3 }));
+//! assert!(matches!(findings[1].kind, FindingKind::Overlap { .. }));
+//!
+//! srt.trim_overlaps(&Default::default());
+//! srt.renumber();
+//! assert!(srt.validate(&Default::default()).is_empty());
+//! assert_eq!(srt.subtitles[0].end.to_string(), "00:00:02,500");
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::time::Duration;
+
+use crate::timeline::Framerate;
+
+/// The options of validation and auto-fixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ValidateOptions {
+    /// The minimum gap between the end of a cue and the start of the next cue,
+    /// or zero to allow cues to touch.
+    pub min_gap: Duration,
+}
+
+impl ValidateOptions {
+    /// Creates the options with the minimum gap of the frames at the framerate.
+    ///
+    /// ## Example
+    /// ```
+    /// use std::time::Duration;
+    /// use subtp::timeline::Framerate;
+    /// use subtp::validate::ValidateOptions;
+    ///
+    /// let options = ValidateOptions::min_gap_frames(2, Framerate::PAL);
+    /// assert_eq!(options.min_gap, Duration::from_millis(80));
+    /// ```
+    pub fn min_gap_frames(
+        frames: u32,
+        framerate: Framerate,
+    ) -> Self {
+        let nanoseconds =
+            frames as u128 * framerate.denominator as u128 * 1_000_000_000
+                / (framerate.numerator as u128).max(1);
+
+        Self {
+            min_gap: Duration::from_nanos(nanoseconds as u64),
+        }
+    }
+}
+
+/// The finding of validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Finding {
+    /// The index of the cue starting from 0.
+    pub cue: usize,
+    /// The kind of finding.
+    pub kind: FindingKind,
+}
+
+impl Display for Finding {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "cue {}: {}", self.cue, self.kind)
+    }
+}
+
+/// The kind of finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FindingKind {
+    /// The end is not after the start.
+    NonPositiveDuration {
+        /// The start of the cue.
+        start: Duration,
+        /// The end of the cue.
+        end: Duration,
+    },
+    /// The start is before the start of the previous cue.
+    OutOfOrder {
+        /// The start of the previous cue.
+        previous_start: Duration,
+        /// The start of the cue.
+        start: Duration,
+    },
+    /// The start is before the latest end of the previous cues.
+    Overlap {
+        /// The duration from the start to the latest end of the previous cues.
+        overlap: Duration,
+    },
+    /// The gap from the latest end of the previous cues is shorter than the minimum.
+    ShortGap {
+        /// The duration of the gap.
+        gap: Duration,
+    },
+    /// The sequence number is the same as of the previous cue at the index.
+    DuplicateSequence {
+        /// The sequence number.
+        sequence: u32,
+        /// The index of the first cue with the sequence number.
+        first: usize,
+    },
+    /// The sequence number is not the index plus one.
+    NonSequentialSequence {
+        /// The expected sequence number.
+        expected: u32,
+        /// The found sequence number.
+        found: u32,
+    },
+}
+
+impl Display for FindingKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::NonPositiveDuration {
+                start,
+                end,
+            } => {
+                write!(
+                    f,
+                    "end {:?} is not after start {:?}",
+                    end, start
+                )
+            },
+            | Self::OutOfOrder {
+                previous_start,
+                start,
+            } => {
+                write!(
+                    f,
+                    "start {:?} is before start {:?} of the previous cue",
+                    start, previous_start
+                )
+            },
+            | Self::Overlap {
+                overlap,
+            } => {
+                write!(
+                    f,
+                    "overlaps a previous cue by {:?}",
+                    overlap
+                )
+            },
+            | Self::ShortGap {
+                gap,
+            } => {
+                write!(
+                    f,
+                    "gap {:?} from the previous cue is too short",
+                    gap
+                )
+            },
+            | Self::DuplicateSequence {
+                sequence,
+                first,
+            } => {
+                write!(
+                    f,
+                    "sequence {} is duplicate of cue {}",
+                    sequence, first
+                )
+            },
+            | Self::NonSequentialSequence {
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "sequence {} is not sequential, expected {}",
+                    found, expected
+                )
+            },
+        }
+    }
+}
+
+/// Validation of the timeline of cues with auto-fixes.
+pub trait Validate {
+    /// The start and the end of each cue.
+    fn cue_timings(&self) -> Vec<(Duration, Duration)>;
+
+    /// Replaces the start and the end of each cue in order.
+    fn set_cue_timings(
+        &mut self,
+        timings: &[(Duration, Duration)],
+    );
+
+    /// The sequence number of each cue, or `None` if the format has no sequence numbers.
+    fn sequences(&self) -> Option<Vec<u32>> {
+        None
+    }
+
+    /// Sorts the cues stably by the start and the end.
+    fn sort_cues(&mut self);
+
+    /// Renumbers the sequence numbers from 1 in order if the format has sequence numbers.
+    fn renumber(&mut self) {
+    }
+
+    /// Validates the cues and returns the findings in order of the cues.
+    fn validate(
+        &self,
+        options: &ValidateOptions,
+    ) -> Vec<Finding> {
+        let mut findings = Vec::new();
+        let mut push = |cue: usize, kind: FindingKind| {
+            findings.push(Finding {
+                cue,
+                kind,
+            })
+        };
+
+        let sequences = self.sequences();
+        let mut seen = HashMap::new();
+        // The start of the previous cue and the latest end of the previous cues.
+        let mut previous: Option<(Duration, Duration)> = None;
+        for (cue, (start, end)) in self
+            .cue_timings()
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(sequence) = sequences
+                .as_ref()
+                .map(|sequences| sequences[cue])
+            {
+                let expected = cue as u32 + 1;
+                if let Some(&first) = seen.get(&sequence) {
+                    push(
+                        cue,
+                        FindingKind::DuplicateSequence {
+                            sequence,
+                            first,
+                        },
+                    );
+                } else if sequence != expected {
+                    push(
+                        cue,
+                        FindingKind::NonSequentialSequence {
+                            expected,
+                            found: sequence,
+                        },
+                    );
+                }
+                seen.entry(sequence)
+                    .or_insert(cue);
+            }
+
+            if end <= start {
+                push(
+                    cue,
+                    FindingKind::NonPositiveDuration {
+                        start,
+                        end,
+                    },
+                );
+            }
+
+            if let Some((previous_start, latest_end)) = previous {
+                if start < previous_start {
+                    push(
+                        cue,
+                        FindingKind::OutOfOrder {
+                            previous_start,
+                            start,
+                        },
+                    );
+                } else if start < latest_end {
+                    push(
+                        cue,
+                        FindingKind::Overlap {
+                            overlap: latest_end - start,
+                        },
+                    );
+                } else if start - latest_end < options.min_gap {
+                    push(
+                        cue,
+                        FindingKind::ShortGap {
+                            gap: start - latest_end,
+                        },
+                    );
+                }
+            }
+            let latest_end = previous
+                .map(|(_, latest_end)| latest_end.max(end))
+                .unwrap_or(end);
+            previous = Some((start, latest_end));
+        }
+
+        findings
+    }
+
+    /// Trims the end of each cue to keep the minimum gap before the next cue,
+    /// or to the start of the next cue if the cue would be empty.
+    ///
+    /// Cues out of order are not trimmed, so that [`Validate::sort_cues`] is expected to be called first.
+    fn trim_overlaps(
+        &mut self,
+        options: &ValidateOptions,
+    ) {
+        let mut timings = self.cue_timings();
+        for i in 1..timings.len() {
+            let (start, end) = timings[i - 1];
+            let next_start = timings[i].0;
+            if next_start < start {
+                continue;
+            }
+
+            let mut trimmed = next_start.saturating_sub(options.min_gap);
+            if trimmed <= start {
+                trimmed = next_start;
+            }
+            timings[i - 1].1 = end.min(trimmed);
+        }
+
+        self.set_cue_timings(&timings);
+    }
+
+    /// Shifts each cue later with the duration kept to start after the minimum gap from the end of the previous cue.
+    fn shift_overlaps(
+        &mut self,
+        options: &ValidateOptions,
+    ) {
+        let mut timings = self.cue_timings();
+        for i in 1..timings.len() {
+            let earliest = timings[i - 1].1 + options.min_gap;
+            let (start, end) = timings[i];
+            if start < earliest {
+                timings[i] = (
+                    earliest,
+                    earliest + end.saturating_sub(start),
+                );
+            }
+        }
+
+        self.set_cue_timings(&timings);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::srt::SubRip;
+    use crate::vtt::VttBlock;
+    use crate::vtt::WebVtt;
+
+    const SRT: &str = r#"1
+00:00:01,000 --> 00:00:02,000
+A
+
+2
+00:00:02,040 --> 00:00:03,000
+B
+
+2
+00:00:02,500 --> 00:00:02,500
+C
+
+5
+00:00:01,500 --> 00:00:04,000
+D
+"#;
+
+    #[test]
+    fn validate() {
+        let srt = SubRip::parse(SRT).unwrap();
+        let options = ValidateOptions::min_gap_frames(2, Framerate::PAL);
+
+        assert_eq!(
+            srt.validate(&options),
+            vec![
+                Finding {
+                    cue: 1,
+                    kind: FindingKind::ShortGap {
+                        gap: Duration::from_millis(40),
+                    },
+                },
+                Finding {
+                    cue: 2,
+                    kind: FindingKind::DuplicateSequence {
+                        sequence: 2,
+                        first: 1,
+                    },
+                },
+                Finding {
+                    cue: 2,
+                    kind: FindingKind::NonPositiveDuration {
+                        start: Duration::from_millis(2_500),
+                        end: Duration::from_millis(2_500),
+                    },
+                },
+                Finding {
+                    cue: 2,
+                    kind: FindingKind::Overlap {
+                        overlap: Duration::from_millis(500),
+                    },
+                },
+                Finding {
+                    cue: 3,
+                    kind: FindingKind::NonSequentialSequence {
+                        expected: 4,
+                        found: 5,
+                    },
+                },
+                Finding {
+                    cue: 3,
+                    kind: FindingKind::OutOfOrder {
+                        previous_start: Duration::from_millis(2_500),
+                        start: Duration::from_millis(1_500),
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            srt.validate(&options)[1].to_string(),
+            "cue 2: sequence 2 is duplicate of cue 1"
+        );
+
+        // Touching cues are valid without the minimum gap.
+        assert_eq!(
+            srt.validate(&Default::default())
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn validate_contained_cues() {
+        let srt = SubRip::parse(
+            "1\n00:00:00,000 --> 00:00:10,000\nA\n\n2\n00:00:01,000 --> 00:00:02,000\nB\n\n3\n00:00:03,000 --> 00:00:04,000\nC\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            srt.validate(&Default::default()),
+            vec![
+                Finding {
+                    cue: 1,
+                    kind: FindingKind::Overlap {
+                        overlap: Duration::from_secs(9),
+                    },
+                },
+                Finding {
+                    cue: 2,
+                    kind: FindingKind::Overlap {
+                        overlap: Duration::from_secs(7),
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn fix() {
+        let options = ValidateOptions {
+            min_gap: Duration::from_millis(100),
+        };
+
+        let mut srt = SubRip::parse(SRT).unwrap();
+        srt.sort_cues();
+        srt.trim_overlaps(&options);
+        srt.renumber();
+        assert_eq!(
+            srt.to_string(),
+            "1\n00:00:01,000 --> 00:00:01,400\nA\n\n2\n00:00:01,500 --> 00:00:01,940\nD\n\n3\n00:00:02,040 --> 00:00:02,400\nB\n\n4\n00:00:02,500 --> 00:00:02,500\nC\n"
+        );
+
+        let mut srt = SubRip::parse(SRT).unwrap();
+        srt.sort_cues();
+        srt.shift_overlaps(&options);
+        srt.renumber();
+        assert_eq!(
+            srt.validate(&options)
+                .into_iter()
+                .map(|finding| finding.kind)
+                .collect::<Vec<_>>(),
+            vec![
+                FindingKind::NonPositiveDuration {
+                    start: Duration::from_millis(5_760),
+                    end: Duration::from_millis(5_760),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn vtt() {
+        let text = r#"WEBVTT
+
+00:02.000 --> 00:03.000
+B
+
+NOTE Between the cues.
+
+00:01.000 --> 00:02.500
+A
+"#;
+        let mut vtt = WebVtt::parse(text).unwrap();
+
+        assert_eq!(
+            vtt.validate(&Default::default()),
+            vec![Finding {
+                cue: 1,
+                kind: FindingKind::OutOfOrder {
+                    previous_start: Duration::from_secs(2),
+                    start: Duration::from_secs(1),
+                },
+            }]
+        );
+
+        vtt.sort_cues();
+        assert_eq!(
+            vtt.validate(&Default::default()),
+            vec![Finding {
+                cue: 1,
+                kind: FindingKind::Overlap {
+                    overlap: Duration::from_millis(500),
+                },
+            }]
+        );
+        assert!(matches!(
+            vtt.blocks[1],
+            VttBlock::Comment(_)
+        ));
+
+        vtt.trim_overlaps(&Default::default());
+        assert!(
+            vtt.validate(&Default::default())
+                .is_empty()
+        );
+    }
+}
@@ -69,6 +69,32 @@
 use std::fmt::Display;
 use std::time::Duration;
 
+use crate::ParseResult;
+use crate::convert::DEFAULT_COLOR_CLASSES;
+use crate::convert::class_colors;
+use crate::convert::color_class;
+use crate::convert::color_style;
+use crate::css::Stylesheet;
+use crate::format::Cue;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::format::position_from_settings;
+use crate::format::push_span;
+use crate::format::settings_from_position;
+use crate::layout::Layout;
+use crate::layout::LayoutOptions;
+use crate::layout::layout_at;
+use crate::lenient::Lenient;
+use crate::lint::Lint;
+use crate::lint::LintCue;
+use crate::lossless::LosslessWebVtt;
+use crate::render::RenderOptions;
+use crate::render::render_blocks;
+use crate::render::render_vtt_cue;
+use crate::timeline::Timeline;
+use crate::validate::Validate;
+use crate::vtt_parser::CueTextToken;
+
 /// The WebVTT (`.vtt`) format.
 ///
 /// Parses from text by [`WebVtt::parse`](WebVtt::parse)
@@ -136,6 +162,7 @@ use std::time::Duration;
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WebVtt {
     /// The header of the WebVTT.
     pub header: VttHeader,
@@ -163,7 +190,84 @@ impl WebVtt {
     /// let vtt = WebVtt::parse(text).unwrap();
     /// ```
     pub fn parse(input: &str) -> Result<Self, crate::error::ParseError> {
-        crate::vtt_parser::vtt(input).map_err(Into::into)
+        crate::vtt_parser::vtt(input)
+            .map_err(|err| crate::error::ParseError::from_peg(input, err))
+    }
+
+    /// Parses the WebVTT format leniently by repairing or skipping malformed blocks,
+    /// see [`subtp::lenient`](crate::lenient) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::vtt::WebVtt;
+    ///
+    /// let text = r#"00:01.000 --> 00:04,000 line:bad align:left
+    /// - Never drink liquid nitrogen.
+    ///
+    /// - It will perforate your stomach.
+    /// "#;
+    ///
+    /// let lenient = WebVtt::parse_lenient(text);
+    /// assert_eq!(
+    ///     lenient.output.render(),
+    ///     r#"WEBVTT
+    ///
+    /// 00:00:01.000 --> 00:00:04.000 align:left
+    /// - Never drink liquid nitrogen.
+    /// - It will perforate your stomach.
+    /// "#
+    /// );
+    /// assert_eq!(lenient.warnings.len(), 4);
+    /// ```
+    pub fn parse_lenient(input: &str) -> Lenient<Self> {
+        crate::lenient::parse_webvtt(input)
+    }
+
+    /// Parses the WebVTT format keeping the original formatting,
+    /// see [`subtp::lossless`](crate::lossless) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::vtt::WebVtt;
+    ///
+    /// let text = "WEBVTT\n\n\n00:01.000  -->  00:04.000\nHello, world!\n";
+    ///
+    /// let lossless = WebVtt::parse_lossless(text).unwrap();
+    /// assert_eq!(lossless.render(), text);
+    /// ```
+    pub fn parse_lossless(input: &str) -> ParseResult<LosslessWebVtt> {
+        LosslessWebVtt::parse(input)
+    }
+
+    /// Parses the WebVTT format from bytes by detecting the encoding,
+    /// see [`subtp::encoding`](crate::encoding) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::encoding::UTF_16LE;
+    /// use subtp::vtt::WebVtt;
+    ///
+    /// let bytes = b"\xFF\xFEW\x00E\x00B\x00V\x00T\x00T\x00\n\x00\n\x00";
+    ///
+    /// let decoded = WebVtt::parse_bytes(bytes).unwrap();
+    /// assert_eq!(decoded.encoding.encoding, UTF_16LE);
+    /// assert!(decoded.encoding.bom);
+    /// assert!(decoded.output.blocks.is_empty());
+    /// ```
+    #[cfg(feature = "encoding")]
+    pub fn parse_bytes(
+        bytes: &[u8]
+    ) -> Result<crate::encoding::Decoded<Self>, crate::error::ParseError> {
+        crate::encoding::parse_bytes(bytes, None, Self::parse)
+    }
+
+    /// Parses the WebVTT format from bytes in the encoding.
+    #[cfg(feature = "encoding")]
+    pub fn parse_bytes_with_encoding(
+        bytes: &[u8],
+        encoding: &'static crate::encoding::Encoding,
+    ) -> Result<crate::encoding::Decoded<Self>, crate::error::ParseError> {
+        crate::encoding::parse_bytes(bytes, Some(encoding), Self::parse)
     }
 
     /// Renders the text from the WebVTT format.
@@ -219,6 +323,72 @@ impl WebVtt {
     pub fn render(&self) -> String {
         self.to_string()
     }
+
+    /// Renders the text from the WebVTT format by the options,
+    /// see [`subtp::render`](crate::render) for details.
+    pub fn render_with(
+        &self,
+        options: &RenderOptions,
+    ) -> String {
+        let blocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => render_vtt_cue(cue, options),
+                    | _ => block.to_string(),
+                }
+            });
+
+        render_blocks(
+            std::iter::once(self.header.to_string()).chain(blocks),
+            options,
+        )
+    }
+
+    /// Writes the text from the WebVTT format by the options to the writer.
+    pub fn write_to(
+        &self,
+        mut writer: impl std::io::Write,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        writer.write_all(
+            self.render_with(options)
+                .as_bytes(),
+        )
+    }
+
+    /// Lays out the cues active at the time on the screen,
+    /// see [`subtp::layout`](crate::layout) for details.
+    pub fn layout_at(
+        &self,
+        time: Duration,
+        options: &LayoutOptions,
+    ) -> Layout<'_> {
+        layout_at(self, time, options)
+    }
+
+    /// Parses the CSS of all STYLE blocks in order into the stylesheet,
+    /// see [`subtp::css`](crate::css) for details.
+    pub fn stylesheet(&self) -> Stylesheet {
+        let rules = self
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Style(style) => {
+                        Some(style.parse_stylesheet().rules)
+                    },
+                    | _ => None,
+                }
+            })
+            .flatten()
+            .collect();
+
+        Stylesheet {
+            rules,
+        }
+    }
 }
 
 impl Default for WebVtt {
@@ -262,6 +432,502 @@ impl Iterator for WebVtt {
     }
 }
 
+impl SubtitleFormat for WebVtt {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues with the colors of the default color classes and the `::cue` rules of STYLE blocks.
+    fn cues(&self) -> Vec<Cue> {
+        let mut colors = DEFAULT_COLOR_CLASSES
+            .iter()
+            .map(|(class, _)| (class.to_string(), class.to_string()))
+            .collect::<Vec<_>>();
+        let mut cues = Vec::new();
+
+        for block in &self.blocks {
+            match block {
+                | VttBlock::Que(cue) => {
+                    cues.push(Cue {
+                        identifier: cue.identifier.clone(),
+                        start: cue.timings.start.into(),
+                        end: cue.timings.end.into(),
+                        spans: spans_from_cue_nodes(
+                            &cue.parse_payload().nodes,
+                            &colors,
+                        ),
+                        position: cue
+                            .settings
+                            .as_ref()
+                            .and_then(position_from_settings),
+                        style: None,
+                    });
+                },
+                | VttBlock::Style(style) => {
+                    if let Some(rules) = class_colors(&style.parse_stylesheet())
+                    {
+                        colors.extend(rules);
+                    }
+                },
+                | VttBlock::Comment(_) | VttBlock::Region(_) => {},
+            }
+        }
+
+        cues
+    }
+
+    /// Creates from the cues with a STYLE block for the colors other than the default color classes.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        let mut color_rules = Vec::new();
+        let cues = cues
+            .into_iter()
+            .map(|cue| {
+                let text = cue_text_from_spans(&cue.spans, &mut color_rules);
+                VttCue {
+                    identifier: cue.identifier,
+                    timings: VttTimings {
+                        start: cue.start.into(),
+                        end: cue.end.into(),
+                    },
+                    settings: cue
+                        .position
+                        .as_ref()
+                        .map(settings_from_position),
+                    payload: text.to_payload(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut blocks: Vec<VttBlock> = Vec::new();
+        if !color_rules.is_empty() {
+            blocks.push(color_style(&color_rules).into());
+        }
+        blocks.extend(
+            cues.into_iter()
+                .map(VttBlock::from),
+        );
+
+        Self {
+            blocks,
+            ..Default::default()
+        }
+    }
+}
+
+impl Timeline for WebVtt {
+    /// Replaces the timings and the internal timestamps in the payload of cues.
+    fn for_each_timestamp(
+        &mut self,
+        f: &mut dyn FnMut(Duration) -> Duration,
+    ) {
+        for block in &mut self.blocks {
+            let VttBlock::Que(cue) = block else {
+                continue;
+            };
+
+            cue.timings.start = f(cue.timings.start.into()).into();
+            cue.timings.end = f(cue.timings.end.into()).into();
+            for line in &mut cue.payload {
+                if line.contains('<') {
+                    *line = retime_payload_line(line, f);
+                }
+            }
+        }
+    }
+
+    fn cue_start(
+        &self,
+        index: usize,
+    ) -> Option<Duration> {
+        self.blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => Some(cue.timings.start.into()),
+                    | _ => None,
+                }
+            })
+            .nth(index)
+    }
+}
+
+impl Validate for WebVtt {
+    fn cue_timings(&self) -> Vec<(Duration, Duration)> {
+        self.blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => {
+                        Some((
+                            cue.timings.start.into(),
+                            cue.timings.end.into(),
+                        ))
+                    },
+                    | _ => None,
+                }
+            })
+            .collect()
+    }
+
+    fn set_cue_timings(
+        &mut self,
+        timings: &[(Duration, Duration)],
+    ) {
+        let cues = self
+            .blocks
+            .iter_mut()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => Some(cue),
+                    | _ => None,
+                }
+            });
+        for (cue, (start, end)) in cues.zip(timings) {
+            cue.timings.start = (*start).into();
+            cue.timings.end = (*end).into();
+        }
+    }
+
+    /// Sorts the cues among the positions of cues and keeps the other blocks in place.
+    fn sort_cues(&mut self) {
+        let mut cues = self
+            .blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => Some(cue.clone()),
+                    | _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        cues.sort_by_key(|cue| (cue.timings.start, cue.timings.end));
+
+        let mut cues = cues.into_iter();
+        for block in &mut self.blocks {
+            if let VttBlock::Que(cue) = block {
+                if let Some(sorted) = cues.next() {
+                    *cue = sorted;
+                }
+            }
+        }
+    }
+}
+
+impl Lint for WebVtt {
+    /// Gets the cues with the plain text of the payload.
+    fn lint_cues(&self) -> Vec<LintCue> {
+        self.blocks
+            .iter()
+            .filter_map(|block| {
+                match block {
+                    | VttBlock::Que(cue) => {
+                        Some(LintCue {
+                            start: cue.timings.start.into(),
+                            end: cue.timings.end.into(),
+                            lines: cue
+                                .parse_payload()
+                                .plain_text()
+                                .split('\n')
+                                .map(|line| line.trim().to_string())
+                                .collect(),
+                        })
+                    },
+                    | _ => None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Replaces the timestamp tags in the line of the payload and keeps the other text as it is.
+fn retime_payload_line(
+    line: &str,
+    f: &mut dyn FnMut(Duration) -> Duration,
+) -> String {
+    let mut retimed = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find('<') {
+        let Some(length) = rest[start..].find('>') else {
+            break;
+        };
+        let tag = &rest[start + 1..start + length];
+        retimed.push_str(&rest[..start]);
+        match crate::vtt_parser::timestamp(tag) {
+            | Ok(timestamp) => {
+                let timestamp: VttTimestamp = f(timestamp.into()).into();
+                retimed.push_str(&format!("<{}>", timestamp));
+            },
+            | Err(_) => retimed.push_str(&rest[start..=start + length]),
+        }
+        rest = &rest[start + length + 1..];
+    }
+    retimed.push_str(rest);
+
+    retimed
+}
+
+/// Converts the nodes of the cue text to the spans of the common cue model.
+fn spans_from_cue_nodes(
+    nodes: &[VttCueNode],
+    colors: &[(String, String)],
+) -> Vec<CueSpan> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    collect_cue_spans(
+        nodes,
+        &CueSpan::default(),
+        colors,
+        &mut start,
+        &mut spans,
+    );
+    spans
+}
+
+/// Collects the spans from the nodes with the formatting of the parent spans.
+fn collect_cue_spans(
+    nodes: &[VttCueNode],
+    format: &CueSpan,
+    colors: &[(String, String)],
+    start: &mut Option<Duration>,
+    spans: &mut Vec<CueSpan>,
+) {
+    for node in nodes {
+        match node {
+            | VttCueNode::Text(text)
+            | VttCueNode::Raw {
+                text,
+                ..
+            } => {
+                push_span(
+                    spans,
+                    CueSpan {
+                        text: text.clone(),
+                        start: *start,
+                        ..format.clone()
+                    },
+                );
+            },
+            | VttCueNode::Timestamp(timestamp) => {
+                *start = Some((*timestamp).into());
+            },
+            | VttCueNode::Span(span) => {
+                let mut format = format.clone();
+                match span.kind {
+                    | VttCueSpanKind::Italic => format.italic = true,
+                    | VttCueSpanKind::Bold => format.bold = true,
+                    | VttCueSpanKind::Underline => format.underline = true,
+                    | VttCueSpanKind::Voice => {
+                        format.voice = span.annotation.clone()
+                    },
+                    | VttCueSpanKind::RubyText => continue,
+                    | VttCueSpanKind::Class
+                    | VttCueSpanKind::Ruby
+                    | VttCueSpanKind::Language => {},
+                }
+                for class in &span.classes {
+                    match colors
+                        .iter()
+                        .rev()
+                        .find(|(name, _)| name == class)
+                    {
+                        | Some((_, color)) => {
+                            format.color = Some(color.clone())
+                        },
+                        | None => {
+                            format
+                                .classes
+                                .push(class.clone())
+                        },
+                    }
+                }
+                collect_cue_spans(
+                    &span.children,
+                    &format,
+                    colors,
+                    start,
+                    spans,
+                );
+            },
+        }
+    }
+}
+
+/// Converts the spans of the common cue model to the cue text,
+/// collecting the `::cue` rules for the colors other than the default color classes.
+fn cue_text_from_spans(
+    spans: &[CueSpan],
+    color_rules: &mut Vec<(String, String)>,
+) -> VttCueText {
+    let span = |kind, classes, annotation, children| {
+        VttCueNode::Span(VttCueSpan {
+            kind,
+            classes,
+            annotation,
+            children,
+            ..Default::default()
+        })
+    };
+
+    let mut runs = Vec::new();
+    let mut run_start = 0;
+    for i in 1..=spans.len() {
+        if i == spans.len() || spans[i].voice != spans[i - 1].voice {
+            runs.push(&spans[run_start..i]);
+            run_start = i;
+        }
+    }
+
+    let mut nodes = Vec::new();
+    let mut start = None;
+    for run in runs {
+        let mut children = Vec::new();
+        for cue_span in run {
+            if cue_span.start.is_some() && cue_span.start != start {
+                start = cue_span.start;
+                if let Some(start) = start {
+                    children.push(VttCueNode::Timestamp(start.into()));
+                }
+            }
+
+            let mut node = VttCueNode::Text(cue_span.text.clone());
+            for (enabled, kind) in [
+                (
+                    cue_span.underline,
+                    VttCueSpanKind::Underline,
+                ),
+                (cue_span.bold, VttCueSpanKind::Bold),
+                (cue_span.italic, VttCueSpanKind::Italic),
+            ] {
+                if enabled {
+                    node = span(kind, vec![], None, vec![node]);
+                }
+            }
+
+            let mut classes = cue_span.classes.clone();
+            if let Some(color) = &cue_span.color {
+                let (class, css) = color_class(color);
+                if let Some(css) = css {
+                    let rule = (class.clone(), css);
+                    if !color_rules.contains(&rule) {
+                        color_rules.push(rule);
+                    }
+                }
+                classes.insert(0, class);
+            }
+            if !classes.is_empty() {
+                node = span(
+                    VttCueSpanKind::Class,
+                    classes,
+                    None,
+                    vec![node],
+                );
+            }
+
+            children.push(node);
+        }
+
+        match &run[0].voice {
+            | Some(voice) => {
+                nodes.push(span(
+                    VttCueSpanKind::Voice,
+                    vec![],
+                    Some(voice.clone()),
+                    children,
+                ))
+            },
+            | None => nodes.extend(children),
+        }
+    }
+
+    VttCueText {
+        nodes,
+    }
+}
+
+/// The WebVTT borrowing the identifiers and the payloads of cues from the input.
+///
+/// This avoids allocating a string for each line of the cues,
+/// while the header and the other blocks are kept as the owned types,
+/// and is converted to [`WebVtt`] by [`WebVttRef::into_owned`].
+///
+/// ## Example
+/// ```
+/// use subtp::vtt::VttBlockRef;
+/// use subtp::vtt::WebVttRef;
+///
+/// let text = r#"WEBVTT
+///
+/// 1
+/// 00:00:01.000 --> 00:00:02.000
+/// Hello, world!
+/// "#;
+///
+/// let vtt = WebVttRef::parse(text).unwrap();
+/// if let VttBlockRef::Que(cue) = &vtt.blocks[0] {
+///     assert_eq!(cue.identifier, Some("1"));
+///     assert_eq!(cue.payload, vec!["Hello, world!"]);
+/// }
+///
+/// let owned = vtt.into_owned();
+/// assert_eq!(owned.blocks.len(), 1);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WebVttRef<'a> {
+    /// The header of the WebVTT.
+    pub header: VttHeader,
+    /// The blocks of the WebVTT.
+    pub blocks: Vec<VttBlockRef<'a>>,
+}
+
+impl<'a> WebVttRef<'a> {
+    /// Parses the WebVTT format from the given text borrowing the cues.
+    pub fn parse(input: &'a str) -> Result<Self, crate::error::ParseError> {
+        crate::vtt_parser::vtt_ref(input)
+            .map_err(|err| crate::error::ParseError::from_peg(input, err))
+    }
+
+    /// Converts into the owned [`WebVtt`].
+    pub fn into_owned(self) -> WebVtt {
+        WebVtt {
+            header: self.header,
+            blocks: self
+                .blocks
+                .into_iter()
+                .map(VttBlockRef::into_owned)
+                .collect(),
+        }
+    }
+}
+
+/// The block of WebVTT borrowing the cue.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VttBlockRef<'a> {
+    /// The cue block.
+    Que(VttCueRef<'a>),
+    /// The comment block.
+    Comment(VttComment),
+    /// The style block.
+    Style(VttStyle),
+    /// The region block.
+    Region(VttRegion),
+}
+
+impl<'a> VttBlockRef<'a> {
+    /// Converts into the owned [`VttBlock`].
+    pub fn into_owned(self) -> VttBlock {
+        match self {
+            | Self::Que(cue) => VttBlock::Que(cue.into_owned()),
+            | Self::Comment(comment) => VttBlock::Comment(comment),
+            | Self::Style(style) => VttBlock::Style(style),
+            | Self::Region(region) => VttBlock::Region(region),
+        }
+    }
+}
+
 /// The header block.
 ///
 /// ## Example
@@ -297,6 +963,7 @@ impl Iterator for WebVtt {
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VttHeader {
     /// The description of this file.
     pub description: Option<VttDescription>,
@@ -343,6 +1010,8 @@ impl Display for VttHeader {
 ///     "\nThis is a description.".to_string()
 /// );
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VttDescription {
     /// From side with "WEBVTT".
     Side(String),
@@ -457,8 +1126,11 @@ impl Display for VttDescription {
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VttBlock {
     /// The cue block.
+    #[cfg_attr(feature = "serde", serde(rename = "cue"))]
     Que(VttCue),
     /// The comment block.
     Comment(VttComment),
@@ -554,6 +1226,7 @@ impl Display for VttBlock {
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VttRegion {
     /// The identifier.
     pub id: Option<RegionId>,
@@ -643,6 +1316,8 @@ impl Display for VttRegion {
 /// );
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum VttComment {
     /// Side with "NOTE".
     Side(String),
@@ -692,6 +1367,7 @@ impl Display for VttComment {
 /// );
 /// ```
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VttStyle {
     pub style: String,
 }
@@ -704,6 +1380,30 @@ impl Default for VttStyle {
     }
 }
 
+impl VttStyle {
+    /// Parses the CSS into the stylesheet, see [`subtp::css`](crate::css) for details.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::css::Property;
+    /// use subtp::vtt::VttStyle;
+    ///
+    /// let style = VttStyle {
+    ///     style: "::cue(.loud) { font-weight: bold; position: absolute; }".to_string(),
+    /// };
+    ///
+    /// let stylesheet = style.parse_stylesheet();
+    /// assert_eq!(stylesheet.rules[0].declarations.len(), 1);
+    /// assert_eq!(
+    ///     stylesheet.rules[0].declarations[0].property,
+    ///     Property::FontWeight
+    /// );
+    /// ```
+    pub fn parse_stylesheet(&self) -> Stylesheet {
+        Stylesheet::parse(&self.style)
+    }
+}
+
 impl Display for VttStyle {
     fn fmt(
         &self,
@@ -789,6 +1489,7 @@ impl Display for VttStyle {
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VttCue {
     /// The identifier.
     pub identifier: Option<String>,
@@ -800,6 +1501,31 @@ pub struct VttCue {
     pub payload: Vec<String>,
 }
 
+impl VttCue {
+    /// Parses the payload into the cue text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::vtt::VttCue;
+    ///
+    /// let cue = VttCue {
+    ///     payload: vec![
+    ///         "<i>Hello</i>,".to_string(),
+    ///         "<00:00:01.000>world!".to_string(),
+    ///     ],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let text = cue.parse_payload();
+    ///
+    /// assert_eq!(text.plain_text(), "Hello,\nworld!");
+    /// assert_eq!(text.to_payload(), cue.payload);
+    /// ```
+    pub fn parse_payload(&self) -> VttCueText {
+        VttCueText::parse(&self.payload.join("\n"))
+    }
+}
+
 impl Default for VttCue {
     fn default() -> Self {
         Self {
@@ -830,6 +1556,529 @@ impl Display for VttCue {
     }
 }
 
+/// The cue block borrowing the identifier and the payload from the input.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VttCueRef<'a> {
+    /// The identifier.
+    pub identifier: Option<&'a str>,
+    /// The timings.
+    pub timings: VttTimings,
+    /// The settings.
+    pub settings: Option<CueSettings>,
+    /// The payload of subtitle text.
+    pub payload: Vec<&'a str>,
+}
+
+impl<'a> VttCueRef<'a> {
+    /// Converts into the owned [`VttCue`].
+    pub fn into_owned(self) -> VttCue {
+        VttCue {
+            identifier: self
+                .identifier
+                .map(str::to_string),
+            timings: self.timings,
+            settings: self.settings,
+            payload: self
+                .payload
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+/// The cue text parsed from the payload of a cue block.
+///
+/// Parses by [`VttCueText::parse`](VttCueText::parse)
+/// or [`VttCue::parse_payload`](VttCue::parse_payload),
+/// following the WebVTT cue text parsing rules.
+///
+/// ## Example
+/// ```
+/// use subtp::vtt::VttCueNode;
+/// use subtp::vtt::VttCueSpan;
+/// use subtp::vtt::VttCueSpanKind;
+/// use subtp::vtt::VttCueText;
+///
+/// let text = VttCueText::parse("<v Roger>Hello <b.loud>world</b>!</v>");
+///
+/// assert_eq!(
+///     text,
+///     VttCueText {
+///         nodes: vec![VttCueNode::Span(VttCueSpan {
+///             kind: VttCueSpanKind::Voice,
+///             annotation: Some("Roger".to_string()),
+///             children: vec![
+///                 VttCueNode::Text("Hello ".to_string()),
+///                 VttCueNode::Span(VttCueSpan {
+///                     kind: VttCueSpanKind::Bold,
+///                     classes: vec!["loud".to_string()],
+///                     children: vec![VttCueNode::Text("world".to_string())],
+///                     ..Default::default()
+///                 }),
+///                 VttCueNode::Text("!".to_string()),
+///             ],
+///             ..Default::default()
+///         })],
+///     }
+/// );
+///
+/// assert_eq!(text.plain_text(), "Hello world!");
+/// assert_eq!(text.render(), "<v Roger>Hello <b.loud>world</b>!</v>");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VttCueText {
+    /// The root nodes.
+    pub nodes: Vec<VttCueNode>,
+}
+
+impl VttCueText {
+    /// Parses the cue text from the given text.
+    ///
+    /// Unknown tags and end tags that do not close the current span are
+    /// kept as raw nodes, and spans left open are closed at the end of the text.
+    pub fn parse(text: &str) -> Self {
+        let tokens = crate::vtt_parser::cue_text(text).unwrap_or_default();
+
+        let mut nodes = Vec::new();
+        let mut stack: Vec<VttCueSpan> = Vec::new();
+        for (token, range) in tokens {
+            let source = &text[range];
+            let raw = VttCueNode::Raw {
+                text: String::new(),
+                source: source.to_string(),
+            };
+            match token {
+                | CueTextToken::Text => {
+                    for node in crate::vtt_parser::cue_text_nodes(source) {
+                        push_cue_node(&mut nodes, &mut stack, node);
+                    }
+                },
+                | CueTextToken::InvalidTimestamp => {
+                    push_cue_node(&mut nodes, &mut stack, raw);
+                },
+                | CueTextToken::Timestamp(timestamp) => {
+                    push_cue_node(
+                        &mut nodes,
+                        &mut stack,
+                        VttCueNode::Timestamp(timestamp),
+                    );
+                },
+                | CueTextToken::StartTag {
+                    name,
+                    classes,
+                    annotation,
+                } => {
+                    let in_ruby = stack
+                        .last()
+                        .map_or(false, |span| {
+                            span.kind == VttCueSpanKind::Ruby
+                        });
+                    match VttCueSpanKind::from_tag(&name) {
+                        | Some(VttCueSpanKind::RubyText) if !in_ruby => {
+                            push_cue_node(&mut nodes, &mut stack, raw);
+                        },
+                        | Some(kind) => {
+                            let mut span = VttCueSpan {
+                                kind,
+                                classes,
+                                annotation,
+                                children: vec![],
+                                closed: false,
+                                start_tag: None,
+                            };
+                            if span.escaped_start_tag() != source {
+                                span.start_tag = Some(source.to_string());
+                            }
+                            stack.push(span);
+                        },
+                        | None => push_cue_node(&mut nodes, &mut stack, raw),
+                    }
+                },
+                | CueTextToken::EndTag(name) => {
+                    let current = stack
+                        .last()
+                        .map(|span| span.kind);
+                    if current.map(|kind| kind.tag()) == Some(name.as_str()) {
+                        close_cue_span(&mut nodes, &mut stack, true);
+                    } else if name == VttCueSpanKind::Ruby.tag()
+                        && current == Some(VttCueSpanKind::RubyText)
+                    {
+                        close_cue_span(&mut nodes, &mut stack, false);
+                        close_cue_span(&mut nodes, &mut stack, true);
+                    } else {
+                        push_cue_node(&mut nodes, &mut stack, raw);
+                    }
+                },
+            }
+        }
+
+        while !stack.is_empty() {
+            close_cue_span(&mut nodes, &mut stack, false);
+        }
+
+        Self {
+            nodes,
+        }
+    }
+
+    /// Renders the cue text.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    /// Renders the cue text into lines of the payload.
+    pub fn to_payload(&self) -> Vec<String> {
+        self.render()
+            .split('\n')
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    /// Gets the plain text without tags, timestamps and ruby texts.
+    pub fn plain_text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.nodes {
+            node.write_plain_text(&mut text);
+        }
+        text
+    }
+}
+
+impl Display for VttCueText {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        for node in &self.nodes {
+            write!(f, "{}", node)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Pushes the node into the current span or into the root.
+fn push_cue_node(
+    nodes: &mut Vec<VttCueNode>,
+    stack: &mut [VttCueSpan],
+    node: VttCueNode,
+) {
+    if let Some(span) = stack.last_mut() {
+        span.children.push(node);
+    } else {
+        nodes.push(node);
+    }
+}
+
+/// Closes the current span and pushes it into its parent.
+fn close_cue_span(
+    nodes: &mut Vec<VttCueNode>,
+    stack: &mut Vec<VttCueSpan>,
+    closed: bool,
+) {
+    if let Some(mut span) = stack.pop() {
+        span.closed = closed;
+        push_cue_node(nodes, stack, VttCueNode::Span(span));
+    }
+}
+
+/// The node of the cue text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VttCueNode {
+    /// The text with decoded character references.
+    Text(String),
+    /// The internal timestamp.
+    Timestamp(VttTimestamp),
+    /// The span with tags.
+    Span(VttCueSpan),
+    /// The source rendered as is, e.g. an unknown tag or a character reference,
+    /// with the text it stands for.
+    Raw {
+        /// The text with decoded character references, or empty for a tag.
+        text: String,
+        /// The source as written.
+        source: String,
+    },
+}
+
+impl VttCueNode {
+    fn write_plain_text(
+        &self,
+        text: &mut String,
+    ) {
+        match self {
+            | Self::Text(t)
+            | Self::Raw {
+                text: t,
+                ..
+            } => {
+                text.push_str(t);
+            },
+            | Self::Timestamp(_) => {},
+            | Self::Span(span) => {
+                if span.kind != VttCueSpanKind::RubyText {
+                    for child in &span.children {
+                        child.write_plain_text(text);
+                    }
+                }
+            },
+        }
+    }
+}
+
+impl Default for VttCueNode {
+    fn default() -> Self {
+        Self::Text(String::new())
+    }
+}
+
+impl Display for VttCueNode {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match self {
+            | Self::Text(text) => write!(f, "{}", escape_cue_text(text)),
+            | Self::Timestamp(timestamp) => {
+                write!(f, "<{}>", timestamp)
+            },
+            | Self::Span(span) => {
+                write!(f, "{}", span)
+            },
+            | Self::Raw {
+                source,
+                ..
+            } => write!(f, "{}", source),
+        }
+    }
+}
+
+/// Escapes the text of the cue text by character references.
+pub(crate) fn escape_cue_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            | '&' => escaped.push_str("&amp;"),
+            | '<' => escaped.push_str("&lt;"),
+            | '>' => escaped.push_str("&gt;"),
+            | '\u{a0}' => escaped.push_str("&nbsp;"),
+            | '\u{200e}' => escaped.push_str("&lrm;"),
+            | '\u{200f}' => escaped.push_str("&rlm;"),
+            | _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// The span of the cue text.
+///
+/// ## Example
+/// ```
+/// use subtp::vtt::VttCueNode;
+/// use subtp::vtt::VttCueSpan;
+/// use subtp::vtt::VttCueSpanKind;
+///
+/// let span = VttCueSpan {
+///     kind: VttCueSpanKind::Language,
+///     annotation: Some("en".to_string()),
+///     children: vec![VttCueNode::Text("Hello".to_string())],
+///     ..Default::default()
+/// };
+///
+/// assert_eq!(
+///     span.to_string(),
+///     "<lang en>Hello</lang>".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VttCueSpan {
+    /// The kind of span.
+    pub kind: VttCueSpanKind,
+    /// The classes.
+    pub classes: Vec<String>,
+    /// The annotation, e.g. a voice name or a language tag.
+    pub annotation: Option<String>,
+    /// The child nodes.
+    pub children: Vec<VttCueNode>,
+    /// Whether the span is closed by an end tag.
+    pub closed: bool,
+    /// The start tag as written if different from the escaped form,
+    /// rendered while the kind, the classes and the annotation are not edited.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub start_tag: Option<String>,
+}
+
+impl Default for VttCueSpan {
+    fn default() -> Self {
+        Self {
+            kind: VttCueSpanKind::default(),
+            classes: vec![],
+            annotation: None,
+            children: vec![],
+            closed: true,
+            start_tag: None,
+        }
+    }
+}
+
+impl VttCueSpan {
+    /// Renders the start tag in the escaped form.
+    fn escaped_start_tag(&self) -> String {
+        let mut start_tag = format!("<{}", self.kind);
+        for class in &self.classes {
+            start_tag.push('.');
+            start_tag.push_str(class);
+        }
+        if let Some(annotation) = &self.annotation {
+            start_tag.push(' ');
+            start_tag.push_str(
+                &annotation
+                    .replace('&', "&amp;")
+                    .replace('>', "&gt;"),
+            );
+        }
+        start_tag.push('>');
+        start_tag
+    }
+
+    /// Checks whether the start tag as written stands for the kind, the classes and the annotation.
+    fn matches_start_tag(
+        &self,
+        start_tag: &str,
+    ) -> bool {
+        match crate::vtt_parser::cue_text(start_tag)
+            .unwrap_or_default()
+            .as_slice()
+        {
+            | [
+                (
+                    CueTextToken::StartTag {
+                        name,
+                        classes,
+                        annotation,
+                    },
+                    _,
+                ),
+            ] => {
+                name == self.kind.tag()
+                    && classes == &self.classes
+                    && annotation == &self.annotation
+            },
+            | _ => false,
+        }
+    }
+}
+
+impl Display for VttCueSpan {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        match &self.start_tag {
+            | Some(start_tag) if self.matches_start_tag(start_tag) => {
+                write!(f, "{}", start_tag)?;
+            },
+            | _ => write!(f, "{}", self.escaped_start_tag())?,
+        }
+
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+
+        if self.closed {
+            write!(f, "</{}>", self.kind)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The kind of span in the cue text.
+///
+/// ## Example
+/// ```
+/// use subtp::vtt::VttCueSpanKind;
+///
+/// let kind = VttCueSpanKind::RubyText;
+///
+/// assert_eq!(
+///     kind.to_string(),
+///     "rt".to_string()
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum VttCueSpanKind {
+    /// The class span `<c>`.
+    Class,
+    /// The italics span `<i>`.
+    Italic,
+    /// The bold span `<b>`.
+    Bold,
+    /// The underline span `<u>`.
+    Underline,
+    /// The ruby span `<ruby>`.
+    Ruby,
+    /// The ruby text span `<rt>`.
+    RubyText,
+    /// The voice span `<v>`.
+    Voice,
+    /// The language span `<lang>`.
+    Language,
+}
+
+impl VttCueSpanKind {
+    /// Gets the kind from the tag name.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            | "c" => Some(Self::Class),
+            | "i" => Some(Self::Italic),
+            | "b" => Some(Self::Bold),
+            | "u" => Some(Self::Underline),
+            | "ruby" => Some(Self::Ruby),
+            | "rt" => Some(Self::RubyText),
+            | "v" => Some(Self::Voice),
+            | "lang" => Some(Self::Language),
+            | _ => None,
+        }
+    }
+
+    /// Gets the tag name.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            | Self::Class => "c",
+            | Self::Italic => "i",
+            | Self::Bold => "b",
+            | Self::Underline => "u",
+            | Self::Ruby => "ruby",
+            | Self::RubyText => "rt",
+            | Self::Voice => "v",
+            | Self::Language => "lang",
+        }
+    }
+}
+
+impl Default for VttCueSpanKind {
+    fn default() -> Self {
+        Self::Class
+    }
+}
+
+impl Display for VttCueSpanKind {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.tag())
+    }
+}
+
 /// The timings.
 ///
 /// ## Example
@@ -854,6 +2103,7 @@ impl Display for VttCue {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VttTimings {
     /// The start timestamp.
     pub start: VttTimestamp,
@@ -909,9 +2159,11 @@ impl Display for VttTimings {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(remote = "Self"))]
 pub struct VttTimestamp {
-    /// The hours.
-    pub hours: u8,
+    /// The hours of two or more digits.
+    pub hours: u32,
     /// The minutes.
     pub minutes: u8,
     /// The seconds.
@@ -944,21 +2196,43 @@ impl Display for VttTimestamp {
     }
 }
 
-impl From<Duration> for VttTimestamp {
-    fn from(duration: Duration) -> Self {
+impl VttTimestamp {
+    /// The maximum timestamp.
+    pub const MAX: Self = Self {
+        hours: u32::MAX,
+        minutes: 59,
+        seconds: 59,
+        milliseconds: 999,
+    };
+
+    /// Converts from the duration truncated to milliseconds, or `None` if the hours overflow.
+    ///
+    /// ## Example
+    /// ```
+    /// use std::time::Duration;
+    /// use subtp::vtt::VttTimestamp;
+    ///
+    /// let timestamp = VttTimestamp::checked_from_duration(Duration::from_secs(100 * 3600)).unwrap();
+    /// assert_eq!(timestamp.to_string(), "100:00:00.000");
+    ///
+    /// assert_eq!(VttTimestamp::checked_from_duration(Duration::MAX), None);
+    /// ```
+    pub fn checked_from_duration(duration: Duration) -> Option<Self> {
         let seconds = duration.as_secs();
-        let milliseconds = duration.subsec_millis() as u16;
 
-        let hours = (seconds / 3600) as u8;
-        let minutes = ((seconds % 3600) / 60) as u8;
-        let seconds = (seconds % 60) as u8;
+        Some(Self {
+            hours: u32::try_from(seconds / 3600).ok()?,
+            minutes: ((seconds % 3600) / 60) as u8,
+            seconds: (seconds % 60) as u8,
+            milliseconds: duration.subsec_millis() as u16,
+        })
+    }
+}
 
-        Self {
-            hours,
-            minutes,
-            seconds,
-            milliseconds,
-        }
+/// Converts from the duration truncated to milliseconds, saturating at [`VttTimestamp::MAX`].
+impl From<Duration> for VttTimestamp {
+    fn from(duration: Duration) -> Self {
+        Self::checked_from_duration(duration).unwrap_or(Self::MAX)
     }
 }
 
@@ -1008,6 +2282,7 @@ impl Into<Duration> for VttTimestamp {
 /// );
 /// ```
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CueSettings {
     /// The vertical setting.
     pub vertical: Option<Vertical>,
@@ -1087,6 +2362,8 @@ impl Display for CueSettings {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Percentage {
     pub value: f32,
 }
@@ -1134,6 +2411,7 @@ impl Display for Percentage {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Anchor {
     /// The horizontal setting.
     pub x: Percentage,
@@ -1176,6 +2454,8 @@ impl Display for Anchor {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Scroll {
     /// The scroll up.
     Up,
@@ -1214,6 +2494,8 @@ impl Display for Scroll {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Vertical {
     /// From right to left.
     Rl,
@@ -1267,6 +2549,8 @@ impl Display for Vertical {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Line {
     /// The percentage.
     Percentage(Percentage, Option<LineAlignment>),
@@ -1318,6 +2602,8 @@ impl Display for Line {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum LineAlignment {
     /// The start alignment.
     Start,
@@ -1387,6 +2673,7 @@ impl Display for LineAlignment {
 /// );
 /// ```
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     /// The position value.
     pub value: Percentage,
@@ -1430,6 +2717,8 @@ impl Display for Position {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum PositionAlignment {
     /// The line left alignment.
     LineLeft,
@@ -1478,6 +2767,8 @@ impl Display for PositionAlignment {
 /// );
 /// ```
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Alignment {
     /// The start alignment.
     Start,
@@ -2021,6 +3312,15 @@ video::cue {
                 ..Default::default()
             }
         );
+
+        let duration = Duration::from_secs(360_000);
+        let timestamp: VttTimestamp = duration.into();
+        assert_eq!(timestamp.hours, 100);
+        assert_eq!(Into::<Duration>::into(timestamp), duration);
+
+        // Saturates without wrapping.
+        let timestamp: VttTimestamp = Duration::MAX.into();
+        assert_eq!(timestamp, VttTimestamp::MAX);
     }
 
     #[test]
@@ -2082,4 +3382,327 @@ video::cue {
 
         assert!(start < end);
     }
+
+    #[test]
+    fn parse_cue_text() {
+        let text =
+            VttCueText::parse("<c.yellow.bg_blue>Yellow</c> and <i>italic</i>");
+        assert_eq!(
+            text,
+            VttCueText {
+                nodes: vec![
+                    VttCueNode::Span(VttCueSpan {
+                        kind: VttCueSpanKind::Class,
+                        classes: vec![
+                            "yellow".to_string(),
+                            "bg_blue".to_string()
+                        ],
+                        children: vec![VttCueNode::Text(
+                            "Yellow".to_string()
+                        )],
+                        ..Default::default()
+                    }),
+                    VttCueNode::Text(" and ".to_string()),
+                    VttCueNode::Span(VttCueSpan {
+                        kind: VttCueSpanKind::Italic,
+                        children: vec![VttCueNode::Text(
+                            "italic".to_string()
+                        )],
+                        ..Default::default()
+                    }),
+                ],
+            }
+        );
+
+        // Unclosed voice span.
+        let text =
+            VttCueText::parse("<v Roger Bingham>We are in New York City");
+        assert_eq!(
+            text,
+            VttCueText {
+                nodes: vec![VttCueNode::Span(
+                    VttCueSpan {
+                        kind: VttCueSpanKind::Voice,
+                        annotation: Some("Roger Bingham".to_string()),
+                        children: vec![VttCueNode::Text(
+                            "We are in New York City".to_string()
+                        )],
+                        closed: false,
+                        ..Default::default()
+                    }
+                )],
+            }
+        );
+
+        // Ruby text closed by the end tag of ruby.
+        let text = VttCueText::parse("<ruby>漢<rt>kan</ruby>字");
+        assert_eq!(
+            text,
+            VttCueText {
+                nodes: vec![
+                    VttCueNode::Span(VttCueSpan {
+                        kind: VttCueSpanKind::Ruby,
+                        children: vec![
+                            VttCueNode::Text("漢".to_string()),
+                            VttCueNode::Span(VttCueSpan {
+                                kind: VttCueSpanKind::RubyText,
+                                children: vec![VttCueNode::Text(
+                                    "kan".to_string()
+                                )],
+                                closed: false,
+                                ..Default::default()
+                            }),
+                        ],
+                        ..Default::default()
+                    }),
+                    VttCueNode::Text("字".to_string()),
+                ],
+            }
+        );
+        assert_eq!(text.plain_text(), "漢字");
+
+        // Ruby text outside of ruby, unknown tags and unmatched end tags are kept as raw nodes.
+        let text = VttCueText::parse("<rt>a</rt><font>b</font><b>c</i></b>");
+        let raw = |source: &str| {
+            VttCueNode::Raw {
+                text: String::new(),
+                source: source.to_string(),
+            }
+        };
+        assert_eq!(
+            text,
+            VttCueText {
+                nodes: vec![
+                    raw("<rt>"),
+                    VttCueNode::Text("a".to_string()),
+                    raw("</rt>"),
+                    raw("<font>"),
+                    VttCueNode::Text("b".to_string()),
+                    raw("</font>"),
+                    VttCueNode::Span(VttCueSpan {
+                        kind: VttCueSpanKind::Bold,
+                        children: vec![
+                            VttCueNode::Text("c".to_string()),
+                            raw("</i>"),
+                        ],
+                        ..Default::default()
+                    }),
+                ],
+            }
+        );
+        assert_eq!(text.plain_text(), "abc");
+
+        // Annotations and character references keep the source.
+        let mut text = VttCueText::parse("<v  Roger   B>A&#65;</v>");
+        let VttCueNode::Span(voice) = &mut text.nodes[0] else {
+            unreachable!()
+        };
+        assert_eq!(
+            voice.annotation,
+            Some("Roger B".to_string())
+        );
+        assert_eq!(text.plain_text(), "AA");
+        assert_eq!(
+            text.render(),
+            "<v  Roger   B>A&#65;</v>"
+        );
+
+        let VttCueNode::Span(voice) = &mut text.nodes[0] else {
+            unreachable!()
+        };
+        voice.annotation = Some("Mary".to_string());
+        assert_eq!(text.render(), "<v Mary>A&#65;</v>");
+
+        // Internal timestamps.
+        let text = VttCueText::parse("Never <00:00:01.000>drink");
+        assert_eq!(
+            text,
+            VttCueText {
+                nodes: vec![
+                    VttCueNode::Text("Never ".to_string()),
+                    VttCueNode::Timestamp(VttTimestamp {
+                        seconds: 1,
+                        ..Default::default()
+                    }),
+                    VttCueNode::Text("drink".to_string()),
+                ],
+            }
+        );
+        assert_eq!(text.plain_text(), "Never drink");
+    }
+
+    #[test]
+    fn render_cue_text() {
+        let texts = [
+            "Plain text",
+            "<b>Bold</b>, <i>italic</i> and <u>underline</u>",
+            "<c.yellow.bg_blue>Class</c>",
+            "<v.loud Roger Bingham>Voice</v>",
+            "<v Roger Bingham>Unclosed voice",
+            "<lang en-US>Language</lang>",
+            "<ruby>漢<rt>kan</rt>字<rt>ji</rt></ruby>",
+            "<00:00:01.000>Karaoke <00:00:02.500>text",
+            "Escaped &lt;&amp;&gt;&nbsp;",
+            "<b>Multiple\nlines</b>",
+            "<font>b</font>",
+            "a &foo b",
+            "A&#65;B",
+            "<v  Roger   B>Voice</v>",
+            "<c.>Empty class</c>",
+            "Literal\u{a0}NBSP > 1",
+            "</b>Unmatched <rt>ruby text</rt> and <00:01.5>timestamp",
+        ];
+
+        for text in texts {
+            assert_eq!(VttCueText::parse(text).render(), text);
+        }
+    }
+
+    #[test]
+    fn parse_payload() {
+        let cue = VttCue {
+            payload: vec![
+                "<v Mary>Hello <b>bold".to_string(),
+                "text</b> across lines</v>".to_string(),
+            ],
+            ..Default::default()
+        };
+
+        let text = cue.parse_payload();
+        assert_eq!(
+            text.plain_text(),
+            "Hello bold\ntext across lines"
+        );
+        assert_eq!(text.to_payload(), cue.payload);
+    }
+
+    #[test]
+    fn subtitle_format() {
+        let text = r#"WEBVTT
+
+STYLE
+::cue(.color_ff8000) { color: #ff8000; }
+
+1
+00:00:01.000 --> 00:00:02.000 line:10% align:left
+<v Alice>Hello, <i>world</i>!</v>
+
+00:00:03.000 --> 00:00:04.000
+<c.yellow.loud>Karaoke</c> <00:00:03.500><c.color_ff8000>text</c>
+"#;
+        let vtt = WebVtt::parse(text).unwrap();
+        let cues = vtt.cues();
+
+        assert_eq!(
+            cues,
+            vec![
+                Cue {
+                    identifier: Some("1".to_string()),
+                    start: Duration::from_secs(1),
+                    end: Duration::from_secs(2),
+                    spans: vec![
+                        CueSpan {
+                            text: "Hello, ".to_string(),
+                            voice: Some("Alice".to_string()),
+                            ..Default::default()
+                        },
+                        CueSpan {
+                            text: "world".to_string(),
+                            italic: true,
+                            voice: Some("Alice".to_string()),
+                            ..Default::default()
+                        },
+                        CueSpan {
+                            text: "!".to_string(),
+                            voice: Some("Alice".to_string()),
+                            ..Default::default()
+                        },
+                    ],
+                    position: Some(crate::format::CuePosition {
+                        line: Some(crate::format::CueLine::Percentage(10.0)),
+                        align: Some(crate::format::CueAlignment::Left),
+                        ..Default::default()
+                    }),
+                    style: None,
+                },
+                Cue {
+                    identifier: None,
+                    start: Duration::from_secs(3),
+                    end: Duration::from_secs(4),
+                    spans: vec![
+                        CueSpan {
+                            text: "Karaoke".to_string(),
+                            color: Some("yellow".to_string()),
+                            classes: vec!["loud".to_string()],
+                            ..Default::default()
+                        },
+                        CueSpan {
+                            text: " ".to_string(),
+                            ..Default::default()
+                        },
+                        CueSpan {
+                            text: "text".to_string(),
+                            start: Some(Duration::from_millis(3500)),
+                            color: Some("#ff8000".to_string()),
+                            ..Default::default()
+                        },
+                    ],
+                    position: None,
+                    style: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            WebVtt::from_cues(cues).render(),
+            r#"WEBVTT
+
+STYLE
+::cue(.color_ff8000) { color: #ff8000; }
+
+1
+00:00:01.000 --> 00:00:02.000 line:10% align:left
+<v Alice>Hello, <i>world</i>!</v>
+
+00:00:03.000 --> 00:00:04.000
+<c.yellow.loud>Karaoke</c> <00:00:03.500><c.color_ff8000>text</c>
+"#
+        );
+    }
+
+    #[test]
+    fn parse_ref() {
+        let text = r#"WEBVTT
+
+NOTE A comment.
+
+cue_id
+00:01.000 --> 00:04.000 align:start
+Hello,
+world!
+"#;
+        let vtt = WebVttRef::parse(text).unwrap();
+
+        assert_eq!(vtt.blocks.len(), 2);
+        assert_eq!(
+            vtt.blocks[0],
+            VttBlockRef::Comment(VttComment::Side("A comment.".to_string()))
+        );
+        match &vtt.blocks[1] {
+            | VttBlockRef::Que(cue) => {
+                assert_eq!(cue.identifier, Some("cue_id"));
+                assert_eq!(cue.payload, vec!["Hello,", "world!"]);
+                assert!(text
+                    .as_bytes()
+                    .as_ptr_range()
+                    .contains(&cue.payload[0].as_ptr()));
+            },
+            | _ => panic!("not a cue"),
+        }
+        assert_eq!(
+            vtt.into_owned(),
+            WebVtt::parse(text).unwrap()
+        );
+        assert!(WebVttRef::parse("WEBVTT\n\n00:01.000 -> 00:04.000\nHello\n").is_err());
+    }
 }
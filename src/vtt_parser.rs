@@ -1,6 +1,156 @@
 //! A parser implementation for the WebVTT format.
 
+pub(crate) use vtt_parser::block;
+pub(crate) use vtt_parser::cue_settings;
+pub(crate) use vtt_parser::cue_text;
+pub(crate) use vtt_parser::header;
+pub(crate) use vtt_parser::timestamp;
 pub(crate) use vtt_parser::vtt;
+pub(crate) use vtt_parser::vtt_ref;
+
+use crate::vtt::VttCueNode;
+use crate::vtt::VttTimestamp;
+use crate::vtt::escape_cue_text;
+
+/// A token of the cue text.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum CueTextToken {
+    /// A text with character references.
+    Text,
+    /// A start tag.
+    StartTag {
+        /// The tag name.
+        name: String,
+        /// The classes.
+        classes: Vec<String>,
+        /// The annotation.
+        annotation: Option<String>,
+    },
+    /// An end tag.
+    EndTag(String),
+    /// A timestamp tag.
+    Timestamp(VttTimestamp),
+    /// A tag starting with a digit but not a valid timestamp.
+    InvalidTimestamp,
+}
+
+/// Splits the source of a text into the text nodes with decoded character references,
+/// where the parts written differently from the escaped form are kept as raw nodes.
+pub(crate) fn cue_text_nodes(source: &str) -> Vec<VttCueNode> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+    let mut raw: Option<(String, String)> = None;
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let (decoded, length) = if c == '&' {
+            rest.find(';')
+                .and_then(|end| {
+                    decode_character_reference(&rest[1..end])
+                        .map(|decoded| (decoded, end + 1))
+                })
+                .unwrap_or(('&', 1))
+        } else {
+            (c, c.len_utf8())
+        };
+        let part = &rest[..length];
+        rest = &rest[length..];
+
+        if escape_cue_text(&decoded.to_string()) == part {
+            if let Some((text, source)) = raw.take() {
+                nodes.push(VttCueNode::Raw {
+                    text,
+                    source,
+                });
+            }
+            text.push(decoded);
+        } else {
+            if !text.is_empty() {
+                nodes.push(VttCueNode::Text(std::mem::take(
+                    &mut text,
+                )));
+            }
+            let (raw_text, raw_source) =
+                raw.get_or_insert_with(Default::default);
+            raw_text.push(decoded);
+            raw_source.push_str(part);
+        }
+    }
+
+    if let Some((text, source)) = raw {
+        nodes.push(VttCueNode::Raw {
+            text,
+            source,
+        });
+    }
+    if !text.is_empty() {
+        nodes.push(VttCueNode::Text(text));
+    }
+
+    nodes
+}
+
+/// Decodes character references in the cue text.
+fn decode_character_references(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest
+            .find(';')
+            .and_then(|end| {
+                decode_character_reference(&rest[1..end]).map(|c| (c, end))
+            });
+        if let Some((c, end)) = reference {
+            decoded.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            decoded.push('&');
+            rest = &rest[1..];
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes a character reference without `&` and `;`.
+fn decode_character_reference(reference: &str) -> Option<char> {
+    match reference {
+        | "amp" => Some('&'),
+        | "lt" => Some('<'),
+        | "gt" => Some('>'),
+        | "lrm" => Some('\u{200e}'),
+        | "rlm" => Some('\u{200f}'),
+        | "nbsp" => Some('\u{a0}'),
+        | _ => {
+            let code = if let Some(hex) = reference
+                .strip_prefix("#x")
+                .or_else(|| reference.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok()?
+            } else {
+                reference
+                    .strip_prefix('#')?
+                    .parse()
+                    .ok()?
+            };
+            char::from_u32(code)
+        },
+    }
+}
+
+/// Collapses whitespaces in the annotation of a start tag.
+fn normalize_annotation(annotation: &str) -> Option<String> {
+    let annotation = decode_character_references(annotation)
+        .split_ascii_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if annotation.is_empty() {
+        None
+    } else {
+        Some(annotation)
+    }
+}
 
 peg::parser! {
     /// The parser for the WebVTT format.
@@ -9,7 +159,9 @@ peg::parser! {
         use crate::vtt::VttHeader;
         use crate::vtt::VttRegion;
         use crate::vtt::VttBlock;
+        use crate::vtt::VttBlockRef;
         use crate::vtt::VttCue;
+        use crate::vtt::VttCueRef;
         use crate::vtt::VttComment;
         use crate::vtt::VttStyle;
         use crate::vtt::VttTimings;
@@ -25,6 +177,10 @@ peg::parser! {
         use crate::vtt::PositionAlignment;
         use crate::vtt::Position;
         use crate::vtt::VttDescription;
+        use crate::vtt::WebVttRef;
+        use super::CueTextToken;
+        use super::normalize_annotation;
+        use std::ops::Range;
 
         /// Whitespace.
         rule whitespace() = [' ' | '\t']
@@ -50,6 +206,12 @@ peg::parser! {
                 n.parse().or(Err("two-digit number"))
             }
 
+        /// Hours of two or more digits.
+        rule hours() -> u32
+            = n:$(['0'..='9']*<2,>) {?
+                n.parse().or(Err("hours in u32"))
+            }
+
         /// Three-digit number.
         rule three_number() -> u16
             = n:$(['0'..='9']['0'..='9']['0'..='9']) {?
@@ -112,19 +274,19 @@ peg::parser! {
             }
 
         /// Single text with newline.
-        rule line() -> String
+        rule line() -> &'input str
             = !(whitespace() / newline()) t:$((!newline() [_])+) newline()
             {
-                t.to_string().trim().to_string()
+                t.trim()
             }
 
         /// Multiple lines.
-        rule multiline() -> Vec<String>
+        rule multiline() -> Vec<&'input str>
             = !(whitespace() / newline()) lines:$(!(whitespace()+ newline()) (!newline() [_])+ newline()) ++ ()
             {
                 lines
                     .iter()
-                    .map(|l| l.to_string().trim().to_string())
+                    .map(|l| l.trim())
                     .collect()
             }
 
@@ -135,7 +297,7 @@ peg::parser! {
 
         /// Timestamp with hours.
         rule timestamp_with_hours() -> VttTimestamp
-            = hours:two_number() ":" minutes:two_number() ":" seconds:two_number() "." milliseconds:three_number()
+            = hours:hours() ":" minutes:two_number() ":" seconds:two_number() "." milliseconds:three_number()
             {
                 VttTimestamp {
                     hours,
@@ -286,17 +448,21 @@ peg::parser! {
 
         /// Cue block
         pub(crate) rule cue() -> VttCue
+            = cue:cue_ref() { cue.into_owned() }
+
+        /// Cue block borrowing the identifier and the payload.
+        rule cue_ref() -> VttCueRef<'input>
             = cue_with_identifier_and_settings()
                 / cue_with_identifier()
                 / cue_with_settings()
                 / cue_minimal()
 
         /// Minimal cue block
-        rule cue_minimal() -> VttCue
+        rule cue_minimal() -> VttCueRef<'input>
             = whitespace()* timings:timings() whitespace()* newline()
                 whitespace()* payload:multiline()
             {
-                VttCue {
+                VttCueRef {
                     identifier: None,
                     timings,
                     settings: None,
@@ -305,12 +471,12 @@ peg::parser! {
             }
 
         /// Cue block with an identifier.
-        rule cue_with_identifier() -> VttCue
+        rule cue_with_identifier() -> VttCueRef<'input>
             = whitespace()* identifier:line()
                 whitespace()* timings:timings() whitespace()* newline()
                 whitespace()* payload:multiline()
             {
-                VttCue {
+                VttCueRef {
                     identifier: Some(identifier),
                     timings,
                     settings: None,
@@ -319,11 +485,11 @@ peg::parser! {
             }
 
         /// Cue block with settings.
-        rule cue_with_settings() -> VttCue
+        rule cue_with_settings() -> VttCueRef<'input>
             = whitespace()* timings:timings() whitespace()+ settings:cue_settings() whitespace()* newline()
                 whitespace()* payload:multiline()
             {
-                VttCue {
+                VttCueRef {
                     identifier: None,
                     timings,
                     settings: Some(settings),
@@ -332,12 +498,12 @@ peg::parser! {
             }
 
         /// Cue block with an identifier and settings.
-        rule cue_with_identifier_and_settings() -> VttCue
+        rule cue_with_identifier_and_settings() -> VttCueRef<'input>
             = whitespace()* identifier:line()
                 whitespace()* timings:timings() whitespace()+ settings:cue_settings() whitespace()* newline()
                 whitespace()* payload:multiline()
             {
-                VttCue {
+                VttCueRef {
                     identifier: Some(identifier),
                     timings,
                     settings: Some(settings),
@@ -353,7 +519,7 @@ peg::parser! {
         rule comment_side() -> VttComment
             = "NOTE" whitespace()+ comment:line()
             {
-                VttComment::Side(comment)
+                VttComment::Side(comment.to_string())
             }
 
         /// Multiple lines comment block.
@@ -423,25 +589,97 @@ peg::parser! {
         pub(crate) rule region_scroll() -> Scroll
             = "scroll:up" { Scroll::Up }
 
-         rule cue_block() -> VttBlock
-            = cue:cue() { cue.into() }
+        rule cue_block() -> VttBlockRef<'input>
+            = cue:cue_ref() { VttBlockRef::Que(cue) }
 
-        rule comment_block() -> VttBlock
-            = comment:comment() { comment.into() }
+        rule comment_block() -> VttBlockRef<'input>
+            = comment:comment() { VttBlockRef::Comment(comment) }
 
-        rule style_block() -> VttBlock
-            = style:style() { style.into() }
+        rule style_block() -> VttBlockRef<'input>
+            = style:style() { VttBlockRef::Style(style) }
 
-        rule region_block() -> VttBlock
-            = region:region() { region.into() }
+        rule region_block() -> VttBlockRef<'input>
+            = region:region() { VttBlockRef::Region(region) }
 
         /// Any block (cue, comment, or style)
         pub(crate) rule block() -> VttBlock
+            = block:block_ref() { block.into_owned() }
+
+        /// Any block borrowing the identifier and the payload of a cue.
+        rule block_ref() -> VttBlockRef<'input>
             = cue_block()
                 / comment_block()
                 / style_block()
                 / region_block()
 
+        /// Cue text tokens with the ranges of the source.
+        pub(crate) rule cue_text() -> Vec<(CueTextToken, Range<usize>)>
+            = (start:position!() t:cue_text_token() end:position!() { (t, start..end) })*
+
+        /// Cue text token.
+        rule cue_text_token() -> CueTextToken
+            = cue_text_end_tag()
+                / cue_text_timestamp_tag()
+                / cue_text_invalid_timestamp_tag()
+                / cue_text_start_tag()
+                / cue_text_string()
+
+        /// End of a tag, which can be omitted at the end of the cue text.
+        rule cue_text_tag_end() = ">" / ![_]
+
+        /// Separator of the tag name and the annotation.
+        rule cue_text_tag_separator() = whitespace() / newline() / "\x0c"
+
+        /// Tag name or class name.
+        rule cue_text_tag_name() -> &'input str
+            = $((!(cue_text_tag_separator() / ['.' | '>']) [_])*)
+
+        /// End tag.
+        rule cue_text_end_tag() -> CueTextToken
+            = "</" name:$((!">" [_])*) cue_text_tag_end()
+            {
+                CueTextToken::EndTag(name.to_string())
+            }
+
+        /// Timestamp tag.
+        rule cue_text_timestamp_tag() -> CueTextToken
+            = "<" timestamp:timestamp() cue_text_tag_end()
+            {
+                CueTextToken::Timestamp(timestamp)
+            }
+
+        /// Tag starting with a digit but not a valid timestamp.
+        rule cue_text_invalid_timestamp_tag() -> CueTextToken
+            = "<" ['0'..='9'] (!">" [_])* cue_text_tag_end()
+            {
+                CueTextToken::InvalidTimestamp
+            }
+
+        /// Start tag.
+        rule cue_text_start_tag() -> CueTextToken
+            = "<" name:cue_text_tag_name()
+                classes:("." class:cue_text_tag_name() { class })*
+                annotation:(cue_text_tag_separator() a:$((!">" [_])*) { a })?
+                cue_text_tag_end()
+            {
+                CueTextToken::StartTag {
+                    name: name.to_string(),
+                    classes: classes
+                        .into_iter()
+                        .filter(|class| !class.is_empty())
+                        .map(|class| class.to_string())
+                        .collect(),
+                    annotation: annotation.and_then(normalize_annotation),
+                }
+            }
+
+        /// Text between tags.
+        rule cue_text_string() -> CueTextToken
+            = (!"<" [_])+
+            {
+                CueTextToken::Text
+            }
+
         /// Header
         pub(crate) rule header() -> VttHeader
             = header_with_below_description()
@@ -469,12 +707,16 @@ peg::parser! {
 
         /// The entire WebVTT file.
         pub(crate) rule vtt() -> WebVtt
-            = header:header() newline()
+            = vtt:vtt_ref() { vtt.into_owned() }
+
+        /// The entire WebVTT file borrowing the identifiers and the payloads of cues.
+        pub(crate) rule vtt_ref() -> WebVttRef<'input>
+            = "\u{feff}"? header:header() newline()
                 (whitespace() / newline())*
-                blocks:block() ** (newline()+)
+                blocks:block_ref() ** (newline()+)
                 (whitespace() / newline())*
             {
-                WebVtt {
+                WebVttRef {
                     header,
                     blocks,
                 }
@@ -537,8 +779,26 @@ mod test {
             }
         );
 
+        // Hours of two or more digits.
+        assert_eq!(
+            vtt_parser::timestamp("123:45:06.789").unwrap(),
+            VttTimestamp {
+                hours: 123,
+                minutes: 45,
+                seconds: 6,
+                milliseconds: 789,
+            }
+        );
+        assert_eq!(
+            vtt_parser::timestamp("4294967295:00:00.000")
+                .unwrap()
+                .hours,
+            u32::MAX
+        );
+        assert!(vtt_parser::timestamp("4294967296:00:00.000").is_err());
+
         // Invalid digits.
-        assert!(vtt_parser::timestamp("000:00:00.000").is_err());
+        assert!(vtt_parser::timestamp("0:00:00.000").is_err());
         assert!(vtt_parser::timestamp("00:000:00.000").is_err());
         assert!(vtt_parser::timestamp("00:00:000.000").is_err());
         assert!(vtt_parser::timestamp("00:00:00.0000").is_err());
@@ -1758,4 +2018,107 @@ What are you waiting for?
 
         assert_eq!(vtt_parser::vtt(text).unwrap(), expected);
     }
+
+    #[test]
+    fn cue_text() {
+        use super::CueTextToken;
+
+        let tokens = |text| {
+            vtt_parser::cue_text(text)
+                .unwrap()
+                .into_iter()
+                .map(|(token, _)| token)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            tokens("Hello, world!"),
+            vec![CueTextToken::Text]
+        );
+
+        assert_eq!(
+            vtt_parser::cue_text("<b>bold</b>").unwrap(),
+            vec![
+                (
+                    CueTextToken::StartTag {
+                        name: "b".to_string(),
+                        classes: vec![],
+                        annotation: None,
+                    },
+                    0..3
+                ),
+                (CueTextToken::Text, 3..7),
+                (
+                    CueTextToken::EndTag("b".to_string()),
+                    7..11
+                ),
+            ]
+        );
+
+        assert_eq!(
+            tokens("<v.first.loud  Roger   Bingham >"),
+            vec![
+                CueTextToken::StartTag {
+                    name: "v".to_string(),
+                    classes: vec![
+                        "first".to_string(),
+                        "loud".to_string()
+                    ],
+                    annotation: Some("Roger Bingham".to_string()),
+                }
+            ]
+        );
+
+        assert_eq!(
+            tokens("<00:00:01.500>next"),
+            vec![
+                CueTextToken::Timestamp(VttTimestamp {
+                    seconds: 1,
+                    milliseconds: 500,
+                    ..Default::default()
+                }),
+                CueTextToken::Text,
+            ]
+        );
+
+        assert_eq!(
+            tokens("<00:01.5>next"),
+            vec![
+                CueTextToken::InvalidTimestamp,
+                CueTextToken::Text,
+            ]
+        );
+
+        // Unterminated tag at the end.
+        assert_eq!(
+            tokens("text<i"),
+            vec![
+                CueTextToken::Text,
+                CueTextToken::StartTag {
+                    name: "i".to_string(),
+                    classes: vec![],
+                    annotation: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn cue_text_nodes() {
+        assert_eq!(
+            super::cue_text_nodes("&lt;&amp;&gt;&nbsp;&#65;&#x42;&unknown;&"),
+            vec![
+                VttCueNode::Text("<&>\u{a0}".to_string()),
+                VttCueNode::Raw {
+                    text: "AB&".to_string(),
+                    source: "&#65;&#x42;&".to_string(),
+                },
+                VttCueNode::Text("unknown;".to_string()),
+                VttCueNode::Raw {
+                    text: "&".to_string(),
+                    source: "&".to_string(),
+                },
+            ]
+        );
+    }
 }
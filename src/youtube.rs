@@ -0,0 +1,751 @@
+//! Parsers for the YouTube timed text formats of `srv3` (`.srv3`, `.xml`) and `json3` (`.json3`, `.json`)
+//! provided by [`subtp::youtube::Srv3`](Srv3) and [`subtp::youtube::Json3`](Json3).
+//!
+//! Both formats consist of the events with the segments of text,
+//! where the offsets of the segments from the event are the word-level timings of the automatic captions.
+//! The word timings are mapped to the starts of the spans in the common cue model,
+//! i.e. the internal timestamps of the WebVTT, by [`SubtitleFormat`].
+//!
+//! The pens, the window styles and the window positions are not preserved.
+//!
+//! ## Example
+//! ```
+//! use std::time::Duration;
+//! use subtp::SubtitleFormat;
+//! use subtp::vtt::WebVtt;
+//! use subtp::youtube::Json3;
+//! use subtp::youtube::Srv3;
+//!
+//! let text = r#"{
+//!   "wireMagic": "pb3",
+//!   "events": [ {
+//!     "tStartMs": 1000,
+//!     "dDurationMs": 2000,
+//!     "segs": [ {
+//!       "utf8": "Hello"
+//!     }, {
+//!       "utf8": " world",
+//!       "tOffsetMs": 500
+//!     } ]
+//!   } ]
+//! }
+//! "#;
+//!
+//! let json3 = Json3::parse(text).unwrap();
+//! assert_eq!(json3.events[0].start, Duration::from_secs(1));
+//! assert_eq!(json3.events[0].text(), "Hello world");
+//! assert_eq!(json3.render(), text);
+//!
+//! let vtt: WebVtt = json3.convert();
+//! assert_eq!(
+//!     vtt.render(),
+//!     "WEBVTT\n\n00:00:01.000 --> 00:00:03.000\nHello<00:00:01.500> world\n"
+//! );
+//!
+//! let srv3: Srv3 = vtt.convert();
+//! assert_eq!(
+//!     srv3.render(),
+//!     r#"<?xml version="1.0" encoding="utf-8" ?>
+//! <timedtext format="3">
+//! <body>
+//! <p t="1000" d="2000"><s>Hello</s><s t="500"> world</s></p>
+//! </body>
+//! </timedtext>
+//! "#
+//! );
+//! ```
+
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::time::Duration;
+
+use crate::ParseError;
+use crate::ParseResult;
+use crate::format::Cue;
+use crate::format::CueSpan;
+use crate::format::SubtitleFormat;
+use crate::format::push_span;
+use crate::ttml::escape_text;
+use crate::youtube_parser;
+
+/// The YouTube timed text of the `srv3` XML format.
+///
+/// Parses from text by [`Srv3::parse`](Srv3::parse)
+/// and renders to text by [`Srv3::render`](Srv3::render).
+///
+/// The `<p>` elements in the `<body>` are the events and the `<s>` elements in them are the segments,
+/// where the other elements such as `<head>` and `<w>` are skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Srv3 {
+    /// The events.
+    pub events: Vec<YouTubeEvent>,
+}
+
+impl Srv3 {
+    /// Parses the `srv3` format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::youtube::Srv3;
+    ///
+    /// let text = r#"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+    /// <body>
+    /// <p t="1000" d="2000">Hello, world!</p>
+    /// </body>
+    /// </timedtext>
+    /// "#;
+    ///
+    /// let srv3 = Srv3::parse(text).unwrap();
+    /// assert_eq!(srv3.events[0].text(), "Hello, world!");
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        youtube_parser::srv3(text)
+            .map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the `srv3` format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Srv3 {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(
+            f,
+            "<?xml version=\"1.0\" encoding=\"utf-8\" ?>"
+        )?;
+        writeln!(f, "<timedtext format=\"3\">")?;
+        writeln!(f, "<body>")?;
+        for event in &self.events {
+            write!(
+                f,
+                "<p t=\"{}\"",
+                event.start.as_millis()
+            )?;
+            if let Some(duration) = event.duration {
+                write!(f, " d=\"{}\"", duration.as_millis())?;
+            }
+            if event.append {
+                write!(f, " a=\"1\"")?;
+            }
+
+            match event.segments.as_slice() {
+                | [] => writeln!(f, "/>")?,
+                | [segment] if segment.offset.is_none() => {
+                    writeln!(f, ">{}</p>", escape_text(&segment.text))?
+                },
+                | segments => {
+                    write!(f, ">")?;
+                    for segment in segments {
+                        match segment.offset {
+                            | Some(offset) => {
+                                write!(f, "<s t=\"{}\">", offset.as_millis())?
+                            },
+                            | None => write!(f, "<s>")?,
+                        }
+                        write!(f, "{}</s>", escape_text(&segment.text))?;
+                    }
+                    writeln!(f, "</p>")?;
+                },
+            }
+        }
+        writeln!(f, "</body>")?;
+        writeln!(f, "</timedtext>")
+    }
+}
+
+impl SubtitleFormat for Srv3 {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the events with text, see [`YouTubeEvent`] for the timings.
+    fn cues(&self) -> Vec<Cue> {
+        cues_from_events(&self.events)
+    }
+
+    /// Creates from the cues with the word timings of the spans, dropping the formatting and the positions.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        Self {
+            events: events_from_cues(cues),
+        }
+    }
+}
+
+impl From<Json3> for Srv3 {
+    fn from(json3: Json3) -> Self {
+        Self {
+            events: json3.events,
+        }
+    }
+}
+
+/// The YouTube timed text of the `json3` format.
+///
+/// Parses from text by [`Json3::parse`](Json3::parse)
+/// and renders to text by [`Json3::render`](Json3::render).
+///
+/// The objects in `events` are the events with `tStartMs`, `dDurationMs`, `aAppend`
+/// and the segments in `segs` with `utf8` and `tOffsetMs`, where the other keys are skipped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Json3 {
+    /// The events.
+    pub events: Vec<YouTubeEvent>,
+}
+
+impl Json3 {
+    /// Parses the `json3` format from the given text.
+    ///
+    /// ## Example
+    /// ```
+    /// use subtp::youtube::Json3;
+    ///
+    /// let text = r#"{"events":[{"tStartMs":1000,"dDurationMs":2000,"segs":[{"utf8":"Hello, world!"}]}]}"#;
+    ///
+    /// let json3 = Json3::parse(text).unwrap();
+    /// assert_eq!(json3.events[0].text(), "Hello, world!");
+    /// ```
+    pub fn parse(text: &str) -> ParseResult<Self> {
+        youtube_parser::json3(text)
+            .map_err(|err| ParseError::from_peg(text, err))
+    }
+
+    /// Renders the text from the `json3` format.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Display for Json3 {
+    fn fmt(
+        &self,
+        f: &mut Formatter<'_>,
+    ) -> std::fmt::Result {
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"wireMagic\": \"pb3\",")?;
+        if self.events.is_empty() {
+            writeln!(f, "  \"events\": [ ]")?;
+            return writeln!(f, "}}");
+        }
+
+        write!(f, "  \"events\": [ ")?;
+        for (i, event) in self.events.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            writeln!(f, "{{")?;
+            write!(
+                f,
+                "    \"tStartMs\": {}",
+                event.start.as_millis()
+            )?;
+            if let Some(duration) = event.duration {
+                write!(
+                    f,
+                    ",\n    \"dDurationMs\": {}",
+                    duration.as_millis()
+                )?;
+            }
+            if event.append {
+                write!(f, ",\n    \"aAppend\": 1")?;
+            }
+            if !event.segments.is_empty() {
+                write!(f, ",\n    \"segs\": [ ")?;
+                for (j, segment) in event
+                    .segments
+                    .iter()
+                    .enumerate()
+                {
+                    if j > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(
+                        f,
+                        "{{\n      \"utf8\": \"{}\"",
+                        escape_json(&segment.text)
+                    )?;
+                    if let Some(offset) = segment.offset {
+                        write!(
+                            f,
+                            ",\n      \"tOffsetMs\": {}",
+                            offset.as_millis()
+                        )?;
+                    }
+                    write!(f, "\n    }}")?;
+                }
+                write!(f, " ]")?;
+            }
+            write!(f, "\n  }}")?;
+        }
+        writeln!(f, " ]")?;
+        writeln!(f, "}}")
+    }
+}
+
+impl SubtitleFormat for Json3 {
+    fn parse(text: &str) -> ParseResult<Self> {
+        Self::parse(text)
+    }
+
+    fn render(&self) -> String {
+        self.render()
+    }
+
+    /// Gets the cues of the events with text, see [`YouTubeEvent`] for the timings.
+    fn cues(&self) -> Vec<Cue> {
+        cues_from_events(&self.events)
+    }
+
+    /// Creates from the cues with the word timings of the spans, dropping the formatting and the positions.
+    fn from_cues(cues: Vec<Cue>) -> Self {
+        Self {
+            events: events_from_cues(cues),
+        }
+    }
+}
+
+impl From<Srv3> for Json3 {
+    fn from(srv3: Srv3) -> Self {
+        Self {
+            events: srv3.events,
+        }
+    }
+}
+
+/// The event of the YouTube timed text.
+///
+/// In the common cue model, the events without text such as the window definitions
+/// and the line breaks appended to the window of the automatic captions are skipped,
+/// and an event without the duration ends at the start of the next cue.
+///
+/// ## Example
+/// ```
+/// use std::time::Duration;
+/// use subtp::youtube::YouTubeEvent;
+/// use subtp::youtube::YouTubeSegment;
+///
+/// let event = YouTubeEvent {
+///     start: Duration::from_secs(1),
+///     duration: Some(Duration::from_secs(2)),
+///     append: false,
+///     segments: vec![
+///         YouTubeSegment {
+///             text: "Hello".to_string(),
+///             offset: None,
+///         },
+///         YouTubeSegment {
+///             text: " world".to_string(),
+///             offset: Some(Duration::from_millis(500)),
+///         },
+///     ],
+/// };
+///
+/// assert_eq!(event.text(), "Hello world");
+/// assert_eq!(event.end(), Some(Duration::from_secs(3)));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct YouTubeEvent {
+    /// The start time of `t` or `tStartMs`.
+    pub start: Duration,
+    /// The duration of `d` or `dDurationMs`.
+    pub duration: Option<Duration>,
+    /// Whether to append to the window of the previous event by `a` or `aAppend`.
+    pub append: bool,
+    /// The segments of text.
+    pub segments: Vec<YouTubeSegment>,
+}
+
+impl YouTubeEvent {
+    /// Gets the end time by the duration.
+    pub fn end(&self) -> Option<Duration> {
+        self.duration
+            .map(|duration| self.start + duration)
+    }
+
+    /// Gets the text of the segments.
+    pub fn text(&self) -> String {
+        self.segments
+            .iter()
+            .map(|segment| segment.text.as_str())
+            .collect()
+    }
+}
+
+/// The segment of text in the event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct YouTubeSegment {
+    /// The text of the content of `<s>` or `utf8`, where lines are separated by `\n`.
+    pub text: String,
+    /// The offset of the word timing from the start of the event by `t` or `tOffsetMs`.
+    pub offset: Option<Duration>,
+}
+
+/// Gets the cues of the events with text.
+fn cues_from_events(events: &[YouTubeEvent]) -> Vec<Cue> {
+    let events = events
+        .iter()
+        .filter(|event| !event.text().trim().is_empty())
+        .collect::<Vec<_>>();
+
+    events
+        .iter()
+        .enumerate()
+        .map(|(i, event)| {
+            let mut spans = Vec::new();
+            for segment in &event.segments {
+                push_span(
+                    &mut spans,
+                    CueSpan {
+                        text: segment.text.clone(),
+                        start: segment
+                            .offset
+                            .map(|offset| event.start + offset),
+                        ..Default::default()
+                    },
+                );
+            }
+            trim_spans(&mut spans);
+
+            Cue {
+                start: event.start,
+                end: event
+                    .end()
+                    .or_else(|| {
+                        events
+                            .get(i + 1)
+                            .map(|next| next.start.max(event.start))
+                    })
+                    .unwrap_or(event.start),
+                spans,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Trims the whitespaces at the start of the first span and the end of the last span.
+fn trim_spans(spans: &mut Vec<CueSpan>) {
+    if let Some(first) = spans.first_mut() {
+        first.text = first
+            .text
+            .trim_start()
+            .to_string();
+    }
+    if let Some(last) = spans.last_mut() {
+        last.text = last
+            .text
+            .trim_end()
+            .to_string();
+    }
+    spans.retain(|span| !span.text.is_empty());
+}
+
+/// Creates the events from the cues with the segments of the spans grouped by the starts.
+fn events_from_cues(cues: Vec<Cue>) -> Vec<YouTubeEvent> {
+    cues.into_iter()
+        .map(|cue| {
+            let mut segments: Vec<YouTubeSegment> = Vec::new();
+            let mut start = None;
+            for span in &cue.spans {
+                match segments.last_mut() {
+                    | Some(last)
+                        if span.start.is_none() || span.start == start =>
+                    {
+                        last.text.push_str(&span.text)
+                    },
+                    | _ => {
+                        start = span.start;
+                        segments.push(YouTubeSegment {
+                            text: span.text.clone(),
+                            offset: span
+                                .start
+                                .map(|start| start.saturating_sub(cue.start))
+                                .filter(|offset| !offset.is_zero()),
+                        });
+                    },
+                }
+            }
+
+            YouTubeEvent {
+                start: cue.start,
+                duration: Some(cue.duration()),
+                append: false,
+                segments,
+            }
+        })
+        .collect()
+}
+
+/// Escapes the string of JSON without the quotes.
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            | '"' => escaped.push_str("\\\""),
+            | '\\' => escaped.push_str("\\\\"),
+            | '\n' => escaped.push_str("\\n"),
+            | '\r' => escaped.push_str("\\r"),
+            | '\t' => escaped.push_str("\\t"),
+            | c if c < ' ' => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            | c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vtt::WebVtt;
+
+    /// The automatic captions with the word timings and the rolling window.
+    const SRV3: &str = r#"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+<head>
+<ws id="0"/>
+<wp id="0"/>
+</head>
+<body>
+<w t="0" id="1" wp="0" ws="0"/>
+<p t="1000" d="3000" w="1"><s ac="0">we&#39;re</s><s t="400" ac="0"> going</s><s t="800" ac="0"> home</s></p>
+<p t="2500" d="1500" w="1" a="1">
+</p>
+<!-- A comment. -->
+<p t="2500" d="2000" w="1"><s ac="0">Tom</s><s t="300" ac="0"> &amp; Jerry</s></p>
+<p t="5000">No duration</p>
+<p t="6000" d="1000"/>
+</body>
+</timedtext>
+"#;
+
+    const JSON3: &str = r#"{
+  "wireMagic": "pb3",
+  "pens": [ {  } ],
+  "wsWinStyles": [ { }, { "mhModeHint": 2 } ],
+  "events": [ {
+    "tStartMs": 0,
+    "dDurationMs": 5000,
+    "id": 1,
+    "wpWinPosId": 1,
+    "wsWinStyleId": 1
+  }, {
+    "tStartMs": 1000,
+    "dDurationMs": 3000,
+    "wWinId": 1,
+    "segs": [ { "utf8": "we're", "acAsrConf": 0 }, { "utf8": " going", "tOffsetMs": 400, "acAsrConf": 0 }, { "utf8": " home", "tOffsetMs": 800 } ]
+  }, {
+    "tStartMs": 2500,
+    "dDurationMs": 1500,
+    "wWinId": 1,
+    "aAppend": 1,
+    "segs": [ { "utf8": "\n" } ]
+  }, {
+    "tStartMs": 2500,
+    "dDurationMs": 2000,
+    "wWinId": 1,
+    "segs": [ { "utf8": "Tom" }, { "utf8": " & Jerry", "tOffsetMs": 300 } ]
+  }, {
+    "tStartMs": 5000,
+    "segs": [ { "utf8": "No duration" } ]
+  }, {
+    "tStartMs": 6000,
+    "dDurationMs": 1000
+  } ]
+}"#;
+
+    fn segment(
+        text: &str,
+        offset: Option<u64>,
+    ) -> YouTubeSegment {
+        YouTubeSegment {
+            text: text.to_string(),
+            offset: offset.map(Duration::from_millis),
+        }
+    }
+
+    fn expected_events() -> Vec<YouTubeEvent> {
+        vec![
+            YouTubeEvent {
+                start: Duration::from_millis(1000),
+                duration: Some(Duration::from_millis(3000)),
+                append: false,
+                segments: vec![
+                    segment("we're", None),
+                    segment(" going", Some(400)),
+                    segment(" home", Some(800)),
+                ],
+            },
+            YouTubeEvent {
+                start: Duration::from_millis(2500),
+                duration: Some(Duration::from_millis(1500)),
+                append: true,
+                segments: vec![segment("\n", None)],
+            },
+            YouTubeEvent {
+                start: Duration::from_millis(2500),
+                duration: Some(Duration::from_millis(2000)),
+                append: false,
+                segments: vec![
+                    segment("Tom", None),
+                    segment(" & Jerry", Some(300)),
+                ],
+            },
+            YouTubeEvent {
+                start: Duration::from_millis(5000),
+                duration: None,
+                append: false,
+                segments: vec![segment(
+                    "No duration",
+                    None,
+                )],
+            },
+            YouTubeEvent {
+                start: Duration::from_millis(6000),
+                duration: Some(Duration::from_millis(1000)),
+                append: false,
+                segments: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn srv3() {
+        let srv3 = Srv3::parse(SRV3).unwrap();
+        assert_eq!(srv3.events, expected_events());
+        assert_eq!(
+            Srv3::parse(&srv3.render()).unwrap(),
+            srv3
+        );
+        assert_eq!(
+            srv3.render(),
+            r#"<?xml version="1.0" encoding="utf-8" ?>
+<timedtext format="3">
+<body>
+<p t="1000" d="3000"><s>we're</s><s t="400"> going</s><s t="800"> home</s></p>
+<p t="2500" d="1500" a="1">
+</p>
+<p t="2500" d="2000"><s>Tom</s><s t="300"> &amp; Jerry</s></p>
+<p t="5000">No duration</p>
+<p t="6000" d="1000"/>
+</body>
+</timedtext>
+"#
+        );
+
+        assert!(
+            Srv3::parse(
+                "<timedtext><body><p t=\"1s\">A</p></body></timedtext>"
+            )
+            .is_err()
+        );
+        assert!(
+            Srv3::parse("<timedtext><body><p t=\"1\">A</s></body></timedtext>")
+                .is_err()
+        );
+
+        // Deep nesting fails instead of overflowing the stack.
+        let nested = |depth| {
+            format!(
+                "<timedtext><body><p t=\"1\">{}A{}</p></body></timedtext>",
+                "<x>".repeat(depth),
+                "</x>".repeat(depth)
+            )
+        };
+        assert!(Srv3::parse(&nested(100)).is_ok());
+        assert!(Srv3::parse(&nested(10_000)).is_err());
+    }
+
+    #[test]
+    fn json3() {
+        let json3 = Json3::parse(JSON3).unwrap();
+        assert_eq!(json3.events[1..], expected_events());
+        assert_eq!(json3.events[0].segments, vec![]);
+        assert_eq!(
+            Json3::parse(&json3.render()).unwrap(),
+            json3
+        );
+        assert_eq!(
+            Json3::parse(
+                r#"{"events":[{"segs":[{"utf8":"\"\\\/\b\f\n\r\té😀"}]}]}"#
+            )
+            .unwrap()
+            .events[0]
+                .text(),
+            "\"\\/\u{8}\u{c}\n\r\té😀"
+        );
+        assert_eq!(
+            Json3::from_cues(vec![Cue {
+                start: Duration::from_secs(1),
+                end: Duration::from_secs(2),
+                spans: vec![CueSpan {
+                    text: "\"A\"\n\u{1}".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }])
+            .render(),
+            "{\n  \"wireMagic\": \"pb3\",\n  \"events\": [ {\n    \"tStartMs\": 1000,\n    \"dDurationMs\": 1000,\n    \"segs\": [ {\n      \"utf8\": \"\\\"A\\\"\\n\\u0001\"\n    } ]\n  } ]\n}\n"
+        );
+        assert_eq!(
+            Json3::default().render(),
+            "{\n  \"wireMagic\": \"pb3\",\n  \"events\": [ ]\n}\n"
+        );
+
+        assert!(Json3::parse("[]").is_err());
+        assert!(Json3::parse(r#"{"events":[{"tStartMs":-1}]}"#).is_err());
+        assert!(Json3::parse(r#"{"events":[{"tStartMs":1,}]}"#).is_err());
+
+        // Deep nesting fails instead of overflowing the stack.
+        let nested = |depth| {
+            format!(
+                r#"{{"events":[],"x":{}0{}}}"#,
+                "[".repeat(depth),
+                "]".repeat(depth)
+            )
+        };
+        assert!(Json3::parse(&nested(100)).is_ok());
+        assert!(Json3::parse(&nested(100_000)).is_err());
+    }
+
+    #[test]
+    fn convert() {
+        let srv3 = Srv3::parse(SRV3).unwrap();
+        let json3 = Json3::parse(JSON3).unwrap();
+        assert_eq!(srv3.cues(), json3.cues());
+
+        let vtt: WebVtt = srv3.convert();
+        assert_eq!(
+            vtt.render(),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:04.000\nwe're<00:00:01.400> going<00:00:01.800> home\n\n00:00:02.500 --> 00:00:04.500\nTom<00:00:02.800> &amp; Jerry\n\n00:00:05.000 --> 00:00:05.000\nNo duration\n"
+        );
+
+        let events = vtt.convert::<Json3>().events;
+        assert_eq!(
+            events[..2],
+            [
+                expected_events()[0].clone(),
+                expected_events()[2].clone()
+            ]
+        );
+        assert_eq!(events[2].duration, Some(Duration::ZERO));
+
+        let vtt = WebVtt::parse(
+            "WEBVTT\n\n00:01.000 --> 00:03.000\n<v Alice><i>Hello</i>, <00:02.000><b>world</b>!</v>\n",
+        )
+        .unwrap();
+        assert_eq!(
+            vtt.convert::<Srv3>().events[0].segments,
+            vec![
+                segment("Hello, ", None),
+                segment("world!", Some(1000)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! A parser implementation for the YouTube timed text formats.
+
+use std::time::Duration;
+
+pub(crate) use json3_parser::json3;
+pub(crate) use srv3_parser::srv3;
+
+use crate::youtube::Json3;
+use crate::youtube::YouTubeEvent;
+use crate::youtube::YouTubeSegment;
+
+/// The maximum nesting depth of elements and values, beyond which parsing fails
+/// instead of overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Gets the time in milliseconds of the attribute by the name.
+fn attribute_milliseconds(
+    attributes: &[(&str, String)],
+    name: &str,
+) -> Result<Option<Duration>, &'static str> {
+    attributes
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, value)| {
+            value
+                .trim()
+                .parse()
+                .map(Duration::from_millis)
+                .or(Err("time in milliseconds"))
+        })
+        .transpose()
+}
+
+/// Creates the event from the attributes of `<p>` and the segments.
+fn event_from_attributes(
+    attributes: &[(&str, String)],
+    segments: Vec<YouTubeSegment>,
+) -> Result<YouTubeEvent, &'static str> {
+    Ok(YouTubeEvent {
+        start: attribute_milliseconds(attributes, "t")?.unwrap_or_default(),
+        duration: attribute_milliseconds(attributes, "d")?,
+        append: attributes
+            .iter()
+            .any(|(name, value)| *name == "a" && value.trim() != "0"),
+        segments,
+    })
+}
+
+peg::parser! {
+    /// The parser for the `srv3` format.
+    grammar srv3_parser() for str {
+        use crate::youtube::Srv3;
+        use crate::youtube::YouTubeEvent;
+        use crate::youtube::YouTubeSegment;
+        use crate::ttml_parser::decode_entities;
+        use super::MAX_DEPTH;
+        use super::attribute_milliseconds;
+        use super::event_from_attributes;
+
+        /// Whitespace including newlines.
+        rule whitespace() = [' ' | '\t' | '\r' | '\n']
+
+        /// Name of an element or an attribute.
+        rule name() -> &'input str
+            = $(['a'..='z' | 'A'..='Z' | '_' | ':']
+                ['a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':' | '-' | '.']*)
+
+        /// Attribute.
+        rule attribute() -> (&'input str, String)
+            = whitespace()+ name:name() whitespace()* "=" whitespace()*
+                value:("\"" v:$([^'"']*) "\"" { v } / "'" v:$([^'\'']*) "'" { v })
+            {
+                (name, decode_entities(value))
+            }
+
+        /// Comment.
+        rule comment()
+            = "<!--" (!"-->" [_])* "-->"
+
+        /// Processing instruction including the XML declaration.
+        rule processing_instruction()
+            = "<?" (!"?>" [_])* "?>"
+
+        /// Nodes ignored between elements.
+        rule ignorable()
+            = (whitespace() / comment() / processing_instruction())*
+
+        /// Character data.
+        rule text() -> String
+            = t:$([^'<']+) { decode_entities(t) }
+
+        /// Start tag of an element with the name until the end of attributes.
+        rule start_tag(expected: &'static str) -> Vec<(&'input str, String)>
+            = "<" n:name() ({?
+                if n == expected {
+                    Ok(())
+                } else {
+                    Err(expected)
+                }
+            }) attributes:attribute()* whitespace()*
+            {
+                attributes
+            }
+
+        /// End tag of an element with the name.
+        rule end_tag(expected: &'static str)
+            = "</" n:name() whitespace()* ">" {?
+                if n == expected {
+                    Ok(())
+                } else {
+                    Err(expected)
+                }
+            }
+
+        /// Nesting of the depth up to the maximum.
+        rule nesting(depth: usize)
+            = "" {?
+                if depth < MAX_DEPTH {
+                    Ok(())
+                } else {
+                    Err("nesting depth of at most 128")
+                }
+            }
+
+        /// Any element skipped.
+        rule any_element(depth: usize)
+            = nesting(depth) "<" n:name() attribute()* whitespace()*
+                (
+                    "/>"
+                    / ">"
+                        (any_element(depth + 1) / comment() / processing_instruction() / [^'<']+)*
+                        "</" e:name() whitespace()* ">" {?
+                            if e == n {
+                                Ok(())
+                            } else {
+                                Err("matching end tag")
+                            }
+                        }
+                )
+
+        /// Segment of `<s>`.
+        rule segment() -> YouTubeSegment
+            = attributes:start_tag("s")
+                text:("/>" { String::new() } / ">" t:text()? end_tag("s") { t.unwrap_or_default() })
+            {?
+                Ok(YouTubeSegment {
+                    text,
+                    offset: attribute_milliseconds(&attributes, "t")?,
+                })
+            }
+
+        /// Content of `<p>`, where the text outside `<s>` is a segment without the offset.
+        rule paragraph_content() -> Option<YouTubeSegment>
+            = s:segment() { Some(s) }
+            / text:text() { Some(YouTubeSegment { text, offset: None }) }
+            / (comment() / processing_instruction() / any_element(3)) { None }
+
+        /// Event of `<p>`.
+        rule paragraph() -> YouTubeEvent
+            = attributes:start_tag("p")
+                segments:(
+                    "/>" { vec![] }
+                    / ">" s:paragraph_content()* end_tag("p") { s.into_iter().flatten().collect() }
+                )
+            {?
+                event_from_attributes(&attributes, segments)
+            }
+
+        /// Events of `<body>`.
+        rule body() -> Vec<YouTubeEvent>
+            = start_tag("body")
+                events:(
+                    "/>" { vec![] }
+                    / ">" ignorable()
+                        e:(p:paragraph() { Some(p) } / !start_tag("p") any_element(2) { None }) ** ignorable()
+                        ignorable() end_tag("body")
+                    {
+                        e.into_iter().flatten().collect()
+                    }
+                )
+            {
+                events
+            }
+
+        /// The entire `srv3`.
+        pub(crate) rule srv3() -> Srv3
+            = "\u{feff}"? ignorable() start_tag("timedtext") ">" ignorable()
+                events:(b:body() { Some(b) } / !start_tag("body") any_element(1) { None }) ** ignorable()
+                ignorable() end_tag("timedtext") ignorable()
+            {
+                Srv3 {
+                    events: events
+                        .into_iter()
+                        .flatten()
+                        .flatten()
+                        .collect(),
+                }
+            }
+    }
+}
+
+/// The value of JSON.
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Gets the value of the object by the key.
+    fn get(
+        &self,
+        key: &str,
+    ) -> Option<&JsonValue> {
+        match self {
+            | Self::Object(members) => {
+                members
+                    .iter()
+                    .find(|(k, _)| k == key)
+                    .map(|(_, value)| value)
+            },
+            | _ => None,
+        }
+    }
+
+    /// Gets the time in milliseconds of the object by the key.
+    fn milliseconds(
+        &self,
+        key: &str,
+    ) -> Result<Option<Duration>, &'static str> {
+        match self.get(key) {
+            | None | Some(Self::Null) => Ok(None),
+            | Some(Self::Number(n)) if n.is_finite() && *n >= 0.0 => {
+                Ok(Some(Duration::from_millis(*n as u64)))
+            },
+            | Some(_) => Err("time in milliseconds"),
+        }
+    }
+
+    /// Gets the array of the object by the key.
+    fn array(
+        &self,
+        key: &str,
+    ) -> Result<&[JsonValue], &'static str> {
+        match self.get(key) {
+            | None | Some(Self::Null) => Ok(&[]),
+            | Some(Self::Array(values)) => Ok(values),
+            | Some(_) => Err("array"),
+        }
+    }
+}
+
+/// Creates the `json3` from the root object.
+fn json3_from_value(value: &JsonValue) -> Result<Json3, &'static str> {
+    if !matches!(value, JsonValue::Object(_)) {
+        return Err("object");
+    }
+
+    Ok(Json3 {
+        events: value
+            .array("events")?
+            .iter()
+            .map(event_from_value)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Creates the event from the object in `events`.
+fn event_from_value(value: &JsonValue) -> Result<YouTubeEvent, &'static str> {
+    if !matches!(value, JsonValue::Object(_)) {
+        return Err("event object");
+    }
+
+    Ok(YouTubeEvent {
+        start: value
+            .milliseconds("tStartMs")?
+            .unwrap_or_default(),
+        duration: value.milliseconds("dDurationMs")?,
+        append: match value.get("aAppend") {
+            | Some(JsonValue::Number(n)) => *n != 0.0,
+            | Some(JsonValue::Bool(b)) => *b,
+            | _ => false,
+        },
+        segments: value
+            .array("segs")?
+            .iter()
+            .map(|segment| {
+                match segment {
+                    | JsonValue::Object(_) => {
+                        Ok(YouTubeSegment {
+                            text: match segment.get("utf8") {
+                                | Some(JsonValue::String(text)) => text.clone(),
+                                | _ => String::new(),
+                            },
+                            offset: segment.milliseconds("tOffsetMs")?,
+                        })
+                    },
+                    | _ => Err("segment object"),
+                }
+            })
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+peg::parser! {
+    /// The parser for the `json3` format.
+    grammar json3_parser() for str {
+        use crate::youtube::Json3;
+        use super::JsonValue;
+        use super::MAX_DEPTH;
+        use super::json3_from_value;
+
+        /// Whitespaces including newlines.
+        rule whitespace() = [' ' | '\t' | '\r' | '\n']*
+
+        /// Four hexadecimal digits.
+        rule hex() -> u32
+            = h:$(['0'..='9' | 'a'..='f' | 'A'..='F']*<4>) {?
+                u32::from_str_radix(h, 16).or(Err("hexadecimal digits"))
+            }
+
+        /// Escape sequence of a character.
+        rule escape() -> char
+            = "\\u" high:hex() "\\u" low:hex() {?
+                if (0xd800..0xdc00).contains(&high) && (0xdc00..0xe000).contains(&low) {
+                    char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))
+                        .ok_or("surrogate pair")
+                } else {
+                    Err("surrogate pair")
+                }
+            }
+            / "\\u" code:hex() {?
+                char::from_u32(code).ok_or("unicode escape")
+            }
+            / "\\" c:[
+                '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't'
+            ] {
+                match c {
+                    | 'b' => '\u{8}',
+                    | 'f' => '\u{c}',
+                    | 'n' => '\n',
+                    | 'r' => '\r',
+                    | 't' => '\t',
+                    | c => c,
+                }
+            }
+
+        /// String.
+        rule string() -> String
+            = "\"" chars:(c:[^'"' | '\\' | '\u{0}'..='\u{1f}'] { c } / escape())* "\""
+            {
+                chars.into_iter().collect()
+            }
+
+        /// Number.
+        rule number() -> f64
+            = n:$("-"? ['0'..='9']+ ("." ['0'..='9']+)? (['e' | 'E'] ['+' | '-']? ['0'..='9']+)?) {?
+                n.parse().or(Err("number"))
+            }
+
+        /// Nesting of the depth up to the maximum.
+        rule nesting(depth: usize)
+            = "" {?
+                if depth < MAX_DEPTH {
+                    Ok(())
+                } else {
+                    Err("nesting depth of at most 128")
+                }
+            }
+
+        /// Member of an object.
+        rule member(depth: usize) -> (String, JsonValue)
+            = whitespace() key:string() whitespace() ":" value:value(depth)
+            {
+                (key, value)
+            }
+
+        /// Value surrounded by whitespaces.
+        rule value(depth: usize) -> JsonValue
+            = whitespace()
+                value:(
+                    nesting(depth) "{" members:member(depth + 1) ** "," whitespace() "}" { JsonValue::Object(members) }
+                    / nesting(depth) "[" values:value(depth + 1) ** "," whitespace() "]" { JsonValue::Array(values) }
+                    / s:string() { JsonValue::String(s) }
+                    / n:number() { JsonValue::Number(n) }
+                    / "true" { JsonValue::Bool(true) }
+                    / "false" { JsonValue::Bool(false) }
+                    / "null" { JsonValue::Null }
+                )
+                whitespace()
+            {
+                value
+            }
+
+        /// The entire `json3`.
+        pub(crate) rule json3() -> Json3
+            = "\u{feff}"? value:value(0) {?
+                json3_from_value(&value)
+            }
+    }
+}
@@ -0,0 +1,254 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+
+const SRT: &str = r#"2
+00:00:03,000 --> 00:00:04,000
+<b>World</b>
+
+1
+00:00:01,000 --> 00:00:02,000
+<i>Hello</i>
+"#;
+
+const VTT: &str = r#"WEBVTT
+
+00:00:01.500 --> 00:00:02.500
+Again
+"#;
+
+/// Runs the binary with the arguments and the standard input.
+fn run(
+    args: &[&str],
+    stdin: &str,
+) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_subtp"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child
+        .wait_with_output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// A temporary directory removed on drop.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Creates the directory unique to the process and the name.
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "subtp-cli-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    /// Writes the contents to a file in the directory.
+    fn file(
+        &self,
+        name: &str,
+        contents: impl AsRef<[u8]>,
+    ) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn convert() {
+    let output = run(&["convert"], SRT);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "WEBVTT\n\n2\n00:00:03.000 --> 00:00:04.000\n<b>World</b>\n\n1\n00:00:01.000 --> 00:00:02.000\n<i>Hello</i>\n"
+    );
+
+    let output = run(
+        &[
+            "convert", "-", "--to", "srt",
+        ],
+        VTT,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "1\n00:00:01,500 --> 00:00:02,500\nAgain\n"
+    );
+
+    let directory = TempDir::new("convert");
+    let input = directory.file("convert.vtt", VTT);
+    let output_path = input.with_file_name("converted.srt");
+    let output = run(
+        &[
+            "convert",
+            input.to_str().unwrap(),
+            "-o",
+            output_path.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        std::fs::read_to_string(output_path).unwrap(),
+        "1\n00:00:01,500 --> 00:00:02,500\nAgain\n"
+    );
+
+    // The input in UTF-16 with the byte order mark is decoded.
+    let input = directory.file(
+        "utf16.srt",
+        [0xFF, 0xFE]
+            .into_iter()
+            .chain(
+                "1\n00:00:01,000 --> 00:00:02,000\nこんにちは\n"
+                    .encode_utf16()
+                    .flat_map(u16::to_le_bytes),
+            )
+            .collect::<Vec<u8>>(),
+    );
+    let output = run(
+        &[
+            "convert",
+            input.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "WEBVTT\n\n1\n00:00:01.000 --> 00:00:02.000\nこんにちは\n"
+    );
+}
+
+#[test]
+fn shift() {
+    let output = run(
+        &[
+            "shift", "--by", "-500",
+        ],
+        VTT,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "WEBVTT\n\n00:00:01.000 --> 00:00:02.000\nAgain\n"
+    );
+
+    let output = run(
+        &[
+            "shift", "--by", "-2000",
+        ],
+        VTT,
+    );
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = run(
+        &[
+            "shift", "--by", "-2000", "--clamp",
+        ],
+        VTT,
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "WEBVTT\n\n00:00:00.000 --> 00:00:00.500\nAgain\n"
+    );
+}
+
+#[test]
+fn validate_and_lint() {
+    let output = run(&["validate"], SRT);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "<stdin>: cue 0: sequence 2 is not sequential, expected 1\n<stdin>: cue 1: sequence 1 is not sequential, expected 2\n<stdin>: cue 1: start 1s is before start 3s of the previous cue\n"
+    );
+
+    let output = run(&["validate"], VTT);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+
+    let output = run(
+        &[
+            "lint", "--preset", "bbc",
+        ],
+        SRT,
+    );
+    assert!(output.status.success());
+
+    let output = run(
+        &["lint"],
+        "1\n00:00:01,000 --> 00:00:01,200\nToo short.\n",
+    );
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("<stdin>: cue 0: "));
+
+    let output = run(
+        &["lint"],
+        "1\n00:00:01,000 -> 00:00:02,000\nInvalid.\n",
+    );
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .starts_with("error: failed to parse <stdin>: ")
+    );
+}
+
+#[test]
+fn renumber_and_merge() {
+    let output = run(&["renumber"], SRT);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "1\n00:00:01,000 --> 00:00:02,000\n<i>Hello</i>\n\n2\n00:00:03,000 --> 00:00:04,000\n<b>World</b>\n"
+    );
+
+    let directory = TempDir::new("merge");
+    let srt = directory.file("merge.srt", SRT);
+    let vtt = directory.file("merge.vtt", VTT);
+    let output = run(
+        &[
+            "merge",
+            srt.to_str().unwrap(),
+            vtt.to_str().unwrap(),
+        ],
+        "",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "1\n00:00:01,000 --> 00:00:02,000\n<i>Hello</i>\n\n2\n00:00:01,500 --> 00:00:02,500\nAgain\n\n3\n00:00:03,000 --> 00:00:04,000\n<b>World</b>\n"
+    );
+}
+
+#[test]
+fn extract_text() {
+    let output = run(&["extract-text"], SRT);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "World\n\nHello\n");
+}